@@ -0,0 +1,35 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use gview::bench::{synthetic_repository, Workload};
+
+// Large enough for the hot paths to dominate, small enough to generate in a few seconds.
+const FILES: usize = 5_000;
+const COMMITS: usize = 500;
+
+fn hot_paths(c: &mut Criterion) {
+    let dir = std::env::temp_dir().join(format!("gview-criterion-{}", std::process::id()));
+    if let Err(e) = synthetic_repository(&dir, FILES, COMMITS) {
+        eprintln!("Failed to generate the repository: {}", e);
+        return;
+    }
+    // the first file is edited by every commit
+    let workload = match Workload::new(&dir, Some("src/module_0/file_0.rs".to_owned())) {
+        Ok(workload) => workload,
+        Err(e) => {
+            eprintln!("Failed to open the repository: {}", e);
+            return;
+        }
+    };
+
+    let mut group = c.benchmark_group("large repository");
+    group.sample_size(20);
+    group.bench_function("tree walk", |b| b.iter(|| workload.tree_walk()));
+    group.bench_function("filter", |b| b.iter(|| workload.filter("src/mod")));
+    group.bench_function("blame", |b| b.iter(|| workload.blame()));
+    group.bench_function("history", |b| b.iter(|| workload.history()));
+    group.finish();
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+criterion_group!(benches, hot_paths);
+criterion_main!(benches);
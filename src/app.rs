@@ -1,33 +1,76 @@
 use crate::{
+    changelog, clipboard,
     components::{
+        checks_modal::ChecksModal,
         commit_modal::CommitModal,
         commit_viewer::CommitViewer,
         content_viewer::ContentViewer,
         filer::Filer,
         filter::Filter,
         help_modal::HelpModal,
+        inspect_modal::InspectModal,
+        languages_modal::LanguagesModal,
+        notes_modal::NotesModal,
         operatable_components::{
             Message, MultipleTimesOperation, OnceOperation, OperatableComponent,
         },
+        picker_modal::PickerModal,
+        prompt_modal::{PromptKind, PromptModal},
+        remote_modal::RemoteModal,
+        search_modal::SearchModal,
+        status_bar::{StatusBar, ViewPosition},
+        whats_new_modal::WhatsNewModal,
     },
-    repository::RepositoryInfo,
+    config::Config,
+    error::GviewError,
+    progress::CancelToken,
+    repository::{self, DateRange, RepositoryInfo},
+    screen, state,
+    theme::Theme,
+    update,
+};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEventKind, MouseButton, MouseEvent, MouseEventKind,
 };
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
 use ratatui::{
-    backend::CrosstermBackend,
-    layout::{Constraint, Direction, Flex, Layout},
+    backend::{CrosstermBackend, TestBackend},
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Position, Rect},
     terminal::Terminal,
     Frame,
 };
 use std::{
+    collections::HashSet,
     io::{self, Stdout},
+    path::PathBuf,
     sync::{Arc, Mutex},
+    thread::JoinHandle,
     time::{Duration, Instant},
 };
 
 // A simple alias for the terminal type used in this example.
 pub type Tui = Terminal<CrosstermBackend<Stdout>>;
 
+// The commits with a trailer containing a query, found on a worker thread.
+type TrailerWalk = JoinHandle<anyhow::Result<HashSet<String>>>;
+
+// Width of the left side while the commit modal docks there next to a preview.
+const PREVIEW_LEFT_PERCENTAGE: u16 = 45;
+// Lines the viewers scroll by for each notch of the mouse wheel.
+const WHEEL_LINES: usize = 3;
+// Bounds of the left side's width, and of the commit panel's share of the right side.
+const LEFT_PERCENTAGES: (u16, u16) = (15, 70);
+const COMMIT_PERCENTAGES: (u16, u16) = (10, 90);
+
+// A divider between panels, dragged with the mouse to resize them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Divider {
+    // between the left side and the right one
+    Column,
+    // between the commit panel and the content viewer
+    Commit,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 enum FocusState {
     Filter,
@@ -47,8 +90,83 @@ impl FocusState {
     }
 }
 
+// The components of the app, to register them with a slot.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ComponentId {
+    Filter,
+    Filer,
+    CommitViewer,
+    ContentViewer,
+    StatusBar,
+    CommitModal,
+    HelpModal,
+    PromptModal,
+    NotesModal,
+    RemoteModal,
+    ChecksModal,
+    SearchModal,
+    PickerModal,
+    WhatsNewModal,
+    InspectModal,
+    LanguagesModal,
+}
+
+// Where a registered component is drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Slot {
+    // a panel of the layout, which takes the focus
+    Panel(FocusState),
+    StatusBar,
+    // over the panels while open; the lower the rank, the earlier a modal takes keys and
+    // the higher it is drawn
+    Modal(u8),
+}
+
+// Every component with its slot, in the order messages reach them. A new component is
+// registered here and in `App::component`.
+const REGISTRY: [(ComponentId, Slot); 16] = [
+    (ComponentId::Filer, Slot::Panel(FocusState::Filer)),
+    (ComponentId::Filter, Slot::Panel(FocusState::Filter)),
+    (ComponentId::ContentViewer, Slot::Panel(FocusState::Viewer)),
+    (ComponentId::CommitViewer, Slot::Panel(FocusState::Commit)),
+    (ComponentId::CommitModal, Slot::Modal(3)),
+    (ComponentId::HelpModal, Slot::Modal(2)),
+    // a prompt captures every key until it is submitted or dismissed, and so does the
+    // picker, whose query is typed directly
+    (ComponentId::PromptModal, Slot::Modal(0)),
+    (ComponentId::NotesModal, Slot::Modal(4)),
+    (ComponentId::RemoteModal, Slot::Modal(5)),
+    (ComponentId::ChecksModal, Slot::Modal(6)),
+    (ComponentId::SearchModal, Slot::Modal(7)),
+    (ComponentId::PickerModal, Slot::Modal(1)),
+    // the modals below take every key while open, so their `process_focus` does nothing
+    (ComponentId::WhatsNewModal, Slot::Modal(8)),
+    (ComponentId::InspectModal, Slot::Modal(9)),
+    (ComponentId::LanguagesModal, Slot::Modal(10)),
+    (ComponentId::StatusBar, Slot::StatusBar),
+];
+
+// The registered modals by rank.
+fn modals() -> Vec<ComponentId> {
+    let mut modals: Vec<(u8, ComponentId)> = REGISTRY
+        .into_iter()
+        .filter_map(|(id, slot)| match slot {
+            Slot::Modal(rank) => Some((rank, id)),
+            _ => None,
+        })
+        .collect();
+    modals.sort_by_key(|(rank, _)| *rank);
+    modals.into_iter().map(|(_, id)| id).collect()
+}
+
 pub struct App {
     left_main_chunk_percentage: u16,
+    // share of the right side for the commit panel once its divider is dragged; until then
+    // the panel grows with the message
+    commit_percentage: Option<u16>,
+    dragging: Option<Divider>,
+    // where the dragged sizes are kept for the next run
+    layout: state::PanelLayout,
     should_exit: bool,
     last_tick: Instant,
     focus_state: FocusState,
@@ -58,68 +176,328 @@ pub struct App {
     content_viewer: ContentViewer,
     commit_modal: CommitModal,
     help_modal: HelpModal,
+    prompt_modal: PromptModal,
+    notes_modal: NotesModal,
+    remote_modal: RemoteModal,
+    checks_modal: ChecksModal,
+    search_modal: SearchModal,
+    picker_modal: PickerModal,
+    whats_new_modal: WhatsNewModal,
+    inspect_modal: InspectModal,
+    languages_modal: LanguagesModal,
+    status_bar: StatusBar,
+    // (commit, file, line) locations to return to after jumping to a definition
+    jump_stack: Vec<(String, String, usize)>,
+    // the commit on screen and the one shown before it, for Ctrl+^
+    viewed_commit: String,
+    previous_commit: Option<String>,
+    config: Config,
+    // external viewer to run once the terminal is released
+    pending_command: Option<Vec<String>>,
+    // text to print on the normal screen, where the terminal can select it natively
+    pending_text: Option<String>,
+    // false under --no-shell-out
+    shell_out: bool,
+    // the last drawn frame, and the one taken for the screen export prompt
+    screen: Buffer,
+    captured_screen: Option<Buffer>,
+    // where each panel was last drawn, to find the one under the mouse
+    panels: Vec<(FocusState, Rect)>,
+    // file errors are appended to, with their causes
+    error_log: Option<PathBuf>,
+    // first key of a chord and when it was pressed
+    pending_key: Option<(char, Instant)>,
+    // browser link waiting for a remote to be picked
+    link_awaiting_remote: Option<OnceOperation>,
+    // running check for a later gview, answering its version if there is one
+    update_check: Option<JoinHandle<anyhow::Result<Option<String>>>>,
+    // file whose missing blob is being fetched from the promisor remote
+    blob_fetch: Option<(String, JoinHandle<anyhow::Result<()>>)>,
+    // trailer the history is being filtered by, handed to the commit modal once walked
+    trailer_walk: Option<(String, TrailerWalk, CancelToken)>,
 }
 
 impl App {
     const TICK_RATE: Duration = Duration::from_millis(50);
+    // How long the first key of a chord waits for the second before acting on its own.
+    const CHORD_TIMEOUT: Duration = Duration::from_secs(1);
+    // Status bar task of a blob fetch from the promisor remote.
+    const FETCH_TASK: &'static str = "fetching missing blob";
 
-    pub fn new(repository_info: RepositoryInfo) -> App {
+    pub fn new(repository_info: RepositoryInfo, config: Config) -> App {
         let repository = Arc::new(Mutex::new(repository_info));
+        let status_bar = StatusBar::new();
         let mut app = Self {
-            left_main_chunk_percentage: 15,
+            left_main_chunk_percentage: LEFT_PERCENTAGES.0,
+            commit_percentage: None,
+            dragging: None,
+            layout: state::PanelLayout::default(),
             should_exit: false,
             last_tick: Instant::now(),
             focus_state: FocusState::Filter,
             filter: Filter::new(),
-            filer: Filer::new(Arc::clone(&repository)),
-            commit_viewer: CommitViewer::new(Arc::clone(&repository)),
+            filer: Filer::new(Arc::clone(&repository), status_bar.reporter()),
+            commit_viewer: CommitViewer::new(Arc::clone(&repository), status_bar.reporter()),
             content_viewer: ContentViewer::new(Arc::clone(&repository)),
             commit_modal: CommitModal::new(Arc::clone(&repository)),
             help_modal: HelpModal::new(),
+            prompt_modal: PromptModal::new(),
+            notes_modal: NotesModal::new(Arc::clone(&repository)),
+            remote_modal: RemoteModal::new(Arc::clone(&repository)),
+            checks_modal: ChecksModal::new(Arc::clone(&repository)),
+            search_modal: SearchModal::new(Arc::clone(&repository), status_bar.reporter()),
+            picker_modal: PickerModal::new(Arc::clone(&repository)),
+            whats_new_modal: WhatsNewModal::new(),
+            inspect_modal: InspectModal::new(Arc::clone(&repository)),
+            languages_modal: LanguagesModal::new(Arc::clone(&repository), status_bar.reporter()),
+            status_bar,
+            jump_stack: Vec::new(),
+            viewed_commit: String::new(),
+            previous_commit: None,
+            config,
+            pending_command: None,
+            pending_text: None,
+            shell_out: true,
+            screen: Buffer::default(),
+            captured_screen: None,
+            panels: Vec::new(),
+            error_log: None,
+            pending_key: None,
+            link_awaiting_remote: None,
+            update_check: None,
+            blob_fetch: None,
+            trailer_walk: None,
         };
+        app.filer.set_live_preview(app.config.live_preview());
+        app.content_viewer.set_scrolloff(app.config.scrolloff());
+        app.commit_viewer.set_emoji(app.config.emoji());
+        app.commit_modal.set_emoji(app.config.emoji());
+        // main has already reported an invalid theme
+        app.set_theme(app.config.theme().unwrap_or_default());
         app.handle_message(Message::MultipleTimes(MultipleTimesOperation::SetUp {
             repository: Arc::clone(&repository),
         }));
+        if let Ok(repo) = repository.lock() {
+            repo.preload_commit_metadata(app.status_bar.reporter());
+        }
         app
     }
 
+    pub fn disable_shell_out(&mut self) {
+        self.shell_out = false;
+    }
+
+    pub fn set_error_log(&mut self, path: Option<PathBuf>) {
+        self.error_log = path;
+    }
+
+    /// Lists what is new since the version gview last ran as, after an upgrade, and
+    /// remembers this version for the next run.
+    pub fn show_whats_new(&mut self) {
+        let mut last_run = state::LastRun::for_user();
+        let news = changelog::news(last_run.version());
+        if last_run.version() != Some(changelog::VERSION) {
+            if let Err(e) = last_run.record(changelog::VERSION) {
+                self.report(&GviewError::new(
+                    "Failed to save the version of this run",
+                    e,
+                ));
+            }
+        }
+        self.whats_new_modal.open(news);
+    }
+
+    // Shows `error` in the status bar and keeps it in the error log.
+    fn report(&mut self, error: &GviewError) {
+        if let Some(path) = &self.error_log {
+            error.log(path);
+        }
+        self.status_bar.notify_error(error.to_string());
+    }
+
+    fn component(&mut self, id: ComponentId) -> &mut dyn OperatableComponent {
+        match id {
+            ComponentId::Filter => &mut self.filter,
+            ComponentId::Filer => &mut self.filer,
+            ComponentId::CommitViewer => &mut self.commit_viewer,
+            ComponentId::ContentViewer => &mut self.content_viewer,
+            ComponentId::StatusBar => &mut self.status_bar,
+            ComponentId::CommitModal => &mut self.commit_modal,
+            ComponentId::HelpModal => &mut self.help_modal,
+            ComponentId::PromptModal => &mut self.prompt_modal,
+            ComponentId::NotesModal => &mut self.notes_modal,
+            ComponentId::RemoteModal => &mut self.remote_modal,
+            ComponentId::ChecksModal => &mut self.checks_modal,
+            ComponentId::SearchModal => &mut self.search_modal,
+            ComponentId::PickerModal => &mut self.picker_modal,
+            ComponentId::WhatsNewModal => &mut self.whats_new_modal,
+            ComponentId::InspectModal => &mut self.inspect_modal,
+            ComponentId::LanguagesModal => &mut self.languages_modal,
+        }
+    }
+
+    fn open_modal(&mut self) -> Option<&mut dyn OperatableComponent> {
+        let id = modals()
+            .into_iter()
+            .find(|id| self.component(*id).is_open())?;
+        Some(self.component(id))
+    }
+
+    fn panel(&mut self, focus: FocusState) -> Option<&mut dyn OperatableComponent> {
+        let (id, _) = REGISTRY
+            .into_iter()
+            .find(|(_, slot)| *slot == Slot::Panel(focus))?;
+        Some(self.component(id))
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        for (id, _) in REGISTRY {
+            self.component(id).set_theme(theme);
+        }
+    }
+
     fn process_focus(&mut self) {
-        match self.focus_state {
-            FocusState::Commit => self.commit_viewer.process_focus(),
-            FocusState::Filter => self.filter.process_focus(),
-            FocusState::Filer => self.filer.process_focus(),
-            FocusState::Viewer => self.content_viewer.process_focus(),
+        if let Some(panel) = self.panel(self.focus_state) {
+            panel.process_focus();
         }
     }
 
+    // The open modal, or else the focused panel: the component keys go to.
+    fn key_target(&mut self) -> Option<&mut dyn OperatableComponent> {
+        let id = modals()
+            .into_iter()
+            .find(|id| self.component(*id).is_open())
+            .or_else(|| {
+                REGISTRY
+                    .into_iter()
+                    .find(|(_, slot)| *slot == Slot::Panel(self.focus_state))
+                    .map(|(id, _)| id)
+            })?;
+        Some(self.component(id))
+    }
+
     fn process_events(&mut self, code: KeyCode) -> Message {
-        // If help modal is open, handle help modal events first
-        if self.help_modal.is_open() {
-            return self.help_modal.process_events(code);
+        self.key_target()
+            .map_or(Message::NoAction, |target| target.process_events(code))
+    }
+
+    fn set_pending_key(&mut self, key: Option<char>) {
+        self.pending_key = key.map(|key| (key, Instant::now()));
+        self.status_bar.set_pending_key(key);
+    }
+
+    // Holds back the first key of one of the chords of the component keys go to.
+    fn start_chord(&mut self, code: KeyCode) -> bool {
+        let KeyCode::Char(key) = code else {
+            return false;
+        };
+        let starts_chord = self
+            .key_target()
+            .is_some_and(|target| target.chords().iter().any(|chord| chord[0] == key));
+        if starts_chord {
+            self.set_pending_key(Some(key));
         }
+        starts_chord
+    }
 
-        // If commit modal is open, handle commit modal events next
-        if self.commit_modal.is_open() {
-            return self.commit_modal.process_events(code);
+    // Runs the chord `event` completes, or lets the held back key act on its own before
+    // `event` does. Esc drops the held back key. Returns whether `event` was used up.
+    fn finish_chord(&mut self, event: event::KeyEvent) -> bool {
+        let Some((first, _)) = self.pending_key else {
+            return false;
+        };
+        self.set_pending_key(None);
+        match event.code {
+            KeyCode::Char(second) if event.modifiers != event::KeyModifiers::CONTROL => {
+                let chord = [first, second];
+                let message = self.key_target().and_then(|target| {
+                    target
+                        .chords()
+                        .contains(&chord)
+                        .then(|| target.process_chord(chord))
+                });
+                if let Some(message) = message {
+                    self.handle_message(message);
+                    return true;
+                }
+            }
+            KeyCode::Esc => return true,
+            _ => {}
         }
+        let message = self.process_events(KeyCode::Char(first));
+        self.handle_message(message);
+        false
+    }
 
-        match self.focus_state {
-            FocusState::Commit => self.commit_viewer.process_events(code),
-            FocusState::Filter => self.filter.process_events(code),
-            FocusState::Filer => self.filer.process_events(code),
-            FocusState::Viewer => self.content_viewer.process_events(code),
+    // Lets a held back key act on its own once no second key came in time.
+    fn expire_pending_key(&mut self, now: Instant) {
+        if let Some((key, since)) = self.pending_key {
+            if now.duration_since(since) >= Self::CHORD_TIMEOUT {
+                self.set_pending_key(None);
+                let message = self.process_events(KeyCode::Char(key));
+                self.handle_message(message);
+            }
         }
     }
 
     #[allow(unconditional_recursion)]
     fn handle_message(&mut self, message: Message) {
+        if let Message::Once(operation) = &message {
+            if let Some(action) = operation.spawned_action().filter(|_| !self.shell_out) {
+                return self.report(&GviewError::unavailable(format!(
+                    "{} is disabled by --no-shell-out",
+                    action
+                )));
+            }
+        }
         // handle itself
         match &message {
             Message::NoAction => return,
+            // with several remotes and none of them origin, ask which one links go to
+            Message::Once(
+                operation @ (OnceOperation::OpenInBrowser { .. }
+                | OnceOperation::OpenDiffInBrowser { .. }
+                | OnceOperation::OpenIssue { .. }),
+            ) if self.remote_is_ambiguous() => {
+                self.link_awaiting_remote = Some(operation.clone());
+                self.handle_message(Message::Once(OnceOperation::OpenRemoteModal));
+                return;
+            }
+            Message::Once(OnceOperation::OpenInBrowser { file, line }) => {
+                let url = match self.commit_viewer.repository.lock() {
+                    Ok(repo) => repo.file_url(file, *line),
+                    Err(_) => Err(anyhow::anyhow!("Failed to acquire repository lock")),
+                };
+                self.open_in_browser(url);
+                return;
+            }
+            Message::Once(OnceOperation::OpenDiffInBrowser { file }) => {
+                let url = match self.commit_viewer.repository.lock() {
+                    Ok(repo) => repo.diff_url(file),
+                    Err(_) => Err(anyhow::anyhow!("Failed to acquire repository lock")),
+                };
+                self.open_in_browser(url);
+                return;
+            }
+            Message::Once(OnceOperation::OpenIssue { reference }) => {
+                let url = match self.commit_viewer.repository.lock() {
+                    Ok(repo) => repo.issue_url(reference),
+                    Err(_) => Err(anyhow::anyhow!("Failed to acquire repository lock")),
+                };
+                self.open_in_browser(url);
+                return;
+            }
             Message::Once(OnceOperation::JumpToContentView) => {
+                // show the selection first if its preview is still pending
+                if let Some(preview) = self.filer.take_preview() {
+                    self.handle_message(preview);
+                }
                 self.focus_state = FocusState::Viewer
             }
             Message::Once(OnceOperation::JumpToFiler) => self.focus_state = FocusState::Filer,
+            Message::MultipleTimes(
+                MultipleTimesOperation::SetUp { .. } | MultipleTimesOperation::ChangeShowCommit,
+            ) => self.record_viewed_commit(),
             Message::Once(OnceOperation::SetCommitById { commit_id }) => {
                 // Close modal and set commit
                 let commit_id = commit_id.clone();
@@ -139,278 +517,1134 @@ impl App {
                 }
                 return; // Early return to avoid processing this message further
             }
+            Message::Once(OnceOperation::ScopeTo { prefix }) => {
+                let scoped = match self.commit_viewer.repository.lock() {
+                    Ok(mut repo) => repo
+                        .set_prefix(prefix)
+                        .map_err(|e| GviewError::new(format!("Cannot scope to {}", prefix), e)),
+                    Err(_) => Err(GviewError::Lock),
+                };
+                if let Err(e) = scoped {
+                    return self.report(&e);
+                }
+                self.handle_message(Message::MultipleTimes(MultipleTimesOperation::ChangeScope));
+                self.process_focus();
+                self.focus_state = FocusState::Filer;
+                self.process_focus();
+                return;
+            }
+            Message::Once(OnceOperation::PassThrough { text }) => {
+                self.pending_text = Some(text.clone());
+                return;
+            }
+            Message::Once(OnceOperation::CopyToClipboard { text }) => {
+                match clipboard::copy(text) {
+                    Ok(()) if text.lines().count() > 1 => self
+                        .status_bar
+                        .notify(format!("Copied {} lines", text.lines().count())),
+                    Ok(()) => self.status_bar.notify(format!("Copied {}", text)),
+                    Err(e) => self.report(&GviewError::new("Failed to copy", e)),
+                }
+                return;
+            }
+            Message::Once(OnceOperation::CopyLines { bytes }) => {
+                let count = bytes.split(|byte| *byte == b'\n').count()
+                    - usize::from(bytes.ends_with(b"\n"));
+                match clipboard::copy_bytes(bytes) {
+                    Ok(()) if count > 1 => {
+                        self.status_bar.notify(format!("Copied {} lines", count))
+                    }
+                    Ok(()) => self.status_bar.notify(format!(
+                        "Copied {}",
+                        String::from_utf8_lossy(bytes).trim_end()
+                    )),
+                    Err(e) => self.report(&GviewError::new("Failed to copy", e)),
+                }
+                return;
+            }
+            Message::Error(error) => {
+                self.report(error);
+                return;
+            }
+            Message::Once(OnceOperation::SelectRemote { name }) => {
+                let selected = match self.commit_viewer.repository.lock() {
+                    Ok(mut repo) => {
+                        repo.set_remote(name);
+                        true
+                    }
+                    Err(_) => false,
+                };
+                if !selected {
+                    return self.report(&GviewError::Lock);
+                }
+                self.status_bar
+                    .notify(format!("Browser links use {}", name));
+                if let Some(operation) = self.link_awaiting_remote.take() {
+                    self.handle_message(Message::Once(operation));
+                }
+            }
+            Message::Once(OnceOperation::CloseRemoteModal) => self.link_awaiting_remote = None,
+            Message::Once(OnceOperation::OpenWithExternal { file }) => {
+                let file = file.clone();
+                self.open_with_external(&file);
+                return;
+            }
+            Message::Once(OnceOperation::FetchBlob { file }) => {
+                let file = file.clone();
+                self.fetch_blob(file);
+                return;
+            }
+            Message::Once(OnceOperation::SubmitPrompt { kind, value }) => {
+                let (kind, value) = (*kind, value.clone());
+                self.handle_prompt(kind, &value);
+                return;
+            }
+            Message::Once(OnceOperation::OpenLocation { commit_id, .. }) => {
+                if let Some(commit_id) = commit_id {
+                    let changed = match self.commit_viewer.repository.lock() {
+                        Ok(mut repo) => {
+                            repo.get_current_commit_id() != *commit_id
+                                && repo.set_commit_by_id(commit_id).is_ok()
+                        }
+                        Err(_) => false,
+                    };
+                    if changed {
+                        self.handle_message(Message::MultipleTimes(
+                            MultipleTimesOperation::ChangeShowCommit,
+                        ));
+                    }
+                }
+                if self.focus_state != FocusState::Viewer {
+                    self.process_focus();
+                    self.focus_state = FocusState::Viewer;
+                    self.process_focus();
+                }
+            }
             _ => {}
         }
 
-        let new_message = self.filer.handle_message(&message);
-        self.handle_message(new_message);
-
-        let new_message = self.filter.handle_message(&message);
-        self.handle_message(new_message);
-
-        let new_message = self.content_viewer.handle_message(&message);
-        self.handle_message(new_message);
+        // each answer is handled before the next component sees the message
+        for (id, _) in REGISTRY {
+            let new_message = self.component(id).handle_message(&message);
+            self.handle_message(new_message);
+        }
+    }
 
-        let new_message = self.commit_viewer.handle_message(&message);
-        self.handle_message(new_message);
+    /// Shows the CI status of the viewed commits when the config opts in. The forge is asked
+    /// through gh, so never with shelling out disabled.
+    pub fn start_checks(&mut self) {
+        if self.shell_out && self.config.checks_enabled() {
+            self.checks_modal.enable(self.config.checks_token());
+        }
+    }
 
-        let new_message = self.commit_modal.handle_message(&message);
-        self.handle_message(new_message);
+    /// Asks in the background whether a later gview is out, when the config opts in and
+    /// the environment does not opt out. The check runs curl, so never with shelling out
+    /// disabled.
+    pub fn start_update_check(&mut self) {
+        if self.shell_out && self.config.update_check() && !update::disabled_by_env() {
+            self.update_check = Some(update::spawn_check());
+        }
+    }
 
-        let new_message = self.help_modal.handle_message(&message);
-        self.handle_message(new_message);
+    // A failed check says nothing: it is only a courtesy, and offline is a fine way to work.
+    fn poll_update_check(&mut self) {
+        if !self
+            .update_check
+            .as_ref()
+            .is_some_and(|check| check.is_finished())
+        {
+            return;
+        }
+        if let Some(Ok(Ok(Some(latest)))) = self.update_check.take().map(|check| check.join()) {
+            self.status_bar.notify(format!(
+                "gview {} is available (this is {})",
+                latest,
+                changelog::VERSION
+            ));
+        }
     }
 
-    pub fn run(&mut self, terminal: &mut Tui) -> io::Result<()> {
-        while !self.should_exit {
-            terminal.draw(|frame| {
-                let _ = self.draw(frame);
-            })?;
-            self.handle_events()?;
-            if self.last_tick.elapsed() >= Self::TICK_RATE {
-                self.last_tick = Instant::now();
+    fn fetch_blob(&mut self, file: String) {
+        if let Some((fetching, _)) = &self.blob_fetch {
+            return self
+                .status_bar
+                .notify(format!("Still fetching {}", fetching));
+        }
+        let fetch = match self.commit_viewer.repository.lock() {
+            Ok(repo) => repo
+                .spawn_blob_fetch(&file)
+                .map_err(|e| GviewError::new(format!("Cannot fetch {}", file), e)),
+            Err(_) => Err(GviewError::Lock),
+        };
+        match fetch {
+            Ok(fetch) => {
+                self.status_bar
+                    .reporter()
+                    .update(Self::FETCH_TASK, 0, Some(1));
+                self.blob_fetch = Some((file, fetch));
             }
+            Err(e) => self.report(&e),
         }
-        Ok(())
     }
 
-    fn handle_events(&mut self) -> io::Result<()> {
-        let timeout = Self::TICK_RATE.saturating_sub(self.last_tick.elapsed());
-        while event::poll(timeout)? {
-            if let Event::Key(event) = event::read()? {
-                if event.kind == KeyEventKind::Press {
-                    match event {
-                        event::KeyEvent {
-                            code: event::KeyCode::Tab,
-                            ..
-                        } => {
-                            self.process_focus();
-                            self.focus_state = self.focus_state.next();
-                            self.process_focus();
-                        }
-                        event::KeyEvent {
-                            code: event::KeyCode::Char('c'),
-                            modifiers: event::KeyModifiers::CONTROL,
-                            ..
-                        } => self.should_exit = true,
-                        event::KeyEvent {
-                            code: event::KeyCode::Char('<'),
-                            ..
-                        } => {
-                            self.left_main_chunk_percentage =
-                                self.left_main_chunk_percentage.saturating_sub(5).max(15);
-                        }
-                        event::KeyEvent {
-                            code: event::KeyCode::Char('>'),
-                            ..
-                        } => {
-                            self.left_main_chunk_percentage =
-                                (self.left_main_chunk_percentage + 5).min(70);
-                        }
-                        event::KeyEvent {
-                            code: event::KeyCode::Char('?'),
-                            ..
-                        } => self.handle_message(Message::Once(OnceOperation::ShowHelpModal)),
-                        _ => {
-                            let message = self.process_events(event.code);
-                            self.handle_message(message)
-                        }
-                    }
+    // Shows the fetched file again, if the viewer still shows it.
+    fn poll_blob_fetch(&mut self) {
+        if !self
+            .blob_fetch
+            .as_ref()
+            .is_some_and(|(_, fetch)| fetch.is_finished())
+        {
+            return;
+        }
+        let Some((file, fetch)) = self.blob_fetch.take() else {
+            return;
+        };
+        self.status_bar.reporter().finish(Self::FETCH_TASK);
+        match fetch.join() {
+            Ok(Ok(())) => {
+                self.status_bar.notify(format!("Fetched {}", file));
+                let shown = self.content_viewer.view_status().map(|(shown, ..)| shown);
+                if shown.as_ref() == Some(&file) {
+                    self.handle_message(Message::Once(OnceOperation::ShowFile { file }));
                 }
             }
+            Ok(Err(e)) => self.report(&GviewError::new(format!("Cannot fetch {}", file), e)),
+            Err(_) => self.report(&GviewError::new(
+                format!("Cannot fetch {}", file),
+                anyhow::anyhow!("the fetch stopped unexpectedly"),
+            )),
         }
-        Ok(())
     }
 
-    pub fn draw(&mut self, frame: &mut Frame) -> anyhow::Result<()> {
-        let main_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage(self.left_main_chunk_percentage),
-                Constraint::Percentage((100_u16).saturating_sub(self.left_main_chunk_percentage)),
-            ])
-            .split(frame.size());
-
-        let left_chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
-            .split(main_chunks[0]);
-
-        // chunks[0], chunks[1]
-        let right_chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .flex(Flex::Legacy)
-            .constraints([Constraint::Length(3), Constraint::Length(5)].as_ref())
-            .split(main_chunks[1]);
-
-        self.filter.draw(frame, left_chunks[0]);
-        self.filer.draw(frame, left_chunks[1]);
-        self.commit_viewer.draw(frame, right_chunks[0]);
-        self.content_viewer.draw(frame, right_chunks[1]);
-
-        // Draw modals on top if they're open
-        self.commit_modal.draw(frame, frame.size());
-        self.help_modal.draw(frame, frame.size());
-
-        Ok(())
+    // Filters the commit modal by the trailer once the history is walked.
+    fn poll_trailer_walk(&mut self) {
+        if !self
+            .trailer_walk
+            .as_ref()
+            .is_some_and(|(_, walk, _)| walk.is_finished())
+        {
+            return;
+        }
+        let Some((query, walk, _)) = self.trailer_walk.take() else {
+            return;
+        };
+        match walk.join() {
+            Ok(Ok(matching)) => self
+                .commit_modal
+                .set_trailer_filter(Some((query, matching))),
+            Ok(Err(e)) => self.report(&GviewError::new("Cannot filter by trailer", e)),
+            Err(_) => self.report(&GviewError::new(
+                "Cannot filter by trailer",
+                anyhow::anyhow!("the walk stopped unexpectedly"),
+            )),
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_focus_state_next_transitions() {
-        assert_eq!(FocusState::Filter.next(), FocusState::Filer);
-        assert_eq!(FocusState::Filer.next(), FocusState::Commit);
-        assert_eq!(FocusState::Commit.next(), FocusState::Viewer);
-        assert_eq!(FocusState::Viewer.next(), FocusState::Filter);
+    fn cancel_trailer_walk(&mut self) -> bool {
+        match self.trailer_walk.take() {
+            Some((_, _, cancel)) => {
+                cancel.cancel();
+                true
+            }
+            None => false,
+        }
     }
 
-    #[test]
-    fn test_focus_state_cycle_complete() {
-        let mut state = FocusState::Filter;
-
-        // Test complete cycle
-        state = state.next();
-        assert_eq!(state, FocusState::Filer);
-
-        state = state.next();
-        assert_eq!(state, FocusState::Commit);
-
-        state = state.next();
-        assert_eq!(state, FocusState::Viewer);
+    // Stops the walks of the whole history running in the background, returning whether
+    // any was running.
+    fn cancel_history_walks(&mut self) -> bool {
+        let filtering = self.cancel_trailer_walk();
+        let finding_children = self.commit_viewer.cancel_children();
+        if filtering || finding_children {
+            self.status_bar
+                .notify("Stopped walking the history".to_owned());
+        }
+        filtering || finding_children
+    }
 
-        state = state.next();
-        assert_eq!(state, FocusState::Filter); // Back to start
+    // The viewed commit and where the content viewer is in it.
+    fn position(&self) -> Option<ViewPosition> {
+        let commit = self
+            .commit_viewer
+            .repository
+            .lock()
+            .ok()?
+            .current_oid()
+            .to_string();
+        Some(ViewPosition {
+            commit: commit[..std::cmp::min(8, commit.len())].to_owned(),
+            file: self.content_viewer.view_status(),
+        })
     }
 
-    #[test]
-    fn test_app_draw_normal_state() {
-        use crate::repository::RepositoryInfo;
-        use insta::assert_snapshot;
-        use ratatui::{backend::TestBackend, Terminal};
-        use std::env;
-        use std::time::{SystemTime, UNIX_EPOCH};
+    fn remote_is_ambiguous(&self) -> bool {
+        self.commit_viewer
+            .repository
+            .lock()
+            .is_ok_and(|repo| repo.remote_is_ambiguous())
+    }
 
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos();
-        let random_suffix = std::process::id();
-        let test_dir =
-            env::temp_dir().join(format!("gview_app_test_{}_{}", timestamp, random_suffix));
-        let _ = std::fs::remove_dir_all(&test_dir);
-        std::fs::create_dir_all(&test_dir).unwrap();
-
-        let repo = git2::Repository::init(&test_dir).unwrap();
-
-        // Create a test file
-        let test_file_path = test_dir.join("test.txt");
-        std::fs::write(&test_file_path, "Hello, world!").unwrap();
-
-        let signature = git2::Signature::new(
-            "Test User",
-            "test@localhost",
-            &git2::Time::new(1234567890, 0),
-        )
-        .unwrap();
-        let tree_id = {
-            let mut index = repo.index().unwrap();
-            index.add_path(std::path::Path::new("test.txt")).unwrap();
-            index.write().unwrap();
-            index.write_tree().unwrap()
-        };
-        let tree = repo.find_tree(tree_id).unwrap();
-
-        let _ = repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            "Initial commit",
-            &tree,
-            &[],
-        );
+    fn open_in_browser(&mut self, url: anyhow::Result<String>) {
+        let notice = match url {
+            Ok(url) if repository::open_in_browser(&url).is_ok() => return,
+            // over SSH or in WSL the URL is handed over through the clipboard
+            Ok(url) => match clipboard::copy(&url) {
+                Ok(()) => format!("No browser here, copied {}", url),
+                Err(_) => url,
+            },
+            Err(e) => return self.report(&GviewError::new("Cannot open in the browser", e)),
+        };
+        self.status_bar.notify(notice);
+    }
 
-        drop(tree);
-        let oid = repo.head().unwrap().target().unwrap();
-        let repo_info = RepositoryInfo::_from_parts(repo, oid);
+    fn handle_prompt(&mut self, kind: PromptKind, value: &str) {
+        let location = self.content_viewer.current_location();
+        let short = |id: &str| id[..std::cmp::min(8, id.len())].to_owned();
+        // answers to queries, shown in the status bar
+        let mut notice = None;
+        // new trailer filter of the commit modal, or the walk finding the matching commits
+        let mut trailer_filter = None;
+        let mut trailer_walk = None;
+        // line of the viewer to go to, once the repository is unlocked
+        let mut go_to = None;
+        let result = match self.commit_viewer.repository.lock() {
+            Ok(mut repo) => match kind {
+                PromptKind::BaseRef if value.is_empty() => {
+                    repo.clear_base_ref();
+                    Ok(Some(MultipleTimesOperation::ChangeBase))
+                }
+                PromptKind::BaseRef => repo
+                    .set_base_ref(value)
+                    .map(|_| Some(MultipleTimesOperation::ChangeBase)),
+                PromptKind::CompareRefs if value.is_empty() => {
+                    repo.clear_base_ref();
+                    Ok(Some(MultipleTimesOperation::ChangeBase))
+                }
+                // git reads A...B as from their merge-base, which a compare is not
+                PromptKind::CompareRefs if value.contains("...") => {
+                    Err(anyhow::anyhow!("expected A..B, not A...B"))
+                }
+                PromptKind::CompareRefs => match value.split_once("..") {
+                    Some((from, to)) if !from.is_empty() && !to.is_empty() => repo
+                        .set_compare_refs(from, to)
+                        .map(|_| Some(MultipleTimesOperation::ChangeBase)),
+                    _ => Err(anyhow::anyhow!("expected two refs as A..B")),
+                },
+                PromptKind::Prefix => repo
+                    .set_prefix(value)
+                    .map(|_| Some(MultipleTimesOperation::ChangeScope)),
+                PromptKind::ContainedIn | PromptKind::MergeBase if value.is_empty() => {
+                    Err(anyhow::anyhow!("expected a ref"))
+                }
+                PromptKind::ContainedIn => repo.is_contained_in(value).map(|contained| {
+                    notice = Some(format!(
+                        "{} is {}in {}",
+                        short(&repo.get_current_commit_id()),
+                        if contained { "" } else { "not " },
+                        value
+                    ));
+                    None
+                }),
+                PromptKind::MergeBase => repo.merge_base_with(value).map(|(id, subject)| {
+                    notice = Some(format!(
+                        "merge-base of {} and {}: {} {}",
+                        short(&repo.get_current_commit_id()),
+                        value,
+                        short(&id),
+                        subject
+                    ));
+                    None
+                }),
+                PromptKind::TrailerFilter if value.is_empty() => {
+                    trailer_filter = Some(None);
+                    Ok(None)
+                }
+                PromptKind::TrailerFilter => {
+                    let cancel = CancelToken::default();
+                    let walk = repo.spawn_trailer_filter(
+                        value,
+                        self.status_bar.reporter(),
+                        cancel.clone(),
+                    );
+                    trailer_walk = Some((value.to_owned(), walk, cancel));
+                    Ok(None)
+                }
+                PromptKind::DateRange => DateRange::parse(value).map(|range| {
+                    repo.set_date_range(range);
+                    Some(MultipleTimesOperation::ChangeDateRange)
+                }),
+                PromptKind::Note if value.is_empty() => Ok(None),
+                PromptKind::Note => match location {
+                    Some((file, line)) => repo
+                        .add_note(&file, line, value)
+                        .map(|_| Some(MultipleTimesOperation::ChangeNotes)),
+                    None => Err(anyhow::anyhow!("No file line to attach the note to")),
+                },
+                PromptKind::ExportNotes => {
+                    let path = if value.is_empty() {
+                        "gview-notes.md"
+                    } else {
+                        value
+                    };
+                    std::fs::write(path, state::notes_to_markdown(repo.notes()))
+                        .map(|_| None)
+                        .map_err(anyhow::Error::from)
+                }
+                PromptKind::ExportSummary => {
+                    let path = if value.is_empty() {
+                        "gview-review.md"
+                    } else {
+                        value
+                    };
+                    repo.review_summary().and_then(|summary| {
+                        std::fs::write(path, summary)
+                            .map(|_| None)
+                            .map_err(anyhow::Error::from)
+                    })
+                }
+                PromptKind::GoToLine => value
+                    .parse()
+                    .map(|line| {
+                        go_to = Some(line);
+                        None
+                    })
+                    .map_err(|_| anyhow::anyhow!("expected a line number")),
+                PromptKind::ExportScreen => match &self.captured_screen {
+                    Some(buffer) if value == "+" => clipboard::copy(&screen::to_text(buffer))
+                        .map(|_| {
+                            notice = Some("Copied the screen".to_owned());
+                            None
+                        })
+                        .map_err(anyhow::Error::from),
+                    Some(buffer) => {
+                        let path = if value.is_empty() {
+                            "gview-screen.txt"
+                        } else {
+                            value
+                        };
+                        let text = if path.ends_with(".ans") {
+                            screen::to_ansi(buffer)
+                        } else {
+                            screen::to_text(buffer)
+                        };
+                        std::fs::write(path, text)
+                            .map(|_| {
+                                notice = Some(format!("Saved the screen to {}", path));
+                                None
+                            })
+                            .map_err(anyhow::Error::from)
+                    }
+                    None => Err(anyhow::anyhow!("No screen to save")),
+                },
+            },
+            Err(_) => Err(anyhow::anyhow!("Failed to acquire repository lock")),
+        };
 
-        let mut app = App::new(repo_info);
+        match result {
+            Ok(operation) => {
+                if let Some(notice) = notice {
+                    self.status_bar.notify(notice);
+                }
+                if let Some(filter) = trailer_filter {
+                    self.cancel_trailer_walk();
+                    self.commit_modal.set_trailer_filter(filter);
+                }
+                if let Some(walk) = trailer_walk {
+                    self.cancel_trailer_walk();
+                    self.trailer_walk = Some(walk);
+                }
+                if let Some(line) = go_to {
+                    self.content_viewer.go_to_line(line);
+                }
+                self.handle_message(Message::Once(OnceOperation::ClosePrompt));
+                if let Some(operation) = operation {
+                    self.handle_message(Message::MultipleTimes(operation));
+                }
+            }
+            Err(e) => self.prompt_modal.set_error(e.to_string()),
+        }
+    }
 
-        let backend = TestBackend::new(120, 40);
-        let mut terminal = Terminal::new(backend).unwrap();
+    fn open_with_external(&mut self, file: &str) {
+        if self.config.open_with(file, "").is_none() {
+            return;
+        }
+        let path = match self.commit_viewer.repository.lock() {
+            Ok(repo) => repo
+                .materialize_blob(file, repo.current_oid())
+                .map_err(|e| GviewError::new(format!("Cannot write {}", file), e)),
+            Err(_) => Err(GviewError::Lock),
+        };
+        match path {
+            Ok(path) => {
+                self.pending_command = self.config.open_with(file, &path.to_string_lossy());
+            }
+            Err(e) => self.report(&e),
+        }
+    }
 
-        terminal
-            .draw(|frame| {
-                let _ = app.draw(frame);
-            })
-            .unwrap();
+    // Leaves the TUI for `action` and waits for Enter before redrawing.
+    fn release_terminal(terminal: &mut Tui, action: impl FnOnce()) -> io::Result<()> {
+        crate::restore_terminal()?;
+        action();
+        println!("Press Enter to return to gview");
+        let _ = io::stdin().read_line(&mut String::new());
+        *terminal = crate::init_terminal()?;
+        terminal.clear()
+    }
 
-        let buffer = terminal.backend().buffer();
-        assert_snapshot!(format!("{:?}", buffer));
+    // Hands the terminal to the external command.
+    fn run_external(&mut self, terminal: &mut Tui, command: &[String]) -> io::Result<()> {
+        Self::release_terminal(terminal, || {
+            if let Some((program, args)) = command.split_first() {
+                if let Err(e) = std::process::Command::new(program).args(args).status() {
+                    eprintln!("failed to run {}: {}", program, e);
+                }
+            }
+        })
     }
 
-    #[test]
-    fn test_app_draw_with_help_modal_open() {
-        use crate::repository::RepositoryInfo;
-        use insta::assert_snapshot;
-        use ratatui::{backend::TestBackend, Terminal};
-        use std::env;
-        use std::time::{SystemTime, UNIX_EPOCH};
+    fn can_jump(&mut self) -> bool {
+        self.focus_state == FocusState::Viewer && self.open_modal().is_none()
+    }
 
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos();
-        let random_suffix = std::process::id();
-        let test_dir = env::temp_dir().join(format!(
-            "gview_app_help_test_{}_{}",
-            timestamp, random_suffix
-        ));
-        let _ = std::fs::remove_dir_all(&test_dir);
-        std::fs::create_dir_all(&test_dir).unwrap();
+    fn jump_to_definition(&mut self) {
+        let (Some((file, line)), Some(word)) = (
+            self.content_viewer.current_location(),
+            self.content_viewer.word_at_cursor(),
+        ) else {
+            return;
+        };
+        let found = match self.commit_viewer.repository.lock() {
+            Ok(mut repo) => Some((
+                repo.get_current_commit_id(),
+                repo.find_definition(&word).unwrap_or_default(),
+            )),
+            Err(_) => None,
+        };
+        let Some((commit_id, definitions)) = found else {
+            return self.report(&GviewError::Lock);
+        };
+        // Already on a definition: cycle to the next one
+        let next = definitions
+            .iter()
+            .position(|(path, number)| *path == file && *number == line)
+            .map_or(0, |index| index + 1);
+        let Some((target_file, target_line)) = definitions
+            .get(next)
+            .or_else(|| definitions.first())
+            .cloned()
+        else {
+            return;
+        };
+        if target_file == file && target_line == line {
+            return;
+        }
 
-        let repo = git2::Repository::init(&test_dir).unwrap();
+        self.jump_stack.push((commit_id, file, line));
+        self.handle_message(Message::Once(OnceOperation::OpenLocation {
+            commit_id: None,
+            file: target_file,
+            line: target_line,
+        }));
+    }
 
-        // Create a test file
-        let test_file_path = test_dir.join("test.txt");
-        std::fs::write(&test_file_path, "Hello, world!").unwrap();
+    fn jump_back(&mut self) {
+        if let Some((commit_id, file, line)) = self.jump_stack.pop() {
+            self.handle_message(Message::Once(OnceOperation::OpenLocation {
+                commit_id: Some(commit_id),
+                file,
+                line,
+            }));
+        }
+    }
 
-        let signature = git2::Signature::new(
-            "Test User",
-            "test@localhost",
-            &git2::Time::new(1234567890, 0),
-        )
-        .unwrap();
-        let tree_id = {
-            let mut index = repo.index().unwrap();
-            index.add_path(std::path::Path::new("test.txt")).unwrap();
-            index.write().unwrap();
-            index.write_tree().unwrap()
-        };
-        let tree = repo.find_tree(tree_id).unwrap();
-
-        let _ = repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            "Initial commit",
-            &tree,
-            &[],
-        );
+    fn record_viewed_commit(&mut self) {
+        let commit_id = match self.commit_viewer.repository.lock() {
+            Ok(repo) => repo.get_current_commit_id(),
+            Err(_) => return,
+        };
+        if commit_id != self.viewed_commit {
+            let previous = std::mem::replace(&mut self.viewed_commit, commit_id);
+            if !previous.is_empty() {
+                self.previous_commit = Some(previous);
+            }
+        }
+    }
 
-        drop(tree);
-        let oid = repo.head().unwrap().target().unwrap();
-        let repo_info = RepositoryInfo::_from_parts(repo, oid);
+    // What Ctrl+O inspects: the selection of the file list or the viewer when focused, the
+    // viewed commit otherwise.
+    fn inspected_path(&self) -> Option<String> {
+        match self.focus_state {
+            FocusState::Filer => self.filer.selected_path().map(str::to_owned),
+            FocusState::Viewer => self.content_viewer.inspected_path(),
+            _ => None,
+        }
+    }
 
-        let mut app = App::new(repo_info);
+    // Taken before the prompt opens, so that it is not in the picture
+    fn capture_screen(&mut self) {
+        self.captured_screen = Some(self.screen.clone());
+        self.handle_message(Message::Once(OnceOperation::OpenPrompt {
+            kind: PromptKind::ExportScreen,
+        }));
+    }
 
-        // Open help modal
-        app.handle_message(Message::Once(OnceOperation::ShowHelpModal));
+    // Setting the commit keeps the open file, as picking one in the commit modal does
+    fn switch_to_previous_commit(&mut self) {
+        if let Some(commit_id) = self.previous_commit.clone() {
+            self.handle_message(Message::Once(OnceOperation::SetCommitById { commit_id }));
+        }
+    }
 
-        let backend = TestBackend::new(120, 40);
+    pub fn run(&mut self, terminal: &mut Tui) -> io::Result<()> {
+        while !self.should_exit {
+            terminal.draw(|frame| {
+                let _ = self.draw(frame);
+            })?;
+            self.handle_events()?;
+            if let Some(command) = self.pending_command.take() {
+                self.run_external(terminal, &command)?;
+            }
+            if let Some(text) = self.pending_text.take() {
+                Self::release_terminal(terminal, || println!("{}", text))?;
+            }
+            if self.last_tick.elapsed() >= Self::TICK_RATE {
+                self.last_tick = Instant::now();
+                self.status_bar.tick();
+                self.expire_pending_key(self.last_tick);
+                self.search_modal.poll();
+                self.picker_modal.poll();
+                self.checks_modal.poll();
+                self.languages_modal.poll();
+                self.content_viewer.poll_blame();
+                self.poll_trailer_walk();
+                if let Some(message) = self.commit_viewer.poll_children() {
+                    self.handle_message(message);
+                }
+                if let Some(message) = self.filer.poll_walk() {
+                    self.handle_message(message);
+                }
+                self.poll_update_check();
+                self.poll_blob_fetch();
+                if let Some(preview) = self.filer.poll_preview(self.last_tick) {
+                    self.handle_message(preview);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Plays `keys` against an off-screen terminal of `width` x `height`, redrawing after each
+    /// key as the real loop does, and returns the final screen as text.
+    pub fn run_script(&mut self, keys: &[event::KeyEvent], width: u16, height: u16) -> String {
+        let mut terminal = match Terminal::new(TestBackend::new(width, height)) {
+            Ok(terminal) => terminal,
+            Err(_) => return String::new(),
+        };
+        let mut draw = |app: &mut App| {
+            let _ = terminal.draw(|frame| {
+                let _ = app.draw(frame);
+            });
+        };
+        draw(self);
+        for key in keys {
+            self.handle_key(*key);
+            if self.should_exit {
+                break;
+            }
+            // background work finishes before the next key, so runs are reproducible
+            if let Some(preview) = self.filer.take_preview() {
+                self.handle_message(preview);
+            }
+            self.search_modal.wait();
+            self.picker_modal.wait();
+            self.wait_trailer_walk();
+            draw(self);
+        }
+        // a chord left unfinished acts as its timeout would have it
+        if let Some((_, since)) = self.pending_key {
+            self.expire_pending_key(since + Self::CHORD_TIMEOUT);
+            draw(self);
+        }
+        screen::to_text(&self.screen)
+    }
+
+    // Blocks until the running trailer filter, if any, is applied.
+    fn wait_trailer_walk(&mut self) {
+        while self.trailer_walk.is_some() {
+            self.poll_trailer_walk();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    fn handle_events(&mut self) -> io::Result<()> {
+        let timeout = Self::TICK_RATE.saturating_sub(self.last_tick.elapsed());
+        while event::poll(timeout)? {
+            match event::read()? {
+                Event::Key(event) if event.kind == KeyEventKind::Press => self.handle_key(event),
+                Event::Mouse(event) => self.handle_mouse(event),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_key(&mut self, event: event::KeyEvent) {
+        if self.finish_chord(event) {
+            return;
+        }
+        // Open prompts, the picker, what's new, the inspector, the language statistics and
+        // searches being typed take every key but Ctrl ones
+        let typing_search =
+            self.focus_state == FocusState::Viewer && self.content_viewer.is_editing_search();
+        if (self.prompt_modal.is_open()
+            || self.picker_modal.is_open()
+            || self.whats_new_modal.is_open()
+            || self.inspect_modal.is_open()
+            || self.languages_modal.is_open()
+            || self.commit_modal.is_editing()
+            || self.help_modal.is_editing()
+            || typing_search)
+            && event.modifiers != event::KeyModifiers::CONTROL
+        {
+            let message = self.process_events(event.code);
+            self.handle_message(message);
+            return;
+        }
+        match event {
+            event::KeyEvent {
+                code: event::KeyCode::Tab,
+                ..
+            } => {
+                // In the filter, Tab completes a path before moving on
+                if self.focus_state == FocusState::Filter {
+                    if let Some(message) = self.filter.complete(self.filer.items()) {
+                        self.handle_message(message);
+                        return;
+                    }
+                }
+                self.process_focus();
+                self.focus_state = self.focus_state.next();
+                self.process_focus();
+            }
+            event::KeyEvent {
+                code: event::KeyCode::Char('c'),
+                modifiers: event::KeyModifiers::CONTROL,
+                ..
+            } => self.should_exit = true,
+            // terminals report Ctrl+] as Ctrl+5
+            event::KeyEvent {
+                code: event::KeyCode::Char(']' | '5'),
+                modifiers: event::KeyModifiers::CONTROL,
+                ..
+            } if self.can_jump() => self.jump_to_definition(),
+            event::KeyEvent {
+                code: event::KeyCode::Char('t'),
+                modifiers: event::KeyModifiers::CONTROL,
+                ..
+            } if self.can_jump() => self.jump_back(),
+            // terminals report Ctrl+^ as Ctrl+6
+            event::KeyEvent {
+                code: event::KeyCode::Char('^' | '6'),
+                modifiers: event::KeyModifiers::CONTROL,
+                ..
+            } if !self.prompt_modal.is_open() => self.switch_to_previous_commit(),
+            event::KeyEvent {
+                code: event::KeyCode::Char('s'),
+                modifiers: event::KeyModifiers::CONTROL,
+                ..
+            } if !self.prompt_modal.is_open() => self.capture_screen(),
+            event::KeyEvent {
+                code: event::KeyCode::Char('p'),
+                modifiers: event::KeyModifiers::CONTROL,
+                ..
+            } if !self.prompt_modal.is_open() => {
+                self.handle_message(Message::Once(OnceOperation::OpenPicker))
+            }
+            event::KeyEvent {
+                code: event::KeyCode::Char('o'),
+                modifiers: event::KeyModifiers::CONTROL,
+                ..
+            } if !self.prompt_modal.is_open() => {
+                self.handle_message(Message::Once(OnceOperation::OpenInspector {
+                    path: self.inspected_path(),
+                }))
+            }
+            event::KeyEvent {
+                code: event::KeyCode::Char(key @ ('d' | 'u')),
+                modifiers: event::KeyModifiers::CONTROL,
+                ..
+            } => {
+                let message = self.key_target().map_or(Message::NoAction, |target| {
+                    target.scroll_half_page(key == 'd')
+                });
+                self.handle_message(message);
+            }
+            event::KeyEvent {
+                code: event::KeyCode::Char('<'),
+                ..
+            } => {
+                self.left_main_chunk_percentage = self
+                    .left_main_chunk_percentage
+                    .saturating_sub(5)
+                    .max(LEFT_PERCENTAGES.0);
+            }
+            event::KeyEvent {
+                code: event::KeyCode::Char('>'),
+                ..
+            } => {
+                self.left_main_chunk_percentage =
+                    (self.left_main_chunk_percentage + 5).min(LEFT_PERCENTAGES.1);
+            }
+            event::KeyEvent {
+                code: event::KeyCode::Char('?'),
+                ..
+            } => self.handle_message(Message::Once(OnceOperation::ShowHelpModal)),
+            // Esc stops a walk of the whole history before anything else, unless typed into
+            // a prompt
+            event::KeyEvent {
+                code: event::KeyCode::Esc,
+                ..
+            } if !self.prompt_modal.is_open() && self.cancel_history_walks() => {}
+            // Esc drops the shown error first, unless an open modal is waiting for it
+            event::KeyEvent {
+                code: event::KeyCode::Esc,
+                ..
+            } if self.open_modal().is_none() && self.status_bar.dismiss_error() => {}
+            _ if self.start_chord(event.code) => {}
+            _ => {
+                let message = self.process_events(event.code);
+                self.handle_message(message)
+            }
+        }
+    }
+
+    fn handle_mouse(&mut self, event: MouseEvent) {
+        let down = match event.kind {
+            MouseEventKind::ScrollDown => true,
+            MouseEventKind::ScrollUp => false,
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.dragging = self
+                    .divider_at(event.column, event.row)
+                    .filter(|_| self.open_modal().is_none());
+                if self.dragging.is_none() {
+                    self.click(event.column, event.row);
+                }
+                return;
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                self.drag(event.column, event.row);
+                return;
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                self.finish_drag();
+                return;
+            }
+            _ => return,
+        };
+        let key = if down { KeyCode::Down } else { KeyCode::Up };
+        // a prompt is typed into; the other modals scroll their list
+        if self.prompt_modal.is_open() {
+            return;
+        }
+        if let Some(modal) = self.open_modal() {
+            let message = modal.process_events(key);
+            self.handle_message(message);
+            return;
+        }
+        // panels scroll under the mouse, without taking the focus
+        let Some(panel) = self.panel_at(event.column, event.row) else {
+            return;
+        };
+        for _ in 0..WHEEL_LINES {
+            let message = match panel {
+                FocusState::Filter => Message::NoAction,
+                // Up and Down walk the history in the commit panel
+                FocusState::Commit => {
+                    self.commit_viewer
+                        .process_events(KeyCode::Char(if down { 'j' } else { 'k' }))
+                }
+                FocusState::Filer | FocusState::Viewer => self
+                    .panel(panel)
+                    .map_or(Message::NoAction, |panel| panel.process_events(key)),
+            };
+            self.handle_message(message);
+            // the file list moves its selection rather than a view
+            if panel == FocusState::Filer {
+                break;
+            }
+        }
+    }
+
+    // Focuses the panel under a click; a click on the file list also shows the file, and
+    // one on the blame gutter jumps to the commit or shows the line's details.
+    fn click(&mut self, column: u16, row: u16) {
+        if self.open_modal().is_some() {
+            return;
+        }
+        let Some(panel) = self.panel_at(column, row) else {
+            return;
+        };
+        if panel != self.focus_state {
+            self.process_focus();
+            self.focus_state = panel;
+            self.process_focus();
+        }
+        let message = match panel {
+            FocusState::Filer => self.filer.click(row),
+            FocusState::Viewer => self.content_viewer.click(column, row),
+            _ => return,
+        };
+        self.handle_message(message);
+    }
+
+    fn panel_area(&self, panel: FocusState) -> Option<Rect> {
+        self.panels
+            .iter()
+            .find(|(other, _)| *other == panel)
+            .map(|(_, area)| *area)
+    }
+
+    // The divider whose borders are under the mouse, the corners going to the column.
+    fn divider_at(&self, column: u16, row: u16) -> Option<Divider> {
+        let filer = self.panel_area(FocusState::Filer)?;
+        let commit = self.panel_area(FocusState::Commit)?;
+        let viewer = self.panel_area(FocusState::Viewer)?;
+        if (column + 1 == filer.right() || column == viewer.x)
+            && (commit.y..viewer.bottom()).contains(&row)
+        {
+            Some(Divider::Column)
+        } else if (row + 1 == commit.bottom() || row == viewer.y)
+            && (viewer.x..viewer.right()).contains(&column)
+        {
+            Some(Divider::Commit)
+        } else {
+            None
+        }
+    }
+
+    // Moves the dragged divider to the mouse, within the bounds the panels keep usable.
+    fn drag(&mut self, column: u16, row: u16) {
+        let (Some(divider), Some(commit), Some(viewer)) = (
+            self.dragging,
+            self.panel_area(FocusState::Commit),
+            self.panel_area(FocusState::Viewer),
+        ) else {
+            return;
+        };
+        // rounded up, so the layout puts the border back under the mouse
+        let percentage = |position: u16, start: u16, end: u16| {
+            let total = end.saturating_sub(start).max(1) as u32;
+            ((position.saturating_sub(start) as u32 + 1) * 100).div_ceil(total)
+        };
+        match divider {
+            Divider::Column => {
+                let left = percentage(column, 0, viewer.right()) as u16;
+                self.left_main_chunk_percentage =
+                    left.clamp(LEFT_PERCENTAGES.0, LEFT_PERCENTAGES.1);
+            }
+            Divider::Commit => {
+                let share = percentage(row, commit.y, viewer.bottom()) as u16;
+                self.commit_percentage =
+                    Some(share.clamp(COMMIT_PERCENTAGES.0, COMMIT_PERCENTAGES.1));
+            }
+        }
+    }
+
+    // Keeps the sizes a drag ends with for the next run.
+    fn finish_drag(&mut self) {
+        if self.dragging.take().is_none() {
+            return;
+        }
+        self.layout.left = Some(self.left_main_chunk_percentage);
+        self.layout.commit = self.commit_percentage;
+        if let Err(e) = self.layout.record() {
+            self.report(&GviewError::new("Failed to save the panel sizes", e));
+        }
+    }
+
+    /// Restores the panel sizes last set by dragging their dividers.
+    pub fn restore_layout(&mut self) {
+        self.layout = state::PanelLayout::for_user();
+        if let Some(left) = self.layout.left {
+            self.left_main_chunk_percentage = left.clamp(LEFT_PERCENTAGES.0, LEFT_PERCENTAGES.1);
+        }
+        self.commit_percentage = self
+            .layout
+            .commit
+            .map(|share| share.clamp(COMMIT_PERCENTAGES.0, COMMIT_PERCENTAGES.1));
+    }
+
+    fn panel_at(&self, column: u16, row: u16) -> Option<FocusState> {
+        let position = Position::new(column, row);
+        self.panels
+            .iter()
+            .find(|(_, area)| area.contains(position))
+            .map(|(panel, _)| *panel)
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame) -> anyhow::Result<()> {
+        let screen_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(frame.size());
+
+        // While a commit is previewed the commit modal docks on a widened left side
+        let previewing = self.commit_modal.is_open() && self.content_viewer.is_previewing();
+        let left_percentage = if previewing {
+            self.left_main_chunk_percentage.max(PREVIEW_LEFT_PERCENTAGE)
+        } else {
+            self.left_main_chunk_percentage
+        };
+        let main_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(left_percentage),
+                Constraint::Percentage((100_u16).saturating_sub(left_percentage)),
+            ])
+            .split(screen_chunks[0]);
+
+        let left_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(self.filer.min_height()),
+            ])
+            .split(main_chunks[0]);
+
+        // the commit panel grows with the message, up to half of the column, or takes the
+        // share its divider was dragged to, as long as the content viewer keeps the rows it
+        // needs
+        let content_height = self.content_viewer.min_height();
+        let commit_height = match self.commit_percentage {
+            Some(share) => ((main_chunks[1].height as u32 * share as u32 / 100) as u16)
+                .max(self.commit_viewer.min_height()),
+            None => self
+                .commit_viewer
+                .height(main_chunks[1].width, main_chunks[1].height / 2),
+        };
+        let commit_height = commit_height.min(
+            main_chunks[1]
+                .height
+                .saturating_sub(content_height)
+                .max(self.commit_viewer.min_height()),
+        );
+        let right_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(commit_height),
+                Constraint::Min(content_height),
+            ])
+            .split(main_chunks[1]);
+
+        let slots = [
+            (Slot::Panel(FocusState::Filter), left_chunks[0]),
+            (Slot::Panel(FocusState::Filer), left_chunks[1]),
+            (Slot::Panel(FocusState::Commit), right_chunks[0]),
+            (Slot::Panel(FocusState::Viewer), right_chunks[1]),
+            (Slot::StatusBar, screen_chunks[1]),
+        ];
+        self.commit_viewer.set_checks(self.checks_modal.summary());
+        self.panels.clear();
+        for (id, slot) in REGISTRY {
+            let Some((_, area)) = slots.into_iter().find(|(other, _)| *other == slot) else {
+                continue;
+            };
+            // the status bar tells where the panels drawn before it are
+            if id == ComponentId::StatusBar {
+                let position = self.position();
+                self.status_bar.set_position(position);
+            }
+            self.component(id).draw(frame, area);
+            if let Slot::Panel(focus) = slot {
+                self.panels.push((focus, area));
+            }
+        }
+
+        // Draw modals on top if they're open
+        self.commit_modal.dock(previewing.then_some(main_chunks[0]));
+        let area = frame.size();
+        for id in modals().into_iter().rev() {
+            self.component(id).draw(frame, area);
+        }
+
+        self.screen = frame.buffer_mut().clone();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_focus_state_next_transitions() {
+        assert_eq!(FocusState::Filter.next(), FocusState::Filer);
+        assert_eq!(FocusState::Filer.next(), FocusState::Commit);
+        assert_eq!(FocusState::Commit.next(), FocusState::Viewer);
+        assert_eq!(FocusState::Viewer.next(), FocusState::Filter);
+    }
+
+    #[test]
+    fn test_focus_state_cycle_complete() {
+        let mut state = FocusState::Filter;
+
+        // Test complete cycle
+        state = state.next();
+        assert_eq!(state, FocusState::Filer);
+
+        state = state.next();
+        assert_eq!(state, FocusState::Commit);
+
+        state = state.next();
+        assert_eq!(state, FocusState::Viewer);
+
+        state = state.next();
+        assert_eq!(state, FocusState::Filter); // Back to start
+    }
+
+    #[test]
+    fn test_registry_slots() {
+        // every panel the focus moves to is registered once
+        let mut focus = FocusState::Filter;
+        for _ in 0..4 {
+            let registered = REGISTRY
+                .iter()
+                .filter(|(_, slot)| *slot == Slot::Panel(focus))
+                .count();
+            assert_eq!(registered, 1, "{:?}", focus);
+            focus = focus.next();
+        }
+        // modals are ranked without ties
+        let ranks: std::collections::HashSet<u8> = REGISTRY
+            .iter()
+            .filter_map(|(_, slot)| match slot {
+                Slot::Modal(rank) => Some(*rank),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(ranks.len(), modals().len());
+        assert_eq!(
+            modals()[..2],
+            [ComponentId::PromptModal, ComponentId::PickerModal]
+        );
+
+        let mut app = create_test_app();
+        assert!(app.open_modal().is_none());
+        app.handle_message(Message::Once(OnceOperation::ShowHelpModal));
+        assert!(app.open_modal().is_some());
+    }
+
+    #[test]
+    fn test_app_draw_normal_state() {
+        use insta::assert_snapshot;
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let mut app = create_test_app();
+
+        let backend = TestBackend::new(120, 40);
         let mut terminal = Terminal::new(backend).unwrap();
 
         terminal
@@ -424,59 +1658,34 @@ mod tests {
     }
 
     #[test]
-    fn test_app_draw_help_modal_large_terminal() {
-        use crate::repository::RepositoryInfo;
+    fn test_app_draw_with_help_modal_open() {
         use insta::assert_snapshot;
         use ratatui::{backend::TestBackend, Terminal};
-        use std::env;
-        use std::time::{SystemTime, UNIX_EPOCH};
 
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos();
-        let random_suffix = std::process::id();
-        let test_dir = env::temp_dir().join(format!(
-            "gview_app_large_test_{}_{}",
-            timestamp, random_suffix
-        ));
-        let _ = std::fs::remove_dir_all(&test_dir);
-        std::fs::create_dir_all(&test_dir).unwrap();
+        let mut app = create_test_app();
 
-        let repo = git2::Repository::init(&test_dir).unwrap();
+        // Open help modal
+        app.handle_message(Message::Once(OnceOperation::ShowHelpModal));
+
+        let backend = TestBackend::new(120, 40);
+        let mut terminal = Terminal::new(backend).unwrap();
 
-        // Create a test file
-        let test_file_path = test_dir.join("test.txt");
-        std::fs::write(&test_file_path, "Hello, world!").unwrap();
+        terminal
+            .draw(|frame| {
+                let _ = app.draw(frame);
+            })
+            .unwrap();
 
-        let signature = git2::Signature::new(
-            "Test User",
-            "test@localhost",
-            &git2::Time::new(1234567890, 0),
-        )
-        .unwrap();
-        let tree_id = {
-            let mut index = repo.index().unwrap();
-            index.add_path(std::path::Path::new("test.txt")).unwrap();
-            index.write().unwrap();
-            index.write_tree().unwrap()
-        };
-        let tree = repo.find_tree(tree_id).unwrap();
-
-        let _ = repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            "Initial commit",
-            &tree,
-            &[],
-        );
+        let buffer = terminal.backend().buffer();
+        assert_snapshot!(format!("{:?}", buffer));
+    }
 
-        drop(tree);
-        let oid = repo.head().unwrap().target().unwrap();
-        let repo_info = RepositoryInfo::_from_parts(repo, oid);
+    #[test]
+    fn test_app_draw_help_modal_large_terminal() {
+        use insta::assert_snapshot;
+        use ratatui::{backend::TestBackend, Terminal};
 
-        let mut app = App::new(repo_info);
+        let mut app = create_test_app();
 
         // Open help modal
         app.handle_message(Message::Once(OnceOperation::ShowHelpModal));
@@ -496,72 +1705,1173 @@ mod tests {
 
     #[test]
     fn test_app_draw_help_modal_small_terminal() {
-        use crate::repository::RepositoryInfo;
         use insta::assert_snapshot;
         use ratatui::{backend::TestBackend, Terminal};
-        use std::env;
-        use std::time::{SystemTime, UNIX_EPOCH};
 
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
+        let mut app = create_test_app();
+
+        // Open help modal
+        app.handle_message(Message::Once(OnceOperation::ShowHelpModal));
+
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                let _ = app.draw(frame);
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        assert_snapshot!(format!("{:?}", buffer));
+    }
+
+    fn create_test_app() -> App {
+        create_test_app_with_files(&[("test.txt", "Hello, world!")])
+    }
+
+    fn create_test_app_with_files(files: &[(&str, &str)]) -> App {
+        create_test_app_with_commits(&[files])
+    }
+
+    // one commit per entry, each on top of the previous
+    fn create_test_app_with_commits(commits: &[&[(&str, &str)]]) -> App {
+        create_test_app_with_repo(create_test_repo(commits))
+    }
+
+    fn create_test_app_with_repo(repo: git2::Repository) -> App {
+        let oid = repo.head().unwrap().target().unwrap();
+        App::new(
+            crate::repository::RepositoryInfo::_from_parts(repo, oid),
+            Config::default(),
+        )
+    }
+
+    fn create_test_repo(commits: &[&[(&str, &str)]]) -> git2::Repository {
+        crate::components::init_test_repo_with_commits("app", commits)
+    }
+
+    #[test]
+    fn test_app_base_ref_prompt() {
+        let mut app = create_test_app();
+        app.handle_message(Message::Once(OnceOperation::OpenPrompt {
+            kind: PromptKind::BaseRef,
+        }));
+        assert!(app.prompt_modal.is_open());
+
+        // An unknown ref keeps the prompt open
+        app.handle_prompt(PromptKind::BaseRef, "no-such-ref");
+        assert!(app.prompt_modal.is_open());
+        assert!(app
+            .commit_viewer
+            .repository
+            .lock()
+            .unwrap()
+            .base_ref()
+            .is_none());
+
+        app.handle_prompt(PromptKind::BaseRef, "HEAD");
+        assert!(!app.prompt_modal.is_open());
+        assert!(app
+            .commit_viewer
+            .repository
+            .lock()
+            .unwrap()
+            .base_ref()
+            .is_some());
+
+        // An empty value clears the base again
+        app.handle_prompt(PromptKind::BaseRef, "");
+        assert!(app
+            .commit_viewer
+            .repository
+            .lock()
+            .unwrap()
+            .base_ref()
+            .is_none());
+    }
+
+    #[test]
+    fn test_app_compare_refs_prompt() {
+        let mut app = create_test_app();
+        app.handle_message(Message::Once(OnceOperation::OpenPrompt {
+            kind: PromptKind::CompareRefs,
+        }));
+
+        // Both sides are required
+        app.handle_prompt(PromptKind::CompareRefs, "HEAD");
+        assert!(app.prompt_modal.is_open());
+        // and a third dot is not taken as part of the second ref
+        app.handle_prompt(PromptKind::CompareRefs, "HEAD...HEAD");
+        assert!(app.prompt_modal.is_open());
+        let mut terminal = Terminal::new(TestBackend::new(140, 30)).unwrap();
+        terminal
+            .draw(|frame| {
+                let _ = app.draw(frame);
+            })
+            .unwrap();
+        assert!(format!("{:?}", terminal.backend().buffer()).contains("not A...B"));
+        assert!(app
+            .commit_viewer
+            .repository
+            .lock()
+            .unwrap()
+            .base_ref()
+            .is_none());
+
+        app.handle_prompt(PromptKind::CompareRefs, "HEAD..HEAD");
+        assert!(!app.prompt_modal.is_open());
+        {
+            let repo = app.commit_viewer.repository.lock().unwrap();
+            assert!(repo.base_ref().is_some());
+            assert_eq!(repo.compare_target(), repo.base_ref());
+        }
+
+        app.handle_prompt(PromptKind::CompareRefs, "");
+        let repo = app.commit_viewer.repository.lock().unwrap();
+        assert!(repo.base_ref().is_none());
+        assert!(repo.compare_target().is_none());
+    }
+
+    #[test]
+    fn test_app_prefix_prompt() {
+        let mut app = create_test_app_with_files(&[
+            ("README.md", "# readme\n"),
+            ("src/main.rs", "fn main() {}\n"),
+        ]);
+        app.handle_message(Message::Once(OnceOperation::OpenPrompt {
+            kind: PromptKind::Prefix,
+        }));
+        app.handle_prompt(PromptKind::Prefix, "nope");
+        assert!(app.prompt_modal.is_open());
+
+        app.handle_prompt(PromptKind::Prefix, "src");
+        assert!(!app.prompt_modal.is_open());
+        assert_eq!(
+            app.content_viewer.current_location(),
+            Some(("src/main.rs".to_owned(), 1))
+        );
+    }
+
+    #[test]
+    fn test_app_containment_query() {
+        let mut app = create_test_app_with_files(&[("README.md", "# readme\n")]);
+        app.handle_message(Message::Once(OnceOperation::OpenPrompt {
+            kind: PromptKind::ContainedIn,
+        }));
+        app.handle_prompt(PromptKind::ContainedIn, "");
+        assert!(app.prompt_modal.is_open());
+
+        app.handle_prompt(PromptKind::ContainedIn, "HEAD");
+        assert!(!app.prompt_modal.is_open());
+        let commit_id = app
+            .commit_viewer
+            .repository
+            .lock()
             .unwrap()
-            .as_nanos();
-        let random_suffix = std::process::id();
-        let test_dir = env::temp_dir().join(format!(
-            "gview_app_small_test_{}_{}",
-            timestamp, random_suffix
+            .get_current_commit_id();
+        assert_eq!(
+            app.status_bar.notice().map(str::to_owned),
+            Some(format!("{} is in HEAD", &commit_id[..8]))
+        );
+    }
+
+    #[test]
+    fn test_app_trailer_filter() {
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let mut app = create_test_app();
+        let screen = |app: &mut App| {
+            let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+            terminal
+                .draw(|frame| {
+                    let _ = app.draw(frame);
+                })
+                .unwrap();
+            let buffer = terminal.backend().buffer().clone();
+            buffer
+                .content()
+                .iter()
+                .map(|cell| cell.symbol())
+                .collect::<String>()
+        };
+        app.handle_message(Message::Once(OnceOperation::OpenCommitModal));
+        let message = app.process_events(KeyCode::Char('t'));
+        assert_eq!(
+            message,
+            Message::Once(OnceOperation::OpenPrompt {
+                kind: PromptKind::TrailerFilter
+            })
+        );
+        app.handle_message(message);
+
+        // the history is walked in the background, and Esc stops the walk
+        app.handle_prompt(PromptKind::TrailerFilter, "Reviewed-by");
+        assert!(!app.prompt_modal.is_open());
+        let key = |code| event::KeyEvent::new(code, event::KeyModifiers::NONE);
+        app.handle_key(key(KeyCode::Esc));
+        assert!(app.trailer_walk.is_none());
+        assert!(app.commit_modal.is_open());
+        assert_eq!(app.status_bar.notice(), Some("Stopped walking the history"));
+        assert!(screen(&mut app).contains("All Commit History"));
+
+        app.handle_prompt(PromptKind::TrailerFilter, "Reviewed-by");
+        app.wait_trailer_walk();
+        let filtered = screen(&mut app);
+        assert!(filtered.contains("Commits with trailer \"Reviewed-by\""));
+        assert!(filtered.contains("No commits found"));
+
+        app.handle_prompt(PromptKind::TrailerFilter, "");
+        let all = screen(&mut app);
+        assert!(all.contains("All Commit History"));
+        assert!(!all.contains("No commits found"));
+    }
+
+    #[test]
+    fn test_app_search_commit_modal() {
+        let mut app = create_test_app_with_commits(&[
+            &[("a.txt", "a")],
+            &[("a.txt", "b")],
+            &[("a.txt", "c")],
+        ]);
+        let key = |code| event::KeyEvent::new(code, event::KeyModifiers::NONE);
+        // rows of the commit list, right inside the modal's border
+        let row = regex::Regex::new("│(→ |  )[0-9a-f]{8} ").unwrap();
+        let listed = |app: &mut App| row.find_iter(&app.run_script(&[], 80, 24)).count();
+        app.handle_message(Message::Once(OnceOperation::OpenCommitModal));
+        assert_eq!(listed(&mut app), 3);
+
+        // keys that are panel shortcuts elsewhere are typed into the query
+        for code in [
+            KeyCode::Char('/'),
+            KeyCode::Char('>'),
+            KeyCode::Backspace,
+            KeyCode::Char('2'),
+        ] {
+            app.handle_key(key(code));
+        }
+        assert!(app.commit_modal.is_editing());
+        assert_eq!(app.left_main_chunk_percentage, 15);
+        let screen = app.run_script(&[], 80, 24);
+        assert!(screen.contains("Partial Match: 2"));
+        assert!(screen.contains("Commit 2"));
+        assert_eq!(listed(&mut app), 1);
+        assert!(matches!(
+            app.process_events(KeyCode::Enter),
+            Message::Once(OnceOperation::SetCommitById { .. })
         ));
-        let _ = std::fs::remove_dir_all(&test_dir);
-        std::fs::create_dir_all(&test_dir).unwrap();
 
-        let repo = git2::Repository::init(&test_dir).unwrap();
+        // the author is searched too, and Esc lists every commit again
+        app.handle_key(key(KeyCode::Backspace));
+        for c in "Test User".chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+        assert_eq!(listed(&mut app), 3);
+        app.handle_key(key(KeyCode::Char('x')));
+        assert_eq!(listed(&mut app), 0);
+        app.handle_key(key(KeyCode::Esc));
+        assert!(!app.commit_modal.is_editing());
+        assert!(app.commit_modal.is_open());
+        assert_eq!(listed(&mut app), 3);
+    }
+
+    #[test]
+    fn test_app_layout_at_several_sizes() {
+        let mut app = create_test_app_with_files(&[
+            ("src/main.rs", "fn main() {\n    println!(\"hello\");\n}\n"),
+            ("README.md", "# test\n"),
+        ]);
+        app.handle_message(Message::Once(OnceOperation::OpenLocation {
+            commit_id: None,
+            file: "src/main.rs".to_owned(),
+            line: 1,
+        }));
+        for (width, height) in [(60, 10), (80, 24), (120, 40)] {
+            let screen = app.run_script(&[], width, height);
+            insta::assert_snapshot!(format!("layout_{}x{}", width, height), screen);
+        }
+    }
+
+    #[test]
+    fn test_app_date_range_prompt() {
+        let mut app = create_test_app();
+        app.handle_message(Message::Once(OnceOperation::OpenCommitModal));
+        let message = app.process_events(KeyCode::Char('d'));
+        assert_eq!(
+            message,
+            Message::Once(OnceOperation::OpenPrompt {
+                kind: PromptKind::DateRange
+            })
+        );
+        app.handle_message(message);
+
+        // the test commit is from 2009-02-13
+        app.handle_prompt(PromptKind::DateRange, "2009-02-14");
+        assert!(app.prompt_modal.is_open());
+        app.handle_prompt(PromptKind::DateRange, "2009-02-14..");
+        assert!(!app.prompt_modal.is_open());
+        let screen = app.run_script(&[], 100, 24);
+        assert!(screen.contains("Commits from 2009-02-14.."));
+        assert!(screen.contains("No commits found"));
+
+        app.handle_prompt(PromptKind::DateRange, "..2009-02-13");
+        let screen = app.run_script(&[], 100, 24);
+        assert!(!screen.contains("No commits found"));
+        app.handle_prompt(PromptKind::DateRange, "");
+        assert!(app.run_script(&[], 100, 24).contains("All Commit History"));
+    }
 
-        // Create a test file
-        let test_file_path = test_dir.join("test.txt");
-        std::fs::write(&test_file_path, "Hello, world!").unwrap();
+    #[test]
+    fn test_app_prompt_captures_keys() {
+        let mut app = create_test_app();
+        app.handle_message(Message::Once(OnceOperation::OpenPrompt {
+            kind: PromptKind::BaseRef,
+        }));
+        let message = app.process_events(KeyCode::Esc);
+        assert_eq!(message, Message::Once(OnceOperation::ClosePrompt));
+        app.handle_message(message);
+        assert!(!app.prompt_modal.is_open());
+    }
 
-        let signature = git2::Signature::new(
-            "Test User",
-            "test@localhost",
-            &git2::Time::new(1234567890, 0),
-        )
-        .unwrap();
-        let tree_id = {
-            let mut index = repo.index().unwrap();
-            index.add_path(std::path::Path::new("test.txt")).unwrap();
-            index.write().unwrap();
-            index.write_tree().unwrap()
-        };
-        let tree = repo.find_tree(tree_id).unwrap();
-
-        let _ = repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            "Initial commit",
-            &tree,
-            &[],
+    #[test]
+    fn test_app_open_modals_take_keys_in_order() {
+        let mut app = create_test_app();
+        app.handle_message(Message::Once(OnceOperation::OpenCommitModal));
+        app.handle_message(Message::Once(OnceOperation::OpenPrompt {
+            kind: PromptKind::TrailerFilter,
+        }));
+        // the prompt opened over the commit modal takes the keys first
+        let message = app.process_events(KeyCode::Esc);
+        assert_eq!(message, Message::Once(OnceOperation::ClosePrompt));
+        app.handle_message(message);
+        assert_eq!(
+            app.process_events(KeyCode::Esc),
+            Message::Once(OnceOperation::CloseCommitModal)
         );
 
-        drop(tree);
-        let oid = repo.head().unwrap().target().unwrap();
-        let repo_info = RepositoryInfo::_from_parts(repo, oid);
+        // no jumping to a definition under a modal
+        app.focus_state = FocusState::Viewer;
+        assert!(!app.can_jump());
+        app.handle_message(Message::Once(OnceOperation::CloseCommitModal));
+        assert!(app.open_modal().is_none());
+        assert!(app.can_jump());
+    }
 
-        let mut app = App::new(repo_info);
+    #[test]
+    fn test_app_note_and_jump_to_location() {
+        let mut app = create_test_app();
+        app.handle_message(Message::Once(OnceOperation::OpenPrompt {
+            kind: PromptKind::Note,
+        }));
+        app.handle_prompt(PromptKind::Note, "greeting is too loud");
+        assert!(!app.prompt_modal.is_open());
+        {
+            let repo = app.commit_viewer.repository.lock().unwrap();
+            assert_eq!(repo.notes().len(), 1);
+            assert_eq!(repo.notes()[0].path, "test.txt");
+            assert_eq!(repo.notes()[0].line, 1);
+        }
+        assert!(app.content_viewer.current_location().is_some());
+
+        app.handle_message(Message::Once(OnceOperation::OpenNotesModal));
+        assert!(app.notes_modal.is_open());
+        let message = app.process_events(KeyCode::Enter);
+        app.handle_message(message);
+        assert!(!app.notes_modal.is_open());
+        assert_eq!(app.focus_state, FocusState::Viewer);
+        assert_eq!(
+            app.content_viewer.current_location(),
+            Some(("test.txt".to_owned(), 1))
+        );
+    }
 
-        // Open help modal
-        app.handle_message(Message::Once(OnceOperation::ShowHelpModal));
+    #[test]
+    fn test_app_picker_jumps_to_line() {
+        let mut app = create_test_app_with_files(&[
+            ("main.rs", "fn main() {\n    helper();\n}\n"),
+            ("util.rs", "// helpers\nfn helper() {}\n"),
+        ]);
+        app.handle_message(Message::Once(OnceOperation::OpenPicker));
+        assert!(app.picker_modal.is_open());
+        assert!(!app.can_jump());
+
+        for c in "fn helper".chars() {
+            let message = app.process_events(KeyCode::Char(c));
+            app.handle_message(message);
+        }
+        app.picker_modal.wait();
+        let message = app.process_events(KeyCode::Enter);
+        app.handle_message(message);
+        assert!(!app.picker_modal.is_open());
+        assert_eq!(app.focus_state, FocusState::Viewer);
+        assert_eq!(
+            app.content_viewer.current_location(),
+            Some(("util.rs".to_owned(), 2))
+        );
+    }
 
-        let backend = TestBackend::new(80, 24);
-        let mut terminal = Terminal::new(backend).unwrap();
+    #[test]
+    fn test_app_breadcrumb_scopes_filer() {
+        let mut app = create_test_app_with_files(&[
+            ("README.md", "# readme\n"),
+            ("src/main.rs", "fn main() {}\n"),
+        ]);
+        app.handle_message(Message::Once(OnceOperation::OpenLocation {
+            commit_id: None,
+            file: "src/main.rs".to_owned(),
+            line: 1,
+        }));
+        for code in [KeyCode::Char('['), KeyCode::Enter] {
+            let message = app.process_events(code);
+            app.handle_message(message);
+        }
+        assert_eq!(app.focus_state, FocusState::Filer);
+        assert_eq!(
+            app.commit_viewer.repository.lock().unwrap().prefix(),
+            Some("src")
+        );
+
+        // a scope that cannot be taken is reported and keeps the current one
+        app.handle_message(Message::Once(OnceOperation::ScopeTo {
+            prefix: "README.md".to_owned(),
+        }));
+        assert_eq!(
+            app.status_bar.notice(),
+            Some("Cannot scope to README.md: README.md is not a directory")
+        );
+        assert_eq!(
+            app.commit_viewer.repository.lock().unwrap().prefix(),
+            Some("src")
+        );
+    }
+
+    #[test]
+    fn test_app_jump_to_definition_and_back() {
+        let mut app = create_test_app_with_files(&[
+            ("main.rs", "fn main() {\n    helper();\n}\n"),
+            ("util.rs", "// helpers\nfn helper() {}\n"),
+        ]);
+        app.handle_message(Message::Once(OnceOperation::OpenLocation {
+            commit_id: None,
+            file: "main.rs".to_owned(),
+            line: 2,
+        }));
+        assert_eq!(
+            app.content_viewer.word_at_cursor(),
+            Some("helper".to_owned())
+        );
+
+        app.jump_to_definition();
+        assert_eq!(
+            app.content_viewer.current_location(),
+            Some(("util.rs".to_owned(), 2))
+        );
+        assert_eq!(app.jump_stack.len(), 1);
+
+        // On the definition itself there is nowhere else to go
+        app.jump_to_definition();
+        assert_eq!(app.jump_stack.len(), 1);
+
+        app.jump_back();
+        assert_eq!(
+            app.content_viewer.current_location(),
+            Some(("main.rs".to_owned(), 2))
+        );
+        assert!(app.jump_stack.is_empty());
+        app.jump_back();
+        assert_eq!(
+            app.content_viewer.current_location(),
+            Some(("main.rs".to_owned(), 2))
+        );
+    }
+
+    #[test]
+    fn test_app_export_screen() {
+        use ratatui::{backend::TestBackend, Terminal};
 
+        let mut app = create_test_app();
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
         terminal
             .draw(|frame| {
                 let _ = app.draw(frame);
             })
             .unwrap();
+        app.capture_screen();
+        assert!(app.prompt_modal.is_open());
+        // the prompt drawn over the screen is not saved
+        terminal
+            .draw(|frame| {
+                let _ = app.draw(frame);
+            })
+            .unwrap();
+
+        let path = std::env::temp_dir().join(format!(
+            "gview_screen_export_{}_{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        let text_path = path.with_extension("txt");
+        app.handle_prompt(PromptKind::ExportScreen, text_path.to_str().unwrap());
+        assert!(!app.prompt_modal.is_open());
+        let text = std::fs::read_to_string(&text_path).unwrap();
+        assert_eq!(text.lines().count(), 24);
+        assert!(text.contains("test.txt"));
+        assert!(!text.contains("Save the screen"));
+
+        let ansi_path = path.with_extension("ans");
+        app.handle_prompt(PromptKind::ExportScreen, ansi_path.to_str().unwrap());
+        let ansi = std::fs::read_to_string(&ansi_path).unwrap();
+        assert!(ansi.contains("\x1b["));
+        assert!(ansi.contains("test.txt"));
+    }
+
+    #[test]
+    fn test_app_light_theme() {
+        use ratatui::{backend::TestBackend, style::Color, Terminal};
 
+        let mut app = create_test_app();
+        app.set_theme(Theme::light());
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal
+            .draw(|frame| {
+                let _ = app.draw(frame);
+            })
+            .unwrap();
         let buffer = terminal.backend().buffer();
-        assert_snapshot!(format!("{:?}", buffer));
+        let colors: Vec<Color> = buffer.content.iter().map(|cell| cell.fg).collect();
+        // unfocused panels use the light gray instead of the dark one
+        assert!(colors.contains(&Theme::light().unfocused));
+        assert!(!colors.contains(&Theme::dark().unfocused));
+        // the focused filter keeps the color of its mode
+        assert_eq!(buffer.get(0, 0).fg, Theme::light().partial_match);
+    }
+
+    #[test]
+    fn test_app_open_in_browser_without_remote() {
+        let mut app = create_test_app();
+        app.handle_message(Message::Once(OnceOperation::OpenInBrowser {
+            file: "test.txt".to_owned(),
+            line: 1,
+        }));
+        // the failure shows up in the status bar rather than nowhere
+        assert!(app
+            .status_bar
+            .notice()
+            .is_some_and(|notice| notice.starts_with("Cannot open in the browser")));
+    }
+
+    #[test]
+    fn test_app_pick_remote_for_link() {
+        let repo = create_test_repo(&[&[("test.txt", "Hello, world!")]]);
+        // URLs no forge is found in, so the link fails instead of opening a browser
+        repo.remote("upstream", "not-a-forge").unwrap();
+        repo.remote("fork", "not-a-forge-either").unwrap();
+        let mut app = create_test_app_with_repo(repo);
+        app.handle_message(Message::Once(OnceOperation::OpenIssue {
+            reference: "#12".to_owned(),
+        }));
+        assert!(app.remote_modal.is_open());
+        assert_eq!(app.status_bar.notice(), None);
+
+        // picking one follows the link with it
+        app.handle_key(event::KeyEvent::new(
+            KeyCode::Enter,
+            event::KeyModifiers::NONE,
+        ));
+        assert!(!app.remote_modal.is_open());
+        assert!(app.link_awaiting_remote.is_none());
+        assert!(app
+            .status_bar
+            .notice()
+            .is_some_and(|notice| notice.starts_with("Cannot open in the browser")));
+
+        // once picked, links go straight to it
+        app.handle_message(Message::Once(OnceOperation::OpenIssue {
+            reference: "#12".to_owned(),
+        }));
+        assert!(!app.remote_modal.is_open());
+    }
+
+    #[test]
+    fn test_app_open_issue_without_remote() {
+        let mut app = create_test_app();
+        app.handle_message(Message::Once(OnceOperation::OpenIssue {
+            reference: "#12".to_owned(),
+        }));
+        assert!(app
+            .status_bar
+            .notice()
+            .is_some_and(|notice| notice.starts_with("Cannot open in the browser")));
+    }
+
+    #[test]
+    fn test_app_error_shows_in_status_bar() {
+        let mut app = create_test_app();
+        let path = std::env::temp_dir()
+            .join(format!("gview_app_error_log_{}", std::process::id()))
+            .join("errors.log");
+        app.set_error_log(Some(path.clone()));
+        app.handle_message(Message::Error(GviewError::unavailable("Failed to copy")));
+        assert_eq!(app.status_bar.notice(), Some("Failed to copy"));
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .trim_end()
+            .ends_with(" Failed to copy"));
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_app_dismiss_errors() {
+        let mut app = create_test_app();
+        let esc = event::KeyEvent::new(KeyCode::Esc, event::KeyModifiers::NONE);
+        app.handle_message(Message::Error(GviewError::unavailable("Failed to copy")));
+        app.handle_message(Message::Error(GviewError::unavailable("Failed to open")));
+        assert_eq!(app.status_bar.notice(), Some("Failed to copy"));
+
+        // an open modal takes Esc before the errors do
+        app.handle_message(Message::Once(OnceOperation::ShowHelpModal));
+        app.handle_key(esc);
+        assert!(!app.help_modal.is_open());
+        assert_eq!(app.status_bar.notice(), Some("Failed to copy"));
+
+        app.handle_key(esc);
+        assert_eq!(app.status_bar.notice(), Some("Failed to open"));
+        app.handle_key(esc);
+        assert_eq!(app.status_bar.notice(), None);
+    }
+
+    #[test]
+    fn test_app_mouse() {
+        use crossterm::event::KeyModifiers;
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let long: String = (1..=60)
+            .map(|number| format!("line {}\n", number))
+            .collect();
+        let mut app = create_test_app_with_files(&[("a.txt", "alpha\n"), ("b.txt", &long)]);
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        let mut draw = |app: &mut App| {
+            terminal
+                .draw(|frame| {
+                    let _ = app.draw(frame);
+                })
+                .unwrap();
+        };
+        let mouse = |kind, column, row| MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        draw(&mut app);
+
+        // the second entry of the file list, below the filter and the list's border
+        app.handle_mouse(mouse(MouseEventKind::Down(MouseButton::Left), 2, 5));
+        assert_eq!(app.focus_state, FocusState::Filer);
+        assert_eq!(
+            app.content_viewer.current_location(),
+            Some(("b.txt".to_owned(), 1))
+        );
+
+        // the wheel scrolls the viewer under the mouse, leaving the focus where it is
+        draw(&mut app);
+        app.handle_mouse(mouse(MouseEventKind::ScrollDown, 40, 15));
+        assert_eq!(app.focus_state, FocusState::Filer);
+        assert_eq!(
+            app.content_viewer.current_location(),
+            Some(("b.txt".to_owned(), 1 + WHEEL_LINES))
+        );
+        app.handle_mouse(mouse(MouseEventKind::ScrollUp, 40, 15));
+        assert_eq!(
+            app.content_viewer.current_location(),
+            Some(("b.txt".to_owned(), 1))
+        );
+
+        // clicks are ignored while a modal is open
+        app.handle_message(Message::Once(OnceOperation::ShowHelpModal));
+        app.handle_mouse(mouse(MouseEventKind::Down(MouseButton::Left), 40, 15));
+        assert_eq!(app.focus_state, FocusState::Filer);
+    }
+
+    #[test]
+    fn test_app_drag_dividers() {
+        use crossterm::event::KeyModifiers;
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let mut app = create_test_app_with_files(&[("a.txt", "alpha\n")]);
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        let mut draw = |app: &mut App| {
+            terminal
+                .draw(|frame| {
+                    let _ = app.draw(frame);
+                })
+                .unwrap();
+        };
+        let mouse = |kind, column, row| MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        draw(&mut app);
+        let area = |app: &App, panel| app.panel_area(panel).unwrap();
+
+        // the left side's border, dragged half way across
+        let border = area(&app, FocusState::Filer).right() - 1;
+        app.handle_mouse(mouse(MouseEventKind::Down(MouseButton::Left), border, 10));
+        assert_eq!(app.dragging, Some(Divider::Column));
+        app.handle_mouse(mouse(MouseEventKind::Drag(MouseButton::Left), 39, 10));
+        assert_eq!(app.left_main_chunk_percentage, 50);
+        app.handle_mouse(mouse(MouseEventKind::Drag(MouseButton::Left), 79, 10));
+        assert_eq!(app.left_main_chunk_percentage, 70);
+        app.handle_mouse(mouse(MouseEventKind::Drag(MouseButton::Left), 39, 10));
+        app.handle_mouse(mouse(MouseEventKind::Up(MouseButton::Left), 39, 10));
+        assert_eq!(app.dragging, None);
+        assert_eq!(app.layout.left, Some(50));
+        draw(&mut app);
+        assert_eq!(area(&app, FocusState::Viewer).x, 40);
+
+        // the commit panel's bottom border follows the mouse
+        let border = area(&app, FocusState::Commit).bottom() - 1;
+        app.handle_mouse(mouse(MouseEventKind::Down(MouseButton::Left), 60, border));
+        assert_eq!(app.dragging, Some(Divider::Commit));
+        app.handle_mouse(mouse(MouseEventKind::Drag(MouseButton::Left), 60, 11));
+        app.handle_mouse(mouse(MouseEventKind::Up(MouseButton::Left), 60, 11));
+        draw(&mut app);
+        assert_eq!(area(&app, FocusState::Commit).bottom(), 12);
+        assert_eq!(app.layout.commit, app.commit_percentage);
+
+        // elsewhere a press is a click
+        app.handle_mouse(mouse(MouseEventKind::Down(MouseButton::Left), 60, 15));
+        assert_eq!(app.dragging, None);
+        assert_eq!(app.focus_state, FocusState::Viewer);
+    }
+
+    #[test]
+    fn test_app_run_script() {
+        let mut app = create_test_app();
+        // complete the filter, focus the file list, open the file and look for a word in it
+        let keys = crate::script::parse("<Tab><Tab><Enter>/world<Enter>").unwrap();
+        let screen = app.run_script(&keys, 80, 24);
+        assert_eq!(screen.lines().count(), 24);
+        assert!(screen.contains("Hello, world!"));
+        assert!(screen.contains("/world (1/1, n/N)"));
+
+        // keys after Ctrl+C are not played
+        let mut app = create_test_app();
+        let keys = crate::script::parse("<C-c>?").unwrap();
+        assert!(!app.run_script(&keys, 80, 24).contains("Global Keys"));
+    }
+
+    #[test]
+    fn test_app_chords() {
+        let mut app =
+            create_test_app_with_files(&[("a.txt", "a\n"), ("b.txt", "b\n"), ("c.txt", "c\n")]);
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        let mut screen = |app: &mut App| {
+            terminal
+                .draw(|frame| {
+                    let _ = app.draw(frame);
+                })
+                .unwrap();
+            screen::to_text(&app.screen)
+        };
+        let press = |app: &mut App, code| {
+            app.handle_key(event::KeyEvent::new(code, event::KeyModifiers::NONE))
+        };
+        for code in [KeyCode::Tab, KeyCode::Down, KeyCode::Down] {
+            press(&mut app, code);
+        }
+        assert!(screen(&mut app).contains(">> c.txt"));
+
+        // the first key waits, shown in the status bar, for the second
+        press(&mut app, KeyCode::Char('g'));
+        let waiting = screen(&mut app);
+        assert!(waiting.contains(">> c.txt"));
+        assert!(waiting
+            .lines()
+            .last()
+            .is_some_and(|line| line.starts_with("g- ")));
+        press(&mut app, KeyCode::Char('g'));
+        let done = screen(&mut app);
+        assert!(done.contains(">> a.txt"));
+        assert!(done.contains("Press ? for help"));
+
+        // any other key lets the first one act on its own before it
+        press(&mut app, KeyCode::Char('g'));
+        press(&mut app, KeyCode::Down);
+        assert!(screen(&mut app).contains(">> b.txt"));
+
+        // Esc drops it, and so does the timeout after letting it act
+        press(&mut app, KeyCode::Char('g'));
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.pending_key, None);
+        press(&mut app, KeyCode::Char('g'));
+        app.expire_pending_key(Instant::now());
+        assert!(app.pending_key.is_some());
+        app.expire_pending_key(Instant::now() + App::CHORD_TIMEOUT);
+        assert_eq!(app.pending_key, None);
+        assert!(screen(&mut app).contains(">> b.txt"));
+    }
+
+    #[test]
+    fn test_app_whats_new() {
+        let mut app = create_test_app_with_files(&[("a.txt", "a\n"), ("b.txt", "b\n")]);
+        app.whats_new_modal.open(changelog::news(Some("0.0.0")));
+        assert!(app.whats_new_modal.is_open());
+        let press = |app: &mut App, code| {
+            app.handle_key(event::KeyEvent::new(code, event::KeyModifiers::NONE))
+        };
+
+        // the modal takes the keys until it is closed
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Down);
+        assert_eq!(app.focus_state, FocusState::Filter);
+        press(&mut app, KeyCode::Esc);
+        assert!(!app.whats_new_modal.is_open());
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.focus_state, FocusState::Filer);
+    }
+
+    #[test]
+    fn test_app_half_page_keys() {
+        let mut app = create_test_app_with_files(&[("a.txt", "a\n"), ("b.txt", "b\n")]);
+        app.focus_state = FocusState::Filer;
+        let ctrl = |app: &mut App, key| {
+            app.handle_key(event::KeyEvent::new(
+                KeyCode::Char(key),
+                event::KeyModifiers::CONTROL,
+            ))
+        };
+        ctrl(&mut app, 'd');
+        assert_eq!(app.filer.selected_path(), Some("b.txt"));
+        ctrl(&mut app, 'u');
+        assert_eq!(app.filer.selected_path(), Some("a.txt"));
+    }
+
+    #[test]
+    fn test_app_go_to_line() {
+        let lines: String = (1..=50)
+            .map(|number| format!("line {}\n", number))
+            .collect();
+        let mut app = create_test_app_with_files(&[("a.txt", &lines)]);
+        app.focus_state = FocusState::Viewer;
+        let message = app.process_events(KeyCode::Char(':'));
+        assert_eq!(
+            message,
+            Message::Once(OnceOperation::OpenPrompt {
+                kind: PromptKind::GoToLine
+            })
+        );
+        app.handle_message(message);
+
+        app.handle_prompt(PromptKind::GoToLine, "forty");
+        assert!(app.prompt_modal.is_open());
+        app.handle_prompt(PromptKind::GoToLine, "42");
+        assert!(!app.prompt_modal.is_open());
+        assert_eq!(
+            app.content_viewer.current_location(),
+            Some(("a.txt".to_owned(), 42))
+        );
+    }
+
+    #[test]
+    fn test_app_poll_blob_fetch() {
+        let mut app = create_test_app_with_files(&[("a.txt", "a\n")]);
+        let finish = |app: &mut App, fetch: anyhow::Result<()>| {
+            app.blob_fetch = Some(("a.txt".to_owned(), std::thread::spawn(move || fetch)));
+            while !app.blob_fetch.as_ref().unwrap().1.is_finished() {
+                std::thread::yield_now();
+            }
+            app.poll_blob_fetch();
+            assert!(app.blob_fetch.is_none());
+        };
+
+        finish(&mut app, Ok(()));
+        assert_eq!(app.status_bar.notice(), Some("Fetched a.txt"));
+        finish(&mut app, Err(anyhow::anyhow!("no promisor remote")));
+        assert!(app
+            .status_bar
+            .notice()
+            .is_some_and(|notice| notice.starts_with("Cannot fetch a.txt")));
+    }
+
+    #[test]
+    fn test_app_inspect_object() {
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let mut app = create_test_app_with_files(&[("a.txt", "a\n"), ("b.txt", "b\n")]);
+        let inspect = |app: &mut App| {
+            app.handle_key(event::KeyEvent::new(
+                KeyCode::Char('o'),
+                event::KeyModifiers::CONTROL,
+            ))
+        };
+
+        // the file list inspects its selection
+        app.focus_state = FocusState::Filer;
+        inspect(&mut app);
+        assert!(app.inspect_modal.is_open());
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal
+            .draw(|frame| {
+                let _ = app.draw(frame);
+            })
+            .unwrap();
+        assert!(format!("{:?}", terminal.backend().buffer()).contains(" blob "));
+        app.handle_key(event::KeyEvent::new(
+            KeyCode::Esc,
+            event::KeyModifiers::NONE,
+        ));
+        assert!(!app.inspect_modal.is_open());
+
+        // the other panels inspect the commit
+        app.focus_state = FocusState::Filter;
+        inspect(&mut app);
+        terminal
+            .draw(|frame| {
+                let _ = app.draw(frame);
+            })
+            .unwrap();
+        assert!(format!("{:?}", terminal.backend().buffer()).contains(" commit "));
+    }
+
+    #[test]
+    fn test_app_language_stats() {
+        let mut app = create_test_app_with_files(&[("a.rs", "fn a() {}\n"), ("b.txt", "b\n")]);
+        app.focus_state = FocusState::Commit;
+        app.handle_key(event::KeyEvent::new(
+            KeyCode::Char('L'),
+            event::KeyModifiers::NONE,
+        ));
+        assert!(app.languages_modal.is_open());
+        let rows = |app: &mut App| {
+            let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+            terminal
+                .draw(|frame| {
+                    let _ = app.draw(frame);
+                })
+                .unwrap();
+            format!("{:?}", terminal.backend().buffer())
+        };
+        // counted in the background, and shown once a tick picks them up
+        while !rows(&mut app).contains("Rust") {
+            app.languages_modal.poll();
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        assert!(rows(&mut app).contains(".txt"));
+
+        // the modal takes the keys of the panel below it
+        app.handle_key(event::KeyEvent::new(
+            KeyCode::Char('q'),
+            event::KeyModifiers::NONE,
+        ));
+        assert!(!app.languages_modal.is_open());
+        assert!(!app.should_exit);
+    }
+
+    #[test]
+    fn test_app_checks_need_shell_out() {
+        let mut app = create_test_app();
+        app.config = Config::parse("[checks]\nenabled = true\n").unwrap();
+        app.disable_shell_out();
+        app.start_checks();
+        // gh is never run, so there is no status to show
+        assert_eq!(app.checks_modal.summary(), None);
+
+        app.shell_out = true;
+        app.start_checks();
+        assert!(app.checks_modal.summary().is_some());
+    }
+
+    #[test]
+    fn test_app_update_check() {
+        let mut app = create_test_app();
+        // off unless the config opts in
+        app.start_update_check();
+        assert!(app.update_check.is_none());
+        // and never under --no-shell-out
+        app.config = Config::parse("update_check = true\n").unwrap();
+        app.disable_shell_out();
+        app.start_update_check();
+        assert!(app.update_check.is_none());
+
+        app.update_check = Some(std::thread::spawn(|| Ok(Some("99.0.0".to_owned()))));
+        while !app.update_check.as_ref().unwrap().is_finished() {
+            std::thread::yield_now();
+        }
+        app.poll_update_check();
+        assert!(app.update_check.is_none());
+        assert_eq!(
+            app.status_bar.notice(),
+            Some(format!("gview 99.0.0 is available (this is {})", changelog::VERSION).as_str())
+        );
+
+        // failures and being up to date stay quiet
+        app.status_bar = StatusBar::new();
+        app.update_check = Some(std::thread::spawn(|| Err(anyhow::anyhow!("offline"))));
+        while !app.update_check.as_ref().unwrap().is_finished() {
+            std::thread::yield_now();
+        }
+        app.poll_update_check();
+        assert_eq!(app.status_bar.notice(), None);
+    }
+
+    #[test]
+    fn test_app_files_changed_in_commit() {
+        let mut app = create_test_app_with_commits(&[
+            &[("a.txt", "a\n"), ("b.txt", "b\n")],
+            &[("a.txt", "changed\n"), ("c.txt", "c\n")],
+        ]);
+        app.left_main_chunk_percentage = 50;
+        let message = app.filer.process_events(KeyCode::Char('c'));
+        app.handle_message(message);
+        let mut items = app.filer.items().to_vec();
+        items.sort();
+        assert_eq!(items, vec!["a.txt", "c.txt"]);
+        let screen = app.run_script(&[], 80, 24);
+        assert!(screen.contains("2 changed files in this commit"));
+        assert!(screen.contains("M a.txt"));
+        assert!(screen.contains("A c.txt"));
+
+        // follows the viewed commit
+        let parent = app
+            .commit_viewer
+            .repository
+            .lock()
+            .unwrap()
+            .parents()
+            .unwrap()[0]
+            .0
+            .clone();
+        app.handle_message(Message::Once(OnceOperation::SetCommitById {
+            commit_id: parent,
+        }));
+        let mut items = app.filer.items().to_vec();
+        items.sort();
+        assert_eq!(items, vec!["a.txt", "b.txt"]);
+
+        let message = app.filer.process_events(KeyCode::Char('c'));
+        app.handle_message(message);
+        assert_eq!(app.filer.items().len(), 2);
+        let screen = app.run_script(&[], 80, 24);
+        assert!(!screen.contains("changed files"));
+        assert!(!screen.contains("A a.txt"));
+    }
+
+    #[test]
+    fn test_app_switch_to_previous_commit() {
+        let mut app =
+            create_test_app_with_commits(&[&[("a.txt", "first\n")], &[("a.txt", "second\n")]]);
+        let (head, parent) = {
+            let repo = app.commit_viewer.repository.lock().unwrap();
+            let head = repo.get_current_commit_id();
+            let parent = repo.parents().unwrap()[0].0.clone();
+            (head, parent)
+        };
+        // nothing viewed before the first commit
+        app.switch_to_previous_commit();
+        assert_eq!(app.viewed_commit, head);
+
+        app.handle_message(Message::Once(OnceOperation::OpenLocation {
+            commit_id: None,
+            file: "a.txt".to_owned(),
+            line: 1,
+        }));
+        app.handle_message(Message::Once(OnceOperation::SetCommitById {
+            commit_id: parent.clone(),
+        }));
+        assert_eq!(app.viewed_commit, parent);
+
+        // toggles back and forth, keeping the open file
+        app.switch_to_previous_commit();
+        assert_eq!(app.viewed_commit, head);
+        assert_eq!(
+            app.content_viewer.current_location(),
+            Some(("a.txt".to_owned(), 1))
+        );
+        app.switch_to_previous_commit();
+        assert_eq!(app.viewed_commit, parent);
+        assert_eq!(
+            app.content_viewer.current_location(),
+            Some(("a.txt".to_owned(), 1))
+        );
+    }
+
+    #[test]
+    fn test_app_open_with_external() {
+        let mut app = create_test_app();
+        app.handle_message(Message::Once(OnceOperation::OpenWithExternal {
+            file: "test.txt".to_owned(),
+        }));
+        assert_eq!(app.pending_command, None);
+
+        app.config = Config::parse("[open_with]\ntxt = \"less -R\"\n").unwrap();
+        app.handle_message(Message::Once(OnceOperation::OpenWithExternal {
+            file: "test.txt".to_owned(),
+        }));
+        let command = app.pending_command.take().unwrap();
+        assert_eq!(command[..2], ["less", "-R"]);
+        assert_eq!(
+            std::fs::read_to_string(&command[2]).unwrap(),
+            "Hello, world!"
+        );
+
+        // --no-shell-out refuses every action that would spawn a process
+        app.disable_shell_out();
+        app.handle_message(Message::Once(OnceOperation::OpenWithExternal {
+            file: "test.txt".to_owned(),
+        }));
+        assert_eq!(app.pending_command, None);
+        assert_eq!(
+            app.status_bar.notice(),
+            Some("Opening in an external program is disabled by --no-shell-out")
+        );
+        // fetching a missing blob runs git
+        app.handle_message(Message::Once(OnceOperation::FetchBlob {
+            file: "test.txt".to_owned(),
+        }));
+        assert!(app.blob_fetch.is_none());
+    }
+
+    #[test]
+    fn test_app_export_notes() {
+        let mut app = create_test_app();
+        app.handle_prompt(PromptKind::Note, "check this");
+
+        let path = std::env::temp_dir().join(format!(
+            "gview_notes_export_{}_{}.md",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        app.handle_prompt(PromptKind::ExportNotes, path.to_str().unwrap());
+        let markdown = std::fs::read_to_string(&path).unwrap();
+        assert!(markdown.contains("### `test.txt`"));
+        assert!(markdown.contains("check this"));
+    }
+
+    #[test]
+    fn test_app_export_review_summary() {
+        let mut app = create_test_app();
+        let path = std::env::temp_dir().join(format!(
+            "gview_review_export_{}_{}.md",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        app.handle_prompt(PromptKind::ExportSummary, path.to_str().unwrap());
+        assert!(!app.prompt_modal.is_open());
+
+        let markdown = std::fs::read_to_string(&path).unwrap();
+        assert!(markdown.starts_with("# Review of `"));
+        assert!(markdown.contains("| `test.txt` | 1 | 0 |"));
     }
 }
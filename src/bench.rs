@@ -0,0 +1,198 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use git2::{Repository, Signature, Time};
+
+use crate::{components::filter::FilterMode, repository::RepositoryInfo};
+
+// Lines of each generated file; commits edit one of them at a time.
+const LINES_PER_FILE: usize = 40;
+// Files edited by each generated commit besides the first one, which every commit edits.
+const FILES_PER_COMMIT: usize = 4;
+
+fn file_path(index: usize) -> String {
+    format!("src/module_{}/file_{}.rs", index % 50, index)
+}
+
+/// Builds a repository of `files` files under 50 directories at `dir`, with `commits`
+/// commits on top of the first one. Every commit edits a line of the first file, so that
+/// it has a long blame, and a few lines spread over the others.
+pub fn synthetic_repository(dir: &Path, files: usize, commits: usize) -> anyhow::Result<()> {
+    let repository = Repository::init(dir)?;
+    let mut contents: Vec<Vec<String>> = (0..files.max(1))
+        .map(|file| {
+            (0..LINES_PER_FILE)
+                .map(|line| format!("fn item_{}_{}() {{}}", file, line))
+                .collect()
+        })
+        .collect();
+    let mut changed: Vec<usize> = (0..contents.len()).collect();
+    for number in 0..=commits {
+        let mut index = repository.index()?;
+        for file in changed.drain(..) {
+            let path = file_path(file);
+            let absolute = dir.join(&path);
+            if let Some(parent) = absolute.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&absolute, contents[file].join("\n") + "\n")?;
+            index.add_path(Path::new(&path))?;
+        }
+        index.write()?;
+        let tree = repository.find_tree(index.write_tree()?)?;
+        let signature = Signature::new(
+            "Bench User",
+            "bench@example.com",
+            &Time::new(1_700_000_000 + number as i64 * 60, 0),
+        )?;
+        let parent = match repository.head() {
+            Ok(head) => Some(head.peel_to_commit()?),
+            Err(_) => None,
+        };
+        let parents: Vec<_> = parent.iter().collect();
+        repository.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            &format!("Commit {}", number),
+            &tree,
+            &parents,
+        )?;
+
+        // the edits of the next commit
+        let next = number + 1;
+        changed.push(0);
+        changed.extend((1..=FILES_PER_COMMIT).map(|step| (next * 7919 + step) % contents.len()));
+        for file in &changed {
+            let line = (next + file) % LINES_PER_FILE;
+            contents[*file][line] = format!("fn item_{}_{}() {{ {} }}", file, line, next);
+        }
+    }
+    Ok(())
+}
+
+/// The hot paths of gview, run against one repository with nothing cached between runs.
+pub struct Workload {
+    path: PathBuf,
+    files: Vec<String>,
+    file: String,
+}
+
+impl Workload {
+    /// Opens the repository at `path`; `file` is the one to blame, by default the first one
+    /// listed.
+    pub fn new(path: &Path, file: Option<String>) -> anyhow::Result<Self> {
+        let mut workload = Self {
+            path: path.to_owned(),
+            files: vec![],
+            file: String::new(),
+        };
+        workload.files = workload.tree_walk()?;
+        workload.file = match file {
+            Some(file) => file,
+            None => workload
+                .files
+                .first()
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("the repository has no files"))?,
+        };
+        Ok(workload)
+    }
+
+    fn open(&self) -> anyhow::Result<RepositoryInfo> {
+        RepositoryInfo::open_uncached(&self.path)
+    }
+
+    pub fn tree_walk(&self) -> anyhow::Result<Vec<String>> {
+        self.open()?.recursive_walk()
+    }
+
+    /// Filters the listed files as the filter panel does in each of its modes.
+    pub fn filter(&self, query: &str) -> [usize; 3] {
+        let query = query.to_owned();
+        [
+            FilterMode::PartialMatch,
+            FilterMode::FuzzyMatch,
+            FilterMode::RegularMatch,
+        ]
+        .map(|mode| mode.filter(self.files.clone(), &query).len())
+    }
+
+    pub fn blame(&self) -> anyhow::Result<usize> {
+        let content = self.open()?.get_content(self.file.clone())?;
+        Ok(content.hunks.len())
+    }
+
+    pub fn history(&self) -> anyhow::Result<usize> {
+        Ok(self.open()?.get_commit_history()?.len())
+    }
+
+    /// Runs each hot path `runs` times and reports its median time.
+    pub fn measure(&self, runs: usize) -> anyhow::Result<Vec<(String, Duration)>> {
+        let median = |action: &mut dyn FnMut() -> anyhow::Result<()>| {
+            let mut times = (0..runs.max(1))
+                .map(|_| {
+                    let start = Instant::now();
+                    action().map(|_| start.elapsed())
+                })
+                .collect::<anyhow::Result<Vec<Duration>>>()?;
+            times.sort();
+            Ok::<_, anyhow::Error>(times[times.len() / 2])
+        };
+        Ok(vec![
+            (
+                format!("tree walk ({} files)", self.files.len()),
+                median(&mut || self.tree_walk().map(|_| ()))?,
+            ),
+            (
+                "filter (partial, fuzzy, regex)".to_owned(),
+                median(&mut || {
+                    self.filter("src/mod");
+                    Ok(())
+                })?,
+            ),
+            (
+                format!("blame ({})", self.file),
+                median(&mut || self.blame().map(|_| ()))?,
+            ),
+            (
+                "history".to_owned(),
+                median(&mut || self.history().map(|_| ()))?,
+            ),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthetic_workload() {
+        let dir = std::env::temp_dir().join(format!(
+            "gview_bench_test_{}_{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        synthetic_repository(&dir, 60, 5).unwrap();
+
+        let workload = Workload::new(&dir, None).unwrap();
+        assert_eq!(workload.tree_walk().unwrap().len(), 60);
+        assert_eq!(workload.history().unwrap(), 6);
+        // the first file is edited by every commit
+        let workload = Workload::new(&dir, Some(file_path(0))).unwrap();
+        assert!(workload.blame().unwrap() > 1);
+        let [partial, fuzzy, regex] = workload.filter("module_1/");
+        assert_eq!((partial, regex), (2, 2));
+        // fuzzy matching also finds module_10/ and the like
+        assert!(fuzzy > partial);
+        assert_eq!(workload.measure(1).unwrap().len(), 4);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
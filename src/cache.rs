@@ -0,0 +1,205 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+pub const DEFAULT_LIMIT: u64 = 64 * 1024 * 1024; // 64MB
+
+// Directory for disposable data, following the XDG base directory spec.
+pub fn cache_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .map(|dir| dir.join("gview"))
+}
+
+// Removes the caches of every repository, returning the directory that was removed.
+pub fn clear() -> io::Result<Option<PathBuf>> {
+    match cache_dir() {
+        Some(dir) if dir.exists() => fs::remove_dir_all(&dir).map(|_| Some(dir)),
+        _ => Ok(None),
+    }
+}
+
+/// Size-limited on-disk cache for one repository. Entries are grouped by kind and the
+/// least recently used ones are evicted once the limit is exceeded.
+#[derive(Debug, Default)]
+pub struct Cache {
+    dir: Option<PathBuf>,
+    limit: u64,
+}
+
+impl Cache {
+    pub fn new(dir: Option<PathBuf>, limit: u64) -> Self {
+        Self { dir, limit }
+    }
+
+    pub fn for_repository(key: &str) -> Self {
+        Self::new(cache_dir().map(|dir| dir.join(key)), DEFAULT_LIMIT)
+    }
+
+    pub fn set_limit(&mut self, limit: u64) {
+        self.limit = limit;
+    }
+
+    fn entry(&self, kind: &str, key: &str) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| dir.join(kind).join(key))
+    }
+
+    pub fn get(&self, kind: &str, key: &str) -> Option<String> {
+        let path = self.entry(kind, key)?;
+        let content = fs::read_to_string(&path).ok()?;
+        // a hit counts as a use, so frequently read entries survive eviction
+        let _ = fs::File::options()
+            .append(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+        Some(content)
+    }
+
+    // Failing to cache only costs a recomputation later, so errors are ignored.
+    pub fn put(&self, kind: &str, key: &str, content: &str) {
+        let Some(path) = self.entry(kind, key) else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            if fs::create_dir_all(parent).is_err() {
+                return;
+            }
+        }
+        if fs::write(&path, content).is_ok() {
+            let _ = self.evict();
+        }
+    }
+
+    fn evict(&self) -> io::Result<()> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        // other workers write and evict at the same time, so entries may be gone by the time
+        // they are looked at
+        let mut entries: Vec<(SystemTime, u64, PathBuf)> = vec![];
+        for kind in fs::read_dir(dir)? {
+            let kind = kind?;
+            if !kind.file_type()?.is_dir() {
+                continue;
+            }
+            let listing = match fs::read_dir(kind.path()) {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                listing => listing?,
+            };
+            for entry in listing {
+                let path = entry?.path();
+                if let Some((modified, size)) = stat(&path)? {
+                    entries.push((modified, size, path));
+                }
+            }
+        }
+
+        let mut total: u64 = entries.iter().map(|entry| entry.1).sum();
+        entries.sort();
+        for (_, size, path) in entries {
+            if total <= self.limit {
+                break;
+            }
+            remove_entry(&path)?;
+            total -= size;
+        }
+        Ok(())
+    }
+}
+
+// Modification time and size of the entry at `path`, or None once it is removed.
+fn stat(path: &Path) -> io::Result<Option<(SystemTime, u64)>> {
+    match fs::metadata(path) {
+        Ok(metadata) => Ok(Some((metadata.modified()?, metadata.len()))),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn remove_entry(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn test_dir(name: &str) -> PathBuf {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "gview_cache_test_{}_{}_{}",
+            name,
+            timestamp,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn age(cache: &Cache, kind: &str, key: &str, seconds: u64) {
+        fs::File::options()
+            .append(true)
+            .open(cache.entry(kind, key).unwrap())
+            .unwrap()
+            .set_modified(UNIX_EPOCH + Duration::from_secs(seconds))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_cache_get_and_put() {
+        let cache = Cache::new(Some(test_dir("get_put")), DEFAULT_LIMIT);
+        assert_eq!(cache.get("files", "abc"), None);
+
+        cache.put("files", "abc", "a.rs\nb.rs");
+        assert_eq!(cache.get("files", "abc"), Some("a.rs\nb.rs".to_owned()));
+        assert_eq!(cache.get("blame", "abc"), None);
+
+        // Without a directory nothing is stored
+        let disabled = Cache::default();
+        disabled.put("files", "abc", "a.rs");
+        assert_eq!(disabled.get("files", "abc"), None);
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let cache = Cache::new(Some(test_dir("evict")), 10);
+        cache.put("files", "old", "1234");
+        cache.put("files", "used", "1234");
+        age(&cache, "files", "old", 1000);
+        age(&cache, "files", "used", 2000);
+        cache.get("files", "old");
+
+        cache.put("blame", "new", "1234");
+        assert_eq!(cache.get("files", "used"), None);
+        assert_eq!(cache.get("files", "old"), Some("1234".to_owned()));
+        assert_eq!(cache.get("blame", "new"), Some("1234".to_owned()));
+    }
+
+    // A link to nothing stands in for an entry another worker removed after it was listed.
+    #[cfg(unix)]
+    #[test]
+    fn test_cache_evicts_past_removed_entries() {
+        let dir = test_dir("evict_removed");
+        let cache = Cache::new(Some(dir.clone()), 10);
+        cache.put("files", "old", "1234");
+        age(&cache, "files", "old", 1000);
+        std::os::unix::fs::symlink(dir.join("gone"), dir.join("files").join("removed")).unwrap();
+        assert_eq!(stat(&dir.join("files").join("removed")).unwrap(), None);
+
+        cache.put("blame", "one", "1234");
+        cache.put("blame", "two", "1234");
+        assert_eq!(cache.get("files", "old"), None);
+        assert_eq!(cache.get("blame", "two"), Some("1234".to_owned()));
+    }
+}
@@ -0,0 +1,96 @@
+// The changelog, built into the binary so gview can tell what changed since its last run.
+const CHANGELOG: &str = include_str!("../CHANGELOG.md");
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The changes of one release, as listed under its `## <version>` heading.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+    pub version: String,
+    pub changes: Vec<String>,
+}
+
+// The releases of `changelog`, newest first.
+fn releases(changelog: &str) -> Vec<Release> {
+    let mut releases: Vec<Release> = vec![];
+    for line in changelog.lines() {
+        if let Some(version) = line.strip_prefix("## ") {
+            releases.push(Release {
+                version: version.trim().to_owned(),
+                changes: vec![],
+            });
+        } else if let (Some(release), Some(change)) = (releases.last_mut(), line.strip_prefix("- "))
+        {
+            release.changes.push(change.trim().to_owned());
+        }
+    }
+    releases
+}
+
+// The releases of `changelog` newer than `last`, or the newest one when `last` is not listed.
+fn releases_since(changelog: &str, last: &str) -> Vec<Release> {
+    let mut releases = releases(changelog);
+    match releases.iter().position(|release| release.version == last) {
+        Some(index) => releases.truncate(index),
+        None => releases.truncate(1),
+    }
+    releases
+}
+
+/// What is new since the version of the last run: nothing on the first run, or when the
+/// version has not changed.
+pub fn news(last: Option<&str>) -> Vec<Release> {
+    match last {
+        Some(last) if last != VERSION => releases_since(CHANGELOG, last),
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANGELOG: &str = "# Changelog\n\
+                             \n\
+                             ## 0.3.0\n\
+                             \n\
+                             - `x` does this\n\
+                             - `y` does that\n\
+                             \n\
+                             ## 0.2.0\n\
+                             \n\
+                             - `z` does more\n\
+                             \n\
+                             ## 0.1.0\n\
+                             \n\
+                             - first release\n";
+
+    #[test]
+    fn test_releases_since() {
+        let versions = |last: &str| -> Vec<String> {
+            releases_since(CHANGELOG, last)
+                .into_iter()
+                .map(|release| release.version)
+                .collect()
+        };
+        assert_eq!(versions("0.1.0"), vec!["0.3.0", "0.2.0"]);
+        assert_eq!(versions("0.3.0"), Vec::<String>::new());
+        // an unknown version only gets the newest release
+        assert_eq!(versions("0.0.9"), vec!["0.3.0"]);
+        assert_eq!(
+            releases_since(CHANGELOG, "0.2.0"),
+            vec![Release {
+                version: "0.3.0".to_owned(),
+                changes: vec!["`x` does this".to_owned(), "`y` does that".to_owned()],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_news() {
+        assert!(news(None).is_empty());
+        assert!(news(Some(VERSION)).is_empty());
+        // the built-in changelog lists the current version
+        assert_eq!(news(Some("0.0.0"))[0].version, VERSION);
+    }
+}
@@ -0,0 +1,144 @@
+use std::{process::Command, thread::JoinHandle};
+
+use serde::Deserialize;
+
+/// Where a CI check of a commit stands.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CheckState {
+    Pending,
+    Passed,
+    Failed,
+    // neutral or skipped runs, which neither pass nor fail the commit
+    Skipped,
+}
+
+impl CheckState {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            CheckState::Pending => "●",
+            CheckState::Passed => "✓",
+            CheckState::Failed => "✗",
+            CheckState::Skipped => "-",
+        }
+    }
+}
+
+/// A check run reported by the forge for a commit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub name: String,
+    pub state: CheckState,
+}
+
+#[derive(Deserialize)]
+struct CheckRuns {
+    check_runs: Vec<CheckRun>,
+}
+
+#[derive(Deserialize)]
+struct CheckRun {
+    name: String,
+    status: String,
+    conclusion: Option<String>,
+}
+
+/// Reads the answer of GitHub's `commits/{sha}/check-runs` endpoint.
+pub fn parse_check_runs(json: &str) -> anyhow::Result<Vec<Check>> {
+    let runs: CheckRuns = serde_json::from_str(json)?;
+    Ok(runs
+        .check_runs
+        .into_iter()
+        .map(|run| {
+            let state = match (run.status.as_str(), run.conclusion.as_deref()) {
+                ("completed", Some("success")) => CheckState::Passed,
+                ("completed", Some("neutral" | "skipped")) => CheckState::Skipped,
+                // failure, cancelled, timed_out, action_required, ...
+                ("completed", _) => CheckState::Failed,
+                _ => CheckState::Pending,
+            };
+            Check {
+                name: run.name,
+                state,
+            }
+        })
+        .collect())
+}
+
+/// One line for the commit panel, such as `CI ✗ 1/4 failed`.
+pub fn summary(checks: &[Check]) -> String {
+    let count = |state| checks.iter().filter(|check| check.state == state).count();
+    let (failed, pending) = (count(CheckState::Failed), count(CheckState::Pending));
+    if checks.is_empty() {
+        "CI: no checks".to_owned()
+    } else if failed > 0 {
+        format!("CI ✗ {}/{} failed", failed, checks.len())
+    } else if pending > 0 {
+        format!("CI ● {}/{} running", pending, checks.len())
+    } else {
+        format!("CI ✓ {}", checks.len())
+    }
+}
+
+/// Asks the forge for the check runs of `sha` on a separate thread. The request goes through
+/// the GitHub CLI, which uses `token` when given and the `gh auth` login otherwise.
+pub fn spawn_fetch(
+    host: String,
+    repo: String,
+    sha: String,
+    token: Option<String>,
+) -> JoinHandle<anyhow::Result<Vec<Check>>> {
+    std::thread::spawn(move || {
+        let mut command = Command::new("gh");
+        command.args([
+            "api",
+            "--hostname",
+            &host,
+            &format!("repos/{}/commits/{}/check-runs?per_page=100", repo, sha),
+        ]);
+        if let Some(token) = token {
+            command.env("GH_TOKEN", token);
+        }
+        let output = command
+            .output()
+            .map_err(|e| anyhow::anyhow!("cannot run gh: {}", e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow::anyhow!("{}", stderr.trim()));
+        }
+        parse_check_runs(&String::from_utf8_lossy(&output.stdout))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_check_runs_and_summary() {
+        let json = r#"{
+            "total_count": 3,
+            "check_runs": [
+                {"name": "test", "status": "completed", "conclusion": "success"},
+                {"name": "lint", "status": "completed", "conclusion": "failure"},
+                {"name": "docs", "status": "in_progress", "conclusion": null}
+            ]
+        }"#;
+        let checks = parse_check_runs(json).unwrap();
+        assert_eq!(
+            checks
+                .iter()
+                .map(|check| (check.name.as_str(), check.state))
+                .collect::<Vec<_>>(),
+            vec![
+                ("test", CheckState::Passed),
+                ("lint", CheckState::Failed),
+                ("docs", CheckState::Pending),
+            ]
+        );
+        assert_eq!(summary(&checks), "CI ✗ 1/3 failed");
+        assert_eq!(summary(&checks[2..]), "CI ● 1/1 running");
+        assert_eq!(summary(&checks[..1]), "CI ✓ 1");
+        assert_eq!(summary(&[]), "CI: no checks");
+        assert!(parse_check_runs("{}").is_err());
+    }
+}
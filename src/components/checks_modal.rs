@@ -0,0 +1,333 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    checks::{self, Check, CheckState},
+    repository::RepositoryInfo,
+    theme::Theme,
+};
+
+use super::{
+    centered_rect,
+    operatable_components::{
+        Focus, Message, MultipleTimesOperation, OnceOperation, OperatableComponent,
+    },
+};
+
+pub struct ChecksModal {
+    focus: Focus,
+    is_open: bool,
+    // whether the forge is asked at all, and the token to ask with
+    enabled: bool,
+    token: Option<String>,
+    // the shown commit
+    commit_id: String,
+    // checks of each commit asked about, or why they could not be read
+    results: HashMap<String, Result<Vec<Check>, String>>,
+    pending: Vec<(String, JoinHandle<anyhow::Result<Vec<Check>>>)>,
+    list_state: ListState,
+    repository: Arc<Mutex<RepositoryInfo>>,
+    theme: Theme,
+}
+
+impl ChecksModal {
+    pub fn new(repository: Arc<Mutex<RepositoryInfo>>) -> Self {
+        Self {
+            focus: Focus::Off,
+            is_open: false,
+            enabled: false,
+            token: None,
+            commit_id: String::new(),
+            results: HashMap::new(),
+            pending: Vec::new(),
+            list_state: ListState::default(),
+            repository,
+            theme: Theme::default(),
+        }
+    }
+
+    // Turns the CI status on and asks about the shown commit right away.
+    pub fn enable(&mut self, token: Option<String>) {
+        self.enabled = true;
+        self.token = token;
+        self.request();
+    }
+
+    // The CI status of the shown commit for the commit panel, when the forge is asked.
+    pub fn summary(&self) -> Option<String> {
+        if !self.enabled {
+            return None;
+        }
+        Some(match self.results.get(&self.commit_id) {
+            Some(Ok(checks)) => checks::summary(checks),
+            Some(Err(_)) => "CI ?".to_owned(),
+            None => "CI …".to_owned(),
+        })
+    }
+
+    // Asks the forge about the shown commit unless it already has been.
+    fn request(&mut self) {
+        let Ok(repo) = self.repository.lock() else {
+            return;
+        };
+        self.commit_id = repo.get_current_commit_id();
+        if !self.enabled
+            || self.results.contains_key(&self.commit_id)
+            || self.pending.iter().any(|(id, _)| *id == self.commit_id)
+        {
+            return;
+        }
+        match repo.remote_repository() {
+            Ok((host, path)) => self.pending.push((
+                self.commit_id.clone(),
+                checks::spawn_fetch(host, path, self.commit_id.clone(), self.token.clone()),
+            )),
+            Err(e) => {
+                self.results.insert(
+                    self.commit_id.clone(),
+                    Err(format!("No forge remote: {}", e)),
+                );
+            }
+        }
+    }
+
+    // Collects the answers that have arrived; called once per tick.
+    pub fn poll(&mut self) {
+        let (finished, pending) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(_, handle)| handle.is_finished());
+        self.pending = pending;
+        for (commit_id, handle) in finished {
+            let result = match handle.join() {
+                Ok(Ok(checks)) => Ok(checks),
+                Ok(Err(e)) => Err(format!("Cannot read the checks: {}", e)),
+                Err(_) => Err("Cannot read the checks".to_owned()),
+            };
+            self.results.insert(commit_id, result);
+        }
+    }
+
+    fn open(&mut self) {
+        self.is_open = true;
+        self.focus = Focus::ON;
+        let has_checks = matches!(
+            self.results.get(&self.commit_id),
+            Some(Ok(checks)) if !checks.is_empty()
+        );
+        self.list_state
+            .select(if has_checks { Some(0) } else { None });
+    }
+
+    fn close(&mut self) {
+        self.is_open = false;
+        self.focus = Focus::Off;
+        self.list_state.select(None);
+    }
+
+    fn checks(&self) -> &[Check] {
+        match self.results.get(&self.commit_id) {
+            Some(Ok(checks)) => checks,
+            _ => &[],
+        }
+    }
+
+    fn _handle_message(&mut self, message: &Message) -> Message {
+        match message {
+            Message::MultipleTimes(
+                MultipleTimesOperation::SetUp { .. } | MultipleTimesOperation::ChangeShowCommit,
+            ) => self.request(),
+            Message::Once(OnceOperation::OpenChecksModal) => self.open(),
+            Message::Once(OnceOperation::CloseChecksModal) => self.close(),
+            _ => {}
+        }
+        Message::NoAction
+    }
+}
+
+impl OperatableComponent for ChecksModal {
+    fn draw(&mut self, frame: &mut Frame, rect: Rect) {
+        if !self.is_open {
+            return;
+        }
+
+        let popup_area = centered_rect(60, 50, rect);
+        frame.render_widget(Clear, popup_area);
+
+        let block = Block::bordered()
+            .title(format!(
+                "CI checks of {} (Esc: close)",
+                &self.commit_id[..self.commit_id.len().min(8)]
+            ))
+            .style(match self.focus {
+                Focus::ON => Style::default(),
+                Focus::Off => Style::default().fg(self.theme.unfocused),
+            });
+        let inner_area = block.inner(popup_area);
+        frame.render_widget(block, popup_area);
+
+        let message = match self.results.get(&self.commit_id) {
+            _ if !self.enabled => Some(
+                "CI status is off. Set `enabled = true` under [checks] in the config.".to_owned(),
+            ),
+            None => Some("Waiting for the forge…".to_owned()),
+            Some(Err(e)) => Some(e.to_owned()),
+            Some(Ok(checks)) if checks.is_empty() => {
+                Some("No checks ran on this commit".to_owned())
+            }
+            Some(Ok(_)) => None,
+        };
+        if let Some(message) = message {
+            let paragraph = Paragraph::new(message)
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true });
+            frame.render_widget(paragraph, inner_area);
+            return;
+        }
+
+        let items: Vec<ListItem> = self
+            .checks()
+            .iter()
+            .map(|check| {
+                let color = match check.state {
+                    CheckState::Passed => self.theme.added,
+                    CheckState::Failed => self.theme.removed,
+                    CheckState::Pending => self.theme.emphasis,
+                    CheckState::Skipped => self.theme.unfocused,
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{} ", check.state.symbol()),
+                        Style::default().fg(color),
+                    ),
+                    Span::raw(check.name.to_owned()),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_style(Style::default().bg(self.theme.selection))
+            .highlight_symbol("→ ");
+        frame.render_stateful_widget(list, inner_area, &mut self.list_state);
+    }
+
+    fn process_focus(&mut self) {
+        match self.focus {
+            Focus::Off => self.focus = Focus::ON,
+            Focus::ON => self.focus = Focus::Off,
+        }
+    }
+
+    fn process_events(&mut self, events: KeyCode) -> Message {
+        if !self.is_open {
+            return Message::NoAction;
+        }
+
+        match events {
+            KeyCode::Esc => return Message::Once(OnceOperation::CloseChecksModal),
+            KeyCode::Up | KeyCode::Char('k') => {
+                if let Some(selected) = self.list_state.selected() {
+                    self.list_state.select(Some(selected.saturating_sub(1)));
+                }
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if let Some(selected) = self.list_state.selected() {
+                    if selected + 1 < self.checks().len() {
+                        self.list_state.select(Some(selected + 1));
+                    }
+                }
+            }
+            _ => {}
+        }
+        Message::NoAction
+    }
+
+    fn handle_message(&mut self, message: &Message) -> Message {
+        match (message, self._handle_message(message)) {
+            (Message::MultipleTimes(_), Message::MultipleTimes(_)) => unreachable!(),
+            (Message::Once(_), Message::MultipleTimes(_)) => unreachable!(),
+            (Message::Once(_), Message::Once(_)) => unreachable!(),
+            (Message::NoAction, Message::MultipleTimes(_)) => unreachable!(),
+            (Message::NoAction, Message::Once(_)) => unreachable!(),
+            (_, new_message) => new_message,
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn create_mock_repo() -> Arc<Mutex<RepositoryInfo>> {
+        crate::components::create_test_repo("checks_modal", &[])
+    }
+
+    #[test]
+    fn test_checks_modal_lists_checks() {
+        let mut modal = ChecksModal::new(create_mock_repo());
+        modal.handle_message(&Message::MultipleTimes(
+            MultipleTimesOperation::ChangeShowCommit,
+        ));
+        // nothing is asked while the feature is off
+        assert_eq!(modal.summary(), None);
+        assert!(modal.results.is_empty());
+
+        // a repository without a remote cannot be asked
+        modal.enable(None);
+        modal.handle_message(&Message::MultipleTimes(
+            MultipleTimesOperation::ChangeShowCommit,
+        ));
+        assert_eq!(modal.summary(), Some("CI ?".to_owned()));
+
+        modal.results.insert(
+            modal.commit_id.clone(),
+            Ok(vec![
+                Check {
+                    name: "test".to_owned(),
+                    state: CheckState::Passed,
+                },
+                Check {
+                    name: "lint".to_owned(),
+                    state: CheckState::Failed,
+                },
+            ]),
+        );
+        assert_eq!(modal.summary(), Some("CI ✗ 1/2 failed".to_owned()));
+
+        modal.handle_message(&Message::Once(OnceOperation::OpenChecksModal));
+        assert!(modal.is_open());
+        modal.process_events(KeyCode::Char('j'));
+        assert_eq!(modal.list_state.selected(), Some(1));
+
+        let mut terminal = Terminal::new(TestBackend::new(50, 10)).unwrap();
+        terminal
+            .draw(|frame| modal.draw(frame, frame.size()))
+            .unwrap();
+        assert_snapshot!(terminal.backend());
+
+        assert_eq!(
+            modal.process_events(KeyCode::Esc),
+            Message::Once(OnceOperation::CloseChecksModal)
+        );
+    }
+}
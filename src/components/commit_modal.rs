@@ -1,24 +1,70 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
 
 use crossterm::event::KeyCode;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
+    style::Style,
     text::{Line, Span},
     widgets::{Block, Clear, List, ListItem, ListState, Paragraph, Wrap},
     Frame,
 };
 
-use crate::repository::RepositoryInfo;
+use crate::{
+    emoji,
+    repository::{CommitStats, Origin, RepositoryInfo},
+    theme::Theme,
+};
+
+// Commits read from the history at a time, as the list is scrolled.
+const PAGE_SIZE: usize = 200;
+// The next page is read once the selection comes this close to the last listed commit.
+const PAGE_MARGIN: usize = 50;
 
-use super::operatable_components::{Focus, Message, OnceOperation, OperatableComponent};
+use super::{
+    centered_rect,
+    filter::FilterMode,
+    operatable_components::{
+        Focus, Message, MultipleTimesOperation, OnceOperation, OperatableComponent,
+    },
+    prompt_modal::PromptKind,
+};
 
 pub struct CommitModal {
     focus: Focus,
     is_open: bool,
+    // the listed commits, narrowed to `commits` by the query
+    history: Vec<(String, String)>,
     commits: Vec<(String, String)>,
+    authors: HashMap<String, String>,
+    query: String,
+    mode: FilterMode,
+    // whether keys go to the query
+    editing: bool,
+    // where the next page of the history starts, while there is more of it
+    next_page: Option<usize>,
+    // commits by the user, highlighted while `highlight_mine` is set
+    mine: HashSet<String>,
+    highlight_mine: bool,
+    // rows of the list last drawn, for paging
+    page_rows: usize,
+    // cherry-picked and reverted commits, by id
+    origins: HashMap<String, Origin>,
+    // the query and the commits with a matching trailer, while only those are listed
+    trailer_filter: Option<(String, HashSet<String>)>,
+    // the dates the history is limited to, when it is
+    date_range: Option<String>,
+    // whether shortcodes in the messages are shown as emoji
+    emoji: bool,
+    // of the highlighted commit
+    stats: Option<CommitStats>,
+    // where the modal docks to leave the content preview visible
+    docked: Option<Rect>,
     list_state: ListState,
     repository: Arc<Mutex<RepositoryInfo>>,
+    theme: Theme,
 }
 
 impl CommitModal {
@@ -26,44 +72,232 @@ impl CommitModal {
         Self {
             focus: Focus::Off,
             is_open: false,
+            history: Vec::new(),
             commits: Vec::new(),
+            authors: HashMap::new(),
+            query: String::new(),
+            mode: FilterMode::PartialMatch,
+            editing: false,
+            next_page: None,
+            mine: HashSet::new(),
+            highlight_mine: false,
+            page_rows: 1,
+            origins: HashMap::new(),
+            trailer_filter: None,
+            date_range: None,
+            emoji: false,
+            stats: None,
+            docked: None,
             list_state: ListState::default(),
             repository,
+            theme: Theme::default(),
         }
     }
 
-    pub fn is_open(&self) -> bool {
-        self.is_open
+    fn load_mine(&mut self) {
+        if let Ok(repo) = self.repository.lock() {
+            self.highlight_mine = repo.highlight_mine();
+        }
     }
 
+    pub fn dock(&mut self, area: Option<Rect>) {
+        self.docked = area;
+    }
+
+    pub fn set_emoji(&mut self, emoji: bool) {
+        self.emoji = emoji;
+    }
+
+    pub fn set_trailer_filter(&mut self, filter: Option<(String, HashSet<String>)>) {
+        self.trailer_filter = filter;
+        self.load_commits();
+        self.load_stats();
+    }
+
+    // Lists the history again from its first page, as far as the viewed commit.
     fn load_commits(&mut self) {
-        if let Ok(repo) = self.repository.lock() {
-            if let Ok(history) = repo.get_commit_history() {
-                let current_commit_id = repo.get_current_commit_id();
-                self.commits = history;
-
-                // Find the current commit position and select it
-                let current_position = self
-                    .commits
-                    .iter()
-                    .position(|(id, _)| id == &current_commit_id)
-                    .unwrap_or(0);
-
-                if !self.commits.is_empty() {
-                    self.list_state.select(Some(current_position));
+        self.load_mine();
+        self.history.clear();
+        self.mine.clear();
+        self.origins.clear();
+        self.authors.clear();
+        self.next_page = Some(0);
+        let current_commit_id = match self.repository.lock() {
+            Ok(repo) => {
+                let range = repo.date_range();
+                self.date_range = range.is_set().then(|| range.to_string());
+                repo.get_current_commit_id()
+            }
+            Err(_) => return,
+        };
+        while !self.history.iter().any(|(id, _)| *id == current_commit_id) && self.load_page() {}
+        // a query searches the whole history
+        if !self.query.is_empty() {
+            while self.load_page() {}
+        }
+        self.apply_query();
+        // Find the current commit position and select it
+        self.select_commit(&current_commit_id);
+        self.load_near_selection();
+    }
+
+    // Adds the next page of the history to the list; false once it is all listed.
+    fn load_page(&mut self) -> bool {
+        let Some(from) = self.next_page else {
+            return false;
+        };
+        let (page, identity) = match self.repository.lock() {
+            Ok(repo) => (repo.history_page(from, PAGE_SIZE), repo.identity()),
+            Err(_) => return false,
+        };
+        let Ok(page) = page else {
+            self.next_page = None;
+            return false;
+        };
+        self.next_page = page.next;
+        for entry in page.entries {
+            if let Some((_, matching)) = &self.trailer_filter {
+                if !matching.contains(&entry.id) {
+                    continue;
                 }
             }
+            if identity.matches(&entry.author, &entry.email) {
+                self.mine.insert(entry.id.clone());
+            }
+            if let Some(origin) = entry.origin {
+                self.origins.insert(entry.id.clone(), origin);
+            }
+            self.authors.insert(entry.id.clone(), entry.author);
+            let subject = if self.emoji {
+                emoji::replace_shortcodes(&entry.subject)
+            } else {
+                entry.subject
+            };
+            self.history.push((entry.id, subject));
+        }
+        true
+    }
+
+    // Reads more of the history while the selection is near the end of the list, keeping
+    // the highlighted commit.
+    fn load_near_selection(&mut self) {
+        let selected = self.list_state.selected().unwrap_or(0);
+        if self.next_page.is_none() || selected + PAGE_MARGIN < self.commits.len() {
+            return;
+        }
+        let id = self.get_selected_commit_id();
+        while selected + PAGE_MARGIN >= self.commits.len() && self.load_page() {
+            self.apply_query();
+        }
+        if let Some(id) = id {
+            self.select_commit(&id);
         }
     }
 
+    // Moves the highlight up to `rows` commits down or up, reading more of the history as it
+    // nears the end of the list.
+    fn move_selection(&mut self, down: bool, rows: usize) -> Message {
+        if !self.commits.is_empty() {
+            let selected = self.list_state.selected().unwrap_or(0);
+            let last = self.commits.len() - 1;
+            self.list_state.select(Some(match down {
+                true => selected.saturating_add(rows).min(last),
+                false => selected.saturating_sub(rows),
+            }));
+        }
+        if down {
+            self.load_near_selection();
+        }
+        self.preview_selected()
+    }
+
+    // `G` and End: the oldest commit, once the whole history is read.
+    fn select_last(&mut self) -> Message {
+        if self.next_page.is_some() {
+            while self.load_page() {}
+            self.apply_query();
+        }
+        self.move_selection(true, usize::MAX)
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.editing
+    }
+
+    // Narrows the list to the commits whose message or author matches the query.
+    fn apply_query(&mut self) {
+        if self.query.is_empty() {
+            self.commits = self.history.clone();
+            return;
+        }
+        let items = self
+            .history
+            .iter()
+            .map(|(id, message)| {
+                let author = self.authors.get(id).map_or("", String::as_str);
+                (
+                    format!("{} {}", message, author),
+                    (id.to_owned(), message.to_owned()),
+                )
+            })
+            .collect();
+        self.commits = self
+            .mode
+            .filter_by(items, &self.query, |(text, _)| text.as_str())
+            .into_iter()
+            .map(|(_, commit)| commit)
+            .collect();
+    }
+
+    // Selects the commit `id`, or the first listed one when it is filtered out.
+    fn select_commit(&mut self, id: &str) {
+        let position = self
+            .commits
+            .iter()
+            .position(|(commit_id, _)| commit_id == id)
+            .unwrap_or(0);
+        self.list_state
+            .select((!self.commits.is_empty()).then_some(position));
+    }
+
+    // Filters again after the query changed, keeping the highlighted commit when it matches.
+    fn search(&mut self) -> Message {
+        let selected = self.get_selected_commit_id().unwrap_or_default();
+        while !self.query.is_empty() && self.load_page() {}
+        self.apply_query();
+        self.select_commit(&selected);
+        self.preview_selected()
+    }
+
+    fn edit_query(&mut self, events: KeyCode) -> Option<Message> {
+        match events {
+            KeyCode::Char(c) => self.query.push(c),
+            KeyCode::Backspace => {
+                self.query.pop();
+            }
+            KeyCode::Tab => self.mode = self.mode.next(),
+            // dropping the query lists every commit again
+            KeyCode::Esc => {
+                self.editing = false;
+                self.query.clear();
+            }
+            // Enter selects and Up and Down move through the matches
+            _ => return None,
+        }
+        Some(self.search())
+    }
+
     fn open(&mut self) {
         self.is_open = true;
         self.focus = Focus::ON;
         self.load_commits();
+        self.load_stats();
     }
 
     fn close(&mut self) {
         self.is_open = false;
+        self.editing = false;
+        self.query.clear();
         self.focus = Focus::Off;
         self.list_state.select(None);
     }
@@ -77,6 +311,44 @@ impl CommitModal {
         None
     }
 
+    fn load_stats(&mut self) {
+        self.stats = match (self.get_selected_commit_id(), self.repository.lock()) {
+            (Some(commit_id), Ok(repo)) => repo.commit_stats(&commit_id).ok(),
+            _ => None,
+        };
+    }
+
+    fn preview_selected(&mut self) -> Message {
+        self.load_stats();
+        match self.get_selected_commit_id() {
+            Some(commit_id) => Message::Once(OnceOperation::PreviewCommit { commit_id }),
+            None => Message::NoAction,
+        }
+    }
+
+    // Moves to the commit the highlighted one was cherry-picked from or reverts.
+    fn jump_to_origin(&mut self) -> Message {
+        let Some(target) = self
+            .get_selected_commit_id()
+            .and_then(|id| self.origins.get(&id))
+            .and_then(Origin::commit)
+            .map(str::to_owned)
+        else {
+            return Message::NoAction;
+        };
+        match self
+            .commits
+            .iter()
+            .position(|(id, _)| id.starts_with(&target))
+        {
+            Some(position) => {
+                self.list_state.select(Some(position));
+                self.preview_selected()
+            }
+            None => Message::Once(OnceOperation::SetCommitById { commit_id: target }),
+        }
+    }
+
     fn _handle_message(&mut self, message: &Message) -> Message {
         match message {
             Message::Once(OnceOperation::OpenCommitModal) => {
@@ -85,6 +357,13 @@ impl CommitModal {
             Message::Once(OnceOperation::CloseCommitModal) => {
                 self.close();
             }
+            Message::MultipleTimes(MultipleTimesOperation::ChangeHighlight) if self.is_open => {
+                self.load_mine();
+            }
+            Message::MultipleTimes(MultipleTimesOperation::ChangeDateRange) if self.is_open => {
+                self.load_commits();
+                self.load_stats();
+            }
             _ => {}
         }
         Message::NoAction
@@ -92,26 +371,60 @@ impl CommitModal {
 }
 
 impl OperatableComponent for CommitModal {
-    fn draw(&mut self, frame: &mut Frame, _rect: Rect) {
+    fn draw(&mut self, frame: &mut Frame, rect: Rect) {
         if !self.is_open {
             return;
         }
 
-        let area = frame.size();
-        let popup_area = centered_rect(80, 80, area);
+        let popup_area = self.docked.unwrap_or_else(|| centered_rect(80, 80, rect));
 
         frame.render_widget(Clear, popup_area);
 
+        let title = match (&self.date_range, &self.trailer_filter) {
+            (Some(range), Some((query, _))) => format!(
+                "Commits from {} with trailer \"{}\" (Press Enter to select, Esc to cancel)",
+                range, query
+            ),
+            (Some(range), None) => format!(
+                "Commits from {} (Press Enter to select, d to change, Esc to cancel)",
+                range
+            ),
+            (None, Some((query, _))) => format!(
+                "Commits with trailer \"{}\" (Press Enter to select, t to change, Esc to cancel)",
+                query
+            ),
+            (None, None) => "All Commit History (Press Enter to select, Esc to cancel)".to_owned(),
+        };
         let block = Block::bordered()
-            .title("All Commit History (Press Enter to select, Esc to cancel)")
+            .title(title)
+            .title_bottom(
+                self.stats
+                    .map(|stats| stats.to_string())
+                    .unwrap_or_default(),
+            )
             .style(match self.focus {
                 Focus::ON => Style::default(),
-                Focus::Off => Style::default().fg(Color::DarkGray),
+                Focus::Off => Style::default().fg(self.theme.unfocused),
             });
 
-        let inner_area = block.inner(popup_area);
+        let mut inner_area = block.inner(popup_area);
         frame.render_widget(block, popup_area);
 
+        if self.editing || !self.query.is_empty() {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Min(0)])
+                .split(inner_area);
+            let (mode, mode_style) = self.mode.appearance(&self.theme);
+            let cursor = if self.editing { "▏" } else { "" };
+            let input = Line::from(vec![
+                Span::styled(format!("{}: ", mode), mode_style),
+                Span::raw(format!("{}{}", self.query, cursor)),
+            ]);
+            frame.render_widget(Paragraph::new(input), chunks[0]);
+            inner_area = chunks[1];
+        }
+
         if self.commits.is_empty() {
             let empty_msg = Paragraph::new("No commits found")
                 .alignment(Alignment::Center)
@@ -120,24 +433,45 @@ impl OperatableComponent for CommitModal {
             return;
         }
 
-        let items: Vec<ListItem> = self
+        let mut items: Vec<ListItem> = self
             .commits
             .iter()
             .map(|(id, message)| {
                 let short_id = &id[..std::cmp::min(8, id.len())];
-                let content = Line::from(vec![
-                    Span::styled(short_id, Style::default().fg(Color::Yellow)),
+                let message_style = if self.highlight_mine && self.mine.contains(id) {
+                    Style::default().fg(self.theme.mine_lines)
+                } else {
+                    Style::default()
+                };
+                let mut spans = vec![
+                    Span::styled(short_id, Style::default().fg(self.theme.emphasis)),
                     Span::raw(" "),
-                    Span::raw(message),
-                ]);
+                ];
+                if let Some(origin) = self.origins.get(id) {
+                    spans.push(Span::styled(
+                        format!("[{}] ", origin.label()),
+                        Style::default().fg(self.theme.tag),
+                    ));
+                }
+                spans.push(Span::styled(message, message_style));
+                let content = Line::from(spans);
                 ListItem::new(content)
             })
             .collect();
+        if self.next_page.is_some() {
+            items.push(ListItem::new(Span::styled(
+                "loading more…",
+                Style::default().fg(self.theme.unfocused),
+            )));
+        }
 
+        // while more is to be read, a row of padding keeps the footer in sight at the end
         let list = List::new(items)
-            .highlight_style(Style::default().bg(Color::Blue))
-            .highlight_symbol("→ ");
+            .highlight_style(Style::default().bg(self.theme.selection))
+            .highlight_symbol("→ ")
+            .scroll_padding(usize::from(self.next_page.is_some()));
 
+        self.page_rows = usize::from(inner_area.height).max(1);
         frame.render_stateful_widget(list, inner_area, &mut self.list_state);
     }
 
@@ -152,28 +486,47 @@ impl OperatableComponent for CommitModal {
         if !self.is_open {
             return Message::NoAction;
         }
+        if self.editing {
+            if let Some(message) = self.edit_query(events) {
+                return message;
+            }
+        }
 
         match events {
             KeyCode::Esc => {
                 return Message::Once(OnceOperation::CloseCommitModal);
             }
-            KeyCode::Enter => {
-                if let Some(commit_id) = self.get_selected_commit_id() {
-                    return Message::Once(OnceOperation::SetCommitById { commit_id });
+            KeyCode::Char('/') => self.editing = true,
+            KeyCode::Char('m') => {
+                if let Ok(mut repo) = self.repository.lock() {
+                    repo.toggle_highlight_mine();
+                    return Message::MultipleTimes(MultipleTimesOperation::ChangeHighlight);
                 }
             }
-            KeyCode::Up => {
-                let selected = self.list_state.selected().unwrap_or(0);
-                if selected > 0 {
-                    self.list_state.select(Some(selected - 1));
-                }
+            KeyCode::Char('o') => {
+                return self.jump_to_origin();
             }
-            KeyCode::Down => {
-                let selected = self.list_state.selected().unwrap_or(0);
-                if selected < self.commits.len().saturating_sub(1) {
-                    self.list_state.select(Some(selected + 1));
+            KeyCode::Char('t') => {
+                return Message::Once(OnceOperation::OpenPrompt {
+                    kind: PromptKind::TrailerFilter,
+                });
+            }
+            KeyCode::Char('d') => {
+                return Message::Once(OnceOperation::OpenPrompt {
+                    kind: PromptKind::DateRange,
+                });
+            }
+            KeyCode::Enter => {
+                if let Some(commit_id) = self.get_selected_commit_id() {
+                    return Message::Once(OnceOperation::SetCommitById { commit_id });
                 }
             }
+            KeyCode::Up => return self.move_selection(false, 1),
+            KeyCode::Down => return self.move_selection(true, 1),
+            KeyCode::PageUp => return self.move_selection(false, self.page_rows),
+            KeyCode::PageDown => return self.move_selection(true, self.page_rows),
+            KeyCode::Home => return self.move_selection(false, usize::MAX),
+            KeyCode::End | KeyCode::Char('G') => return self.select_last(),
             _ => {}
         }
         Message::NoAction
@@ -189,24 +542,145 @@ impl OperatableComponent for CommitModal {
             (_, new_message) => new_message,
         }
     }
+
+    fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    fn chords(&self) -> &'static [[char; 2]] {
+        &[['g', 'g']]
+    }
+
+    // gg highlights the newest commit
+    fn process_chord(&mut self, chord: [char; 2]) -> Message {
+        match chord {
+            ['g', 'g'] => self.move_selection(false, usize::MAX),
+            _ => Message::NoAction,
+        }
+    }
+
+    fn scroll_half_page(&mut self, down: bool) -> Message {
+        self.move_selection(down, (self.page_rows / 2).max(1))
+    }
 }
 
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
-        ])
-        .split(r);
-
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
-        ])
-        .split(popup_layout[1])[1]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    // A repository of `count` commits on one line of history.
+    fn create_repo_with_commits(count: usize) -> Arc<Mutex<RepositoryInfo>> {
+        let test_dir = std::env::temp_dir().join(format!(
+            "gview_commit_modal_test_{}_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos(),
+            std::process::id()
+        ));
+        let repo = git2::Repository::init(&test_dir).unwrap();
+        let signature = git2::Signature::new(
+            "Test User",
+            "test@localhost",
+            &git2::Time::new(1234567890, 0),
+        )
+        .unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let mut head = None;
+        for number in 0..count {
+            let tree = repo.find_tree(tree_id).unwrap();
+            let parent = head.map(|oid| repo.find_commit(oid).unwrap());
+            let parents: Vec<_> = parent.iter().collect();
+            let message = format!("Commit {}", number + 1);
+            head = Some(
+                repo.commit(
+                    Some("HEAD"),
+                    &signature,
+                    &signature,
+                    &message,
+                    &tree,
+                    &parents,
+                )
+                .unwrap(),
+            );
+        }
+        let head = head.unwrap();
+        Arc::new(Mutex::new(RepositoryInfo::_from_parts(repo, head)))
+    }
+
+    #[test]
+    fn test_commit_modal_reads_history_in_pages() {
+        let mut modal = CommitModal::new(create_repo_with_commits(260));
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        let mut screen = |modal: &mut CommitModal| {
+            terminal
+                .draw(|frame| modal.draw(frame, frame.size()))
+                .unwrap();
+            format!("{:?}", terminal.backend().buffer())
+        };
+        modal.handle_message(&Message::Once(OnceOperation::OpenCommitModal));
+        assert_eq!(modal.history.len(), PAGE_SIZE);
+        assert_eq!(modal.list_state.selected(), Some(0));
+
+        // nearing the end of the list reads the rest, keeping the selection
+        for _ in 0..150 {
+            modal.process_events(KeyCode::Down);
+        }
+        assert_eq!(modal.history.len(), 260);
+        assert_eq!(modal.list_state.selected(), Some(150));
+        assert_eq!(modal.next_page, None);
+        assert!(!screen(&mut modal).contains("loading more…"));
+    }
+
+    #[test]
+    fn test_commit_modal_page_keys() {
+        let mut modal = CommitModal::new(create_repo_with_commits(260));
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        modal.handle_message(&Message::Once(OnceOperation::OpenCommitModal));
+        terminal
+            .draw(|frame| modal.draw(frame, frame.size()))
+            .unwrap();
+        let page = modal.page_rows;
+        assert!(page > 1);
+
+        modal.process_events(KeyCode::PageDown);
+        assert_eq!(modal.list_state.selected(), Some(page));
+        modal.scroll_half_page(false);
+        assert_eq!(modal.list_state.selected(), Some(page - page / 2));
+        modal.process_events(KeyCode::PageUp);
+        assert_eq!(modal.list_state.selected(), Some(0));
+
+        // the oldest commit is at the end of the whole history
+        modal.process_events(KeyCode::Char('G'));
+        assert_eq!(modal.history.len(), 260);
+        assert_eq!(modal.list_state.selected(), Some(259));
+        modal.process_chord(['g', 'g']);
+        assert_eq!(modal.list_state.selected(), Some(0));
+        modal.process_events(KeyCode::End);
+        modal.process_events(KeyCode::Home);
+        assert_eq!(modal.list_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_commit_modal_shows_more_to_load() {
+        let mut modal = CommitModal::new(create_repo_with_commits(PAGE_SIZE + 1));
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        modal.handle_message(&Message::Once(OnceOperation::OpenCommitModal));
+        // the footer comes after the last listed commit
+        modal.list_state.select(Some(PAGE_SIZE - 1));
+        terminal
+            .draw(|frame| modal.draw(frame, frame.size()))
+            .unwrap();
+        assert!(format!("{:?}", terminal.backend().buffer()).contains("loading more…"));
+
+        // a query searches the whole history
+        modal.process_events(KeyCode::Char('/'));
+        modal.process_events(KeyCode::Char('1'));
+        assert_eq!(modal.history.len(), PAGE_SIZE + 1);
+    }
 }
@@ -1,45 +1,390 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    ops::Range,
+    sync::{Arc, Mutex},
+};
 
 use crossterm::event::KeyCode;
 use ratatui::{
     layout::Rect,
-    style::{Color, Style, Stylize},
-    widgets::{Block, Paragraph},
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Paragraph, Wrap},
     Frame,
 };
 
-use crate::repository::RepositoryInfo;
+use crate::{
+    emoji,
+    error::GviewError,
+    progress::{CancelToken, ProgressReporter},
+    repository::{ChildrenJob, CommitStats, RepositoryInfo},
+    theme::Theme,
+    trailers::{self, Trailer},
+};
 
-use super::operatable_components::{
-    Focus, Message, MultipleTimesOperation, OnceOperation, OperatableComponent,
+use super::{
+    operatable_components::{
+        panel_block, Focus, Message, MultipleTimesOperation, OnceOperation, OperatableComponent,
+    },
+    prompt_modal::PromptKind,
 };
 
+// The panel grows with the commit message up to this many lines.
+const MAX_MESSAGE_LINES: u16 = 10;
+
 pub struct CommitViewer {
     focus: Focus,
+    // id and message, without the trailers
     content: String,
+    // byte ranges of the issue references in `content`, and the one last opened with I
+    issues: Vec<Range<usize>>,
+    selected_issue: Option<usize>,
+    trailers: Vec<Trailer>,
+    // author followed by the co-authors, when there are any
+    authors: Option<String>,
+    // author, committer, parents and diff stat, shown below the message when toggled with d
+    details: Vec<(&'static str, String)>,
+    show_details: bool,
+    // whether shortcodes in the message are shown as emoji
+    emoji: bool,
+    // parents, then children of the commit as (label, id, subject)
+    neighbours: Vec<(&'static str, String, String)>,
+    // entry of `neighbours` selected with ←/→
+    selected_neighbour: Option<usize>,
+    // children being found in the background, listed once `poll_children` picks them up
+    children_job: Option<(ChildrenJob, CancelToken)>,
+    progress: ProgressReporter,
+    base: Option<String>,
+    target: Option<String>,
+    stats: Option<CommitStats>,
+    // CI status of the commit, when the forge is asked
+    checks: Option<String>,
+    // first shown line of a message longer than the panel
+    scroll: u16,
+    // lines of the message and rows of the panel, as of the last draw
+    line_count: u16,
+    height: u16,
     pub repository: Arc<Mutex<RepositoryInfo>>,
+    theme: Theme,
 }
 
 impl CommitViewer {
-    pub fn new(repository: Arc<Mutex<RepositoryInfo>>) -> Self {
+    pub fn new(repository: Arc<Mutex<RepositoryInfo>>, progress: ProgressReporter) -> Self {
         Self {
             focus: Focus::Off,
             content: "".to_owned(),
+            issues: Vec::new(),
+            selected_issue: None,
+            trailers: Vec::new(),
+            authors: None,
+            details: Vec::new(),
+            show_details: false,
+            emoji: false,
+            neighbours: Vec::new(),
+            selected_neighbour: None,
+            children_job: None,
+            progress,
+            base: None,
+            target: None,
+            stats: None,
+            checks: None,
+            scroll: 0,
+            line_count: 0,
+            height: 0,
             repository,
+            theme: Theme::default(),
+        }
+    }
+
+    pub fn set_emoji(&mut self, emoji: bool) {
+        self.emoji = emoji;
+    }
+
+    pub fn set_checks(&mut self, checks: Option<String>) {
+        self.checks = checks;
+    }
+
+    // The message with its issue references underlined.
+    fn message_text(&self) -> Text<'_> {
+        let content = self.content.trim_end();
+        if content.is_empty() {
+            return Text::default();
+        }
+        let mut start = 0;
+        let lines: Vec<Line> = content
+            .split('\n')
+            .map(|line| {
+                let (line_start, end) = (start, start + line.len());
+                start = end + 1;
+                let mut spans = Vec::new();
+                let mut shown = 0;
+                for (index, range) in self.issues.iter().enumerate() {
+                    if range.start < line_start || range.end > end {
+                        continue;
+                    }
+                    let (from, to) = (range.start - line_start, range.end - line_start);
+                    let mut style = Style::default()
+                        .fg(self.theme.emphasis)
+                        .add_modifier(Modifier::UNDERLINED);
+                    if self.selected_issue == Some(index) {
+                        style = style.add_modifier(Modifier::REVERSED);
+                    }
+                    spans.push(Span::raw(&line[shown..from]));
+                    spans.push(Span::styled(&line[from..to], style));
+                    shown = to;
+                }
+                spans.push(Span::raw(line[shown..].trim_end_matches('\r')));
+                Line::from(spans)
+            })
+            .collect();
+        Text::from(lines)
+    }
+
+    // (key, value) rows with the values aligned.
+    fn table<'a>(&self, rows: impl Iterator<Item = (&'a str, &'a str)> + Clone) -> Vec<Line<'a>> {
+        let width = rows.clone().map(|(key, _)| key.len()).max().unwrap_or(0);
+        rows.map(|(key, value)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:<width$}  ", key),
+                    Style::default().fg(self.theme.heading),
+                ),
+                Span::raw(value),
+            ])
+        })
+        .collect()
+    }
+
+    fn paragraph(&self) -> Paragraph<'_> {
+        let mut text = self.message_text();
+        if self.show_details && !self.details.is_empty() {
+            text.lines.push(Line::default());
+            text.lines.extend(
+                self.table(
+                    self.details
+                        .iter()
+                        .map(|(key, value)| (*key, value.as_str())),
+                ),
+            );
+        }
+        if !self.trailers.is_empty() {
+            // the trailers as a table below the message
+            let rows = self
+                .authors
+                .iter()
+                .map(|authors| ("Authors", authors.as_str()))
+                .chain(
+                    self.trailers
+                        .iter()
+                        .map(|trailer| (trailer.key.as_str(), trailer.value.as_str())),
+                );
+            text.lines.push(Line::default());
+            text.lines.extend(self.table(rows));
+        }
+        if !self.neighbours.is_empty() {
+            text.lines.push(Line::default());
+            text.lines.extend(self.neighbours.iter().enumerate().map(
+                |(index, (label, id, subject))| {
+                    let selected = self.selected_neighbour == Some(index);
+                    Line::from(vec![
+                        Span::styled(
+                            format!("{} {:<6}  ", if selected { "→" } else { " " }, label),
+                            Style::default().fg(self.theme.heading),
+                        ),
+                        Span::styled(short_id(id), Style::default().fg(self.theme.emphasis)),
+                        Span::raw(format!(" {}", subject)),
+                    ])
+                    .style(if selected {
+                        Style::default().add_modifier(Modifier::REVERSED)
+                    } else {
+                        Style::default()
+                    })
+                },
+            ));
+        }
+        Paragraph::new(text).wrap(Wrap { trim: false })
+    }
+
+    // Moves the selection among the parents and children by `step`, wrapping around.
+    fn select_neighbour(&mut self, step: isize) {
+        let count = self.neighbours.len() as isize;
+        if count == 0 {
+            return;
+        }
+        self.selected_neighbour = Some(match self.selected_neighbour {
+            Some(index) => (index as isize + step).rem_euclid(count) as usize,
+            None if step > 0 => 0,
+            None => count as usize - 1,
+        });
+        // the entries close the message
+        self.scroll = u16::MAX;
+    }
+
+    fn show_current_commit(&mut self, repository: &mut RepositoryInfo) {
+        let (commit_id, commit_message) = repository.current_commit().unwrap();
+        let (message, trailers) = trailers::split(&commit_message);
+        self.stats = repository.commit_stats(&commit_id).ok();
+        self.content = if self.emoji {
+            format!("{}: {}", commit_id, emoji::replace_shortcodes(message))
+        } else {
+            format!("{}: {}", commit_id, message)
+        };
+        let co_authors: Vec<&str> = trailers
+            .iter()
+            .filter(|trailer| trailer.is_co_author())
+            .map(|trailer| trailer.value.as_str())
+            .collect();
+        self.authors = match repository.commit_info() {
+            Ok(info) if !co_authors.is_empty() => Some(format!(
+                "{} <{}>, {}",
+                info.author,
+                info.email,
+                co_authors.join(", ")
+            )),
+            _ => None,
+        };
+        self.details = Self::details(repository, self.stats);
+        self.issues = repository.issue_references(&self.content);
+        self.selected_issue = None;
+        self.trailers = trailers;
+        self.load_neighbours(repository);
+        self.selected_neighbour = None;
+        self.scroll = 0;
+    }
+
+    // Lists the parents and children of the current commit; the children are listed once
+    // found, when finding them is left to a worker thread.
+    fn load_neighbours(&mut self, repository: &mut RepositoryInfo) {
+        let parents = repository.parents().unwrap_or_default();
+        let children = match repository.children() {
+            Some(children) => children.unwrap_or_default(),
+            None => {
+                self.spawn_children(repository);
+                Vec::new()
+            }
+        };
+        self.neighbours = parents
+            .into_iter()
+            .map(|(id, subject)| ("parent", id, subject))
+            .chain(
+                children
+                    .into_iter()
+                    .map(|(id, subject)| ("child", id, subject)),
+            )
+            .collect();
+    }
+
+    // Starts finding the children, unless they are already being found from the same HEAD.
+    fn spawn_children(&mut self, repository: &RepositoryInfo) {
+        let running = self.children_job.as_ref().map(|(job, _)| job.head());
+        if running.is_some() && running == repository.head_id().ok() {
+            return;
+        }
+        self.cancel_children();
+        let cancel = CancelToken::default();
+        if let Ok(job) = repository.spawn_children(self.progress.clone(), cancel.clone()) {
+            self.children_job = Some((job, cancel));
+        }
+    }
+
+    /// Stops finding the children, returning whether they were being found. They are
+    /// looked for again when another commit is shown.
+    pub fn cancel_children(&mut self) -> bool {
+        match self.children_job.take() {
+            Some((_, cancel)) => {
+                cancel.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Picks up the children found in the background and lists those of the shown commit.
+    pub fn poll_children(&mut self) -> Option<Message> {
+        if !self
+            .children_job
+            .as_ref()
+            .is_some_and(|(job, _)| job.is_finished())
+        {
+            return None;
+        }
+        let (job, _) = self.children_job.take()?;
+        let repository = Arc::clone(&self.repository);
+        let mut repository = match repository.lock() {
+            Ok(repository) => repository,
+            Err(_) => return Some(Message::Error(GviewError::Lock)),
+        };
+        if let Err(e) = repository.finish_children(job) {
+            return Some(Message::Error(GviewError::new(
+                "Cannot find the children",
+                e,
+            )));
+        }
+        self.load_neighbours(&mut repository);
+        self.selected_neighbour = self
+            .selected_neighbour
+            .filter(|index| *index < self.neighbours.len());
+        None
+    }
+
+    // Rows of the detail view of the current commit.
+    fn details(
+        repository: &RepositoryInfo,
+        stats: Option<CommitStats>,
+    ) -> Vec<(&'static str, String)> {
+        let mut details = Vec::new();
+        if let Ok(info) = repository.commit_info() {
+            details.push((
+                "Author",
+                format!("{} <{}>  {}", info.author, info.email, info.date),
+            ));
+        }
+        if let Ok((name, email, date)) = repository.committer() {
+            details.push(("Committer", format!("{} <{}>  {}", name, email, date)));
         }
+        let parents = repository.parents().unwrap_or_default();
+        details.push((
+            "Parents",
+            match parents.is_empty() {
+                true => "none, the root commit".to_owned(),
+                false => parents
+                    .into_iter()
+                    .map(|(id, _)| id)
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            },
+        ));
+        if let Some(stats) = stats {
+            details.push(("Changes", stats.to_string()));
+        }
+        details
+    }
+
+    fn max_scroll(&self) -> u16 {
+        self.line_count.saturating_sub(self.height)
+    }
+
+    // Rows the panel needs at `width`, borders included: one message line at least, and at
+    // most MAX_MESSAGE_LINES or `max_height`.
+    pub fn height(&self, width: u16, max_height: u16) -> u16 {
+        let lines = self.paragraph().line_count(width.saturating_sub(2)) as u16;
+        (lines.clamp(1, MAX_MESSAGE_LINES) + 2).min(max_height.max(3))
     }
 
     fn _handle_message(&mut self, message: &Message) -> Message {
         match message {
             Message::MultipleTimes(MultipleTimesOperation::SetUp { repository }) => {
                 let mut repository = repository.lock().unwrap();
-                let (commit_id, commit_message) = repository.current_commit().unwrap();
-                self.content = format!("{}: {}", commit_id, commit_message);
+                self.show_current_commit(&mut repository);
             }
             Message::MultipleTimes(MultipleTimesOperation::ChangeShowCommit) => {
-                let mut repository = self.repository.lock().unwrap();
-                let (commit_id, commit_message) = repository.current_commit().unwrap();
-                self.content = format!("{}: {}", commit_id, commit_message);
+                let repository = Arc::clone(&self.repository);
+                let mut repository = repository.lock().unwrap();
+                self.show_current_commit(&mut repository);
+            }
+            Message::MultipleTimes(MultipleTimesOperation::ChangeBase) => {
+                let repository = self.repository.lock().unwrap();
+                self.base = repository.base_ref();
+                self.target = repository.compare_target();
             }
             _ => {}
         }
@@ -49,9 +394,44 @@ impl CommitViewer {
 
 impl OperatableComponent for CommitViewer {
     fn draw(&mut self, frame: &mut Frame, rect: Rect) {
-        let right_paragraph = Paragraph::new(self.content.to_owned())
-            .block(title_block("current commit (g: go to commit)", self.focus));
-        frame.render_widget(right_paragraph, rect);
+        let short = short_id;
+        let title = match (&self.base, &self.target) {
+            (Some(base), Some(target)) => format!(
+                "current commit (g: go to commit) [compare: {}..{}]",
+                short(base),
+                short(target)
+            ),
+            (Some(base), None) => {
+                format!("current commit (g: go to commit) [base: {}]", short(base))
+            }
+            _ => "current commit (g: go to commit)".to_owned(),
+        };
+        let title = match &self.stats {
+            Some(stats) => format!("{} · {}", title, stats),
+            None => title,
+        };
+        let title = match &self.checks {
+            Some(checks) => format!("{} · {} (C)", title, checks),
+            None => title,
+        };
+        self.line_count = self.paragraph().line_count(rect.width.saturating_sub(2)) as u16;
+        self.height = rect.height.saturating_sub(2);
+        self.scroll = self.scroll.min(self.max_scroll());
+        let mut block = panel_block(&title, self.focus, &self.theme);
+        if self.max_scroll() > 0 {
+            // which part of the message is shown
+            block = block.title_bottom(
+                Line::from(format!(
+                    " j/k: {}-{}/{} ",
+                    self.scroll + 1,
+                    self.scroll + self.height,
+                    self.line_count
+                ))
+                .right_aligned(),
+            );
+        }
+        let paragraph = self.paragraph().block(block).scroll((self.scroll, 0));
+        frame.render_widget(paragraph, rect);
     }
     fn process_focus(&mut self) {
         match self.focus {
@@ -71,9 +451,81 @@ impl OperatableComponent for CommitViewer {
                 let _ = binding.set_next_commit();
                 return Message::MultipleTimes(MultipleTimesOperation::ChangeShowCommit);
             }
+            KeyCode::Left => self.select_neighbour(-1),
+            KeyCode::Right => self.select_neighbour(1),
+            KeyCode::Enter => {
+                if let Some((_, id, _)) = self
+                    .selected_neighbour
+                    .and_then(|index| self.neighbours.get(index))
+                {
+                    return Message::Once(OnceOperation::SetCommitById {
+                        commit_id: id.to_owned(),
+                    });
+                }
+            }
+            KeyCode::Char('j') if self.scroll < self.max_scroll() => {
+                self.scroll += 1;
+            }
+            KeyCode::Char('k') => {
+                self.scroll = self.scroll.saturating_sub(1);
+            }
             KeyCode::Char('g') => {
                 return Message::Once(OnceOperation::OpenCommitModal);
             }
+            KeyCode::Char('b') => {
+                return Message::Once(OnceOperation::OpenPrompt {
+                    kind: PromptKind::BaseRef,
+                });
+            }
+            KeyCode::Char('c') => {
+                return Message::Once(OnceOperation::OpenPrompt {
+                    kind: PromptKind::CompareRefs,
+                });
+            }
+            KeyCode::Char('y') => {
+                return match self.repository.lock() {
+                    Ok(repo) => Message::Once(OnceOperation::CopyToClipboard {
+                        text: repo.get_current_commit_id(),
+                    }),
+                    Err(_) => Message::Error(GviewError::Lock),
+                };
+            }
+            KeyCode::Char('e') => {
+                return Message::Once(OnceOperation::OpenPrompt {
+                    kind: PromptKind::ExportSummary,
+                });
+            }
+            KeyCode::Char('i') => {
+                return Message::Once(OnceOperation::OpenPrompt {
+                    kind: PromptKind::ContainedIn,
+                });
+            }
+            KeyCode::Char('m') => {
+                return Message::Once(OnceOperation::OpenPrompt {
+                    kind: PromptKind::MergeBase,
+                });
+            }
+            KeyCode::Char('C') => return Message::Once(OnceOperation::OpenChecksModal),
+            KeyCode::Char('L') => return Message::Once(OnceOperation::OpenLanguages),
+            KeyCode::Char('d') => {
+                self.show_details = !self.show_details;
+                self.scroll = 0;
+            }
+            // each press opens the next reference of the message
+            KeyCode::Char('I') => {
+                if self.issues.is_empty() {
+                    return Message::Error(GviewError::unavailable(
+                        "No issue references in this commit message",
+                    ));
+                }
+                let index = self
+                    .selected_issue
+                    .map_or(0, |index| (index + 1) % self.issues.len());
+                self.selected_issue = Some(index);
+                return Message::Once(OnceOperation::OpenIssue {
+                    reference: self.content[self.issues[index].clone()].to_owned(),
+                });
+            }
             _ => {}
         }
         Message::NoAction
@@ -94,26 +546,39 @@ impl OperatableComponent for CommitViewer {
             (_, new_message) => new_message,
         }
     }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    // the subject line
+    fn min_height(&self) -> u16 {
+        3
+    }
 }
 
-fn title_block(title: &str, focus: Focus) -> Block {
-    Block::bordered()
-        .title(title.bold().into_left_aligned_line())
-        .style(match focus {
-            Focus::ON => Style::default(),
-            Focus::Off => Style::default().fg(Color::DarkGray),
-        })
+fn short_id(id: &str) -> String {
+    id[..std::cmp::min(8, id.len())].to_owned()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::repository::RepositoryInfo;
+    use crate::{progress::ProgressTracker, repository::RepositoryInfo};
     use insta::assert_snapshot;
     use ratatui::{backend::TestBackend, Terminal};
     use std::sync::{Arc, Mutex};
 
     fn create_mock_repo() -> Arc<Mutex<RepositoryInfo>> {
+        create_mock_repo_with_message("Initial commit")
+    }
+
+    fn create_mock_repo_with_message(message: &str) -> Arc<Mutex<RepositoryInfo>> {
+        create_mock_repo_with_messages(&[message])
+    }
+
+    // A chain of empty commits, the last one being HEAD.
+    fn create_mock_repo_with_messages(messages: &[&str]) -> Arc<Mutex<RepositoryInfo>> {
         use std::env;
         use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -142,14 +607,24 @@ mod tests {
         };
         let tree = repo.find_tree(tree_id).unwrap();
 
-        let _ = repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            "Initial commit",
-            &tree,
-            &[],
-        );
+        let mut parent: Option<git2::Oid> = None;
+        for message in messages {
+            let parents: Vec<git2::Commit> = parent
+                .map(|oid| repo.find_commit(oid).unwrap())
+                .into_iter()
+                .collect();
+            let parents: Vec<&git2::Commit> = parents.iter().collect();
+            parent = repo
+                .commit(
+                    Some("HEAD"),
+                    &signature,
+                    &signature,
+                    message,
+                    &tree,
+                    &parents,
+                )
+                .ok();
+        }
 
         drop(tree);
         let oid = repo.head().unwrap().target().unwrap();
@@ -161,7 +636,7 @@ mod tests {
     #[test]
     fn test_commit_viewer_draw_empty() {
         let mock_repo = create_mock_repo();
-        let mut commit_viewer = CommitViewer::new(mock_repo);
+        let mut commit_viewer = CommitViewer::new(mock_repo, ProgressTracker::new().reporter());
         commit_viewer.focus = Focus::ON;
         commit_viewer.content = "".to_string();
 
@@ -182,7 +657,7 @@ mod tests {
     #[test]
     fn test_commit_viewer_draw_with_commit() {
         let mock_repo = create_mock_repo();
-        let mut commit_viewer = CommitViewer::new(mock_repo);
+        let mut commit_viewer = CommitViewer::new(mock_repo, ProgressTracker::new().reporter());
         commit_viewer.focus = Focus::ON;
         commit_viewer.content = "abc123def456: Initial commit message".to_string();
 
@@ -203,7 +678,7 @@ mod tests {
     #[test]
     fn test_commit_viewer_draw_unfocused() {
         let mock_repo = create_mock_repo();
-        let mut commit_viewer = CommitViewer::new(mock_repo);
+        let mut commit_viewer = CommitViewer::new(mock_repo, ProgressTracker::new().reporter());
         commit_viewer.focus = Focus::Off;
         commit_viewer.content = "def789ghi012: Add new feature implementation".to_string();
 
@@ -224,7 +699,7 @@ mod tests {
     #[test]
     fn test_commit_viewer_draw_long_commit_message() {
         let mock_repo = create_mock_repo();
-        let mut commit_viewer = CommitViewer::new(mock_repo);
+        let mut commit_viewer = CommitViewer::new(mock_repo, ProgressTracker::new().reporter());
         commit_viewer.focus = Focus::ON;
         commit_viewer.content = "abcdef123456: This is a very long commit message that should demonstrate how the commit viewer handles longer text content that might wrap or be truncated depending on the terminal width".to_string();
 
@@ -245,7 +720,7 @@ mod tests {
     #[test]
     fn test_commit_viewer_draw_multiline_commit() {
         let mock_repo = create_mock_repo();
-        let mut commit_viewer = CommitViewer::new(mock_repo);
+        let mut commit_viewer = CommitViewer::new(mock_repo, ProgressTracker::new().reporter());
         commit_viewer.focus = Focus::ON;
         commit_viewer.content = "commit123: Fix critical bug\n\nThis commit addresses a critical issue where the application\nwould crash under certain conditions. The fix includes:\n- Better error handling\n- Input validation\n- Memory management improvements".to_string();
 
@@ -262,4 +737,338 @@ mod tests {
         let buffer = terminal.backend().buffer();
         assert_snapshot!(format!("{:?}", buffer));
     }
+
+    #[test]
+    fn test_commit_viewer_trailers() {
+        let mock_repo = create_mock_repo_with_message(
+            "Pair on it\n\nCo-authored-by: Bob <bob@example.com>\nSigned-off-by: Test User <test@example.com>\n",
+        );
+        let mut commit_viewer =
+            CommitViewer::new(Arc::clone(&mock_repo), ProgressTracker::new().reporter());
+        commit_viewer.handle_message(&Message::MultipleTimes(
+            MultipleTimesOperation::ChangeShowCommit,
+        ));
+        assert!(commit_viewer.content.ends_with(": Pair on it"));
+        assert_eq!(
+            commit_viewer.authors.as_deref(),
+            Some("Test User <test@example.com>, Bob <bob@example.com>")
+        );
+
+        let backend = TestBackend::new(80, 8);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|frame| commit_viewer.draw(frame, frame.size()))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let row = |y: u16| {
+            (1..79)
+                .map(|x| buffer.get(x, y).symbol())
+                .collect::<String>()
+                .trim_end()
+                .to_owned()
+        };
+        assert_eq!(row(2), "");
+        assert_eq!(
+            row(3),
+            "Authors         Test User <test@example.com>, Bob <bob@example.com>"
+        );
+        assert_eq!(row(4), "Co-authored-by  Bob <bob@example.com>");
+        assert_eq!(row(5), "Signed-off-by   Test User <test@example.com>");
+        assert_eq!(commit_viewer.height(80, 20), 7);
+    }
+
+    #[test]
+    fn test_commit_viewer_details() {
+        let mock_repo = create_mock_repo_with_messages(&["First", "Second"]);
+        let mut commit_viewer =
+            CommitViewer::new(Arc::clone(&mock_repo), ProgressTracker::new().reporter());
+        commit_viewer.handle_message(&Message::MultipleTimes(
+            MultipleTimesOperation::ChangeShowCommit,
+        ));
+        let parent = mock_repo.lock().unwrap().parents().unwrap()[0].0.clone();
+        let height = commit_viewer.height(100, 20);
+
+        assert_eq!(
+            commit_viewer.process_events(KeyCode::Char('d')),
+            Message::NoAction
+        );
+        assert_eq!(commit_viewer.height(100, 20), height + 5);
+        let mut terminal = Terminal::new(TestBackend::new(100, 12)).unwrap();
+        terminal
+            .draw(|frame| commit_viewer.draw(frame, frame.size()))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let row = |y: u16| {
+            (1..99)
+                .map(|x| buffer.get(x, y).symbol())
+                .collect::<String>()
+                .trim_end()
+                .to_owned()
+        };
+        assert_eq!(
+            row(3),
+            "Author     Test User <test@example.com>  2009-02-13 23:31:30 +0000"
+        );
+        assert_eq!(
+            row(4),
+            "Committer  Test User <test@example.com>  2009-02-13 23:31:30 +0000"
+        );
+        assert_eq!(row(5), format!("Parents    {}", parent));
+        assert_eq!(row(6), "Changes    0 files changed, +0 −0");
+
+        // toggled off again
+        commit_viewer.process_events(KeyCode::Char('d'));
+        assert_eq!(commit_viewer.height(100, 20), height);
+    }
+
+    #[test]
+    fn test_commit_viewer_issue_references() {
+        let mock_repo = create_mock_repo_with_message("Fix crash (#12)\n\nRefs #3, not issue#4");
+        let mut commit_viewer = CommitViewer::new(mock_repo, ProgressTracker::new().reporter());
+        assert!(matches!(
+            commit_viewer.process_events(KeyCode::Char('I')),
+            Message::Error(GviewError::Unavailable(_))
+        ));
+        commit_viewer.handle_message(&Message::MultipleTimes(
+            MultipleTimesOperation::ChangeShowCommit,
+        ));
+        let underlined: Vec<String> = commit_viewer
+            .message_text()
+            .lines
+            .iter()
+            .flat_map(|line| line.spans.iter())
+            .filter(|span| span.style.add_modifier.contains(Modifier::UNDERLINED))
+            .map(|span| span.content.to_string())
+            .collect();
+        assert_eq!(underlined, vec!["#12", "#3"]);
+
+        // each press opens the next reference, wrapping around
+        for reference in ["#12", "#3", "#12"] {
+            assert_eq!(
+                commit_viewer.process_events(KeyCode::Char('I')),
+                Message::Once(OnceOperation::OpenIssue {
+                    reference: reference.to_owned()
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_commit_viewer_emoji() {
+        let mock_repo = create_mock_repo_with_message(":sparkles: Add emoji");
+        let mut commit_viewer = CommitViewer::new(mock_repo, ProgressTracker::new().reporter());
+        let show = Message::MultipleTimes(MultipleTimesOperation::ChangeShowCommit);
+        commit_viewer.handle_message(&show);
+        assert!(commit_viewer.content.ends_with(": :sparkles: Add emoji"));
+
+        commit_viewer.set_emoji(true);
+        commit_viewer.handle_message(&show);
+        assert!(commit_viewer.content.ends_with(": ✨ Add emoji"));
+    }
+
+    #[test]
+    fn test_commit_viewer_scroll() {
+        let mut commit_viewer =
+            CommitViewer::new(create_mock_repo(), ProgressTracker::new().reporter());
+        commit_viewer.content = (1..=12)
+            .map(|number| format!("line {}", number))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut terminal = Terminal::new(TestBackend::new(40, 6)).unwrap();
+        let mut draw = |commit_viewer: &mut CommitViewer| {
+            terminal
+                .draw(|frame| commit_viewer.draw(frame, frame.size()))
+                .unwrap();
+            let buffer = terminal.backend().buffer();
+            let row = |y: u16| {
+                (0..40)
+                    .map(|x| buffer.get(x, y).symbol())
+                    .collect::<String>()
+            };
+            (row(1), row(5))
+        };
+        let (first, bottom) = draw(&mut commit_viewer);
+        assert!(first.starts_with("│line 1 "));
+        assert!(bottom.ends_with(" j/k: 1-4/12 ┘"));
+
+        commit_viewer.process_events(KeyCode::Char('j'));
+        let (first, bottom) = draw(&mut commit_viewer);
+        assert!(first.starts_with("│line 2 "));
+        assert!(bottom.ends_with(" j/k: 2-5/12 ┘"));
+
+        // the last lines stay at the bottom of the panel
+        for _ in 0..20 {
+            commit_viewer.process_events(KeyCode::Char('j'));
+        }
+        assert_eq!(commit_viewer.scroll, 8);
+        commit_viewer.process_events(KeyCode::Char('k'));
+        let (first, _) = draw(&mut commit_viewer);
+        assert!(first.starts_with("│line 8 "));
+
+        // a message that fits has nothing to scroll
+        commit_viewer.content = "abc123: Short".to_owned();
+        let (first, bottom) = draw(&mut commit_viewer);
+        assert!(first.starts_with("│abc123: Short"));
+        assert!(!bottom.contains("j/k"));
+    }
+
+    #[test]
+    fn test_commit_viewer_neighbours() {
+        let mock_repo = create_mock_repo_with_messages(&["First", "Second", "Third"]);
+        let mut commit_viewer =
+            CommitViewer::new(Arc::clone(&mock_repo), ProgressTracker::new().reporter());
+        let show = Message::MultipleTimes(MultipleTimesOperation::ChangeShowCommit);
+        mock_repo.lock().unwrap().set_parent_commit();
+        commit_viewer.handle_message(&show);
+        let labels: Vec<(&str, &str)> = commit_viewer
+            .neighbours
+            .iter()
+            .map(|(label, _, subject)| (*label, subject.as_str()))
+            .collect();
+        assert_eq!(labels, vec![("parent", "First"), ("child", "Third")]);
+
+        // nothing is selected until ←/→
+        assert_eq!(
+            commit_viewer.process_events(KeyCode::Enter),
+            Message::NoAction
+        );
+        commit_viewer.process_events(KeyCode::Left);
+        assert_eq!(commit_viewer.selected_neighbour, Some(1));
+        commit_viewer.process_events(KeyCode::Right);
+        assert_eq!(commit_viewer.selected_neighbour, Some(0));
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 8)).unwrap();
+        terminal
+            .draw(|frame| commit_viewer.draw(frame, frame.size()))
+            .unwrap();
+        let row = |y: u16| {
+            (1..39)
+                .map(|x| terminal.backend().buffer().get(x, y).symbol())
+                .collect::<String>()
+        };
+        let parent = commit_viewer.neighbours[0].1.clone();
+        // below the wrapped message and a blank line
+        assert!(row(4).starts_with(&format!("→ parent  {} First ", &parent[..8])));
+        assert!(row(5).starts_with("  child   "));
+
+        let message = commit_viewer.process_events(KeyCode::Enter);
+        assert_eq!(
+            message,
+            Message::Once(OnceOperation::SetCommitById { commit_id: parent })
+        );
+    }
+
+    #[test]
+    fn test_commit_viewer_children_in_background() {
+        let mock_repo = create_mock_repo_with_messages(&["First", "Second", "Third"]);
+        mock_repo.lock().unwrap().set_background(true);
+        mock_repo.lock().unwrap().set_parent_commit();
+        let mut commit_viewer =
+            CommitViewer::new(Arc::clone(&mock_repo), ProgressTracker::new().reporter());
+        let show = Message::MultipleTimes(MultipleTimesOperation::ChangeShowCommit);
+        let labels = |commit_viewer: &CommitViewer| -> Vec<&'static str> {
+            commit_viewer
+                .neighbours
+                .iter()
+                .map(|(label, ..)| *label)
+                .collect()
+        };
+
+        // the parents are listed at once, and the children once found; Esc stops the walk
+        commit_viewer.handle_message(&show);
+        assert_eq!(labels(&commit_viewer), vec!["parent"]);
+        assert!(commit_viewer.cancel_children());
+        assert!(!commit_viewer.cancel_children());
+
+        commit_viewer.handle_message(&show);
+        while commit_viewer.children_job.is_some() {
+            std::thread::yield_now();
+            assert_eq!(commit_viewer.poll_children(), None);
+        }
+        assert_eq!(labels(&commit_viewer), vec!["parent", "child"]);
+    }
+
+    #[test]
+    fn test_commit_viewer_height() {
+        let mut commit_viewer =
+            CommitViewer::new(create_mock_repo(), ProgressTracker::new().reporter());
+        commit_viewer.content = "abc123: Initial commit\n".to_string();
+        assert_eq!(commit_viewer.height(80, 20), 3);
+
+        commit_viewer.content = "abc123: Fix bug\n\nDetails\n".to_string();
+        assert_eq!(commit_viewer.height(80, 20), 5);
+        // a long subject wraps
+        commit_viewer.content = format!("abc123: {}", "word ".repeat(20));
+        assert_eq!(commit_viewer.height(42, 20), 5);
+
+        commit_viewer.content = "abc123: Many lines\n".repeat(30);
+        assert_eq!(commit_viewer.height(80, 20), MAX_MESSAGE_LINES + 2);
+        assert_eq!(commit_viewer.height(80, 8), 8);
+        assert_eq!(commit_viewer.height(80, 1), 3);
+    }
+
+    #[test]
+    fn test_commit_viewer_base_key_opens_prompt() {
+        let mock_repo = create_mock_repo();
+        let mut commit_viewer = CommitViewer::new(mock_repo, ProgressTracker::new().reporter());
+        let message = commit_viewer.process_events(KeyCode::Char('b'));
+        assert_eq!(
+            message,
+            Message::Once(OnceOperation::OpenPrompt {
+                kind: PromptKind::BaseRef
+            })
+        );
+
+        let message = commit_viewer.process_events(KeyCode::Char('e'));
+        assert_eq!(
+            message,
+            Message::Once(OnceOperation::OpenPrompt {
+                kind: PromptKind::ExportSummary
+            })
+        );
+
+        let message = commit_viewer.process_events(KeyCode::Char('c'));
+        assert_eq!(
+            message,
+            Message::Once(OnceOperation::OpenPrompt {
+                kind: PromptKind::CompareRefs
+            })
+        );
+    }
+
+    #[test]
+    fn test_commit_viewer_copy_hash() {
+        let mock_repo = create_mock_repo();
+        let hash = mock_repo.lock().unwrap().get_current_commit_id();
+        let mut commit_viewer = CommitViewer::new(mock_repo, ProgressTracker::new().reporter());
+        assert_eq!(hash.len(), 40);
+        assert_eq!(
+            commit_viewer.process_events(KeyCode::Char('y')),
+            Message::Once(OnceOperation::CopyToClipboard { text: hash })
+        );
+    }
+
+    #[test]
+    fn test_commit_viewer_tracks_base() {
+        let mock_repo = create_mock_repo();
+        let mut commit_viewer =
+            CommitViewer::new(Arc::clone(&mock_repo), ProgressTracker::new().reporter());
+        mock_repo.lock().unwrap().set_base_ref("HEAD").unwrap();
+
+        commit_viewer.handle_message(&Message::MultipleTimes(MultipleTimesOperation::ChangeBase));
+        assert_eq!(commit_viewer.base.as_ref().map(String::len), Some(40));
+        assert_eq!(commit_viewer.target, None);
+
+        mock_repo
+            .lock()
+            .unwrap()
+            .set_compare_refs("HEAD", "HEAD")
+            .unwrap();
+        commit_viewer.handle_message(&Message::MultipleTimes(MultipleTimesOperation::ChangeBase));
+        assert_eq!(commit_viewer.target, commit_viewer.base);
+
+        mock_repo.lock().unwrap().clear_base_ref();
+        commit_viewer.handle_message(&Message::MultipleTimes(MultipleTimesOperation::ChangeBase));
+        assert_eq!(commit_viewer.base, None);
+    }
 }
@@ -1,27 +1,60 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::BTreeSet,
+    ops::RangeInclusive,
+    sync::{Arc, Mutex},
+};
 
 use crossterm::event::KeyCode;
 use ratatui::{
-    layout::Rect,
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style, Stylize},
-    widgets::{Block, Paragraph, Wrap},
+    text::{Line, Span, Text},
+    widgets::{Block, Clear, Paragraph, Wrap},
     Frame,
 };
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{
+    error::GviewError,
+    highlight::Highlighter,
+    progress,
+    repository::{
+        self, BlameJob, BlameRequest, CommitRow, DiffRow, RepositoryInfo, Source, SplitDiff,
+        Window, WINDOW_LINES,
+    },
+    summary, tags,
+    theme::Theme,
+};
 
-use crate::repository::{CommitRow, RepositoryInfo};
-
-use super::operatable_components::{Focus, Message, OnceOperation, OperatableComponent};
+use super::{
+    operatable_components::{
+        panel_block, Focus, Message, MultipleTimesOperation, OnceOperation, OperatableComponent,
+    },
+    prompt_modal::PromptKind,
+};
 
 pub enum ShowMode {
     WithLine,
     WithBlame,
     NoLine,
+    Diff,
+    SplitDiff,
+    // binary files, shown as a hex dump
+    Hex,
 }
 
 impl ShowMode {
-    fn concat(&mut self, rows: Vec<CommitRow>) -> String {
+    // Lines carrying a note get a `*` in the gutter next to the separator.
+    // Unless `repeat_blame` is set, the commit is only shown on the first line of a hunk.
+    fn concat(
+        &mut self,
+        rows: Vec<CommitRow>,
+        noted: &BTreeSet<usize>,
+        repeat_blame: bool,
+    ) -> String {
+        let marker = |number: usize| if noted.contains(&number) { '*' } else { ' ' };
         match self {
-            Self::NoLine => rows
+            Self::NoLine | Self::Diff | Self::SplitDiff | Self::Hex => rows
                 .iter()
                 .map(|row| row.line.to_owned())
                 .collect::<Vec<String>>()
@@ -32,8 +65,9 @@ impl ShowMode {
                 rows.iter()
                     .map(|row| {
                         format!(
-                            "{:width$} | {} ",
+                            "{:width$}{}| {} ",
                             row.number,
+                            marker(row.number),
                             row.line.to_owned(),
                             width = width
                         )
@@ -43,11 +77,321 @@ impl ShowMode {
             }
             Self::WithBlame => rows
                 .iter()
-                .map(|row| format!("{} | {} ", row.commit, row.line.to_owned()))
+                .enumerate()
+                .map(|(index, row)| {
+                    // lines without blame keep the gutter aligned
+                    let commit = if row.commit.is_zero() {
+                        format!("{:<width$}", "unavailable", width = BLAME_GUTTER_WIDTH - 1)
+                    } else {
+                        row.commit.to_string()
+                    };
+                    let continues = index > 0 && rows[index - 1].commit == row.commit;
+                    format!(
+                        "{}{}| {} ",
+                        if continues && !repeat_blame {
+                            " ".repeat(commit.len())
+                        } else {
+                            commit
+                        },
+                        marker(row.number),
+                        row.line.to_owned()
+                    )
+                })
                 .collect::<Vec<String>>()
                 .join("\n"),
         }
     }
+
+    // Characters before the code on each line built by `concat`.
+    fn gutter_width(&self, rows: &[CommitRow]) -> usize {
+        match self {
+            Self::NoLine | Self::Diff | Self::SplitDiff | Self::Hex => 0,
+            Self::WithLine => {
+                let max_line_number = rows.iter().map(|row| row.number).max().unwrap_or(0);
+                max_line_number.to_string().len() + 3
+            }
+            Self::WithBlame => BLAME_GUTTER_WIDTH + 2,
+        }
+    }
+}
+
+// Shown after the last line of a file that does not end with a newline, as in diffs.
+const NO_NEWLINE_MARKER: &str = "\n\\ No newline at end of file";
+
+// Bytes of a binary file shown in its hex dump; the rest is only counted.
+const HEX_DUMP_LIMIT: usize = 64 * 1024;
+
+// Scrolling this close to either end of the lines read from a large file reads more.
+const WINDOW_MARGIN: usize = WINDOW_LINES / 4;
+
+// Width of the blame gutter: a full commit id followed by the note marker.
+const BLAME_GUTTER_WIDTH: usize = 41;
+
+// Lines the layout keeps for the content when it can.
+const MIN_VISIBLE_LINES: u16 = 3;
+
+// Alternates a flag each time the blamed commit changes, so hunks can be shaded.
+fn blame_shading(rows: &[CommitRow]) -> Vec<bool> {
+    let mut shaded = false;
+    rows.iter()
+        .enumerate()
+        .map(|(index, row)| {
+            if index > 0 && rows[index - 1].commit != row.commit {
+                shaded = !shaded;
+            }
+            shaded
+        })
+        .collect()
+}
+
+// Incremental search in the shown content, started with `/`.
+#[derive(Default)]
+struct Search {
+    query: String,
+    // while the query is typed; Enter keeps it for `n`/`N`, Esc drops it
+    editing: bool,
+    // scroll position to return to when the search is dropped
+    origin: usize,
+    // (line, column) of the match last jumped to
+    current: Option<(usize, usize)>,
+}
+
+// Row of the view the current line is kept at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CursorRow {
+    Top,
+    Middle,
+    Bottom,
+}
+
+// Details of a blamed line, opened by clicking its gutter.
+struct BlamePopup {
+    // the line, relative to the start of `content`
+    index: usize,
+    rows: Vec<(&'static str, String)>,
+}
+
+// Character columns where `query` starts in `line`, from `from` on and without overlaps.
+// Like smartcase in vim, the query ignores case unless it has capitals.
+fn find_matches(line: &str, query: &str, from: usize) -> Vec<usize> {
+    let fold = |c: char| c.to_lowercase().next().unwrap_or(c);
+    let ignore_case = !query.chars().any(char::is_uppercase);
+    let normalize = |c: char| if ignore_case { fold(c) } else { c };
+    let query: Vec<char> = query.chars().map(normalize).collect();
+    let line: Vec<char> = line.chars().map(normalize).collect();
+    let mut matches = vec![];
+    let mut column = from;
+    while !query.is_empty() && column + query.len() <= line.len() {
+        if line[column..column + query.len()] == query[..] {
+            matches.push(column);
+            column += query.len();
+        } else {
+            column += 1;
+        }
+    }
+    matches
+}
+
+// Patches the styles of `ranges`, as (start, end, style) in characters, over `line`.
+fn overlay(line: Line<'static>, ranges: &[(usize, usize, Style)]) -> Line<'static> {
+    let mut spans: Vec<Span<'static>> = vec![];
+    let mut column = 0;
+    for span in line.spans {
+        let mut piece = String::new();
+        let mut piece_style = span.style;
+        for c in span.content.chars() {
+            let style = ranges
+                .iter()
+                .find(|(start, end, _)| (*start..*end).contains(&column))
+                .map_or(span.style, |(_, _, style)| span.style.patch(*style));
+            if style != piece_style && !piece.is_empty() {
+                spans.push(Span::styled(std::mem::take(&mut piece), piece_style));
+            }
+            piece_style = style;
+            piece.push(c);
+            column += 1;
+        }
+        if !piece.is_empty() {
+            spans.push(Span::styled(piece, piece_style));
+        }
+    }
+    Line::from(spans).style(line.style)
+}
+
+// Drops the first `count` characters of `spans`, as scrolled out horizontally.
+fn skip_chars(spans: &[Span<'static>], mut count: usize) -> Vec<Span<'static>> {
+    spans
+        .iter()
+        .filter_map(|span| {
+            let len = span.content.chars().count();
+            if count >= len {
+                count -= len;
+                return None;
+            }
+            let content: String = span.content.chars().skip(count).collect();
+            count = 0;
+            Some(Span::styled(content, span.style))
+        })
+        .collect()
+}
+
+// Shown in place of a file whose blob the object database cannot give, with a way to fetch it
+// when `remote` promises the objects of a partial clone.
+fn unavailable_object(file: &str, remote: Option<&str>, error: anyhow::Error) -> String {
+    let fetch = remote
+        .map(|remote| format!("Press F to fetch it from {}.\n\n", remote))
+        .unwrap_or_default();
+    format!(
+        "Cannot show {}: object unavailable (promisor/partial clone?)\n\n{}{}",
+        file, fetch, error
+    )
+}
+
+// `bytes` the way `hexdump -C` shows them: the offset, sixteen bytes in two groups of
+// eight, and the printable ones as ASCII.
+fn hex_dump(bytes: &[u8]) -> String {
+    let mut lines: Vec<String> = bytes[..bytes.len().min(HEX_DUMP_LIMIT)]
+        .chunks(16)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let hex = (0..16)
+                .map(|column| {
+                    let byte = chunk
+                        .get(column)
+                        .map_or("  ".to_owned(), |b| format!("{:02x}", b));
+                    match column {
+                        8 => format!(" {}", byte),
+                        _ => byte,
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join(" ");
+            let ascii: String = chunk
+                .iter()
+                .map(|byte| match byte {
+                    0x20..=0x7e => *byte as char,
+                    _ => '.',
+                })
+                .collect();
+            format!("{:08x}  {}  |{}|", index * 16, hex, ascii)
+        })
+        .collect();
+    if bytes.len() > HEX_DUMP_LIMIT {
+        lines.push(format!("… {} more bytes", bytes.len() - HEX_DUMP_LIMIT));
+    }
+    lines.join("\n")
+}
+
+fn format_diff(rows: Vec<DiffRow>) -> String {
+    rows.iter()
+        .map(|row| match row.origin {
+            'H' => row.line.to_owned(),
+            origin => format!("{}{}", origin, row.line),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// A row of the side-by-side diff: the 0-based line shown on each side, if any, and whether
+// the row is part of a hunk.
+#[derive(Debug, Clone, PartialEq)]
+struct SplitRow {
+    old: Option<usize>,
+    new: Option<usize>,
+    changed: bool,
+}
+
+// Pairs the lines of both sides: unchanged lines face each other, and within a hunk the
+// removed lines face the added ones, the shorter side padded with empty rows.
+fn align(diff: &SplitDiff) -> Vec<SplitRow> {
+    let mut rows = vec![];
+    let (mut old, mut new) = (0, 0);
+    for hunk in &diff.hunks {
+        // an empty side starts after the line it follows
+        let old_begin = hunk.old_start - usize::from(hunk.old_lines > 0);
+        let new_begin = hunk.new_start - usize::from(hunk.new_lines > 0);
+        while old < old_begin && new < new_begin {
+            rows.push(SplitRow {
+                old: Some(old),
+                new: Some(new),
+                changed: false,
+            });
+            (old, new) = (old + 1, new + 1);
+        }
+        (old, new) = (old_begin, new_begin);
+        for offset in 0..hunk.old_lines.max(hunk.new_lines) {
+            rows.push(SplitRow {
+                old: (offset < hunk.old_lines).then_some(old + offset),
+                new: (offset < hunk.new_lines).then_some(new + offset),
+                changed: true,
+            });
+        }
+        (old, new) = (old + hunk.old_lines, new + hunk.new_lines);
+    }
+    while old < diff.old.len() || new < diff.new.len() {
+        rows.push(SplitRow {
+            old: (old < diff.old.len()).then_some(old),
+            new: (new < diff.new.len()).then_some(new),
+            changed: false,
+        });
+        (old, new) = (old + 1, new + 1);
+    }
+    rows
+}
+
+fn side_text(lines: &[String], index: Option<usize>) -> &str {
+    index
+        .and_then(|index| lines.get(index))
+        .map_or("", String::as_str)
+}
+
+// `line` from its `skip`th character on, cut to at most `columns` terminal columns.
+fn fit_columns(line: &str, skip: usize, columns: usize) -> String {
+    let mut used = 0;
+    line.chars()
+        .skip(skip)
+        .take_while(|c| {
+            used += c.width().unwrap_or(0);
+            used <= columns
+        })
+        .collect()
+}
+
+// The side-by-side diff as text, like `diff --side-by-side`, for searching and printing.
+fn format_split(diff: &SplitDiff, rows: &[SplitRow]) -> String {
+    let width = diff.old.iter().map(|line| line.width()).max().unwrap_or(0);
+    rows.iter()
+        .map(|row| {
+            let marker = match (row.changed, row.old, row.new) {
+                (false, _, _) => ' ',
+                (true, Some(_), Some(_)) => '|',
+                (true, Some(_), None) => '<',
+                (true, None, _) => '>',
+            };
+            let old = side_text(&diff.old, row.old);
+            // wide characters such as CJK take two columns
+            let padding = width - old.width();
+            format!(
+                "{}{} {} {}",
+                old,
+                " ".repeat(padding),
+                marker,
+                side_text(&diff.new, row.new)
+            )
+            .trim_end()
+            .to_owned()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn diff_line_style(line: &str, theme: &Theme) -> Style {
+    match line.chars().next() {
+        Some('+') => Style::default().fg(theme.added),
+        Some('-') => Style::default().fg(theme.removed),
+        Some('@') => Style::default().fg(theme.hunk_header),
+        _ => Style::default(),
+    }
 }
 
 pub struct ContentViewer {
@@ -60,6 +404,55 @@ pub struct ContentViewer {
     height: usize,
     repository: Arc<Mutex<RepositoryInfo>>,
     mode: ShowMode,
+    diff_label: String,
+    shaded_lines: Vec<bool>,
+    current_lines: Vec<bool>,
+    // lines last changed by the user, while their lines are highlighted
+    mine_lines: Vec<bool>,
+    // syntax colored code of each line, starting `code_start` characters into the line
+    syntax: Option<Highlighter>,
+    code_start: usize,
+    repeat_blame: bool,
+    source: Source,
+    directory: bool,
+    // short id of the commit previewed from the commit modal
+    preview: Option<String>,
+    // (file, commit, line) of the pinned line, followed across commits
+    pin: Option<(String, String, usize)>,
+    // commit the shown file was read at
+    shown_commit: String,
+    // why the shown file could not be blamed
+    blame_error: Option<String>,
+    // breadcrumb component selected with `[` and `]`, 0 being the repository root
+    crumb: Option<usize>,
+    search: Option<Search>,
+    // both sides of the side-by-side diff and how their lines face each other
+    split: SplitDiff,
+    split_rows: Vec<SplitRow>,
+    // line the selection started at with `V`; it ends at the top line
+    selection: Option<usize>,
+    // the lines of a large file `content` holds; positions are relative to its start
+    window: Option<Window>,
+    // size of the binary file shown as a hex dump
+    blob_size: Option<usize>,
+    // the shown file, when its blob is missing and a promisor remote can give it
+    missing_blob: Option<String>,
+    // blame of a shown file being worked out in the background, and the spinner frame
+    blame_job: Option<BlameJob>,
+    spinner: usize,
+    // blame given up on with Esc, not started again until blame mode is toggled
+    cancelled_blame: Option<BlameRequest>,
+    // the first screen row of each blamed line last drawn, to tell which gutter is clicked
+    blame_rows: Vec<(u16, usize)>,
+    // where the lines were last drawn, inside the border
+    lines_area: Rect,
+    blame_popup: Option<BlamePopup>,
+    // where in the view the current line is kept, set with zt, zz and zb; the lines before
+    // it are context
+    cursor_row: CursorRow,
+    // rows of context kept before and after the current line
+    scrolloff: usize,
+    theme: Theme,
 }
 
 impl ContentViewer {
@@ -74,214 +467,2174 @@ impl ContentViewer {
             scroll_position: 0,
             horizontal_scroll: 0,
             mode: ShowMode::WithLine,
+            diff_label: "vs parent".to_owned(),
+            shaded_lines: vec![],
+            current_lines: vec![],
+            mine_lines: vec![],
+            syntax: None,
+            code_start: 0,
+            repeat_blame: false,
+            source: Source::Commit,
+            directory: false,
+            preview: None,
+            pin: None,
+            shown_commit: String::new(),
+            blame_error: None,
+            crumb: None,
+            search: None,
+            split: SplitDiff::default(),
+            split_rows: vec![],
+            selection: None,
+            window: None,
+            blob_size: None,
+            missing_blob: None,
+            blame_job: None,
+            spinner: 0,
+            cancelled_blame: None,
+            blame_rows: vec![],
+            lines_area: Rect::default(),
+            blame_popup: None,
+            cursor_row: CursorRow::Top,
+            scrolloff: 0,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Picks up blame worked out in the background, redrawing the file it belongs to where
+    /// it is scrolled to. Called once per tick, which also turns the spinner.
+    pub fn poll_blame(&mut self) {
+        self.spinner = self.spinner.wrapping_add(1);
+        if !self.blame_job.as_ref().is_some_and(BlameJob::is_finished) {
+            return;
+        }
+        let Some(job) = self.blame_job.take() else {
+            return;
+        };
+        let shown = self.committed_file() == Some(job.request().path.as_str());
+        match self.repository.lock() {
+            Ok(mut repository) => repository.finish_blame(job),
+            Err(_) => return,
+        }
+        if shown {
+            let position = self.scroll_position;
+            self.refresh_content();
+            self.scroll_position = position;
         }
     }
 
+    pub fn set_scrolloff(&mut self, scrolloff: usize) {
+        self.scrolloff = scrolloff;
+    }
+
+    // Rows the current line is below the top of the view: where zt, zz or zb put it, kept
+    // `scrolloff` rows from either edge, and higher up near the start of the file.
+    fn cursor_offset(&self) -> usize {
+        self.cursor_row_offset().min(self.scroll_position)
+    }
+
+    // Row of the current line away from the start of the file.
+    fn cursor_row_offset(&self) -> usize {
+        // the height includes the borders
+        let last_row = self.height.saturating_sub(3);
+        let margin = self.scrolloff.min(last_row / 2);
+        let row = match self.cursor_row {
+            CursorRow::Top => 0,
+            CursorRow::Middle => last_row / 2,
+            CursorRow::Bottom => last_row,
+        };
+        row.clamp(margin, last_row - margin)
+    }
+
+    // The first line drawn, relative to the start of `content`.
+    fn view_top(&self) -> usize {
+        self.scroll_position - self.cursor_offset()
+    }
+
     fn toggle_line_numbers(&mut self) {
         self.mode = match self.mode {
             ShowMode::NoLine => ShowMode::WithLine,
             ShowMode::WithLine => ShowMode::NoLine,
             ShowMode::WithBlame => ShowMode::WithLine,
+            ShowMode::Diff | ShowMode::SplitDiff | ShowMode::Hex => ShowMode::WithLine,
         };
         self.refresh_content();
     }
 
+    // Whether blame mode is waiting for the shown file's blame.
+    fn is_blaming(&self) -> bool {
+        matches!(self.mode, ShowMode::WithBlame)
+            && self
+                .blame_job
+                .as_ref()
+                .is_some_and(|job| self.committed_file() == Some(job.request().path.as_str()))
+    }
+
+    // Gives up on the blame of the shown file and shows it plain. git2 cannot stop a blame
+    // midway, so the worker is left to finish and what it finds is dropped.
+    fn cancel_blame(&mut self) {
+        if !self.is_blaming() {
+            return;
+        }
+        self.cancelled_blame = self.blame_job.take().map(|job| job.request().clone());
+        self.mode = ShowMode::NoLine;
+        let position = self.scroll_position;
+        self.refresh_content();
+        self.scroll_position = position;
+    }
+
     fn toggle_blame_mode(&mut self) {
+        self.cancelled_blame = None;
         self.mode = match self.mode {
             ShowMode::NoLine => ShowMode::WithBlame,
             ShowMode::WithLine => ShowMode::WithBlame,
             ShowMode::WithBlame => ShowMode::NoLine,
+            ShowMode::Diff | ShowMode::SplitDiff | ShowMode::Hex => ShowMode::WithBlame,
         };
         self.refresh_content();
     }
 
-    fn refresh_content(&mut self) {
-        let mut repository = match self.repository.lock() {
-            Ok(repo) => repo,
-            Err(_) => return,
+    fn toggle_diff_mode(&mut self) {
+        self.mode = match self.mode {
+            ShowMode::Diff => ShowMode::WithLine,
+            _ => ShowMode::Diff,
         };
-        if let Ok(rows) = repository.get_content(self.title.to_owned()) {
-            self.content = self.mode.concat(rows);
-            self.scroll_position = 0;
-        }
+        self.refresh_content();
     }
 
-    fn _handle_message(&mut self, message: &Message) -> Message {
-        match message {
-            Message::Once(OnceOperation::ShowFile { file }) => {
-                // update content view
-                file.clone_into(&mut self.title);
-                let mut repository = match self.repository.lock() {
-                    Ok(repo) => repo,
-                    Err(_) => {
-                        return Message::Error {
-                            _message: "Failed to acquire repository lock".to_owned(),
+    fn toggle_split_diff_mode(&mut self) {
+        self.mode = match self.mode {
+            ShowMode::SplitDiff => ShowMode::WithLine,
+            _ => ShowMode::SplitDiff,
+        };
+        self.refresh_content();
+    }
+
+    fn clear_syntax(&mut self) {
+        self.syntax = None;
+        self.code_start = 0;
+    }
+
+    fn highlight(&mut self, file: &str, rows: &[CommitRow], code_start: usize) {
+        let lines: Vec<&str> = rows.iter().map(|row| row.line.as_str()).collect();
+        self.syntax = Highlighter::new(file, &lines, self.theme.syntax);
+        self.code_start = code_start;
+    }
+
+    fn load(&mut self, repository: &mut RepositoryInfo, file: &str) -> anyhow::Result<String> {
+        self.split_rows.clear();
+        self.selection = None;
+        self.blame_popup = None;
+        self.window = None;
+        self.blob_size = None;
+        self.missing_blob = None;
+        if self.directory {
+            self.shaded_lines.clear();
+            self.current_lines.clear();
+            self.mine_lines.clear();
+            self.clear_syntax();
+            return Ok(summary::directory_summary(
+                file,
+                &repository.changed_file_stats()?,
+            ));
+        }
+        match self.mode {
+            ShowMode::Diff | ShowMode::SplitDiff => {
+                self.clear_syntax();
+                let short = |id: String| id[..std::cmp::min(8, id.len())].to_owned();
+                self.diff_label = match (self.source, repository.base_ref()) {
+                    (Source::Index | Source::WorkingTree, _) => {
+                        format!("vs {}", short(repository.get_current_commit_id()))
+                    }
+                    (Source::Commit, Some(base)) => match repository.compare_target() {
+                        Some(target) => format!("{}..{}", short(base), short(target)),
+                        None => format!("vs {}", short(base)),
+                    },
+                    (Source::Commit, None) => "vs parent".to_owned(),
+                };
+                if matches!(self.mode, ShowMode::SplitDiff) {
+                    self.split = repository.get_split_diff(file, self.source)?;
+                    self.split_rows = align(&self.split);
+                    return Ok(format_split(&self.split, &self.split_rows));
+                }
+                match self.source {
+                    Source::Index => Ok(format_diff(repository.get_index_diff(file)?)),
+                    Source::WorkingTree => Ok(format_diff(repository.get_worktree_diff(file)?)),
+                    Source::Commit => Ok(format_diff(repository.get_diff(file)?)),
+                }
+            }
+            // binary files have no lines to number or blame, whichever mode was asked for
+            _ if self.source == Source::Commit && repository.is_binary_file(file) => {
+                self.mode = ShowMode::Hex;
+                self.blame_error = None;
+                self.shaded_lines.clear();
+                self.current_lines.clear();
+                self.mine_lines.clear();
+                self.clear_syntax();
+                let bytes = repository.get_raw_blob(file)?;
+                self.blob_size = Some(bytes.len());
+                Ok(hex_dump(&bytes))
+            }
+            _ => {
+                if matches!(self.mode, ShowMode::Hex) {
+                    self.mode = ShowMode::WithLine;
+                }
+                // notes belong to commit lines, so they are only shown on the commit
+                self.blame_error = None;
+                self.mine_lines.clear();
+                let (noted, (rows, missing_newline)) = match self.source {
+                    Source::Commit => {
+                        let content = repository.get_content(file.to_owned())?;
+                        if let Some(request) = content.pending_blame.clone() {
+                            if self.blame_job.as_ref().map(BlameJob::request) != Some(&request)
+                                && self.cancelled_blame.as_ref() != Some(&request)
+                            {
+                                self.blame_job = Some(repository.spawn_blame(request));
+                            }
+                        }
+                        self.window = content.window;
+                        self.blame_error = content.blame_error.clone();
+                        if matches!(self.mode, ShowMode::WithBlame) && repository.highlight_mine() {
+                            let identity = repository.identity();
+                            self.mine_lines = (1..=content.lines.len())
+                                .map(|number| {
+                                    content.hunk_at(number).is_some_and(|hunk| {
+                                        identity.matches(&hunk.author, &hunk.email)
+                                    })
+                                })
+                                .collect();
                         }
+                        (
+                            repository.noted_lines(file),
+                            (content.rows(), content.missing_newline),
+                        )
+                    }
+                    Source::Index => (BTreeSet::new(), repository.get_index_content(file)?),
+                    Source::WorkingTree => {
+                        (BTreeSet::new(), repository.get_worktree_content(file)?)
                     }
                 };
-
-                if let Ok(rows) = repository.get_content(file.to_owned()) {
-                    self.content = self.mode.concat(rows);
-                    self.scroll_position = 0
-                } else {
-                    return Message::Error {
-                        _message: "failed to get content".to_owned(),
-                    };
+                let current = repository.get_current_commit_id();
+                (self.shaded_lines, self.current_lines) = match self.mode {
+                    ShowMode::WithBlame => (
+                        blame_shading(&rows),
+                        rows.iter()
+                            .map(|row| row.commit.to_string() == current)
+                            .collect(),
+                    ),
+                    _ => (vec![], vec![]),
+                };
+                self.highlight(file, &rows, self.mode.gutter_width(&rows));
+                let mut content = self.mode.concat(rows, &noted, self.repeat_blame);
+                if missing_newline {
+                    content.push_str(NO_NEWLINE_MARKER);
                 }
+                Ok(content)
             }
-            Message::Once(OnceOperation::JumpToContentView) => self.focus = Focus::ON,
-            _ => {}
         }
-        Message::NoAction
     }
-}
 
-impl OperatableComponent for ContentViewer {
-    fn draw(&mut self, frame: &mut Frame, rect: Rect) {
-        let contents: Vec<String> = self
-            .content
-            .lines()
-            .skip(self.scroll_position)
-            .take(rect.height as usize)
-            .map(|line| {
-                let line_chars: Vec<char> = line.chars().collect();
-                let start = self.horizontal_scroll.min(line_chars.len());
-                let visible_line: String = line_chars.iter().skip(start).collect();
-                format!("{}\n", visible_line)
-            })
-            .collect();
+    // Lines of the file before the first one in `content`.
+    fn window_start(&self) -> usize {
+        self.window.map_or(0, |window| window.start)
+    }
 
-        let paragraph = Paragraph::new(contents.concat())
-            .block(title_block(&self.title, self.focus))
-            .wrap(Wrap { trim: false });
+    // Replaces `content` with the lines of the large `file` from `start` on.
+    fn read_window(
+        &mut self,
+        repository: &RepositoryInfo,
+        file: &str,
+        start: usize,
+    ) -> anyhow::Result<()> {
+        let content = repository.get_content_window(file, start)?;
+        let rows = content.rows();
+        self.window = content.window;
+        self.shaded_lines.clear();
+        self.current_lines.clear();
+        self.mine_lines.clear();
+        self.highlight(file, &rows, self.mode.gutter_width(&rows));
+        self.content = self
+            .mode
+            .concat(rows, &repository.noted_lines(file), self.repeat_blame);
+        if content.missing_newline {
+            self.content.push_str(NO_NEWLINE_MARKER);
+        }
+        Ok(())
+    }
 
-        self.context_size = Paragraph::new(self.content.clone()).line_count(rect.width);
-        self.height = rect.height as usize;
-        frame.render_widget(paragraph, rect)
+    // Scrolls the 0-based file line `index` to the top, reading the lines around it first
+    // when a large file is shown and the line is near the edge of those read.
+    fn reveal(&mut self, repository: &RepositoryInfo, index: usize) {
+        if let (Some(window), Some(file)) = (self.window, self.committed_file()) {
+            let end = (window.start + WINDOW_LINES).min(window.total);
+            let near_start = window.start > 0 && index < window.start + WINDOW_MARGIN;
+            let near_end = end < window.total && index + self.height + WINDOW_MARGIN > end;
+            if near_start || near_end {
+                let file = file.to_owned();
+                let _ = self.read_window(repository, &file, index.saturating_sub(WINDOW_LINES / 2));
+            }
+        }
+        self.scroll_position = index.saturating_sub(self.window_start());
     }
 
-    fn process_focus(&mut self) {
-        match self.focus {
-            Focus::Off => self.focus = Focus::ON,
-            Focus::ON => self.focus = Focus::Off,
+    // Keeps lines read ahead of scrolling through a large file.
+    fn slide_window(&mut self) {
+        if self.window.is_none() {
+            return;
         }
+        let repository = Arc::clone(&self.repository);
+        let Ok(repository) = repository.lock() else {
+            return;
+        };
+        self.reveal(&repository, self.window_start() + self.scroll_position);
     }
 
-    fn process_events(&mut self, events: KeyCode) -> Message {
-        match events {
-            KeyCode::Up | KeyCode::Char('k') => {
-                if self.scroll_position > 0 {
-                    self.scroll_position -= 1;
-                }
+    fn refresh_content(&mut self) {
+        let repository = Arc::clone(&self.repository);
+        let mut repository = match repository.lock() {
+            Ok(repo) => repo,
+            Err(_) => return,
+        };
+        let file = self.title.to_owned();
+        match self.load(&mut repository, &file) {
+            Ok(content) => {
+                self.content = content;
+                self.scroll_position = 0;
             }
-            KeyCode::Down | KeyCode::Char('j') => {
-                // 4 is the using frame size
-                if self.scroll_position < 4 + self.context_size.saturating_sub(1 + self.height) {
-                    self.scroll_position += 1;
-                }
+            Err(e) if repository::is_unavailable_object(&e) => {
+                let remote = repository.promisor_remote();
+                self.content = unavailable_object(&file, remote.as_deref(), e);
+                self.missing_blob = remote.map(|_| file);
+                self.clear_syntax();
+                self.scroll_position = 0;
+            }
+            // e.g. the file was deleted or never checked out
+            Err(e) if self.source != Source::Commit => {
+                self.content = self.unreadable_source(e);
+                self.clear_syntax();
+                self.scroll_position = 0;
             }
-            KeyCode::Left | KeyCode::Char('h') => {
-                if self.horizontal_scroll > 0 {
-                    self.horizontal_scroll -= 1;
+            Err(_) => {}
+        }
+    }
+
+    fn unreadable_source(&self, error: anyhow::Error) -> String {
+        format!(
+            "Cannot read the {} version: {}",
+            self.source.label().to_lowercase(),
+            error
+        )
+    }
+
+    // Switches to `source`, or back to the commit if it is already shown.
+    fn toggle_source(&mut self, source: Source) {
+        self.source = if self.source == source {
+            Source::Commit
+        } else {
+            source
+        };
+        self.refresh_content();
+    }
+
+    fn show_file(&mut self, file: &str) -> Message {
+        // the top line, to be carried over when the same file is shown at another commit
+        let previous = self
+            .current_location()
+            .filter(|(shown, _)| shown == file)
+            .map(|(_, line)| (self.shown_commit.clone(), line));
+        // update content view
+        file.clone_into(&mut self.title);
+        self.crumb = None;
+        self.directory = false;
+        let repository = Arc::clone(&self.repository);
+        let mut repository = match repository.lock() {
+            Ok(repo) => repo,
+            Err(_) => return Message::Error(GviewError::Lock),
+        };
+
+        match self.load(&mut repository, file) {
+            Ok(content) => {
+                self.content = content;
+                self.scroll_position = 0;
+                let current = repository.get_current_commit_id();
+                if let Some((commit, line)) = previous.filter(|(commit, _)| *commit != current) {
+                    if let Ok(mapped) = repository.map_line(&commit, &current, file, line) {
+                        self.reveal(&repository, mapped.saturating_sub(1));
+                    }
                 }
+                self.follow_pin(&repository, file);
+                self.shown_commit = current;
+                Message::NoAction
             }
-            KeyCode::Right | KeyCode::Char('l') => {
-                self.horizontal_scroll += 1;
+            // the rest of the commit may well be readable, so this is no error
+            Err(e) if repository::is_unavailable_object(&e) => {
+                let remote = repository.promisor_remote();
+                self.content = unavailable_object(file, remote.as_deref(), e);
+                self.missing_blob = remote.map(|_| file.to_owned());
+                self.clear_syntax();
+                self.scroll_position = 0;
+                Message::NoAction
             }
-            KeyCode::Char('n') => {
-                self.toggle_line_numbers();
+            Err(e) if self.source != Source::Commit => {
+                self.content = self.unreadable_source(e);
+                self.clear_syntax();
+                self.scroll_position = 0;
+                Message::NoAction
             }
-            KeyCode::Char('b') => {
-                self.toggle_blame_mode();
+            Err(e) => Message::Error(GviewError::new(format!("Cannot show {}", file), e)),
+        }
+    }
+
+    // The file shown at the viewed commit, in any mode.
+    fn shown_file(&self) -> Option<&str> {
+        if self.directory
+            || self.title.is_empty()
+            || self.title == "not found"
+            || self.title == "Content Viewer"
+        {
+            return None;
+        }
+        Some(&self.title)
+    }
+
+    fn committed_file(&self) -> Option<&str> {
+        self.shown_file().filter(|_| self.source == Source::Commit)
+    }
+
+    // Path components of the shown file, preceded by the repository root.
+    fn breadcrumb(&self) -> Option<Vec<&str>> {
+        let file = self.shown_file()?;
+        Some(std::iter::once("/").chain(file.split('/')).collect())
+    }
+
+    // `[` selects the enclosing directory, then walks up towards the root.
+    fn select_outer_crumb(&mut self) {
+        let Some(components) = self.breadcrumb() else {
+            return;
+        };
+        self.crumb = Some(match self.crumb {
+            Some(index) => index.saturating_sub(1),
+            None => components.len() - 2,
+        });
+    }
+
+    // `]` walks back down, clearing the selection past the innermost directory.
+    fn select_inner_crumb(&mut self) {
+        let Some(components) = self.breadcrumb() else {
+            return;
+        };
+        self.crumb = self
+            .crumb
+            .map(|index| index + 1)
+            .filter(|index| index + 1 < components.len());
+    }
+
+    // Directory of the selected breadcrumb component, empty for the repository root.
+    fn selected_directory(&self) -> Option<String> {
+        let components = self.breadcrumb()?;
+        let index = self.crumb?;
+        Some(components[1..=index].join("/"))
+    }
+
+    /// The directory of the selected breadcrumb, or else the shown file, to inspect.
+    pub fn inspected_path(&self) -> Option<String> {
+        self.selected_directory()
+            .or_else(|| self.shown_file().map(str::to_owned))
+    }
+
+    fn breadcrumb_line(&self) -> Option<Line<'static>> {
+        let components = self.breadcrumb()?;
+        let last = components.len() - 1;
+        let mut spans = vec![];
+        for (index, component) in components.iter().enumerate() {
+            if index > 0 {
+                spans.push(Span::styled(
+                    " › ",
+                    Style::default().fg(self.theme.unfocused),
+                ));
             }
-            KeyCode::Char('g') => {
-                if self.title != "not found" && !self.title.is_empty() {
-                    let current_line = self.scroll_position + 1;
-                    let repository = match self.repository.lock() {
-                        Ok(repo) => repo,
-                        Err(_) => {
-                            return Message::Error {
-                                _message: "Failed to acquire repository lock".to_owned(),
-                            }
-                        }
-                    };
-                    if let Err(e) = repository.open_file_in_browser(&self.title, current_line) {
-                        return Message::Error {
-                            _message: format!("Failed to open in browser: {}", e),
-                        };
-                    }
-                }
+            let style = if self.crumb == Some(index) {
+                Style::default().reversed()
+            } else if index == last {
+                Style::default().bold()
+            } else {
+                Style::default().fg(self.theme.heading)
+            };
+            spans.push(Span::styled(component.to_string(), style));
+        }
+        Some(Line::from(spans))
+    }
+
+    // File and 1-based line at the top of the view, if a file is shown as lines.
+    pub fn current_location(&self) -> Option<(String, usize)> {
+        if matches!(
+            self.mode,
+            ShowMode::Diff | ShowMode::SplitDiff | ShowMode::Hex
+        ) {
+            return None;
+        }
+        let file = self.committed_file()?;
+        Some((
+            file.to_owned(),
+            self.window_start() + self.scroll_position + 1,
+        ))
+    }
+
+    /// The shown file, the name of its mode and the share of it scrolled through, down to
+    /// the bottom line of the view, for the status bar.
+    pub fn view_status(&self) -> Option<(String, &'static str, usize)> {
+        let file = self.shown_file()?;
+        let mode = match self.mode {
+            ShowMode::WithLine => "lines",
+            ShowMode::WithBlame => "blame",
+            ShowMode::NoLine => "plain",
+            ShowMode::Diff => "diff",
+            ShowMode::SplitDiff => "split diff",
+            ShowMode::Hex => "hex",
+        };
+        let total = self.window.map_or(self.context_size, |window| window.total);
+        // the height includes the borders
+        let bottom = self.window_start() + self.view_top() + self.height.saturating_sub(2);
+        let percent = match total {
+            0 => 100,
+            total => bottom.min(total) * 100 / total,
+        };
+        Some((file.to_owned(), mode, percent))
+    }
+
+    // Width of the blame or line number gutter of `line`.
+    fn line_gutter(&self, line: &str) -> Option<usize> {
+        match self.mode {
+            ShowMode::NoLine | ShowMode::Hex => Some(0),
+            _ => line.find("| ").map(|index| index + 2),
+        }
+    }
+
+    // Identifier at the horizontal scroll column of the top line, used as the cursor word.
+    pub fn word_at_cursor(&self) -> Option<String> {
+        self.current_location()?;
+        let line = self.content.lines().nth(self.scroll_position)?;
+        let gutter = self.line_gutter(line)?;
+        tags::identifier_at(
+            &line[gutter..],
+            self.horizontal_scroll.saturating_sub(gutter),
+        )
+    }
+
+    // Lines from the selection's start to the top line, or the top line alone.
+    fn selected_lines(&self) -> Option<RangeInclusive<usize>> {
+        self.current_location()?;
+        let anchor = self.selection.unwrap_or(self.scroll_position);
+        Some(anchor.min(self.scroll_position)..=anchor.max(self.scroll_position))
+    }
+
+    fn toggle_selection(&mut self) {
+        self.selection = match self.selection {
+            Some(_) => None,
+            None => self.current_location().map(|_| self.scroll_position),
+        };
+    }
+
+    // The selected lines of the file without their gutter.
+    fn copy_lines(&mut self) -> Message {
+        let (Some(range), Some(file)) = (
+            self.selected_lines(),
+            self.committed_file().map(str::to_owned),
+        ) else {
+            return Message::Error(GviewError::unavailable("No file lines to copy"));
+        };
+        // the blob's own bytes, as the shown lines lose carriage returns and undecodable bytes
+        let start = self.window_start() + 1;
+        let lines = start + range.start()..=start + range.end();
+        let bytes = match self.repository.lock() {
+            Ok(repo) => repo.get_raw_lines(&file, lines),
+            Err(_) => return Message::Error(GviewError::Lock),
+        };
+        self.selection = None;
+        match bytes {
+            Ok(bytes) => Message::Once(OnceOperation::CopyLines { bytes }),
+            Err(e) => Message::Error(GviewError::new(format!("Cannot copy from {}", file), e)),
+        }
+    }
+
+    fn toggle_pin(&mut self) {
+        if self.pin.take().is_some() {
+            return;
+        }
+        let Some((file, line)) = self.current_location() else {
+            return;
+        };
+        let commit = match self.repository.lock() {
+            Ok(repo) => repo.get_current_commit_id(),
+            Err(_) => return,
+        };
+        self.pin = Some((file, commit, line));
+    }
+
+    // Moves the pin to where its line ended up in the viewed commit and centers it.
+    fn follow_pin(&mut self, repository: &RepositoryInfo, file: &str) {
+        let Some((pinned_file, commit, line)) = &mut self.pin else {
+            return;
+        };
+        if pinned_file != file {
+            return;
+        }
+        let current = repository.get_current_commit_id();
+        if *commit != current {
+            // the line stays where it was when the file is missing on either side
+            if let Ok(mapped) = repository.map_line(commit, &current, file, *line) {
+                *line = mapped;
             }
-            _ => {}
+            *commit = current;
         }
-        Message::NoAction
+        let top = line.saturating_sub(self.height / 2 + 1);
+        self.reveal(repository, top);
+    }
+
+    // Index of the pinned line when it is in view.
+    fn pinned_index(&self) -> Option<usize> {
+        match &self.pin {
+            Some((file, _, line))
+                if *file == self.title && self.preview.is_none() && !self.directory =>
+            {
+                (line - 1).checked_sub(self.window_start())
+            }
+            _ => None,
+        }
+    }
+
+    pub fn is_previewing(&self) -> bool {
+        self.preview.is_some()
+    }
+
+    // Shows the file as of `commit_id` while keeping the scroll position,
+    // so scrubbing through history shows the changes in place.
+    fn preview_commit(&mut self, commit_id: &str) {
+        let Some(file) = self.committed_file().map(str::to_owned) else {
+            return;
+        };
+        let rows = match self.repository.lock() {
+            Ok(repo) => repo.get_content_at(commit_id, &file),
+            Err(_) => return,
+        };
+        let short = commit_id[..std::cmp::min(8, commit_id.len())].to_owned();
+        // the preview holds the whole file
+        self.scroll_position += self.window_start();
+        self.window = None;
+        self.content = match rows {
+            Ok(rows) => {
+                let mut mode = match self.mode {
+                    ShowMode::NoLine => ShowMode::NoLine,
+                    _ => ShowMode::WithLine,
+                };
+                self.highlight(&file, &rows, mode.gutter_width(&rows));
+                mode.concat(rows, &BTreeSet::new(), false)
+            }
+            Err(_) => {
+                self.clear_syntax();
+                format!("{} does not exist at {}", file, short)
+            }
+        };
+        self.shaded_lines.clear();
+        self.current_lines.clear();
+        self.preview = Some(short);
+    }
+
+    pub fn is_editing_search(&self) -> bool {
+        self.search.as_ref().is_some_and(|search| search.editing)
+    }
+
+    // (line, column) of every match of the search query, skipping the gutter.
+    fn search_matches(&self) -> Vec<(usize, usize)> {
+        let Some(search) = &self.search else {
+            return vec![];
+        };
+        self.content
+            .lines()
+            .enumerate()
+            .flat_map(|(index, line)| {
+                find_matches(line, &search.query, self.code_start)
+                    .into_iter()
+                    .map(move |column| (index, column))
+            })
+            .collect()
+    }
+
+    // Jumps to the next match after the current one, or the previous one, wrapping around;
+    // while typing, to the first match from where the search started.
+    fn jump_to_match(&mut self, forward: bool) {
+        let matches = self.search_matches();
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        let target = match (search.current, forward) {
+            _ if search.editing => matches
+                .iter()
+                .find(|(line, _)| *line >= search.origin)
+                .or(matches.first()),
+            (Some(current), true) => matches
+                .iter()
+                .find(|found| **found > current)
+                .or(matches.first()),
+            (Some(current), false) => matches
+                .iter()
+                .rev()
+                .find(|found| **found < current)
+                .or(matches.last()),
+            (None, true) => matches.first(),
+            (None, false) => matches.last(),
+        };
+        search.current = target.copied();
+        match target {
+            Some((line, _)) => self.scroll_position = *line,
+            None => self.scroll_position = search.origin,
+        }
+    }
+
+    fn edit_search(&mut self, events: KeyCode) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        match events {
+            KeyCode::Char(c) => search.query.push(c),
+            KeyCode::Backspace => {
+                search.query.pop();
+            }
+            KeyCode::Enter if !search.query.is_empty() => {
+                search.editing = false;
+                return;
+            }
+            KeyCode::Enter | KeyCode::Esc => {
+                self.scroll_position = search.origin;
+                self.search = None;
+                return;
+            }
+            _ => return,
+        }
+        self.jump_to_match(true);
+    }
+
+    // A content line as scrolled horizontally, with its shading, gutter and syntax colors.
+    fn styled_line(
+        &self,
+        index: usize,
+        line: &str,
+        is_diff: bool,
+        pinned: Option<usize>,
+    ) -> Line<'static> {
+        let line_chars: Vec<char> = line.chars().collect();
+        let start = self.horizontal_scroll.min(line_chars.len());
+        let visible_line: String = line_chars.iter().skip(start).collect();
+        if is_diff {
+            return Line::styled(visible_line, diff_line_style(line, &self.theme));
+        }
+        let mut style = match self.shaded_lines.get(index) {
+            Some(true) => Style::default().bg(self.theme.shaded),
+            _ => Style::default(),
+        };
+        if pinned == Some(index) {
+            style = style.fg(self.theme.tag).bold();
+        }
+        // lines touched by the viewed commit, or by the user when highlighting their
+        // lines, get a colored gutter
+        let gutter_style = if self.current_lines.get(index) == Some(&true) {
+            Some(Style::default().fg(self.theme.current_lines).bold())
+        } else if self.mine_lines.get(index) == Some(&true) {
+            Some(Style::default().fg(self.theme.mine_lines).bold())
+        } else {
+            None
+        };
+        // the pinned line keeps its own color
+        let code = self
+            .syntax
+            .as_ref()
+            .and_then(|syntax| syntax.line(index))
+            .filter(|_| pinned != Some(index));
+        if gutter_style.is_none() && code.is_none() {
+            return Line::styled(visible_line, style);
+        }
+        // columns of the line: colored gutter, rest of the gutter, then the code
+        let gutter_end = gutter_style.map_or(start, |_| BLAME_GUTTER_WIDTH.max(start));
+        let code_start = code
+            .map_or(line_chars.len(), |_| self.code_start)
+            .max(gutter_end);
+        let segment = |from: usize, to: usize| -> String {
+            let len = line_chars.len();
+            line_chars[from.min(len)..to.min(len)].iter().collect()
+        };
+        let mut spans = vec![
+            Span::styled(segment(start, gutter_end), gutter_style.unwrap_or_default()),
+            Span::raw(segment(gutter_end, code_start)),
+        ];
+        if let Some(code) = code {
+            spans.extend(skip_chars(code, start.saturating_sub(self.code_start)));
+        }
+        Line::from(spans).style(style)
+    }
+
+    // A row of the side-by-side diff across `width` columns: old side, separator, new side.
+    fn split_line(&self, row: &SplitRow, width: usize) -> Line<'static> {
+        let digits = self
+            .split
+            .old
+            .len()
+            .max(self.split.new.len())
+            .to_string()
+            .len();
+        let side = |lines: &[String], index: Option<usize>, width: usize, color: Color| {
+            let Some(index) = index else {
+                // the other side has lines this one does not
+                let filler = Style::default().bg(self.theme.shaded);
+                return vec![Span::styled(" ".repeat(width), filler)];
+            };
+            let gutter: String = format!("{:>digits$} ", index + 1)
+                .chars()
+                .take(width)
+                .collect();
+            let room = width - gutter.chars().count();
+            let text = fit_columns(side_text(lines, Some(index)), self.horizontal_scroll, room);
+            let padding = " ".repeat(room - text.width());
+            let style = match row.changed {
+                true => Style::default().fg(color),
+                false => Style::default(),
+            };
+            vec![
+                Span::styled(gutter, Style::default().fg(self.theme.unfocused)),
+                Span::styled(text + &padding, style),
+            ]
+        };
+        let left = width.saturating_sub(1) / 2;
+        let right = width.saturating_sub(1 + left);
+        let mut spans = side(&self.split.old, row.old, left, self.theme.removed);
+        spans.push(Span::styled("│", Style::default().fg(self.theme.unfocused)));
+        spans.extend(side(&self.split.new, row.new, right, self.theme.added));
+        Line::from(spans)
+    }
+
+    /// Handles a click on the blame gutter: a commit id jumps to its commit and the rest of
+    /// the gutter opens the details of the line. Any click closes the details.
+    pub fn click(&mut self, column: u16, row: u16) -> Message {
+        self.blame_popup = None;
+        let Some(&(_, index)) = self.blame_rows.iter().find(|(start, _)| *start == row) else {
+            return Message::NoAction;
+        };
+        let offset = self.horizontal_scroll + column.saturating_sub(self.lines_area.x) as usize;
+        if column < self.lines_area.x || offset >= self.mode.gutter_width(&[]) {
+            return Message::NoAction;
+        }
+        let shown_commit = |line: &str| {
+            line.get(..BLAME_GUTTER_WIDTH - 1)
+                .map(str::trim)
+                .filter(|commit| !commit.is_empty())
+                .map(str::to_owned)
+        };
+        let lines: Vec<&str> = self.content.lines().take(index + 1).collect();
+        // a line continuing a hunk leaves its commit to the first line of the hunk
+        let Some(commit) = lines.iter().rev().find_map(|line| shown_commit(line)) else {
+            return Message::NoAction;
+        };
+        if git2::Oid::from_str(&commit).is_err() {
+            return Message::NoAction;
+        }
+        if offset < BLAME_GUTTER_WIDTH - 1
+            && lines.last().and_then(|line| shown_commit(line)).is_some()
+        {
+            return Message::Once(OnceOperation::SetCommitById { commit_id: commit });
+        }
+        let info = match self.repository.lock() {
+            Ok(repository) => repository.commit_info_of(&commit),
+            Err(_) => return Message::Error(GviewError::Lock),
+        };
+        let info = match info {
+            Ok(info) => info,
+            Err(e) => return Message::Error(GviewError::new("Cannot read the blamed commit", e)),
+        };
+        self.blame_popup = Some(BlamePopup {
+            index,
+            rows: vec![
+                ("Line", (self.window_start() + index + 1).to_string()),
+                ("Commit", info.id),
+                ("Author", format!("{} <{}>", info.author, info.email)),
+                ("Date", info.date),
+                (
+                    "Summary",
+                    info.message.lines().next().unwrap_or_default().to_owned(),
+                ),
+            ],
+        });
+        Message::NoAction
+    }
+
+    // Draws the details of a clicked line below it, or above it near the bottom.
+    fn draw_blame_popup(&mut self, frame: &mut Frame) {
+        let Some(popup) = &self.blame_popup else {
+            return;
+        };
+        // scrolled out of sight, or no longer blamed
+        let Some(&(row, _)) = self
+            .blame_rows
+            .iter()
+            .find(|(_, index)| *index == popup.index)
+        else {
+            self.blame_popup = None;
+            return;
+        };
+        let key_width = popup
+            .rows
+            .iter()
+            .map(|(key, _)| key.len())
+            .max()
+            .unwrap_or(0);
+        let lines: Vec<Line> = popup
+            .rows
+            .iter()
+            .map(|(key, value)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:<width$}  ", key, width = key_width),
+                        Style::default().fg(self.theme.heading),
+                    ),
+                    Span::raw(value.to_owned()),
+                ])
+            })
+            .collect();
+        let area = self.lines_area;
+        let widest = lines.iter().map(Line::width).max().unwrap_or(0) as u16;
+        let width = (widest + 2).min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let y = if row + 1 + height <= area.bottom() {
+            row + 1
+        } else {
+            row.saturating_sub(height).max(area.y)
+        };
+        let popup_area = Rect::new(area.x, y, width, height);
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(
+            Paragraph::new(lines).block(
+                Block::bordered()
+                    .title(" Blame (Esc: close) ")
+                    .style(Style::default().fg(self.theme.text)),
+            ),
+            popup_area,
+        );
+    }
+
+    pub fn scroll_to_line(&mut self, line: usize) {
+        if self.window.is_none() {
+            self.scroll_position = line.saturating_sub(1);
+            return;
+        }
+        let repository = Arc::clone(&self.repository);
+        let Ok(repository) = repository.lock() else {
+            return;
+        };
+        self.reveal(&repository, line.saturating_sub(1));
+    }
+
+    // Rows of the file in view, the borders aside.
+    fn page_rows(&self) -> usize {
+        self.height.saturating_sub(2).max(1)
+    }
+
+    fn can_scroll_down(&self) -> bool {
+        // 4 is the using frame size
+        self.scroll_position
+            < 4 + self.cursor_offset() + self.context_size.saturating_sub(1 + self.height)
+    }
+
+    // Moves the current line up to `count` lines down or up, as far as the file goes.
+    fn scroll_lines(&mut self, down: bool, count: usize) {
+        for _ in 0..count {
+            match down {
+                true if self.can_scroll_down() => self.scroll_position += 1,
+                false if self.scroll_position > 0 => self.scroll_position -= 1,
+                _ => break,
+            }
+        }
+        self.slide_window();
+    }
+
+    // `G` and End: the end of the file, read first when it is read a window at a time.
+    fn scroll_to_end(&mut self) {
+        match self.window {
+            Some(window) => self.scroll_to_line(window.total),
+            None => self.scroll_lines(true, usize::MAX),
+        }
+    }
+
+    /// Jumps to 1-based `line` of the shown file, kept within its length, and keeps it in the
+    /// middle of the view as zz does.
+    pub fn go_to_line(&mut self, line: usize) {
+        let total = self
+            .window
+            .map_or(self.content.lines().count(), |window| window.total);
+        self.scroll_to_line(line.clamp(1, total.max(1)));
+        // the view starts half a page above the line, and the current line keeps its row
+        let index = self.scroll_position;
+        let half = self.height.saturating_sub(3) / 2;
+        let row = self.cursor_row_offset();
+        let position = match index.checked_sub(half) {
+            Some(top) => top + row,
+            None => index.min(row),
+        };
+        self.scroll_position = position.min(self.content.lines().count().saturating_sub(1));
+    }
+
+    fn _handle_message(&mut self, message: &Message) -> Message {
+        match message {
+            Message::Once(OnceOperation::ShowFile { file }) => return self.show_file(file),
+            Message::Once(OnceOperation::ShowDirectorySummary { dir }) => {
+                dir.clone_into(&mut self.title);
+                self.directory = true;
+                self.refresh_content();
+            }
+            Message::Once(OnceOperation::OpenLocation { file, line, .. }) => {
+                // note lines refer to the file itself, not to the diff
+                if matches!(self.mode, ShowMode::Diff | ShowMode::SplitDiff) {
+                    self.mode = ShowMode::WithLine;
+                }
+                let message = self.show_file(file);
+                self.scroll_to_line(*line);
+                return message;
+            }
+            Message::Once(OnceOperation::JumpToContentView) => self.focus = Focus::ON,
+            Message::Once(OnceOperation::OpenCommitModal) => {
+                let current = match self.repository.lock() {
+                    Ok(repo) => repo.get_current_commit_id(),
+                    Err(_) => return Message::NoAction,
+                };
+                self.preview_commit(&current);
+            }
+            Message::Once(OnceOperation::PreviewCommit { commit_id }) => {
+                self.preview_commit(commit_id)
+            }
+            Message::Once(OnceOperation::CloseCommitModal) if self.preview.is_some() => {
+                self.preview = None;
+                let top = self.window_start() + self.scroll_position;
+                self.refresh_content();
+                self.scroll_to_line(top + 1);
+            }
+            Message::MultipleTimes(MultipleTimesOperation::ChangeBase) => {
+                // reviewing against a base is done file by file through the diff
+                let has_base = match self.repository.lock() {
+                    Ok(repo) => repo.base_ref().is_some(),
+                    Err(_) => false,
+                };
+                if has_base && !matches!(self.mode, ShowMode::Diff | ShowMode::SplitDiff) {
+                    self.mode = ShowMode::Diff;
+                }
+                self.refresh_content();
+            }
+            Message::MultipleTimes(MultipleTimesOperation::ChangeNotes)
+            | Message::MultipleTimes(MultipleTimesOperation::ChangeHighlight)
+                if self.preview.is_none() =>
+            {
+                let top = self.window_start() + self.scroll_position;
+                self.refresh_content();
+                self.scroll_to_line(top + 1);
+            }
+            _ => {}
+        }
+        Message::NoAction
+    }
+}
+
+impl OperatableComponent for ContentViewer {
+    fn draw(&mut self, frame: &mut Frame, rect: Rect) {
+        let rect = match self.breadcrumb_line() {
+            Some(breadcrumb) => {
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(1), Constraint::Min(0)])
+                    .split(rect);
+                frame.render_widget(Paragraph::new(breadcrumb), chunks[0]);
+                chunks[1]
+            }
+            None => rect,
+        };
+        let is_diff = matches!(self.mode, ShowMode::Diff) && self.preview.is_none();
+        let is_split = matches!(self.mode, ShowMode::SplitDiff) && self.preview.is_none();
+        // colors the shown lines and a page below them, for scrolling on
+        let view_end = self.view_top() + 2 * rect.height as usize;
+        if let Some(syntax) = self.syntax.as_mut() {
+            syntax.advance(view_end);
+        }
+        let pinned = self.pinned_index().filter(|_| !is_diff && !is_split);
+        let selected = self.selected_lines().filter(|_| self.selection.is_some());
+        // the current line is underlined once it is not the top one
+        let current = (self.cursor_offset() > 0).then_some(self.scroll_position);
+        let search_style = Style::default()
+            .fg(self.theme.match_text)
+            .bg(self.theme.search_match);
+        let current_match_style = Style::default()
+            .fg(self.theme.match_text)
+            .bg(self.theme.current_match);
+        // both sides are cut at the view's width rather than wrapped, to keep them aligned
+        let contents: Vec<Line> = if is_split && !self.split_rows.is_empty() {
+            self.split_rows
+                .iter()
+                .skip(self.view_top())
+                .take(rect.height as usize)
+                .map(|row| self.split_line(row, rect.width.saturating_sub(2) as usize))
+                .collect()
+        } else {
+            self.content
+                .lines()
+                .enumerate()
+                .skip(self.view_top())
+                .take(rect.height as usize)
+                .map(|(index, line)| {
+                    let mut styled = self.styled_line(index, line, is_diff, pinned);
+                    if selected
+                        .as_ref()
+                        .is_some_and(|range| range.contains(&index))
+                    {
+                        styled = styled.patch_style(Style::default().bg(self.theme.selection));
+                    }
+                    if current == Some(index) {
+                        styled = styled.patch_style(Style::default().underlined());
+                    }
+                    let Some(search) = self
+                        .search
+                        .as_ref()
+                        .filter(|search| !search.query.is_empty())
+                    else {
+                        return styled;
+                    };
+                    let length = search.query.chars().count();
+                    let start = self.horizontal_scroll.min(line.chars().count());
+                    // matches scrolled out to the left are cut at the view's edge
+                    let ranges: Vec<(usize, usize, Style)> =
+                        find_matches(line, &search.query, self.code_start)
+                            .into_iter()
+                            .map(|column| {
+                                let style = if search.current == Some((index, column)) {
+                                    current_match_style
+                                } else {
+                                    search_style
+                                };
+                                (
+                                    column.saturating_sub(start),
+                                    (column + length).saturating_sub(start),
+                                    style,
+                                )
+                            })
+                            .collect();
+                    overlay(styled, &ranges)
+                })
+                .collect()
+        };
+
+        let mut title = if self.directory {
+            format!("{}/ (diffstat)", self.title)
+        } else if let Some(short) = &self.preview {
+            format!("{} @ {} (preview)", self.title, short)
+        } else if is_diff {
+            format!("{} (diff {})", self.title, self.diff_label)
+        } else if is_split {
+            format!("{} (side-by-side diff {})", self.title, self.diff_label)
+        } else {
+            self.title.to_owned()
+        };
+        if self.source != Source::Commit {
+            title = format!("[{}] {}", self.source.label(), title);
+        }
+        if let Some(index) = pinned {
+            title = format!(
+                "{} (pinned line {})",
+                title,
+                self.window_start() + index + 1
+            );
+        }
+        if let Some(window) = self
+            .window
+            .filter(|_| self.preview.is_none() && !is_diff && !is_split)
+        {
+            title = format!("{} (large file, {} lines)", title, window.total);
+        }
+        if let Some(size) = self
+            .blob_size
+            .filter(|_| self.preview.is_none() && !is_diff && !is_split)
+        {
+            title = format!("{} (hex, {} bytes)", title, size);
+        }
+        if !self.mine_lines.is_empty() {
+            title = format!("{} (highlighting my lines)", title);
+        }
+        if let Some(range) = &selected {
+            title = format!(
+                "{} (lines {}-{} selected, Y to copy)",
+                title,
+                self.window_start() + range.start() + 1,
+                self.window_start() + range.end() + 1
+            );
+        }
+        if let Some(search) = &self.search {
+            title = if search.editing {
+                format!("{} /{}▏", title, search.query)
+            } else {
+                let matches = self.search_matches();
+                let position = matches
+                    .iter()
+                    .position(|found| Some(*found) == search.current)
+                    .map_or("-".to_owned(), |index| (index + 1).to_string());
+                format!(
+                    "{} /{} ({}/{}, n/N)",
+                    title,
+                    search.query,
+                    position,
+                    matches.len()
+                )
+            };
+        }
+        if let (ShowMode::WithBlame, Some(error), None) =
+            (&self.mode, &self.blame_error, &self.preview)
+        {
+            title = format!("{} (blame unavailable: {})", title, error);
+        }
+        if self.is_blaming() {
+            title = format!(
+                "{} {} blaming (Esc: cancel)",
+                title,
+                progress::spinner(self.spinner)
+            );
+        }
+        let block = panel_block(&title, self.focus, &self.theme);
+        self.lines_area = block.inner(rect);
+        self.blame_rows.clear();
+        if matches!(self.mode, ShowMode::WithBlame) && self.preview.is_none() {
+            let mut row = self.lines_area.y;
+            for (offset, line) in contents.iter().enumerate() {
+                if row >= self.lines_area.bottom() {
+                    break;
+                }
+                self.blame_rows.push((row, self.view_top() + offset));
+                row += Paragraph::new(line.clone())
+                    .wrap(Wrap { trim: false })
+                    .line_count(self.lines_area.width) as u16;
+            }
+        }
+        let paragraph = Paragraph::new(Text::from(contents))
+            .block(block)
+            .wrap(Wrap { trim: false });
+
+        self.context_size = if is_split && !self.split_rows.is_empty() {
+            self.split_rows.len()
+        } else {
+            Paragraph::new(self.content.clone()).line_count(rect.width)
+        };
+        self.height = rect.height as usize;
+        frame.render_widget(paragraph, rect);
+        self.draw_blame_popup(frame);
+    }
+
+    fn process_focus(&mut self) {
+        match self.focus {
+            Focus::Off => self.focus = Focus::ON,
+            Focus::ON => self.focus = Focus::Off,
+        }
+    }
+
+    fn process_events(&mut self, events: KeyCode) -> Message {
+        if self.is_editing_search() {
+            self.edit_search(events);
+            return Message::NoAction;
+        }
+        match events {
+            KeyCode::Char('/') => {
+                self.search = Some(Search {
+                    editing: true,
+                    origin: self.scroll_position,
+                    ..Search::default()
+                });
+            }
+            KeyCode::Char('n') if self.search.is_some() => self.jump_to_match(true),
+            KeyCode::Char('N') if self.search.is_some() => self.jump_to_match(false),
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_lines(false, 1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_lines(true, 1),
+            KeyCode::PageUp => self.scroll_lines(false, self.page_rows()),
+            KeyCode::PageDown => self.scroll_lines(true, self.page_rows()),
+            KeyCode::Home => self.scroll_to_line(1),
+            KeyCode::End | KeyCode::Char('G') => self.scroll_to_end(),
+            KeyCode::Left | KeyCode::Char('h') if self.horizontal_scroll > 0 => {
+                self.horizontal_scroll -= 1;
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.horizontal_scroll += 1;
+            }
+            KeyCode::Char('n') => {
+                self.toggle_line_numbers();
+            }
+            KeyCode::Char('b') => {
+                self.toggle_blame_mode();
+            }
+            KeyCode::Char('d') => {
+                self.toggle_diff_mode();
+            }
+            KeyCode::Char('D') => self.toggle_split_diff_mode(),
+            KeyCode::Char('w') => self.toggle_source(Source::WorkingTree),
+            KeyCode::Char('i') => self.toggle_source(Source::Index),
+            KeyCode::Char('B') => {
+                self.repeat_blame = !self.repeat_blame;
+                if matches!(self.mode, ShowMode::WithBlame) {
+                    self.refresh_content();
+                }
+            }
+            KeyCode::Char('a') if self.current_location().is_some() => {
+                return Message::Once(OnceOperation::OpenPrompt {
+                    kind: PromptKind::Note,
+                });
+            }
+            KeyCode::Char('A') => return Message::Once(OnceOperation::OpenNotesModal),
+            KeyCode::Char('R') => return Message::Once(OnceOperation::OpenRemoteModal),
+            KeyCode::Char('p') => self.toggle_pin(),
+            KeyCode::Char('m') => {
+                if let Ok(mut repository) = self.repository.lock() {
+                    repository.toggle_highlight_mine();
+                    return Message::MultipleTimes(MultipleTimesOperation::ChangeHighlight);
+                }
+            }
+            KeyCode::Char('F') => {
+                if let Some(file) = &self.missing_blob {
+                    return Message::Once(OnceOperation::FetchBlob {
+                        file: file.to_owned(),
+                    });
+                }
+            }
+            KeyCode::Char(':') if self.current_location().is_some() => {
+                return Message::Once(OnceOperation::OpenPrompt {
+                    kind: PromptKind::GoToLine,
+                })
+            }
+            KeyCode::Char('[') => self.select_outer_crumb(),
+            KeyCode::Char(']') => self.select_inner_crumb(),
+            KeyCode::Esc => {
+                self.cancel_blame();
+                self.crumb = None;
+                self.search = None;
+                self.selection = None;
+                self.blame_popup = None;
+            }
+            KeyCode::Enter => {
+                if let Some(prefix) = self.selected_directory() {
+                    self.crumb = None;
+                    return Message::Once(OnceOperation::ScopeTo { prefix });
+                }
+            }
+            KeyCode::Char('V') => self.toggle_selection(),
+            KeyCode::Char('Y') => return self.copy_lines(),
+            KeyCode::Char('y') => {
+                if let Some(file) = self.shown_file() {
+                    return Message::Once(OnceOperation::CopyToClipboard {
+                        text: file.to_owned(),
+                    });
+                }
+            }
+            KeyCode::Char('v') if !self.content.is_empty() => {
+                return Message::Once(OnceOperation::PassThrough {
+                    text: self.content.to_owned(),
+                });
+            }
+            KeyCode::Char('o') => {
+                if let Some(file) = self.committed_file() {
+                    return Message::Once(OnceOperation::OpenWithExternal {
+                        file: file.to_owned(),
+                    });
+                }
+            }
+            KeyCode::Char('*') => {
+                if let Some(word) = self.word_at_cursor() {
+                    return Message::Once(OnceOperation::OpenSearchModal { word });
+                }
+            }
+            KeyCode::Char('g') if self.title != "not found" && !self.title.is_empty() => {
+                return Message::Once(OnceOperation::OpenInBrowser {
+                    file: self.title.to_owned(),
+                    line: self.window_start() + self.scroll_position + 1,
+                });
+            }
+            _ => {}
+        }
+        Message::NoAction
+    }
+
+    fn handle_message(&mut self, message: &Message) -> Message {
+        self._handle_message(message)
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    // a few lines of the file, under the breadcrumb when there is one
+    fn min_height(&self) -> u16 {
+        2 + MIN_VISIBLE_LINES + u16::from(self.breadcrumb_line().is_some())
+    }
+
+    fn chords(&self) -> &'static [[char; 2]] {
+        &[['g', 'g'], ['z', 't'], ['z', 'z'], ['z', 'b']]
+    }
+
+    // gg goes to the first line; zt, zz and zb keep the current line at the top, middle
+    // or bottom of the view while scrolling
+    fn process_chord(&mut self, chord: [char; 2]) -> Message {
+        match chord {
+            ['g', 'g'] => self.scroll_to_line(1),
+            ['z', 't'] => self.cursor_row = CursorRow::Top,
+            ['z', 'z'] => self.cursor_row = CursorRow::Middle,
+            ['z', 'b'] => self.cursor_row = CursorRow::Bottom,
+            _ => {}
+        }
+        Message::NoAction
+    }
+
+    fn scroll_half_page(&mut self, down: bool) -> Message {
+        self.scroll_lines(down, (self.page_rows() / 2).max(1));
+        Message::NoAction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::RepositoryInfo;
+    use insta::assert_snapshot;
+    use ratatui::{backend::TestBackend, Terminal};
+    use std::sync::{Arc, Mutex};
+
+    fn create_mock_repo() -> Arc<Mutex<RepositoryInfo>> {
+        use std::env;
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let random_suffix = std::process::id();
+        let test_dir = env::temp_dir().join(format!(
+            "gview_content_viewer_test_{}_{}",
+            timestamp, random_suffix
+        ));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).unwrap();
+
+        let repo = git2::Repository::init(&test_dir).unwrap();
+        let signature = git2::Signature::new(
+            "Test User",
+            "test@localhost",
+            &git2::Time::new(1234567890, 0),
+        )
+        .unwrap();
+        let tree_id = {
+            let mut index = repo.index().unwrap();
+            index.write_tree().unwrap()
+        };
+        let tree = repo.find_tree(tree_id).unwrap();
+
+        let _ = repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "Initial commit",
+            &tree,
+            &[],
+        );
+
+        drop(tree);
+        let oid = repo.head().unwrap().target().unwrap();
+
+        let repo_info = RepositoryInfo::_from_parts(repo, oid);
+        Arc::new(Mutex::new(repo_info))
+    }
+
+    // One commit of `test.txt` per content, viewing the last one.
+    fn create_mock_repo_with_history(
+        contents: &[&str],
+    ) -> (Arc<Mutex<RepositoryInfo>>, Vec<String>) {
+        use std::env;
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let test_dir = env::temp_dir().join(format!(
+            "gview_content_viewer_history_test_{}_{}",
+            timestamp,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).unwrap();
+
+        let repo = git2::Repository::init(&test_dir).unwrap();
+        // the committer is the configured user
+        repo.config()
+            .unwrap()
+            .set_str("user.name", "Test User")
+            .unwrap();
+        let signature = git2::Signature::new(
+            "Test User",
+            "test@localhost",
+            &git2::Time::new(1234567890, 0),
+        )
+        .unwrap();
+        let mut commits: Vec<git2::Oid> = vec![];
+        for content in contents {
+            std::fs::write(test_dir.join("test.txt"), content).unwrap();
+            let mut index = repo.index().unwrap();
+            index.add_path(std::path::Path::new("test.txt")).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parent_commits: Vec<git2::Commit> = commits
+                .last()
+                .map(|oid| repo.find_commit(*oid).unwrap())
+                .into_iter()
+                .collect();
+            let parent_refs: Vec<&git2::Commit> = parent_commits.iter().collect();
+            let oid = repo
+                .commit(
+                    Some("HEAD"),
+                    &signature,
+                    &signature,
+                    "Edit test file",
+                    &tree,
+                    &parent_refs,
+                )
+                .unwrap();
+            commits.push(oid);
+        }
+
+        let repo_info = RepositoryInfo::_from_parts(repo, *commits.last().unwrap());
+        let commits = commits.iter().map(|oid| oid.to_string()).collect();
+        (Arc::new(Mutex::new(repo_info)), commits)
+    }
+
+    #[test]
+    fn test_show_file_with_unavailable_object() {
+        use std::env;
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let test_dir = env::temp_dir().join(format!(
+            "gview_content_viewer_unavailable_test_{}_{}",
+            timestamp,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).unwrap();
+        let repo = git2::Repository::init(&test_dir).unwrap();
+        std::fs::write(test_dir.join("gone.txt"), "gone\n").unwrap();
+        std::fs::write(test_dir.join("kept.txt"), "kept\n").unwrap();
+        let signature = git2::Signature::new(
+            "Test User",
+            "test@localhost",
+            &git2::Time::new(1234567890, 0),
+        )
+        .unwrap();
+        let oid = {
+            let mut index = repo.index().unwrap();
+            index.add_path(std::path::Path::new("gone.txt")).unwrap();
+            index.add_path(std::path::Path::new("kept.txt")).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            repo.commit(Some("HEAD"), &signature, &signature, "Add", &tree, &[])
+                .unwrap()
+        };
+        // as in a partial clone, where the blob was never fetched
+        let blob = repo.blob("gone\n".as_bytes()).unwrap().to_string();
+        let objects = repo.path().join("objects");
+        std::fs::remove_file(objects.join(&blob[..2]).join(&blob[2..])).unwrap();
+        let repo = git2::Repository::open(&test_dir).unwrap();
+        let mock_repo = Arc::new(Mutex::new(RepositoryInfo::_from_parts(repo, oid)));
+
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        let message = content_viewer.handle_message(&Message::Once(OnceOperation::ShowFile {
+            file: "gone.txt".to_owned(),
+        }));
+        assert_eq!(message, Message::NoAction);
+        assert!(content_viewer
+            .content
+            .starts_with("Cannot show gone.txt: object unavailable (promisor/partial clone?)"));
+        // nothing to fetch it from
+        assert_eq!(
+            content_viewer.process_events(KeyCode::Char('F')),
+            Message::NoAction
+        );
+
+        // the other files still show
+        let message = content_viewer.handle_message(&Message::Once(OnceOperation::ShowFile {
+            file: "kept.txt".to_owned(),
+        }));
+        assert_eq!(message, Message::NoAction);
+        assert_eq!(content_viewer.content, "1 | kept ");
+
+        // a partial clone offers to fetch it
+        git2::Repository::open(&test_dir)
+            .unwrap()
+            .config()
+            .unwrap()
+            .set_bool("remote.origin.promisor", true)
+            .unwrap();
+        content_viewer.handle_message(&Message::Once(OnceOperation::ShowFile {
+            file: "gone.txt".to_owned(),
+        }));
+        assert!(content_viewer
+            .content
+            .contains("Press F to fetch it from origin."));
+        assert_eq!(
+            content_viewer.process_events(KeyCode::Char('F')),
+            Message::Once(OnceOperation::FetchBlob {
+                file: "gone.txt".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn test_blame_in_background() {
+        let (mock_repo, commits) = create_mock_repo_with_history(&["a\nb\nc\n"]);
+        mock_repo.lock().unwrap().set_background(true);
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        content_viewer.mode = ShowMode::WithBlame;
+        content_viewer.handle_message(&Message::Once(OnceOperation::ShowFile {
+            file: "test.txt".to_owned(),
+        }));
+        let short = &commits[0][..7];
+        // the file shows at once, and the title spins until its blame comes
+        assert!(!content_viewer.content.contains(short));
+        let mut terminal = Terminal::new(TestBackend::new(60, 6)).unwrap();
+        let mut title = |content_viewer: &mut ContentViewer| {
+            terminal
+                .draw(|frame| content_viewer.draw(frame, frame.size()))
+                .unwrap();
+            // under the breadcrumb
+            let buffer = terminal.backend().buffer();
+            (0..60)
+                .map(|x| buffer.get(x, 1).symbol())
+                .collect::<String>()
+        };
+        assert!(title(&mut content_viewer).contains("⠋ blaming"));
+
+        content_viewer.scroll_position = 1;
+        while !content_viewer.blame_job.as_ref().unwrap().is_finished() {
+            std::thread::yield_now();
+        }
+        content_viewer.poll_blame();
+        assert!(content_viewer.blame_job.is_none());
+        assert!(content_viewer.content.contains(short));
+        assert_eq!(content_viewer.scroll_position, 1);
+        assert!(!title(&mut content_viewer).contains("blaming"));
+    }
+
+    #[test]
+    fn test_cancel_blame() {
+        let (mock_repo, commits) = create_mock_repo_with_history(&["a\nb\nc\n"]);
+        mock_repo.lock().unwrap().set_background(true);
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        content_viewer.mode = ShowMode::WithBlame;
+        content_viewer.handle_message(&Message::Once(OnceOperation::ShowFile {
+            file: "test.txt".to_owned(),
+        }));
+        content_viewer.scroll_position = 1;
+        assert!(content_viewer.is_blaming());
+
+        content_viewer.process_events(KeyCode::Esc);
+        assert!(content_viewer.blame_job.is_none());
+        assert!(matches!(content_viewer.mode, ShowMode::NoLine));
+        assert_eq!(content_viewer.content, "a\nb\nc");
+        assert_eq!(content_viewer.scroll_position, 1);
+        // showing the file again does not start over, blame mode does
+        content_viewer.refresh_content();
+        assert!(content_viewer.blame_job.is_none());
+        content_viewer.process_events(KeyCode::Char('b'));
+        assert!(content_viewer.is_blaming());
+        while !content_viewer.blame_job.as_ref().unwrap().is_finished() {
+            std::thread::yield_now();
+        }
+        content_viewer.poll_blame();
+        assert!(content_viewer.content.contains(&commits[0]));
+    }
+
+    #[test]
+    fn test_click_blame_gutter() {
+        let (mock_repo, commits) = create_mock_repo_with_history(&["a\nb\n", "a\nb\nc\n"]);
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        content_viewer.mode = ShowMode::WithBlame;
+        content_viewer.handle_message(&Message::Once(OnceOperation::ShowFile {
+            file: "test.txt".to_owned(),
+        }));
+        let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+        let mut screen = |content_viewer: &mut ContentViewer| {
+            terminal
+                .draw(|frame| content_viewer.draw(frame, frame.size()))
+                .unwrap();
+            format!("{:?}", terminal.backend().buffer())
+        };
+        screen(&mut content_viewer);
+        let jump = |commit: &str| {
+            Message::Once(OnceOperation::SetCommitById {
+                commit_id: commit.to_owned(),
+            })
+        };
+        // the lines start under the breadcrumb and the border
+        assert_eq!(content_viewer.click(5, 2), jump(&commits[0]));
+        assert_eq!(content_viewer.click(5, 4), jump(&commits[1]));
+        assert_eq!(content_viewer.click(60, 2), Message::NoAction);
+
+        // the second line continues the hunk of the first, so its gutter shows the details
+        assert_eq!(content_viewer.click(5, 3), Message::NoAction);
+        let popup = content_viewer.blame_popup.as_ref().unwrap();
+        assert_eq!(popup.index, 1);
+        assert_eq!(popup.rows[0], ("Line", "2".to_owned()));
+        assert_eq!(popup.rows[1], ("Commit", commits[0].clone()));
+        assert_eq!(popup.rows[4], ("Summary", "Edit test file".to_owned()));
+        assert!(screen(&mut content_viewer).contains("Blame (Esc: close)"));
+
+        content_viewer.process_events(KeyCode::Esc);
+        assert!(!screen(&mut content_viewer).contains("Blame (Esc: close)"));
+        content_viewer.click(5, 3);
+        content_viewer.click(60, 3);
+        assert!(content_viewer.blame_popup.is_none());
+
+        // only the blame gutter takes clicks
+        content_viewer.mode = ShowMode::WithLine;
+        content_viewer.handle_message(&Message::Once(OnceOperation::ShowFile {
+            file: "test.txt".to_owned(),
+        }));
+        screen(&mut content_viewer);
+        assert_eq!(content_viewer.click(1, 2), Message::NoAction);
+        assert!(content_viewer.blame_popup.is_none());
+    }
+
+    #[test]
+    fn test_missing_newline_marker() {
+        let (mock_repo, _) = create_mock_repo_with_history(&["line 1\r\nline 2"]);
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        content_viewer.handle_message(&Message::Once(OnceOperation::ShowFile {
+            file: "test.txt".to_owned(),
+        }));
+        assert_eq!(
+            content_viewer.content,
+            "1 | line 1 \n2 | line 2 \n\\ No newline at end of file"
+        );
+
+        // the checked-out file gets the marker too
+        content_viewer.process_events(KeyCode::Char('w'));
+        assert_eq!(content_viewer.source, Source::WorkingTree);
+        assert_eq!(
+            content_viewer.content,
+            "1 | line 1 \n2 | line 2 \n\\ No newline at end of file"
+        );
+    }
+
+    #[test]
+    fn test_large_file_is_read_in_windows() {
+        let lines = 30_000;
+        let content: String = (1..=lines)
+            .map(|number| format!("row {}\n", number))
+            .collect();
+        let (mock_repo, _) = create_mock_repo_with_history(&[content.as_str()]);
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        content_viewer.handle_message(&Message::Once(OnceOperation::ShowFile {
+            file: "test.txt".to_owned(),
+        }));
+        // only the top of the file is read
+        assert_eq!(content_viewer.content.lines().count(), WINDOW_LINES);
+        assert_eq!(
+            content_viewer.window,
+            Some(Window {
+                start: 0,
+                total: lines
+            })
+        );
+
+        content_viewer.scroll_to_line(25_000);
+        assert_eq!(
+            content_viewer.current_location(),
+            Some(("test.txt".to_owned(), 25_000))
+        );
+        let top = content_viewer
+            .content
+            .lines()
+            .nth(content_viewer.scroll_position)
+            .unwrap();
+        assert_eq!(top, "25000 | row 25000 ");
+        assert!(content_viewer.content.lines().count() <= WINDOW_LINES);
+
+        // the end of the file is read as it is scrolled to
+        content_viewer.scroll_to_line(lines);
+        assert!(content_viewer.content.ends_with("30000 | row 30000 "));
+    }
+
+    #[test]
+    fn test_binary_file_is_shown_as_hex_dump() {
+        assert_eq!(
+            hex_dump(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\x01"),
+            "00000000  89 50 4e 47 0d 0a 1a 0a  00 00 00 0d 49 48 44 52  |.PNG........IHDR|\n\
+             00000010  00 01                                             |..|"
+        );
+        assert_eq!(
+            hex_dump(&vec![0; HEX_DUMP_LIMIT + 3]).lines().last(),
+            Some("… 3 more bytes")
+        );
+
+        let (mock_repo, _) = create_mock_repo_with_history(&["GIF89a\0\x01"]);
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        content_viewer.mode = ShowMode::WithBlame;
+        content_viewer.handle_message(&Message::Once(OnceOperation::ShowFile {
+            file: "test.txt".to_owned(),
+        }));
+        assert!(matches!(content_viewer.mode, ShowMode::Hex));
+        assert_eq!(content_viewer.blob_size, Some(8));
+        assert!(content_viewer.content.ends_with("|GIF89a..|"));
+        assert_eq!(content_viewer.current_location(), None);
+    }
+
+    #[test]
+    fn test_copy_lines() {
+        let (mock_repo, _) = create_mock_repo_with_history(&["fn a() {}\nfn b() {}\nfn c() {}\n"]);
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        assert!(matches!(
+            content_viewer.process_events(KeyCode::Char('Y')),
+            Message::Error(GviewError::Unavailable(_))
+        ));
+        content_viewer.handle_message(&Message::Once(OnceOperation::ShowFile {
+            file: "test.txt".to_owned(),
+        }));
+
+        // the top line alone, without its gutter
+        assert_eq!(
+            content_viewer.process_events(KeyCode::Char('Y')),
+            Message::Once(OnceOperation::CopyLines {
+                bytes: b"fn a() {}\n".to_vec()
+            })
+        );
+
+        // a selection runs from where V was pressed to the top line
+        content_viewer.process_events(KeyCode::Char('j'));
+        content_viewer.process_events(KeyCode::Char('V'));
+        content_viewer.process_events(KeyCode::Char('j'));
+        assert_eq!(content_viewer.selected_lines(), Some(1..=2));
+        assert_eq!(
+            content_viewer.process_events(KeyCode::Char('Y')),
+            Message::Once(OnceOperation::CopyLines {
+                bytes: b"fn b() {}\nfn c() {}\n".to_vec()
+            })
+        );
+        assert_eq!(content_viewer.selection, None);
+    }
+
+    #[test]
+    fn test_copy_lines_keeps_crlf() {
+        let (mock_repo, _) = create_mock_repo_with_history(&["a\r\nb\r\nc"]);
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        content_viewer.handle_message(&Message::Once(OnceOperation::ShowFile {
+            file: "test.txt".to_owned(),
+        }));
+        // the shown lines lose their carriage returns, the copy does not
+        assert!(!content_viewer.content.contains('\r'));
+        content_viewer.process_events(KeyCode::Char('V'));
+        content_viewer.process_events(KeyCode::Char('G'));
+        assert_eq!(
+            content_viewer.process_events(KeyCode::Char('Y')),
+            Message::Once(OnceOperation::CopyLines {
+                bytes: b"a\r\nb\r\nc".to_vec()
+            })
+        );
+    }
+
+    #[test]
+    fn test_breadcrumb_selection() {
+        let mut content_viewer = ContentViewer::new(create_mock_repo());
+        assert_eq!(content_viewer.breadcrumb(), None);
+        assert_eq!(
+            content_viewer.process_events(KeyCode::Char('y')),
+            Message::NoAction
+        );
+
+        content_viewer.title = "src/components/filer.rs".to_owned();
+        assert_eq!(
+            content_viewer.breadcrumb(),
+            Some(vec!["/", "src", "components", "filer.rs"])
+        );
+        assert_eq!(
+            content_viewer.process_events(KeyCode::Char('y')),
+            Message::Once(OnceOperation::CopyToClipboard {
+                text: "src/components/filer.rs".to_owned()
+            })
+        );
+
+        content_viewer.process_events(KeyCode::Char('['));
+        assert_eq!(
+            content_viewer.selected_directory(),
+            Some("src/components".to_owned())
+        );
+        content_viewer.process_events(KeyCode::Char('['));
+        content_viewer.process_events(KeyCode::Char('['));
+        content_viewer.process_events(KeyCode::Char('['));
+        assert_eq!(content_viewer.selected_directory(), Some("".to_owned()));
+        content_viewer.process_events(KeyCode::Char(']'));
+        assert_eq!(content_viewer.selected_directory(), Some("src".to_owned()));
+        assert_eq!(
+            content_viewer.process_events(KeyCode::Enter),
+            Message::Once(OnceOperation::ScopeTo {
+                prefix: "src".to_owned()
+            })
+        );
+        assert_eq!(content_viewer.crumb, None);
+
+        // the file itself is not selectable
+        content_viewer.process_events(KeyCode::Char('['));
+        content_viewer.process_events(KeyCode::Char(']'));
+        assert_eq!(content_viewer.crumb, None);
+        assert_eq!(
+            content_viewer.process_events(KeyCode::Enter),
+            Message::NoAction
+        );
+    }
+
+    #[test]
+    fn test_highlight_my_lines() {
+        let (mock_repo, _) = create_mock_repo_with_history(&["a\nb\n"]);
+        let mut content_viewer = ContentViewer::new(Arc::clone(&mock_repo));
+        content_viewer.handle_message(&Message::Once(OnceOperation::ShowFile {
+            file: "test.txt".to_owned(),
+        }));
+        content_viewer.process_events(KeyCode::Char('b'));
+        assert!(content_viewer.mine_lines.is_empty());
+
+        let message = content_viewer.process_events(KeyCode::Char('m'));
+        assert_eq!(
+            message,
+            Message::MultipleTimes(MultipleTimesOperation::ChangeHighlight)
+        );
+        content_viewer.handle_message(&message);
+        assert!(mock_repo.lock().unwrap().highlight_mine());
+        assert_eq!(content_viewer.mine_lines, [true, true]);
+
+        // only blame shows who changed a line
+        content_viewer.process_events(KeyCode::Char('n'));
+        assert!(content_viewer.mine_lines.is_empty());
+    }
+
+    #[test]
+    fn test_syntax_highlighting() {
+        let rows: Vec<CommitRow> = ["fn main() {", "    let x = 42;", "}"]
+            .iter()
+            .enumerate()
+            .map(|(index, line)| CommitRow {
+                _author: "Test Author".to_string(),
+                number: index + 1,
+                line: line.to_string(),
+                commit: git2::Oid::zero(),
+            })
+            .collect();
+        let mut content_viewer = ContentViewer::new(create_mock_repo());
+        content_viewer.focus = Focus::ON;
+        content_viewer.highlight("main.rs", &rows, ShowMode::WithLine.gutter_width(&rows));
+        assert_eq!(content_viewer.code_start, 4);
+        content_viewer.content = ShowMode::WithLine.concat(rows, &BTreeSet::new(), false);
+
+        let draw = |content_viewer: &mut ContentViewer| {
+            let mut terminal = Terminal::new(TestBackend::new(30, 5)).unwrap();
+            terminal
+                .draw(|frame| content_viewer.draw(frame, frame.size()))
+                .unwrap();
+            terminal.backend().buffer().clone()
+        };
+        // `1 | fn main() {` inside the border
+        let buffer = draw(&mut content_viewer);
+        let text: String = (1..15).map(|x| buffer.get(x, 1).symbol()).collect();
+        assert_eq!(text, "1 | fn main() ");
+        assert_eq!(buffer.get(1, 1).fg, Color::Reset);
+        assert!(matches!(buffer.get(5, 1).fg, Color::Rgb(..)));
+        let main_color = buffer.get(8, 1).fg;
+
+        // scrolled into the code, the colors stay with their characters
+        content_viewer.horizontal_scroll = 7;
+        let buffer = draw(&mut content_viewer);
+        let text: String = (1..8).map(|x| buffer.get(x, 1).symbol()).collect();
+        assert_eq!(text, "main() ");
+        assert_eq!(buffer.get(1, 1).fg, main_color);
+    }
+
+    #[test]
+    fn test_find_matches() {
+        assert_eq!(find_matches("foo Foo FOO", "foo", 0), vec![0, 4, 8]);
+        // capitals make the query case-sensitive
+        assert_eq!(find_matches("foo Foo FOO", "Foo", 0), vec![4]);
+        assert_eq!(find_matches("aaaa", "aa", 0), vec![0, 2]);
+        assert_eq!(find_matches("1 | foo", "1", 4), Vec::<usize>::new());
+        assert_eq!(find_matches("日本語の日本", "日本", 0), vec![0, 4]);
+        assert!(find_matches("foo", "", 0).is_empty());
+    }
+
+    #[test]
+    fn test_incremental_search() {
+        let mut content_viewer = ContentViewer::new(create_mock_repo());
+        content_viewer.focus = Focus::ON;
+        content_viewer.content = (0..30)
+            .map(|number| match number {
+                3 | 20 => format!("let target_{} = 1;", number),
+                _ => format!("line {}", number),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        content_viewer.scroll_position = 5;
+
+        content_viewer.process_events(KeyCode::Char('/'));
+        assert!(content_viewer.is_editing_search());
+        for c in "targ".chars() {
+            content_viewer.process_events(KeyCode::Char(c));
+        }
+        // the first match from where the search started
+        assert_eq!(content_viewer.scroll_position, 20);
+        content_viewer.process_events(KeyCode::Enter);
+        assert!(!content_viewer.is_editing_search());
+
+        content_viewer.process_events(KeyCode::Char('n'));
+        assert_eq!(content_viewer.scroll_position, 3);
+        content_viewer.process_events(KeyCode::Char('N'));
+        assert_eq!(content_viewer.scroll_position, 20);
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 6)).unwrap();
+        terminal
+            .draw(|frame| content_viewer.draw(frame, frame.size()))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let title: String = (0..40).map(|x| buffer.get(x, 0).symbol()).collect();
+        assert!(title.contains("/targ (2/2, n/N)"));
+        // `let target_20` on the top line, the match marked as the current one
+        assert_eq!(buffer.get(4, 1).bg, Color::Reset);
+        assert_eq!(buffer.get(5, 1).bg, Color::LightRed);
+        assert_eq!(buffer.get(8, 1).bg, Color::LightRed);
+        assert_eq!(buffer.get(9, 1).bg, Color::Reset);
+
+        // Esc drops the search and `n` toggles line numbers again
+        content_viewer.process_events(KeyCode::Esc);
+        assert!(content_viewer.search.is_none());
+        content_viewer.process_events(KeyCode::Char('n'));
+        assert!(matches!(content_viewer.mode, ShowMode::NoLine));
+
+        // a dropped search returns to where it started
+        content_viewer.process_events(KeyCode::Char('/'));
+        for c in "line 1".chars() {
+            content_viewer.process_events(KeyCode::Char(c));
+        }
+        // wrapped around, no line below matches
+        assert_eq!(content_viewer.scroll_position, 1);
+        content_viewer.process_events(KeyCode::Esc);
+        assert_eq!(content_viewer.scroll_position, 20);
     }
 
-    fn handle_message(&mut self, message: &Message) -> Message {
-        self._handle_message(message)
+    #[test]
+    fn test_pass_through_content() {
+        let (mock_repo, _) = create_mock_repo_with_history(&["line 1\nline 2\n"]);
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        assert_eq!(
+            content_viewer.process_events(KeyCode::Char('v')),
+            Message::NoAction
+        );
+        content_viewer.handle_message(&Message::Once(OnceOperation::ShowFile {
+            file: "test.txt".to_owned(),
+        }));
+        assert_eq!(
+            content_viewer.process_events(KeyCode::Char('v')),
+            Message::Once(OnceOperation::PassThrough {
+                text: "1 | line 1 \n2 | line 2 ".to_owned()
+            })
+        );
     }
-}
-
-fn title_block(title: &str, focus: Focus) -> Block {
-    Block::bordered()
-        .title(title.bold().into_left_aligned_line())
-        .style(match focus {
-            Focus::ON => Style::default(),
-            Focus::Off => Style::default().fg(Color::DarkGray),
-        })
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::repository::RepositoryInfo;
-    use insta::assert_snapshot;
-    use ratatui::{backend::TestBackend, Terminal};
-    use std::sync::{Arc, Mutex};
 
-    fn create_mock_repo() -> Arc<Mutex<RepositoryInfo>> {
-        use std::env;
-        use std::time::{SystemTime, UNIX_EPOCH};
+    #[test]
+    fn test_pin_follows_line_across_commits() {
+        let (mock_repo, commits) = create_mock_repo_with_history(&[
+            "fn a() {}\nfn b() {}\n",
+            "// header\nfn a() {}\nfn b() {}\n",
+        ]);
+        let mut content_viewer = ContentViewer::new(Arc::clone(&mock_repo));
+        let show_file = |content_viewer: &mut ContentViewer| {
+            content_viewer.handle_message(&Message::Once(OnceOperation::ShowFile {
+                file: "test.txt".to_owned(),
+            }))
+        };
+        show_file(&mut content_viewer);
+        content_viewer.scroll_to_line(3);
+        assert_eq!(
+            content_viewer.process_events(KeyCode::Char('p')),
+            Message::NoAction
+        );
+        assert_eq!(content_viewer.pinned_index(), Some(2));
 
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
+        // `fn b` is one line higher in the first commit
+        mock_repo
+            .lock()
             .unwrap()
-            .as_nanos();
-        let random_suffix = std::process::id();
-        let test_dir = env::temp_dir().join(format!(
-            "gview_content_viewer_test_{}_{}",
-            timestamp, random_suffix
-        ));
-        let _ = std::fs::remove_dir_all(&test_dir);
-        std::fs::create_dir_all(&test_dir).unwrap();
+            .set_commit_by_id(&commits[0])
+            .unwrap();
+        show_file(&mut content_viewer);
+        assert_eq!(
+            content_viewer.pin,
+            Some(("test.txt".to_owned(), commits[0].clone(), 2))
+        );
+        assert_eq!(content_viewer.scroll_position, 1);
 
-        let repo = git2::Repository::init(&test_dir).unwrap();
-        let signature = git2::Signature::new(
-            "Test User",
-            "test@localhost",
-            &git2::Time::new(1234567890, 0),
-        )
-        .unwrap();
-        let tree_id = {
-            let mut index = repo.index().unwrap();
-            index.write_tree().unwrap()
+        content_viewer.process_events(KeyCode::Char('p'));
+        assert_eq!(content_viewer.pin, None);
+    }
+
+    #[test]
+    fn test_scroll_follows_diff_across_commits() {
+        let (mock_repo, commits) =
+            create_mock_repo_with_history(&["a\nb\nc\nd\n", "x\ny\na\nb\nc\nd\n"]);
+        let mut content_viewer = ContentViewer::new(Arc::clone(&mock_repo));
+        let show_file = |content_viewer: &mut ContentViewer| {
+            content_viewer.handle_message(&Message::Once(OnceOperation::ShowFile {
+                file: "test.txt".to_owned(),
+            }))
         };
-        let tree = repo.find_tree(tree_id).unwrap();
+        show_file(&mut content_viewer);
+        content_viewer.scroll_to_line(5);
 
-        let _ = repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            "Initial commit",
-            &tree,
-            &[],
-        );
+        // `c` is two lines higher in the first commit
+        mock_repo
+            .lock()
+            .unwrap()
+            .set_commit_by_id(&commits[0])
+            .unwrap();
+        show_file(&mut content_viewer);
+        assert_eq!(content_viewer.scroll_position, 2);
 
-        drop(tree);
-        let oid = repo.head().unwrap().target().unwrap();
+        // reselecting the file at the same commit still starts from the top
+        show_file(&mut content_viewer);
+        assert_eq!(content_viewer.scroll_position, 0);
+    }
 
-        let repo_info = RepositoryInfo::_from_parts(repo, oid);
-        Arc::new(Mutex::new(repo_info))
+    #[test]
+    fn test_preview_commit_from_commit_modal() {
+        let (mock_repo, commits) =
+            create_mock_repo_with_history(&["line 1\nline 2\n", "line 1\nline two\n"]);
+        let first = commits[0].clone();
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        content_viewer.handle_message(&Message::Once(OnceOperation::ShowFile {
+            file: "test.txt".to_owned(),
+        }));
+        assert_eq!(content_viewer.content, "1 | line 1 \n2 | line two ");
+
+        // Opening the modal previews the viewed commit without blame
+        content_viewer.handle_message(&Message::Once(OnceOperation::OpenCommitModal));
+        assert!(content_viewer.is_previewing());
+
+        content_viewer.handle_message(&Message::Once(OnceOperation::PreviewCommit {
+            commit_id: first.clone(),
+        }));
+        assert_eq!(content_viewer.content, "1 | line 1 \n2 | line 2 ");
+        assert_eq!(content_viewer.preview, Some(first[..8].to_owned()));
+
+        content_viewer.handle_message(&Message::Once(OnceOperation::CloseCommitModal));
+        assert!(!content_viewer.is_previewing());
+        assert_eq!(content_viewer.content, "1 | line 1 \n2 | line two ");
     }
 
     #[test]
@@ -477,6 +2830,133 @@ mod tests {
         assert_eq!(content_viewer.scroll_position, 0);
     }
 
+    #[test]
+    fn test_cursor_row_chords_and_scrolloff() {
+        let mock_repo = create_mock_repo();
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        content_viewer.mode = ShowMode::NoLine;
+        content_viewer.content = (1..=30)
+            .map(|number| format!("Line {}", number))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+        // the first line drawn, inside the border
+        let mut top = |content_viewer: &mut ContentViewer| {
+            terminal
+                .draw(|frame| content_viewer.draw(frame, frame.size()))
+                .unwrap();
+            let buffer = terminal.backend().buffer();
+            (1..8)
+                .map(|x| buffer.get(x, 1).symbol())
+                .collect::<String>()
+                .trim()
+                .to_owned()
+        };
+        top(&mut content_viewer);
+        content_viewer.scroll_position = 10;
+        assert_eq!(top(&mut content_viewer), "Line 11");
+
+        // ten rows inside the border: the current line goes to the fifth or the last one
+        content_viewer.process_chord(['z', 'z']);
+        assert_eq!(top(&mut content_viewer), "Line 7");
+        content_viewer.process_events(KeyCode::Char('j'));
+        assert_eq!(top(&mut content_viewer), "Line 8");
+        content_viewer.process_chord(['z', 'b']);
+        assert_eq!(top(&mut content_viewer), "Line 3");
+        // the start of the file stays at the top
+        content_viewer.scroll_position = 2;
+        assert_eq!(top(&mut content_viewer), "Line 1");
+        content_viewer.process_chord(['z', 't']);
+        assert_eq!(top(&mut content_viewer), "Line 3");
+
+        // scrolloff keeps context before the current line, and after it at the bottom
+        content_viewer.set_scrolloff(3);
+        content_viewer.scroll_position = 10;
+        assert_eq!(top(&mut content_viewer), "Line 8");
+        content_viewer.process_chord(['z', 'b']);
+        assert_eq!(top(&mut content_viewer), "Line 5");
+    }
+
+    #[test]
+    fn test_page_keys() {
+        let mock_repo = create_mock_repo();
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        content_viewer.mode = ShowMode::NoLine;
+        content_viewer.content = (1..=50)
+            .map(|number| format!("Line {}", number))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+        terminal
+            .draw(|frame| content_viewer.draw(frame, frame.size()))
+            .unwrap();
+
+        // ten rows inside the border
+        content_viewer.process_events(KeyCode::PageDown);
+        assert_eq!(content_viewer.scroll_position, 10);
+        content_viewer.scroll_half_page(true);
+        assert_eq!(content_viewer.scroll_position, 15);
+        content_viewer.scroll_half_page(false);
+        content_viewer.process_events(KeyCode::PageUp);
+        assert_eq!(content_viewer.scroll_position, 0);
+
+        // the end is as far as j goes
+        content_viewer.process_events(KeyCode::Char('G'));
+        let end = content_viewer.scroll_position;
+        assert!(end >= 40);
+        content_viewer.process_events(KeyCode::Char('j'));
+        content_viewer.process_events(KeyCode::PageDown);
+        assert_eq!(content_viewer.scroll_position, end);
+        content_viewer.process_events(KeyCode::Home);
+        assert_eq!(content_viewer.scroll_position, 0);
+        content_viewer.process_events(KeyCode::End);
+        assert_eq!(content_viewer.scroll_position, end);
+    }
+
+    #[test]
+    fn test_go_to_line() {
+        let mock_repo = create_mock_repo();
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        content_viewer.mode = ShowMode::NoLine;
+        content_viewer.content = (1..=30)
+            .map(|number| format!("Line {}", number))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+        let mut top = |content_viewer: &mut ContentViewer| {
+            terminal
+                .draw(|frame| content_viewer.draw(frame, frame.size()))
+                .unwrap();
+            let buffer = terminal.backend().buffer();
+            (1..8)
+                .map(|x| buffer.get(x, 1).symbol())
+                .collect::<String>()
+                .trim()
+                .to_owned()
+        };
+        top(&mut content_viewer);
+
+        // the line goes to the middle of the ten rows, and the current line stays on top
+        content_viewer.go_to_line(20);
+        assert_eq!(content_viewer.scroll_position, 15);
+        assert!(matches!(content_viewer.cursor_row, CursorRow::Top));
+        assert_eq!(top(&mut content_viewer), "Line 16");
+        // past the end is the last line, and 0 the first
+        content_viewer.go_to_line(100);
+        assert_eq!(top(&mut content_viewer), "Line 26");
+        content_viewer.go_to_line(0);
+        assert_eq!(top(&mut content_viewer), "Line 1");
+
+        // a current line in the middle is the one gone to
+        content_viewer.process_chord(['z', 'z']);
+        content_viewer.go_to_line(20);
+        assert_eq!(content_viewer.scroll_position, 19);
+        assert_eq!(top(&mut content_viewer), "Line 16");
+        content_viewer.go_to_line(3);
+        assert_eq!(content_viewer.scroll_position, 2);
+        assert_eq!(top(&mut content_viewer), "Line 1");
+    }
+
     #[test]
     fn test_horizontal_scrolling() {
         let mock_repo = create_mock_repo();
@@ -629,22 +3109,32 @@ mod tests {
 
         // Test NoLine mode
         let mut mode = ShowMode::NoLine;
-        let result = mode.concat(commit_rows.clone());
+        let result = mode.concat(commit_rows.clone(), &BTreeSet::new(), false);
         assert_eq!(result, "fn main() {\n    println!(\"Hello\");");
 
         // Test WithLine mode
         let mut mode = ShowMode::WithLine;
-        let result = mode.concat(commit_rows.clone());
+        let result = mode.concat(commit_rows.clone(), &BTreeSet::new(), false);
         assert!(result.contains("1 | fn main() { "));
         assert!(result.contains("2 |     println!(\"Hello\"); "));
 
         // Test WithBlame mode
         let mut mode = ShowMode::WithBlame;
-        let result = mode.concat(commit_rows);
+        let result = mode.concat(commit_rows, &BTreeSet::new(), false);
         assert!(result.contains("abc123456789abcd1234567890abcdef12345678 | fn main() { "));
         assert!(
             result.contains("def456789012cdef1234567890abcdef12345678 |     println!(\"Hello\"); ")
         );
+
+        // Lines that could not be blamed
+        let rows = vec![CommitRow::new(
+            "".to_owned(),
+            git2::Oid::zero(),
+            1,
+            "fn main() {".to_owned(),
+        )];
+        let result = ShowMode::WithBlame.concat(rows, &BTreeSet::new(), false);
+        assert_eq!(result, format!("{:<40} | fn main() {{ ", "unavailable"));
     }
 
     #[test]
@@ -673,4 +3163,409 @@ mod tests {
         let _message = content_viewer.process_events(KeyCode::Char('l'));
         assert_eq!(content_viewer.horizontal_scroll, 1);
     }
+
+    #[test]
+    fn test_toggle_diff_mode() {
+        let mock_repo = create_mock_repo();
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        content_viewer.title = "test.rs".to_string();
+
+        let message = content_viewer.process_events(KeyCode::Char('d'));
+        assert_eq!(message, Message::NoAction);
+        assert!(matches!(content_viewer.mode, ShowMode::Diff));
+        assert_eq!(content_viewer.diff_label, "vs parent");
+
+        content_viewer.toggle_diff_mode();
+        assert!(matches!(content_viewer.mode, ShowMode::WithLine));
+
+        // Leaving diff mode through the other toggles
+        content_viewer.mode = ShowMode::Diff;
+        content_viewer.toggle_line_numbers();
+        assert!(matches!(content_viewer.mode, ShowMode::WithLine));
+        content_viewer.mode = ShowMode::Diff;
+        content_viewer.toggle_blame_mode();
+        assert!(matches!(content_viewer.mode, ShowMode::WithBlame));
+    }
+
+    #[test]
+    fn test_align_split_diff() {
+        use crate::repository::DiffHunk;
+
+        let lines = |lines: &[&str]| lines.iter().map(|line| line.to_string()).collect();
+        // a changed line, a removed one and two added ones
+        let diff = SplitDiff {
+            old: lines(&["a", "b", "c", "d"]),
+            new: lines(&["a", "B", "d", "e", "f"]),
+            hunks: vec![
+                DiffHunk {
+                    old_start: 2,
+                    old_lines: 2,
+                    new_start: 2,
+                    new_lines: 1,
+                },
+                DiffHunk {
+                    old_start: 4,
+                    old_lines: 0,
+                    new_start: 4,
+                    new_lines: 2,
+                },
+            ],
+        };
+        let rows = align(&diff);
+        let pairs: Vec<(Option<usize>, Option<usize>, bool)> = rows
+            .iter()
+            .map(|row| (row.old, row.new, row.changed))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (Some(0), Some(0), false),
+                (Some(1), Some(1), true),
+                (Some(2), None, true),
+                (Some(3), Some(2), false),
+                (None, Some(3), true),
+                (None, Some(4), true),
+            ]
+        );
+        assert_eq!(
+            format_split(&diff, &rows),
+            "a   a\nb | B\nc <\nd   d\n  > e\n  > f"
+        );
+        assert_eq!(fit_columns("日本語のコメント", 1, 5), "本語");
+    }
+
+    #[test]
+    fn test_split_diff_view() {
+        let (mock_repo, _) = create_mock_repo_with_history(&["one\ntwo\n", "one\n2\nthree\n"]);
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        content_viewer.handle_message(&Message::Once(OnceOperation::ShowFile {
+            file: "test.txt".to_owned(),
+        }));
+        assert_eq!(
+            content_viewer.process_events(KeyCode::Char('D')),
+            Message::NoAction
+        );
+        assert!(matches!(content_viewer.mode, ShowMode::SplitDiff));
+        assert_eq!(content_viewer.current_location(), None);
+
+        let mut terminal = Terminal::new(TestBackend::new(30, 6)).unwrap();
+        terminal
+            .draw(|frame| content_viewer.draw(frame, frame.size()))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let rows: Vec<String> = (1..6)
+            .map(|y| (0..30).map(|x| buffer.get(x, y).symbol()).collect())
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                "┌test.txt (side-by-side diff ┐",
+                "│1 one        │1 one         │",
+                "│2 two        │2 2           │",
+                "│             │3 three       │",
+                "└────────────────────────────┘",
+            ]
+        );
+        assert_eq!(buffer.get(3, 3).fg, Color::Red);
+        assert_eq!(buffer.get(17, 4).fg, Color::Green);
+        assert_eq!(buffer.get(3, 4).bg, Color::Indexed(236));
+
+        // `d` switches to the unified diff, `D` back to the file
+        content_viewer.process_events(KeyCode::Char('d'));
+        assert!(matches!(content_viewer.mode, ShowMode::Diff));
+        content_viewer.toggle_split_diff_mode();
+        content_viewer.toggle_split_diff_mode();
+        assert!(matches!(content_viewer.mode, ShowMode::WithLine));
+    }
+
+    #[test]
+    fn test_format_diff_and_line_style() {
+        let rows = vec![
+            DiffRow {
+                origin: 'H',
+                old_number: None,
+                new_number: None,
+                line: "@@ -1 +1 @@".to_string(),
+            },
+            DiffRow {
+                origin: '-',
+                old_number: Some(1),
+                new_number: None,
+                line: "old".to_string(),
+            },
+            DiffRow {
+                origin: '+',
+                old_number: None,
+                new_number: Some(1),
+                line: "new".to_string(),
+            },
+            DiffRow {
+                origin: ' ',
+                old_number: Some(2),
+                new_number: Some(2),
+                line: "same".to_string(),
+            },
+        ];
+        let content = format_diff(rows);
+        assert_eq!(content, "@@ -1 +1 @@\n-old\n+new\n same");
+
+        let theme = Theme::dark();
+        assert_eq!(diff_line_style("+new", &theme).fg, Some(Color::Green));
+        assert_eq!(diff_line_style("-old", &theme).fg, Some(Color::Red));
+        assert_eq!(diff_line_style("@@ -1 +1 @@", &theme).fg, Some(Color::Cyan));
+        assert_eq!(diff_line_style(" same", &theme).fg, None);
+    }
+
+    #[test]
+    fn test_change_base_without_base_keeps_mode() {
+        let mock_repo = create_mock_repo();
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        let message = content_viewer
+            .handle_message(&Message::MultipleTimes(MultipleTimesOperation::ChangeBase));
+        assert_eq!(message, Message::NoAction);
+        assert!(matches!(content_viewer.mode, ShowMode::WithLine));
+    }
+
+    #[test]
+    fn test_show_mode_concat_note_markers() {
+        use git2::Oid;
+        let oid = Oid::from_str("abc123456789abcd1234567890abcdef12345678").unwrap();
+        let commit_rows: Vec<CommitRow> = (1..=2)
+            .map(|number| CommitRow {
+                _author: "Test Author".to_string(),
+                number,
+                line: format!("line {}", number),
+                commit: oid,
+            })
+            .collect();
+        let noted = BTreeSet::from([2]);
+
+        let result = ShowMode::WithLine.concat(commit_rows.clone(), &noted, false);
+        assert_eq!(result, "1 | line 1 \n2*| line 2 ");
+
+        let result = ShowMode::WithBlame.concat(commit_rows, &noted, true);
+        assert!(result.ends_with("abc123456789abcd1234567890abcdef12345678*| line 2 "));
+    }
+
+    #[test]
+    fn test_word_at_cursor_and_search_key() {
+        let mock_repo = create_mock_repo();
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        assert_eq!(
+            content_viewer.process_events(KeyCode::Char('*')),
+            Message::NoAction
+        );
+
+        content_viewer.title = "src/main.rs".to_string();
+        content_viewer.content = " 9 | fn main() { \n10 | let app = App::new(); ".to_string();
+        content_viewer.scroll_to_line(2);
+        assert_eq!(content_viewer.word_at_cursor(), Some("let".to_string()));
+
+        // The gutter is skipped when scrolling horizontally
+        content_viewer.horizontal_scroll = 10;
+        assert_eq!(content_viewer.word_at_cursor(), Some("app".to_string()));
+        assert_eq!(
+            content_viewer.process_events(KeyCode::Char('*')),
+            Message::Once(OnceOperation::OpenSearchModal {
+                word: "app".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_note_keys_and_current_location() {
+        let mock_repo = create_mock_repo();
+        let mut content_viewer = ContentViewer::new(mock_repo);
+
+        // Nothing to annotate before a file is shown
+        assert_eq!(content_viewer.current_location(), None);
+        assert_eq!(
+            content_viewer.process_events(KeyCode::Char('a')),
+            Message::NoAction
+        );
+
+        content_viewer.title = "src/main.rs".to_string();
+        content_viewer.scroll_to_line(5);
+        assert_eq!(
+            content_viewer.current_location(),
+            Some(("src/main.rs".to_string(), 5))
+        );
+        assert_eq!(
+            content_viewer.process_events(KeyCode::Char('a')),
+            Message::Once(OnceOperation::OpenPrompt {
+                kind: PromptKind::Note
+            })
+        );
+        assert_eq!(
+            content_viewer.process_events(KeyCode::Char('A')),
+            Message::Once(OnceOperation::OpenNotesModal)
+        );
+        assert_eq!(
+            content_viewer.process_events(KeyCode::Char('o')),
+            Message::Once(OnceOperation::OpenWithExternal {
+                file: "src/main.rs".to_string()
+            })
+        );
+        assert_eq!(
+            content_viewer.process_events(KeyCode::Char('g')),
+            Message::Once(OnceOperation::OpenInBrowser {
+                file: "src/main.rs".to_string(),
+                line: 5
+            })
+        );
+
+        content_viewer.mode = ShowMode::Diff;
+        assert_eq!(content_viewer.current_location(), None);
+    }
+
+    #[test]
+    fn test_blame_shading_alternates_per_hunk() {
+        use git2::Oid;
+        let oid1 = Oid::from_str("abc123456789abcd1234567890abcdef12345678").unwrap();
+        let oid2 = Oid::from_str("def456789012cdef1234567890abcdef12345678").unwrap();
+        let rows: Vec<CommitRow> = [oid1, oid1, oid2, oid1, oid1]
+            .iter()
+            .enumerate()
+            .map(|(index, oid)| CommitRow::new("a".to_string(), *oid, index + 1, "x".to_string()))
+            .collect();
+
+        assert_eq!(blame_shading(&rows), vec![false, false, true, false, false]);
+        assert!(blame_shading(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_content_viewer_draws_shaded_blame_hunks() {
+        let mock_repo = create_mock_repo();
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        content_viewer.mode = ShowMode::WithBlame;
+        content_viewer.content = "a | one\nb | two".to_string();
+        content_viewer.shaded_lines = vec![false, true];
+
+        let backend = TestBackend::new(20, 4);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|frame| content_viewer.draw(frame, Rect::new(0, 0, 20, 4)))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        assert_eq!(buffer.get(1, 1).bg, Color::Reset);
+        assert_eq!(buffer.get(1, 2).bg, Color::Indexed(236));
+    }
+
+    #[test]
+    fn test_show_mode_concat_dedupes_blame_commits() {
+        use git2::Oid;
+        let oid1 = Oid::from_str("abc123456789abcd1234567890abcdef12345678").unwrap();
+        let oid2 = Oid::from_str("def456789012cdef1234567890abcdef12345678").unwrap();
+        let rows: Vec<CommitRow> = [oid1, oid1, oid2]
+            .iter()
+            .enumerate()
+            .map(|(index, oid)| {
+                CommitRow::new("a".to_string(), *oid, index + 1, format!("l{}", index + 1))
+            })
+            .collect();
+        let blank = " ".repeat(40);
+
+        let result = ShowMode::WithBlame.concat(rows.clone(), &BTreeSet::new(), false);
+        assert_eq!(
+            result,
+            format!("{} | l1 \n{} | l2 \n{} | l3 ", oid1, blank, oid2)
+        );
+
+        let result = ShowMode::WithBlame.concat(rows, &BTreeSet::new(), true);
+        assert_eq!(
+            result,
+            format!("{} | l1 \n{} | l2 \n{} | l3 ", oid1, oid1, oid2)
+        );
+    }
+
+    #[test]
+    fn test_toggle_repeat_blame_key() {
+        let mock_repo = create_mock_repo();
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        assert!(!content_viewer.repeat_blame);
+        let message = content_viewer.process_events(KeyCode::Char('B'));
+        assert_eq!(message, Message::NoAction);
+        assert!(content_viewer.repeat_blame);
+    }
+
+    #[test]
+    fn test_content_viewer_highlights_current_commit_lines() {
+        let mock_repo = create_mock_repo();
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        content_viewer.mode = ShowMode::WithBlame;
+        let commit = "abc123456789abcd1234567890abcdef12345678";
+        content_viewer.content = format!("{} | one\n{} | two", commit, commit);
+        content_viewer.current_lines = vec![true, false];
+
+        let backend = TestBackend::new(60, 4);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|frame| content_viewer.draw(frame, Rect::new(0, 0, 60, 4)))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        assert_eq!(buffer.get(1, 1).fg, Color::Yellow);
+        assert_ne!(buffer.get(45, 1).fg, Color::Yellow);
+        assert_ne!(buffer.get(1, 2).fg, Color::Yellow);
+    }
+
+    #[test]
+    fn test_toggle_worktree_mode() {
+        let mock_repo = create_mock_repo();
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        content_viewer.title = "missing.rs".to_string();
+        assert!(content_viewer.current_location().is_some());
+
+        let message = content_viewer.process_events(KeyCode::Char('w'));
+        assert_eq!(message, Message::NoAction);
+        assert_eq!(content_viewer.source, Source::WorkingTree);
+        assert!(content_viewer
+            .content
+            .starts_with("Cannot read the working tree version"));
+        // Notes can only be attached to committed lines
+        assert_eq!(content_viewer.current_location(), None);
+
+        let backend = TestBackend::new(60, 4);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|frame| content_viewer.draw(frame, Rect::new(0, 0, 60, 4)))
+            .unwrap();
+        let top: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            // below the breadcrumb
+            .skip(60)
+            .take(60)
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(top.contains("[WORKING TREE] missing.rs"));
+
+        content_viewer.process_events(KeyCode::Char('i'));
+        assert_eq!(content_viewer.source, Source::Index);
+        assert!(content_viewer
+            .content
+            .starts_with("Cannot read the index version"));
+        content_viewer.process_events(KeyCode::Char('i'));
+        assert_eq!(content_viewer.source, Source::Commit);
+    }
+
+    #[test]
+    fn test_show_directory_summary() {
+        let mock_repo = create_mock_repo();
+        let mut content_viewer = ContentViewer::new(mock_repo);
+        content_viewer.handle_message(&Message::Once(OnceOperation::ShowDirectorySummary {
+            dir: "src".to_string(),
+        }));
+        assert!(content_viewer.directory);
+        assert_eq!(content_viewer.content, "src: 0 files changed, +0 -0\n");
+        assert_eq!(content_viewer.current_location(), None);
+
+        content_viewer.handle_message(&Message::Once(OnceOperation::ShowFile {
+            file: "not found".to_string(),
+        }));
+        assert!(!content_viewer.directory);
+    }
 }
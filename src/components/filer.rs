@@ -1,25 +1,112 @@
 use std::{
     cmp::min,
+    collections::{BTreeMap, HashMap, HashSet},
     sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
 use crossterm::event::KeyCode;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
+    style::Style,
     widgets::{Block, Borders, List, ListItem, ListState},
     Frame,
 };
 
-use crate::repository::RepositoryInfo;
+use crate::{
+    error::GviewError,
+    progress::{self, ProgressReporter},
+    repository::{Rename, RepositoryInfo, WalkJob},
+    state::ReviewState,
+    theme::Theme,
+};
 
 use super::{
     filter::FilterMode,
     operatable_components::{
-        Focus, Message, MultipleTimesOperation, OnceOperation, OperatableComponent,
+        panel_block, Focus, Message, MultipleTimesOperation, OnceOperation, OperatableComponent,
     },
+    prompt_modal::PromptKind,
 };
 
+// How long the selection has to rest before the file is shown.
+const PREVIEW_DEBOUNCE: Duration = Duration::from_millis(150);
+
+// A line of the tree mode: a directory, or a file of an expanded one.
+#[derive(Debug, Clone, PartialEq)]
+struct TreeRow {
+    path: String,
+    depth: usize,
+    directory: bool,
+}
+
+#[derive(Default)]
+struct TreeNode<'a> {
+    directories: BTreeMap<&'a str, TreeNode<'a>>,
+    files: Vec<&'a str>,
+}
+
+// The rows of `files` as a tree, directories first, with the contents of the `expanded`
+// directories only.
+fn tree_rows(files: &[String], expanded: &HashSet<String>) -> Vec<TreeRow> {
+    let mut rows = Vec::new();
+    push_rows(&visible_tree(files, expanded), "", 0, expanded, &mut rows);
+    rows
+}
+
+// The part of the tree of `files` that is shown: a collapsed directory gets its own node
+// but nothing below it, so deep trees cost only as much as what is open.
+fn visible_tree<'a>(files: &'a [String], expanded: &HashSet<String>) -> TreeNode<'a> {
+    let mut root = TreeNode::default();
+    for file in files {
+        let mut node = &mut root;
+        let mut start = 0;
+        let mut components = file.split('/').peekable();
+        while let Some(component) = components.next() {
+            if components.peek().is_none() {
+                node.files.push(file);
+                break;
+            }
+            node = node.directories.entry(component).or_default();
+            let end = start + component.len();
+            if !expanded.contains(&file[..end]) {
+                break;
+            }
+            start = end + 1;
+        }
+    }
+    root
+}
+
+fn push_rows(
+    node: &TreeNode,
+    parent: &str,
+    depth: usize,
+    expanded: &HashSet<String>,
+    rows: &mut Vec<TreeRow>,
+) {
+    for (name, child) in &node.directories {
+        let path = match parent {
+            "" => name.to_string(),
+            _ => format!("{}/{}", parent, name),
+        };
+        let open = expanded.contains(&path);
+        rows.push(TreeRow {
+            path: path.clone(),
+            depth,
+            directory: true,
+        });
+        if open {
+            push_rows(child, &path, depth + 1, expanded, rows);
+        }
+    }
+    rows.extend(node.files.iter().map(|file| TreeRow {
+        path: file.to_string(),
+        depth,
+        directory: false,
+    }));
+}
+
 pub struct Filer {
     focus: Focus,
     selected: usize,
@@ -30,10 +117,40 @@ pub struct Filer {
     repository: Arc<Mutex<RepositoryInfo>>,
     items: Vec<String>,
     results: Vec<String>,
+    review_base: Option<String>,
+    review_target: Option<String>,
+    review_state: ReviewState,
+    renames: HashMap<String, Rename>,
+    // the list is limited to the sparse checkout
+    sparse: bool,
+    // directory the session is scoped to
+    prefix: Option<String>,
+    // list only the files the viewed commit changed, with their status letters
+    commit_only: bool,
+    statuses: HashMap<String, char>,
+    // show the selected file once the selection rests, not only on Enter
+    live_preview: bool,
+    // when the selection last moved without the file being shown
+    moved_at: Option<Instant>,
+    // rows of the list last drawn, for paging
+    page_rows: usize,
+    // where the list was last drawn and its first visible item, to map clicks to items
+    list_area: Rect,
+    list_offset: usize,
+    // list the results as a tree of collapsible directories
+    tree: bool,
+    tree_rows: Vec<TreeRow>,
+    // directories opened in tree mode, kept while the filter and the commit change
+    expanded: HashSet<String>,
+    // files of the viewed tree being listed in the background, and the spinner frame
+    walk: Option<WalkJob>,
+    spinner: usize,
+    progress: ProgressReporter,
+    theme: Theme,
 }
 
 impl Filer {
-    pub fn new(repository: Arc<Mutex<RepositoryInfo>>) -> Self {
+    pub fn new(repository: Arc<Mutex<RepositoryInfo>>, progress: ProgressReporter) -> Self {
         Self {
             focus: Focus::Off,
             selected: 0,
@@ -44,6 +161,433 @@ impl Filer {
             repository,
             items: vec![],
             results: vec![],
+            review_base: None,
+            review_target: None,
+            review_state: ReviewState::default(),
+            renames: HashMap::new(),
+            sparse: false,
+            prefix: None,
+            commit_only: false,
+            statuses: HashMap::new(),
+            live_preview: true,
+            moved_at: None,
+            page_rows: 1,
+            list_area: Rect::default(),
+            list_offset: 0,
+            tree: false,
+            tree_rows: vec![],
+            expanded: HashSet::new(),
+            walk: None,
+            spinner: 0,
+            progress,
+            theme: Theme::default(),
+        }
+    }
+
+    pub fn set_live_preview(&mut self, live_preview: bool) {
+        self.live_preview = live_preview;
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index;
+        self.moved_at = Some(Instant::now());
+    }
+
+    // Moves the selection up to `rows` lines down or up, staying in the list.
+    fn move_selection(&mut self, down: bool, rows: usize) {
+        let last = self.row_count().saturating_sub(1);
+        let selected = match down {
+            true => self.selected.saturating_add(rows).min(last),
+            false => self.selected.saturating_sub(rows),
+        };
+        if selected != self.selected {
+            self.select(selected);
+        }
+    }
+
+    // Lines of the list: tree rows in tree mode, the results otherwise.
+    fn row_count(&self) -> usize {
+        match self.tree {
+            true => self.tree_rows.len(),
+            false => self.results.len(),
+        }
+    }
+
+    // The selected file, unless a directory of the tree is selected.
+    fn selected_file(&self) -> Option<&String> {
+        match self.tree {
+            true => self
+                .tree_rows
+                .get(self.selected)
+                .filter(|row| !row.directory)
+                .map(|row| &row.path),
+            false => self.results.get(self.selected),
+        }
+    }
+
+    /// The selected file, or directory of the tree, to inspect.
+    pub fn selected_path(&self) -> Option<&str> {
+        match self.tree {
+            true => self
+                .tree_rows
+                .get(self.selected)
+                .map(|row| row.path.as_str()),
+            false => self
+                .results
+                .get(self.selected)
+                .map(String::as_str)
+                .filter(|file| *file != "not found"),
+        }
+    }
+
+    fn show_selected(&self) -> Message {
+        match self.selected_file() {
+            Some(file) => Message::Once(OnceOperation::ShowFile {
+                file: file.to_owned(),
+            }),
+            None => Message::NoAction,
+        }
+    }
+
+    // Rebuilds the tree rows from the results, keeping the selection on the same path.
+    fn refresh_tree(&mut self) {
+        if !self.tree {
+            return;
+        }
+        let selected = self
+            .tree_rows
+            .get(self.selected)
+            .map(|row| row.path.clone());
+        self.tree_rows = tree_rows(&self.results, &self.expanded);
+        if let Some(index) =
+            selected.and_then(|path| self.tree_rows.iter().position(|row| row.path == path))
+        {
+            self.selected = index;
+        }
+        self.selected = min(self.selected, self.tree_rows.len().saturating_sub(1));
+    }
+
+    fn toggle_tree(&mut self) -> Message {
+        let selected = self.selected_file().cloned();
+        self.tree = !self.tree;
+        self.start_position = 0;
+        match self.tree {
+            true => {
+                // open the way to the selected file
+                if let Some(file) = &selected {
+                    self.expand_parents(file);
+                }
+                self.tree_rows = tree_rows(&self.results, &self.expanded);
+                self.selected = selected
+                    .and_then(|file| self.tree_rows.iter().position(|row| row.path == file))
+                    .unwrap_or(0);
+            }
+            false => {
+                self.tree_rows.clear();
+                self.selected = selected
+                    .and_then(|file| self.results.iter().position(|item| *item == file))
+                    .unwrap_or(0);
+            }
+        }
+        Message::NoAction
+    }
+
+    fn expand_parents(&mut self, file: &str) {
+        let mut end = 0;
+        while let Some(index) = file[end..].find('/') {
+            end += index;
+            self.expanded.insert(file[..end].to_owned());
+            end += 1;
+        }
+    }
+
+    // Opens or closes the selected directory of the tree.
+    fn set_expanded(&mut self, open: bool) {
+        let Some(row) = self
+            .tree_rows
+            .get(self.selected)
+            .filter(|row| row.directory)
+        else {
+            return;
+        };
+        let path = row.path.clone();
+        match open {
+            true => self.expanded.insert(path),
+            false => self.expanded.remove(&path),
+        };
+        self.refresh_tree();
+    }
+
+    // Closes the selected directory, or else goes to the directory holding the selection.
+    fn collapse(&mut self) {
+        let Some(row) = self.tree_rows.get(self.selected) else {
+            return;
+        };
+        if row.directory && self.expanded.contains(&row.path) {
+            return self.set_expanded(false);
+        }
+        let parent = row
+            .path
+            .rsplit_once('/')
+            .map(|(parent, _)| parent.to_owned());
+        if let Some(index) =
+            parent.and_then(|parent| self.tree_rows.iter().position(|row| row.path == parent))
+        {
+            self.select(index);
+        }
+    }
+
+    // Selects the item drawn on screen `row` and shows it.
+    pub fn click(&mut self, row: u16) -> Message {
+        if row < self.list_area.y || row >= self.list_area.bottom() {
+            return Message::NoAction;
+        }
+        let index = self.list_offset + (row - self.list_area.y) as usize;
+        if let Some(row) = self
+            .tree_rows
+            .get(index)
+            .filter(|row| self.tree && row.directory)
+        {
+            let open = !self.expanded.contains(&row.path);
+            self.selected = index;
+            self.set_expanded(open);
+            return Message::NoAction;
+        }
+        if index >= self.row_count() {
+            return Message::NoAction;
+        }
+        let previous = self.selected;
+        self.selected = index;
+        match self.selected_file() {
+            Some(file) if file != "not found" => {
+                self.select(index);
+                self.take_preview().unwrap_or(Message::NoAction)
+            }
+            _ => {
+                self.selected = previous;
+                Message::NoAction
+            }
+        }
+    }
+
+    // Shows the selected file once the selection has rested for the debounce; called every tick.
+    pub fn poll_preview(&mut self, now: Instant) -> Option<Message> {
+        match self.moved_at {
+            Some(moved_at) if self.live_preview && now - moved_at >= PREVIEW_DEBOUNCE => {
+                self.take_preview()
+            }
+            _ => None,
+        }
+    }
+
+    // Shows the selected file right away if it is not shown yet.
+    pub fn take_preview(&mut self) -> Option<Message> {
+        self.moved_at.take()?;
+        let file = self.selected_file()?;
+        Some(Message::Once(OnceOperation::ShowFile {
+            file: file.to_owned(),
+        }))
+    }
+
+    // With a base ref set only the files changed against it are listed.
+    fn load_items(&mut self) -> Vec<String> {
+        let mut binding = self.repository.lock().unwrap();
+        self.sparse = binding.sparse_only();
+        self.prefix = binding.prefix().map(str::to_owned);
+        self.review_base = binding.base_ref();
+        self.review_target = binding.compare_target();
+        let items = match &self.review_base {
+            Some(base) => {
+                let target = self
+                    .review_target
+                    .clone()
+                    .unwrap_or_else(|| binding.get_current_commit_id());
+                self.review_state = ReviewState::for_pair(base, &target);
+                self.renames = binding.renamed_files().unwrap_or_default();
+                self.statuses.clear();
+                binding.changed_files()
+            }
+            None if self.commit_only => {
+                self.renames = binding.renamed_files().unwrap_or_default();
+                self.statuses = binding.change_statuses().unwrap_or_default();
+                binding.changed_files()
+            }
+            None => {
+                self.renames.clear();
+                self.statuses.clear();
+                match binding.listed_files() {
+                    Some(files) => files,
+                    // listed once `poll_walk` picks the files up
+                    None => {
+                        let tree = binding.viewed_tree_id().ok();
+                        if self.walk.as_ref().map(WalkJob::tree) != tree {
+                            self.walk = binding.spawn_walk(self.progress.clone()).ok();
+                        }
+                        Ok(vec![])
+                    }
+                }
+            }
+        };
+        items.unwrap_or_default()
+    }
+
+    /// Picks up the files listed in the background and lists them again, showing the
+    /// selected one. Called once per tick, which also turns the spinner.
+    pub fn poll_walk(&mut self) -> Option<Message> {
+        self.spinner = self.spinner.wrapping_add(1);
+        if !self.walk.as_ref().is_some_and(WalkJob::is_finished) {
+            return None;
+        }
+        let job = self.walk.take()?;
+        let finished = match self.repository.lock() {
+            Ok(mut repository) => repository.finish_walk(job),
+            Err(_) => return Some(Message::Error(GviewError::Lock)),
+        };
+        Some(match finished {
+            Ok(()) => self.reload(),
+            Err(e) => Message::Error(GviewError::new("Cannot list the files", e)),
+        })
+    }
+
+    fn reload(&mut self) -> Message {
+        self.moved_at = None;
+        let items = self.load_items();
+        self.items.clone_from(&items);
+        self.results = self.mode.filter(items.clone(), &self.query);
+        // nothing is found before the files are listed
+        if self.results.is_empty() && self.walk.is_none() {
+            self.results.push("not found".to_owned())
+        }
+
+        if !self.tree {
+            self.selected = min(self.selected, self.results.len().saturating_sub(1));
+        }
+        self.refresh_tree();
+        self.start_position = 0;
+        self.show_selected()
+    }
+
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+
+    // Switches between the sparse checkout and every file, when there is a sparse checkout.
+    fn toggle_sparse(&mut self) -> Message {
+        {
+            let mut repository = match self.repository.lock() {
+                Ok(repo) => repo,
+                Err(_) => return Message::NoAction,
+            };
+            if !repository.has_sparse_checkout() {
+                return Message::NoAction;
+            }
+            let sparse_only = repository.sparse_only();
+            repository.set_sparse_only(!sparse_only);
+        }
+        self.reload()
+    }
+
+    // Switches between every file and the files changed in the viewed commit.
+    fn toggle_commit_only(&mut self) -> Message {
+        self.commit_only = !self.commit_only;
+        self.selected = 0;
+        self.reload()
+    }
+
+    // The status letter of a file changed in the commit, e.g. `M `, in that view only.
+    fn status(&self, item: &str) -> String {
+        self.statuses
+            .get(item)
+            .map(|status| format!("{} ", status))
+            .unwrap_or_default()
+    }
+
+    // Renamed files are shown as `old → new (similarity%)`.
+    fn label(&self, item: &str) -> String {
+        match self.renames.get(item) {
+            Some(rename) => format!(
+                "{}{} → {} ({}%)",
+                self.status(item),
+                rename.from,
+                item,
+                rename.similarity
+            ),
+            None => format!("{}{}", self.status(item), item),
+        }
+    }
+
+    // A tree row indented by its depth: a directory with whether it is open, or the name of a
+    // file with where it was renamed from.
+    fn tree_label(&self, row: &TreeRow) -> String {
+        let indent = "  ".repeat(row.depth);
+        let name = row.path.rsplit('/').next().unwrap_or(&row.path);
+        if row.directory {
+            let marker = if self.expanded.contains(&row.path) {
+                "▾"
+            } else {
+                "▸"
+            };
+            return format!("{}{} {}/", indent, marker, name);
+        }
+        match self.renames.get(&row.path) {
+            Some(rename) => format!(
+                "{}  {}{} ← {} ({}%)",
+                indent,
+                self.status(&row.path),
+                name,
+                rename.from,
+                rename.similarity
+            ),
+            None => format!("{}  {}{}", indent, self.status(&row.path), name),
+        }
+    }
+
+    // Summarizes the changes under the selected file's directory.
+    fn directory_summary(&self) -> Message {
+        if let Some(row) = self
+            .tree_rows
+            .get(self.selected)
+            .filter(|row| self.tree && row.directory)
+        {
+            return Message::Once(OnceOperation::ShowDirectorySummary {
+                dir: row.path.to_owned(),
+            });
+        }
+        match self.selected_file() {
+            Some(file) if file != "not found" => {
+                Message::Once(OnceOperation::ShowDirectorySummary {
+                    dir: file
+                        .rsplit_once('/')
+                        .map(|(dir, _)| dir.to_owned())
+                        .unwrap_or_default(),
+                })
+            }
+            _ => Message::NoAction,
+        }
+    }
+
+    // Opens the forge's diff of the selected file, in the changed-files view only.
+    fn open_diff(&self) -> Message {
+        match self.selected_file() {
+            Some(file) if self.review_base.is_some() && file != "not found" => {
+                Message::Once(OnceOperation::OpenDiffInBrowser {
+                    file: file.to_owned(),
+                })
+            }
+            _ => Message::NoAction,
+        }
+    }
+
+    fn toggle_reviewed(&mut self) -> Message {
+        let Some(file) = self.selected_file().cloned() else {
+            return Message::NoAction;
+        };
+        if self.review_base.is_none() || file == "not found" {
+            return Message::NoAction;
+        }
+        match self.review_state.toggle(&file) {
+            Ok(()) => Message::NoAction,
+            Err(e) => Message::Error(GviewError::new("Failed to save review state", e)),
         }
     }
 
@@ -52,42 +596,49 @@ impl Filer {
             Message::Once(OnceOperation::JumpToContentView) => self.focus = Focus::Off,
             Message::Once(OnceOperation::JumpToFiler) => self.focus = Focus::ON,
             Message::MultipleTimes(MultipleTimesOperation::SetUp { repository: _ }) => {
-                let mut binding = self.repository.lock().unwrap();
-                let items = binding.recursive_walk().unwrap();
+                let items = self.load_items();
                 self.items.clone_from(&items);
                 self.results = items;
-                return Message::Once(OnceOperation::ShowFile {
-                    file: self.results[0].to_owned(),
-                });
+                self.refresh_tree();
+                // a list still being walked shows its first file once `poll_walk` has it
+                return match self.results.first() {
+                    Some(file) => Message::Once(OnceOperation::ShowFile {
+                        file: file.to_owned(),
+                    }),
+                    None => Message::NoAction,
+                };
             }
-            Message::MultipleTimes(MultipleTimesOperation::ChangeShowCommit) => {
-                let mut binding = self.repository.lock().unwrap();
-                let items = binding.recursive_walk().unwrap();
-                self.items.clone_from(&items);
-                self.results = self.mode.filter(items.clone(), &self.query);
-                if self.results.is_empty() {
-                    self.results.push("not found".to_owned())
+            Message::MultipleTimes(
+                MultipleTimesOperation::ChangeShowCommit
+                | MultipleTimesOperation::ChangeBase
+                | MultipleTimesOperation::ChangeScope,
+            ) => return self.reload(),
+            Message::Once(OnceOperation::OpenLocation { file, .. }) => {
+                if self.tree && self.results.contains(file) {
+                    self.expand_parents(file);
+                    self.tree_rows = tree_rows(&self.results, &self.expanded);
+                    if let Some(index) = self.tree_rows.iter().position(|row| row.path == *file) {
+                        self.selected = index;
+                    }
+                } else if let Some(index) = self.results.iter().position(|item| item == file) {
+                    self.selected = index;
                 }
-
-                self.selected = min(self.selected, self.results.len().saturating_sub(1));
-                self.start_position = 0;
-                return Message::Once(OnceOperation::ShowFile {
-                    file: self.results[self.selected].to_owned(),
-                });
             }
             Message::MultipleTimes(MultipleTimesOperation::Filtering { query, mode }) => {
                 query.clone_into(&mut self.query);
                 self.mode = *mode;
+                self.moved_at = None;
                 self.results = self.mode.filter(self.items.clone(), query);
-                if self.results.is_empty() {
+                if self.results.is_empty() && self.walk.is_none() {
                     self.results.push("not found".to_owned())
                 }
 
-                self.selected = min(self.selected, self.results.len().saturating_sub(1));
+                if !self.tree {
+                    self.selected = min(self.selected, self.results.len().saturating_sub(1));
+                }
+                self.refresh_tree();
                 self.start_position = 0;
-                return Message::Once(OnceOperation::ShowFile {
-                    file: self.results[self.selected].to_owned(),
-                });
+                return self.show_selected();
             }
             _ => {}
         }
@@ -97,12 +648,44 @@ impl Filer {
 
 impl OperatableComponent for Filer {
     fn draw(&mut self, frame: &mut Frame, rect: Rect) {
-        let title = if self.results.len() == 1 && self.results[0] == "not found" {
-            "0 files".to_string()
+        let count = if self.results.len() == 1 && self.results[0] == "not found" {
+            0
         } else {
-            format!("{} files", self.results.len())
+            self.results.len()
+        };
+        let short = |id: &str| id[..std::cmp::min(8, id.len())].to_owned();
+        let title = match (&self.review_base, &self.review_target) {
+            (Some(base), Some(target)) => format!(
+                "{} changed files in {}..{} ({} reviewed)",
+                count,
+                short(base),
+                short(target),
+                self.review_state.count_reviewed(&self.results)
+            ),
+            (Some(base), None) => format!(
+                "{} changed files vs {} ({} reviewed)",
+                count,
+                short(base),
+                self.review_state.count_reviewed(&self.results)
+            ),
+            _ if self.commit_only => format!("{} changed files in this commit", count),
+            _ if self.sparse => format!("{} files (sparse)", count),
+            _ => format!("{} files", count),
         };
-        frame.render_widget(Block::default().title(title).borders(Borders::ALL), rect);
+        // the scope as a breadcrumb, e.g. `src › app · 12 files`
+        let title = match &self.prefix {
+            Some(prefix) => format!(
+                "{} · {}",
+                prefix.split('/').collect::<Vec<_>>().join(" › "),
+                title
+            ),
+            None => title,
+        };
+        let title = match &self.walk {
+            Some(_) => format!("{} {} listing", title, progress::spinner(self.spinner)),
+            None => title,
+        };
+        frame.render_widget(panel_block(&title, self.focus, &self.theme), rect);
 
         let chunk = Layout::default()
             .vertical_margin(1)
@@ -111,23 +694,42 @@ impl OperatableComponent for Filer {
             .constraints([Constraint::Percentage(100)].as_ref())
             .split(rect)[0];
 
-        let list_items: Vec<ListItem> = self
-            .results
+        // each line with the file it stands for, if it is not a directory
+        let lines: Vec<(String, Option<&String>)> = match self.tree {
+            true => self
+                .tree_rows
+                .iter()
+                .map(|row| {
+                    (
+                        self.tree_label(row),
+                        Some(&row.path).filter(|_| !row.directory),
+                    )
+                })
+                .collect(),
+            false => self
+                .results
+                .iter()
+                .map(|item| (self.label(item), Some(item)))
+                .collect(),
+        };
+        let list_items: Vec<ListItem> = lines
             .iter()
-            .map(|item| {
-                if self.start_position < item.len() {
-                    ListItem::new(item[self.start_position..].to_owned())
-                } else {
-                    ListItem::new("".to_owned())
+            .map(|(label, file)| {
+                let visible: String = label.chars().skip(self.start_position).collect();
+                match (&self.review_base, file) {
+                    (Some(_), Some(file)) if self.review_state.is_reviewed(file) => {
+                        ListItem::new(format!("[x] {}", visible))
+                    }
+                    (Some(_), Some(_)) => ListItem::new(format!("[ ] {}", visible)),
+                    _ => ListItem::new(visible.to_owned()),
                 }
             })
             .collect();
 
         // 3 is the size of ">> "
-        self.max_scroll = self
-            .results
+        self.max_scroll = lines
             .iter()
-            .map(String::len)
+            .map(|(label, _)| label.chars().count())
             .max()
             .unwrap_or(0)
             .saturating_sub(chunk.width as usize - 3);
@@ -136,12 +738,15 @@ impl OperatableComponent for Filer {
             .highlight_symbol(">> ")
             .style(match self.focus {
                 Focus::ON => Style::default(),
-                Focus::Off => Style::default().fg(Color::DarkGray),
+                Focus::Off => Style::default().fg(self.theme.unfocused),
             });
 
         let mut list_state = ListState::default();
         list_state.select(Some(self.selected));
+        self.page_rows = usize::from(chunk.height).max(1);
         frame.render_stateful_widget(list, chunk, &mut list_state);
+        self.list_area = chunk;
+        self.list_offset = list_state.offset();
     }
 
     fn process_focus(&mut self) {
@@ -152,32 +757,46 @@ impl OperatableComponent for Filer {
     }
     fn process_events(&mut self, code: KeyCode) -> Message {
         match code {
-            KeyCode::Up => {
-                if self.selected > 0 {
-                    self.selected -= 1;
-                    return Message::Once(OnceOperation::ShowFile {
-                        file: self.results[self.selected].clone(),
-                    });
-                }
-            }
-            KeyCode::Down => {
-                if self.selected < self.results.len().saturating_sub(1) {
-                    self.selected += 1;
-                    return Message::Once(OnceOperation::ShowFile {
-                        file: self.results[self.selected].clone(),
-                    });
-                }
-            }
-            KeyCode::Left => {
-                if self.start_position > 0 {
-                    self.start_position -= 1
-                }
+            KeyCode::Up => self.move_selection(false, 1),
+            KeyCode::Down => self.move_selection(true, 1),
+            KeyCode::PageUp => self.move_selection(false, self.page_rows),
+            KeyCode::PageDown => self.move_selection(true, self.page_rows),
+            KeyCode::Home => return self.process_chord(['g', 'g']),
+            KeyCode::End | KeyCode::Char('G') => self.move_selection(true, usize::MAX),
+            KeyCode::Left | KeyCode::Char('h') if self.tree => self.collapse(),
+            KeyCode::Right | KeyCode::Char('l') if self.tree => self.set_expanded(true),
+            KeyCode::Enter if self.selected_file().is_none() && self.tree => {
+                let open = self
+                    .tree_rows
+                    .get(self.selected)
+                    .is_some_and(|row| !self.expanded.contains(&row.path));
+                self.set_expanded(open);
             }
+            KeyCode::Char('t') => return self.toggle_tree(),
+            KeyCode::Left if self.start_position > 0 => self.start_position -= 1,
             KeyCode::Right => {
                 self.start_position += 1;
                 self.start_position = std::cmp::min(self.start_position, self.max_scroll)
             }
             KeyCode::Enter => return Message::Once(OnceOperation::JumpToContentView),
+            KeyCode::Char(' ') => return self.toggle_reviewed(),
+            KeyCode::Char('s') => return self.directory_summary(),
+            KeyCode::Char('y') => match self.selected_file() {
+                Some(file) if file != "not found" => {
+                    return Message::Once(OnceOperation::CopyToClipboard {
+                        text: file.to_owned(),
+                    })
+                }
+                _ => {}
+            },
+            KeyCode::Char('g') => return self.open_diff(),
+            KeyCode::Char('S') => return self.toggle_sparse(),
+            KeyCode::Char('c') => return self.toggle_commit_only(),
+            KeyCode::Char(':') => {
+                return Message::Once(OnceOperation::OpenPrompt {
+                    kind: PromptKind::Prefix,
+                })
+            }
             _ => {}
         }
         Message::NoAction
@@ -198,12 +817,39 @@ impl OperatableComponent for Filer {
             (_, new_message) => new_message,
         }
     }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    // a few files between the borders
+    fn min_height(&self) -> u16 {
+        2 + 3
+    }
+
+    fn chords(&self) -> &'static [[char; 2]] {
+        &[['g', 'g']]
+    }
+
+    // gg selects the first file
+    fn process_chord(&mut self, chord: [char; 2]) -> Message {
+        if chord == ['g', 'g'] {
+            self.select(0);
+            self.start_position = 0;
+        }
+        Message::NoAction
+    }
+
+    fn scroll_half_page(&mut self, down: bool) -> Message {
+        self.move_selection(down, (self.page_rows / 2).max(1));
+        Message::NoAction
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::repository::RepositoryInfo;
+    use crate::{progress::ProgressTracker, repository::RepositoryInfo};
     use insta::assert_snapshot;
     use ratatui::{backend::TestBackend, Terminal};
     use std::sync::{Arc, Mutex};
@@ -253,10 +899,46 @@ mod tests {
         Arc::new(Mutex::new(repo_info))
     }
 
+    #[test]
+    fn test_filer_lists_in_background() {
+        let mock_repo = create_mock_repo();
+        mock_repo.lock().unwrap().set_background(true);
+        let mut filer = Filer::new(Arc::clone(&mock_repo), ProgressTracker::new().reporter());
+        let message =
+            filer.handle_message(&Message::MultipleTimes(MultipleTimesOperation::SetUp {
+                repository: mock_repo,
+            }));
+        assert_eq!(message, Message::NoAction);
+        assert!(filer.results.is_empty());
+        let mut terminal = Terminal::new(TestBackend::new(40, 5)).unwrap();
+        terminal
+            .draw(|frame| filer.draw(frame, frame.size()))
+            .unwrap();
+        assert!(format!("{:?}", terminal.backend().buffer()).contains("listing"));
+
+        let message = loop {
+            if let Some(message) = filer.poll_walk() {
+                break message;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        };
+        assert_eq!(
+            message,
+            Message::Once(OnceOperation::ShowFile {
+                file: "not found".to_owned()
+            })
+        );
+        // listed once, the tree is not walked again
+        filer.handle_message(&Message::MultipleTimes(
+            MultipleTimesOperation::ChangeShowCommit,
+        ));
+        assert!(filer.walk.is_none());
+    }
+
     #[test]
     fn test_filer_navigation_up_down() {
         let mock_repo = create_mock_repo();
-        let mut filer = Filer::new(mock_repo);
+        let mut filer = Filer::new(mock_repo, ProgressTracker::new().reporter());
         filer.results = vec![
             "file1.txt".to_string(),
             "file2.txt".to_string(),
@@ -267,11 +949,13 @@ mod tests {
         // Test moving up
         let message = filer.process_events(KeyCode::Up);
         assert_eq!(filer.selected, 0);
-        if let Message::Once(OnceOperation::ShowFile { file }) = message {
+        assert_eq!(message, Message::NoAction);
+        if let Some(Message::Once(OnceOperation::ShowFile { file })) = filer.take_preview() {
             assert_eq!(file, "file1.txt");
         } else {
             panic!("Expected ShowFile message");
         }
+        assert_eq!(filer.take_preview(), None);
 
         // Test moving up at boundary
         let message = filer.process_events(KeyCode::Up);
@@ -281,17 +965,71 @@ mod tests {
         // Test moving down
         let message = filer.process_events(KeyCode::Down);
         assert_eq!(filer.selected, 1);
-        if let Message::Once(OnceOperation::ShowFile { file }) = message {
+        assert_eq!(message, Message::NoAction);
+        if let Some(Message::Once(OnceOperation::ShowFile { file })) = filer.take_preview() {
             assert_eq!(file, "file2.txt");
         } else {
             panic!("Expected ShowFile message");
         }
     }
 
+    #[test]
+    fn test_filer_live_preview_debounce() {
+        let mut filer = Filer::new(create_mock_repo(), ProgressTracker::new().reporter());
+        filer.results = vec!["file1.txt".to_string(), "file2.txt".to_string()];
+        filer.process_events(KeyCode::Down);
+
+        let now = Instant::now();
+        assert_eq!(filer.poll_preview(now), None);
+        assert_eq!(
+            filer.poll_preview(now + PREVIEW_DEBOUNCE),
+            Some(Message::Once(OnceOperation::ShowFile {
+                file: "file2.txt".to_owned()
+            }))
+        );
+        assert_eq!(filer.poll_preview(now + PREVIEW_DEBOUNCE), None);
+
+        // Without live preview the file waits for Enter
+        filer.set_live_preview(false);
+        filer.process_events(KeyCode::Up);
+        assert_eq!(filer.poll_preview(now + PREVIEW_DEBOUNCE * 2), None);
+        assert_eq!(
+            filer.take_preview(),
+            Some(Message::Once(OnceOperation::ShowFile {
+                file: "file1.txt".to_owned()
+            }))
+        );
+    }
+
+    #[test]
+    fn test_filer_page_keys() {
+        let mut filer = Filer::new(create_mock_repo(), ProgressTracker::new().reporter());
+        filer.results = (1..=30)
+            .map(|number| format!("file{}.txt", number))
+            .collect();
+        filer.page_rows = 10;
+
+        filer.process_events(KeyCode::PageDown);
+        assert_eq!(filer.selected, 10);
+        filer.scroll_half_page(true);
+        assert_eq!(filer.selected, 15);
+        filer.process_events(KeyCode::PageDown);
+        filer.process_events(KeyCode::PageDown);
+        assert_eq!(filer.selected, 29);
+        filer.scroll_half_page(false);
+        assert_eq!(filer.selected, 24);
+        filer.process_events(KeyCode::Home);
+        assert_eq!(filer.selected, 0);
+        filer.process_events(KeyCode::Char('G'));
+        assert_eq!(filer.selected, 29);
+        filer.process_events(KeyCode::PageUp);
+        assert_eq!(filer.selected, 19);
+    }
+
     #[test]
     fn test_filer_navigation_down_at_boundary() {
         let mock_repo = create_mock_repo();
-        let mut filer = Filer::new(mock_repo);
+        let mut filer = Filer::new(mock_repo, ProgressTracker::new().reporter());
         filer.results = vec!["file1.txt".to_string(), "file2.txt".to_string()];
         filer.selected = 1; // Last item
 
@@ -303,7 +1041,7 @@ mod tests {
     #[test]
     fn test_filer_horizontal_scrolling() {
         let mock_repo = create_mock_repo();
-        let mut filer = Filer::new(mock_repo);
+        let mut filer = Filer::new(mock_repo, ProgressTracker::new().reporter());
         filer.max_scroll = 10;
         filer.start_position = 5;
 
@@ -330,7 +1068,7 @@ mod tests {
     #[test]
     fn test_filer_enter_key() {
         let mock_repo = create_mock_repo();
-        let mut filer = Filer::new(mock_repo);
+        let mut filer = Filer::new(mock_repo, ProgressTracker::new().reporter());
 
         let message = filer.process_events(KeyCode::Enter);
         assert_eq!(message, Message::Once(OnceOperation::JumpToContentView));
@@ -339,7 +1077,7 @@ mod tests {
     #[test]
     fn test_filer_focus_toggle() {
         let mock_repo = create_mock_repo();
-        let mut filer = Filer::new(mock_repo);
+        let mut filer = Filer::new(mock_repo, ProgressTracker::new().reporter());
 
         assert_eq!(filer.focus, Focus::Off);
 
@@ -353,7 +1091,7 @@ mod tests {
     #[test]
     fn test_filer_draw_snapshot() {
         let mock_repo = create_mock_repo();
-        let mut filer = Filer::new(mock_repo);
+        let mut filer = Filer::new(mock_repo, ProgressTracker::new().reporter());
         filer.results = vec![
             "src/main.rs".to_string(),
             "src/lib.rs".to_string(),
@@ -379,7 +1117,7 @@ mod tests {
     #[test]
     fn test_filer_draw_no_files_found() {
         let mock_repo = create_mock_repo();
-        let mut filer = Filer::new(mock_repo);
+        let mut filer = Filer::new(mock_repo, ProgressTracker::new().reporter());
         filer.results = vec!["not found".to_string()];
         filer.selected = 0;
         filer.focus = Focus::ON;
@@ -401,7 +1139,7 @@ mod tests {
     #[test]
     fn test_filer_draw_unfocused() {
         let mock_repo = create_mock_repo();
-        let mut filer = Filer::new(mock_repo);
+        let mut filer = Filer::new(mock_repo, ProgressTracker::new().reporter());
         filer.results = vec![
             "src/main.rs".to_string(),
             "src/lib.rs".to_string(),
@@ -427,7 +1165,7 @@ mod tests {
     #[test]
     fn test_filer_draw_long_filenames() {
         let mock_repo = create_mock_repo();
-        let mut filer = Filer::new(mock_repo);
+        let mut filer = Filer::new(mock_repo, ProgressTracker::new().reporter());
         filer.results = vec![
             "src/very/long/path/to/some/deeply/nested/file.rs".to_string(),
             "another/extremely/long/path/with/many/directories/file.txt".to_string(),
@@ -450,4 +1188,252 @@ mod tests {
         let buffer = terminal.backend().buffer();
         assert_snapshot!(format!("{:?}", buffer));
     }
+
+    #[test]
+    fn test_filer_toggle_reviewed_only_in_review_mode() {
+        let mock_repo = create_mock_repo();
+        let mut filer = Filer::new(mock_repo, ProgressTracker::new().reporter());
+        filer.results = vec!["src/main.rs".to_string()];
+
+        // Outside review mode the key does nothing
+        assert_eq!(filer.process_events(KeyCode::Char(' ')), Message::NoAction);
+        assert!(!filer.review_state.is_reviewed("src/main.rs"));
+
+        filer.review_base = Some("0123456789abcdef".to_string());
+        assert_eq!(filer.process_events(KeyCode::Char(' ')), Message::NoAction);
+        assert!(filer.review_state.is_reviewed("src/main.rs"));
+
+        filer.process_events(KeyCode::Char(' '));
+        assert!(!filer.review_state.is_reviewed("src/main.rs"));
+    }
+
+    #[test]
+    fn test_filer_sparse_toggle_without_sparse_checkout() {
+        let mock_repo = create_mock_repo();
+        let mut filer = Filer::new(Arc::clone(&mock_repo), ProgressTracker::new().reporter());
+        assert_eq!(filer.process_events(KeyCode::Char('S')), Message::NoAction);
+        assert!(!filer.sparse);
+        assert!(!mock_repo.lock().unwrap().sparse_only());
+    }
+
+    #[test]
+    fn test_filer_directory_summary_key() {
+        let mock_repo = create_mock_repo();
+        let mut filer = Filer::new(mock_repo, ProgressTracker::new().reporter());
+        filer.results = vec![
+            "src/components/filer.rs".to_string(),
+            "README.md".to_string(),
+        ];
+
+        assert_eq!(
+            filer.process_events(KeyCode::Char('s')),
+            Message::Once(OnceOperation::ShowDirectorySummary {
+                dir: "src/components".to_string()
+            })
+        );
+        filer.selected = 1;
+        assert_eq!(
+            filer.process_events(KeyCode::Char('s')),
+            Message::Once(OnceOperation::ShowDirectorySummary {
+                dir: "".to_string()
+            })
+        );
+
+        filer.results = vec!["not found".to_string()];
+        filer.selected = 0;
+        assert_eq!(filer.process_events(KeyCode::Char('s')), Message::NoAction);
+    }
+
+    #[test]
+    fn test_filer_copy_path() {
+        let mut filer = Filer::new(create_mock_repo(), ProgressTracker::new().reporter());
+        filer.results = vec!["src/app.rs".to_string()];
+        assert_eq!(
+            filer.process_events(KeyCode::Char('y')),
+            Message::Once(OnceOperation::CopyToClipboard {
+                text: "src/app.rs".to_string()
+            })
+        );
+        filer.results = vec!["not found".to_string()];
+        assert_eq!(filer.process_events(KeyCode::Char('y')), Message::NoAction);
+    }
+
+    #[test]
+    fn test_filer_open_diff_only_in_review_mode() {
+        let mut filer = Filer::new(create_mock_repo(), ProgressTracker::new().reporter());
+        filer.results = vec!["src/app.rs".to_string()];
+        assert_eq!(filer.process_events(KeyCode::Char('g')), Message::NoAction);
+
+        filer.review_base = Some("0123456789abcdef".to_string());
+        assert_eq!(
+            filer.process_events(KeyCode::Char('g')),
+            Message::Once(OnceOperation::OpenDiffInBrowser {
+                file: "src/app.rs".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_filer_label_shows_renames() {
+        let mock_repo = create_mock_repo();
+        let mut filer = Filer::new(mock_repo, ProgressTracker::new().reporter());
+        filer.renames.insert(
+            "src/new.rs".to_string(),
+            Rename {
+                from: "src/old.rs".to_string(),
+                similarity: 87,
+            },
+        );
+        assert_eq!(filer.label("src/new.rs"), "src/old.rs → src/new.rs (87%)");
+        assert_eq!(filer.label("src/other.rs"), "src/other.rs");
+    }
+
+    #[test]
+    fn test_filer_toggle_reviewed_ignores_not_found() {
+        let mock_repo = create_mock_repo();
+        let mut filer = Filer::new(mock_repo, ProgressTracker::new().reporter());
+        filer.review_base = Some("0123456789abcdef".to_string());
+        filer.results = vec!["not found".to_string()];
+        filer.process_events(KeyCode::Char(' '));
+        assert!(!filer.review_state.is_reviewed("not found"));
+    }
+
+    #[test]
+    fn test_filer_draw_review_checklist() {
+        let mock_repo = create_mock_repo();
+        let mut filer = Filer::new(mock_repo, ProgressTracker::new().reporter());
+        filer.results = vec!["src/main.rs".to_string(), "README.md".to_string()];
+        filer.review_base = Some("0123456789abcdef".to_string());
+        filer.review_state.toggle("README.md").unwrap();
+        filer.focus = Focus::ON;
+
+        let backend = TestBackend::new(50, 6);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|frame| {
+                let rect = ratatui::layout::Rect::new(0, 0, 50, 6);
+                filer.draw(frame, rect);
+            })
+            .unwrap();
+
+        assert_snapshot!(format!("{:?}", terminal.backend().buffer()));
+    }
+
+    #[test]
+    fn test_visible_tree_stops_at_collapsed_directories() {
+        let files: Vec<String> = vec![
+            "a/b/c/d/e.rs".to_owned(),
+            "a/b/f.rs".to_owned(),
+            "g/h.rs".to_owned(),
+        ];
+        fn nodes(node: &TreeNode) -> usize {
+            let directories = node.directories.values();
+            node.files.len() + directories.map(|child| 1 + nodes(child)).sum::<usize>()
+        }
+        // only the top level while everything is collapsed
+        let root = visible_tree(&files, &HashSet::new());
+        assert_eq!(nodes(&root), 2);
+
+        let expanded: HashSet<String> = ["a".to_owned(), "a/b".to_owned()].into();
+        let root = visible_tree(&files, &expanded);
+        // a, a/b, a/b/c, a/b/f.rs and g
+        assert_eq!(nodes(&root), 5);
+        assert_eq!(
+            tree_rows(&files, &expanded)
+                .into_iter()
+                .map(|row| (row.path, row.depth))
+                .collect::<Vec<_>>(),
+            vec![
+                ("a".to_owned(), 0),
+                ("a/b".to_owned(), 1),
+                ("a/b/c".to_owned(), 2),
+                ("a/b/f.rs".to_owned(), 2),
+                ("g".to_owned(), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filer_tree_mode() {
+        let mut filer = Filer::new(create_mock_repo(), ProgressTracker::new().reporter());
+        filer.items = vec![
+            "README.md".to_string(),
+            "src/app.rs".to_string(),
+            "src/components/filer.rs".to_string(),
+            "src/main.rs".to_string(),
+        ];
+        filer.results = filer.items.clone();
+        filer.focus = Focus::ON;
+
+        // the tree opens on the way to the selection
+        filer.selected = 2;
+        filer.process_events(KeyCode::Char('t'));
+        let paths = |filer: &Filer| {
+            filer
+                .tree_rows
+                .iter()
+                .map(|row| row.path.to_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            paths(&filer),
+            vec![
+                "src",
+                "src/components",
+                "src/components/filer.rs",
+                "src/app.rs",
+                "src/main.rs",
+                "README.md"
+            ]
+        );
+        assert_eq!(
+            filer.selected_file().map(String::as_str),
+            Some("src/components/filer.rs")
+        );
+
+        // h goes to the directory, then closes it; directories are not shown as files
+        filer.process_events(KeyCode::Char('h'));
+        assert_eq!(filer.selected, 1);
+        assert_eq!(filer.take_preview(), None);
+        filer.process_events(KeyCode::Char('h'));
+        assert_eq!(filer.tree_rows.len(), 5);
+        filer.process_events(KeyCode::Enter);
+        assert_eq!(filer.tree_rows.len(), 6);
+        filer.process_events(KeyCode::Left);
+
+        // the open directories are kept while filtering
+        let message =
+            filer.handle_message(&Message::MultipleTimes(MultipleTimesOperation::Filtering {
+                query: "main".to_owned(),
+                mode: FilterMode::PartialMatch,
+            }));
+        assert_eq!(paths(&filer), vec!["src", "src/main.rs"]);
+        assert_eq!(
+            message,
+            Message::Once(OnceOperation::ShowFile {
+                file: "src/main.rs".to_owned()
+            })
+        );
+        filer.handle_message(&Message::MultipleTimes(MultipleTimesOperation::Filtering {
+            query: "".to_owned(),
+            mode: FilterMode::PartialMatch,
+        }));
+        assert_eq!(filer.tree_rows.len(), 5);
+
+        let backend = TestBackend::new(40, 8);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|frame| filer.draw(frame, frame.size()))
+            .unwrap();
+        assert_snapshot!(format!("{:?}", terminal.backend().buffer()));
+
+        // back to the flat list on the same file
+        filer.process_events(KeyCode::Up);
+        filer.process_events(KeyCode::Char('t'));
+        assert_eq!(
+            filer.selected_file().map(String::as_str),
+            Some("src/app.rs")
+        );
+        assert_eq!(filer.selected, 1);
+    }
 }
@@ -2,14 +2,18 @@ use crossterm::event::KeyCode;
 use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
-    widgets::{Block, Borders, Paragraph},
+    style::Style,
+    widgets::Paragraph,
     Frame,
 };
 use regex::Regex;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::theme::Theme;
 
 use super::operatable_components::{
-    Focus, Message, MultipleTimesOperation, OnceOperation, OperatableComponent,
+    panel_block, Focus, Message, MultipleTimesOperation, OnceOperation, OperatableComponent,
 };
 
 #[allow(clippy::enum_variant_names)]
@@ -21,7 +25,7 @@ pub enum FilterMode {
 }
 
 impl FilterMode {
-    fn next(self) -> FilterMode {
+    pub fn next(self) -> FilterMode {
         match self {
             FilterMode::PartialMatch => FilterMode::FuzzyMatch,
             FilterMode::FuzzyMatch => FilterMode::RegularMatch,
@@ -37,46 +41,52 @@ impl FilterMode {
         }
     }
 
-    fn appearance(self) -> (String, Style) {
-        match self {
-            FilterMode::PartialMatch => {
-                ("Partial Match".to_owned(), Style::default().fg(Color::Blue))
-            }
-            FilterMode::FuzzyMatch => ("Fuzzy Search".to_owned(), Style::default().fg(Color::Red)),
-            FilterMode::RegularMatch => (
-                "Regular Search".to_owned(),
-                Style::default().fg(Color::Green),
-            ),
+    pub fn appearance(self, theme: &Theme) -> (String, Style) {
+        let (title, color) = match self {
+            FilterMode::PartialMatch => ("Partial Match", theme.partial_match),
+            FilterMode::FuzzyMatch => ("Fuzzy Search", theme.fuzzy_match),
+            FilterMode::RegularMatch => ("Regular Search", theme.regular_match),
+        };
+        (title.to_owned(), Style::default().fg(color))
+    }
+
+    pub fn filter(self, items: Vec<String>, query: &str) -> Vec<String> {
+        if self == FilterMode::RegularMatch && Regex::new(query).is_err() {
+            // TODO: popup regular expression error
+            return vec!["error".to_owned()];
         }
+        self.filter_by(items, query, |item| item.as_str())
     }
 
-    pub fn filter(self, items: Vec<String>, query: &String) -> Vec<String> {
+    // Keeps the items whose `key` matches `query`, best first for fuzzy matching. Nothing
+    // matches an invalid regular expression.
+    pub fn filter_by<T>(self, items: Vec<T>, query: &str, key: impl Fn(&T) -> &str) -> Vec<T> {
         match self {
             FilterMode::PartialMatch => items
                 .into_iter()
-                .filter(|item| query.is_empty() || item.contains(query))
+                .filter(|item| query.is_empty() || key(item).contains(query))
                 .collect(),
             FilterMode::FuzzyMatch => {
                 let matcher = SkimMatcherV2::default();
                 let mut results = items
                     .into_iter()
-                    .filter_map(|item| matcher.fuzzy_match(&item, query).map(|score| (item, score)))
+                    .filter_map(|item| {
+                        matcher
+                            .fuzzy_match(key(&item), query)
+                            .map(|score| (item, score))
+                    })
                     .collect::<Vec<_>>();
-                results.sort_by(|item, other| other.1.cmp(&item.1));
+                results.sort_by_key(|item| std::cmp::Reverse(item.1));
                 results
                     .into_iter()
                     .map(|(item, _)| item)
                     .collect::<Vec<_>>()
             }
-            FilterMode::RegularMatch => {
-                if let Ok(re) = Regex::new(query) {
-                    // TODO: check the regular expression behavior
-                    items.into_iter().filter(|s| re.is_match(s)).collect()
-                } else {
-                    // TODO: popup regular expression error
-                    vec!["error".to_owned()]
-                }
-            }
+            FilterMode::RegularMatch => match Regex::new(query) {
+                // TODO: check the regular expression behavior
+                Ok(re) => items.into_iter().filter(|s| re.is_match(key(s))).collect(),
+                Err(_) => Vec::new(),
+            },
         }
     }
 }
@@ -86,6 +96,7 @@ pub struct Filter {
     mode: FilterMode,
     input: String,
     character_index: usize,
+    theme: Theme,
 }
 
 impl Filter {
@@ -95,6 +106,7 @@ impl Filter {
             mode: FilterMode::PartialMatch,
             input: "".to_owned(),
             character_index: 0,
+            theme: Theme::default(),
         }
     }
 
@@ -104,14 +116,23 @@ impl Filter {
         self.move_cursor_right();
     }
 
+    // The cursor moves by grapheme, so composed input such as `か` + `゙` is stepped over whole.
     fn move_cursor_left(&mut self) {
-        let cursor_moved_left = self.character_index.saturating_sub(1);
-        self.character_index = self.clamp_cursor(cursor_moved_left);
+        let index = self.byte_index();
+        let previous = self.input[..index]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(start, _)| start);
+        self.character_index = self.clamp_cursor(self.input[..previous].chars().count());
     }
 
     fn move_cursor_right(&mut self) {
-        let cursor_moved_right = self.character_index.saturating_add(1);
-        self.character_index = self.clamp_cursor(cursor_moved_right);
+        let index = self.byte_index();
+        let next = self.input[index..]
+            .graphemes(true)
+            .next()
+            .map_or(index, |grapheme| index + grapheme.len());
+        self.character_index = self.clamp_cursor(self.input[..next].chars().count());
     }
 
     fn byte_index(&self) -> usize {
@@ -126,26 +147,61 @@ impl Filter {
         new_cursor_pos.clamp(0, self.input.chars().count())
     }
 
+    // Deletes the grapheme before the cursor, with any combining marks it carries.
     fn delete_char(&mut self) {
         let is_not_cursor_leftmost = self.character_index != 0;
         if is_not_cursor_leftmost {
-            // Method "remove" is not used on the saved text for deleting the selected char.
-            // Reason: Using remove on String works on bytes instead of the chars.
-            // Using remove would require special care because of char boundaries.
-
-            let current_index = self.character_index;
-            let from_left_to_current_index = current_index - 1;
+            let end = self.byte_index();
+            self.move_cursor_left();
+            let start = self.byte_index();
+            self.input.replace_range(start..end, "");
+        }
+    }
 
-            // Getting all characters before the selected character.
-            let before_char_to_delete = self.input.chars().take(from_left_to_current_index);
-            // Getting all characters after selected character.
-            let after_char_to_delete = self.input.chars().skip(current_index);
+    // Input from where it has to start for the cursor to fit in `width` columns, and the
+    // column of the cursor in it.
+    fn visible_input(&self, width: u16) -> (&str, u16) {
+        let index = self.byte_index();
+        let mut start = 0;
+        for (offset, grapheme) in self.input[..index].grapheme_indices(true) {
+            if self.input[start..index].width() < width as usize {
+                break;
+            }
+            start = offset + grapheme.len();
+        }
+        let visible = &self.input[start..];
+        (visible, self.input[start..index].width() as u16)
+    }
 
-            // Put all characters together except the selected one.
-            // By leaving the selected one out, it is forgotten and therefore deleted.
-            self.input = before_char_to_delete.chain(after_char_to_delete).collect();
-            self.move_cursor_left();
+    // Completes the input like a shell: up to the end of the next path component shared by
+    // every item it is a prefix of. None when there is nothing to add.
+    pub fn complete(&mut self, items: &[String]) -> Option<Message> {
+        let mut completions = items
+            .iter()
+            .filter_map(|item| item.strip_prefix(self.input.as_str()))
+            .map(|rest| match rest.find('/') {
+                Some(index) => &rest[..=index],
+                None => rest,
+            });
+        let first = completions.next()?;
+        let common = completions.fold(first, |common, completion| {
+            let length = common
+                .chars()
+                .zip(completion.chars())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a.len_utf8())
+                .sum();
+            &common[..length]
+        });
+        if common.is_empty() {
+            return None;
         }
+        self.input.push_str(common);
+        self.character_index = self.input.chars().count();
+        Some(Message::MultipleTimes(MultipleTimesOperation::Filtering {
+            query: self.input.to_owned(),
+            mode: self.mode,
+        }))
     }
 
     fn _handle_message(&mut self, message: &Message) -> Message {
@@ -163,15 +219,13 @@ impl Filter {
 
 impl OperatableComponent for Filter {
     fn draw(&mut self, frame: &mut Frame, rect: Rect) {
-        let (title, border_style) = self.mode.appearance();
+        let (title, border_style) = self.mode.appearance(&self.theme);
+        let block = panel_block(&title, self.focus, &self.theme);
         frame.render_widget(
-            Block::default()
-                .title(title)
-                .borders(Borders::ALL)
-                .border_style(match self.focus {
-                    Focus::Off => Style::default().fg(Color::DarkGray),
-                    Focus::ON => border_style,
-                }),
+            match self.focus {
+                Focus::Off => block,
+                Focus::ON => block.border_style(border_style),
+            },
             rect,
         );
 
@@ -182,21 +236,15 @@ impl OperatableComponent for Filter {
             .constraints([Constraint::Percentage(100)].as_ref())
             .split(rect)[0];
 
-        let width = chunk.width;
-        let input = self.input.to_owned();
-        let overflow = input.len().saturating_sub(width as usize);
-        if overflow > 0 {
-            input[overflow..].clone_into(&mut input.to_owned())
-        }
-
-        let filter_paragraph = Paragraph::new(input).style(match self.focus {
+        // wide characters take two columns, so the window is measured in display width
+        let (input, cursor) = self.visible_input(chunk.width);
+        let filter_paragraph = Paragraph::new(input.to_owned()).style(match self.focus {
             Focus::ON => Style::default(),
-            Focus::Off => Style::default().fg(Color::DarkGray),
+            Focus::Off => Style::default().fg(self.theme.unfocused),
         });
         frame.render_widget(filter_paragraph, chunk);
 
-        let cursor_position = std::cmp::min(chunk.x + self.character_index as u16, chunk.width);
-        frame.set_cursor(cursor_position, chunk.y);
+        frame.set_cursor(chunk.x + cursor, chunk.y);
     }
 
     fn process_focus(&mut self) {
@@ -229,6 +277,8 @@ impl OperatableComponent for Filter {
                     mode: self.mode,
                 });
             }
+            KeyCode::Left => self.move_cursor_left(),
+            KeyCode::Right => self.move_cursor_right(),
             KeyCode::Enter => return Message::Once(OnceOperation::JumpToFiler),
             KeyCode::Backspace => {
                 self.delete_char();
@@ -257,6 +307,10 @@ impl OperatableComponent for Filter {
             (_, new_message) => new_message,
         }
     }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
 }
 
 #[cfg(test)]
@@ -276,6 +330,27 @@ mod tests {
         assert_eq!(FilterMode::RegularMatch.prev(), FilterMode::FuzzyMatch);
     }
 
+    #[test]
+    fn test_filter_mode_filter_by_key() {
+        let items = vec![(1, "fix parser"), (2, "add docs"), (3, "fix docs")];
+        let ids = |items: Vec<(i32, &str)>| items.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        fn key<'a>(item: &'a (i32, &str)) -> &'a str {
+            item.1
+        }
+        assert_eq!(
+            ids(FilterMode::PartialMatch.filter_by(items.clone(), "docs", key)),
+            vec![2, 3]
+        );
+        assert_eq!(
+            ids(FilterMode::RegularMatch.filter_by(items.clone(), "^fix", key)),
+            vec![1, 3]
+        );
+        // an invalid expression matches nothing rather than an "error" item
+        assert!(FilterMode::RegularMatch
+            .filter_by(items, "(", key)
+            .is_empty());
+    }
+
     #[test]
     fn test_filter_mode_partial_match() {
         let items = vec!["hello".to_string(), "world".to_string(), "help".to_string()];
@@ -380,6 +455,94 @@ mod tests {
         assert_eq!(filter.character_index, 0);
     }
 
+    #[test]
+    fn test_filter_cjk_editing() {
+        let mut filter = Filter::new();
+        for char in "日本語".chars() {
+            filter.enter_char(char);
+        }
+        filter.process_events(KeyCode::Left);
+        filter.enter_char('の');
+        assert_eq!(filter.input, "日本の語");
+        assert_eq!(filter.character_index, 3);
+
+        // a kana composed with a combining voiced mark is one grapheme
+        filter.input = "か\u{3099}な".to_owned();
+        filter.character_index = 3;
+        filter.process_events(KeyCode::Left);
+        assert_eq!(filter.character_index, 2);
+        filter.process_events(KeyCode::Left);
+        assert_eq!(filter.character_index, 0);
+        filter.process_events(KeyCode::Right);
+        assert_eq!(filter.character_index, 2);
+        filter.delete_char();
+        assert_eq!(filter.input, "な");
+        assert_eq!(filter.character_index, 0);
+    }
+
+    #[test]
+    fn test_filter_draw_wide_input() {
+        let mut filter = Filter::new();
+        for char in "日本語のテキスト".chars() {
+            filter.enter_char(char);
+        }
+        let backend = TestBackend::new(12, 3);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|frame| filter.draw(frame, Rect::new(0, 0, 12, 3)))
+            .unwrap();
+        let line: String = terminal.backend().buffer().content()[12..24]
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        // the start scrolls out so that the cursor stays inside the box
+        // wide characters leave an empty cell behind them
+        assert_eq!(line, "│テ キ ス ト   │");
+        assert_eq!(terminal.get_cursor().unwrap(), (9, 1));
+
+        filter.character_index = 1;
+        terminal
+            .draw(|frame| filter.draw(frame, Rect::new(0, 0, 12, 3)))
+            .unwrap();
+        assert_eq!(terminal.get_cursor().unwrap(), (3, 1));
+    }
+
+    #[test]
+    fn test_filter_complete() {
+        let items: Vec<String> = [
+            "src/components/filer.rs",
+            "src/components/filter.rs",
+            "src/compat.rs",
+            "README.md",
+        ]
+        .iter()
+        .map(|item| item.to_string())
+        .collect();
+        let mut filter = Filter::new();
+        for char in "src/c".chars() {
+            filter.enter_char(char);
+        }
+
+        assert_eq!(
+            filter.complete(&items),
+            Some(Message::MultipleTimes(MultipleTimesOperation::Filtering {
+                query: "src/comp".to_owned(),
+                mode: FilterMode::PartialMatch,
+            }))
+        );
+        assert_eq!(filter.complete(&items), None);
+
+        filter.enter_char('o');
+        filter.complete(&items);
+        assert_eq!(filter.input, "src/components/");
+        assert_eq!(filter.character_index, 15);
+        filter.complete(&items);
+        assert_eq!(filter.input, "src/components/fil");
+
+        filter.input = "docs/".to_owned();
+        assert_eq!(filter.complete(&items), None);
+    }
+
     #[test]
     fn test_filter_draw_snapshot() {
         let mut filter = Filter::new();
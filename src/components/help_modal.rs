@@ -1,18 +1,37 @@
 use crossterm::event::KeyCode;
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    layout::Rect,
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, Clear, List, ListItem},
     Frame,
 };
 
-use super::operatable_components::{Focus, Message, OnceOperation, OperatableComponent};
+use crate::theme::Theme;
+
+use super::{
+    centered_rect,
+    operatable_components::{Focus, Message, OnceOperation, OperatableComponent},
+};
+
+// A line of the help: a section heading, a key with what it does, a blank line or the hint on
+// using the modal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum HelpLine {
+    Heading(&'static str),
+    Key(&'static str, &'static str),
+    Blank,
+    Hint,
+}
 
 pub struct HelpModal {
     visible: bool,
     focus: Focus,
     scroll_offset: usize,
+    // keys are limited to those whose key or description contains the query
+    query: String,
+    editing: bool,
+    theme: Theme,
 }
 
 impl HelpModal {
@@ -21,108 +40,220 @@ impl HelpModal {
             visible: false,
             focus: Focus::Off,
             scroll_offset: 0,
+            query: String::new(),
+            editing: false,
+            theme: Theme::default(),
         }
     }
 
-    pub fn is_open(&self) -> bool {
-        self.visible
+    pub fn is_editing(&self) -> bool {
+        self.visible && self.editing
     }
 
-    fn get_help_content() -> Vec<ListItem<'static>> {
+    fn get_help_content(&self) -> Vec<HelpLine> {
         vec![
-            ListItem::new(Line::from(vec![Span::styled(
-                "Global Keys:",
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD),
-            )])),
-            ListItem::new(Line::from("")),
-            Self::create_key_line("Tab", "Switch focus between panels"),
-            Self::create_key_line("Ctrl+C", "Exit gview"),
-            Self::create_key_line("<", "Decrease left panel width"),
-            Self::create_key_line(">", "Increase left panel width"),
-            Self::create_key_line("?", "Show this help modal"),
-            Self::create_key_line("ESC", "Close help modal"),
-            ListItem::new(Line::from("")),
-            ListItem::new(Line::from(vec![Span::styled(
-                "Filter Panel:",
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD),
-            )])),
-            ListItem::new(Line::from("")),
-            Self::create_key_line("Enter", "Apply filter"),
-            Self::create_key_line("Ctrl+A", "Select all text"),
-            ListItem::new(Line::from("")),
-            ListItem::new(Line::from(vec![Span::styled(
-                "File List Panel:",
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD),
-            )])),
-            ListItem::new(Line::from("")),
-            Self::create_key_line("↑/↓, j/k", "Navigate files"),
-            Self::create_key_line("Enter", "Select file"),
-            ListItem::new(Line::from("")),
-            ListItem::new(Line::from(vec![Span::styled(
-                "Commit Panel:",
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD),
-            )])),
-            ListItem::new(Line::from("")),
-            Self::create_key_line("o", "Open commit modal"),
-            ListItem::new(Line::from("")),
-            ListItem::new(Line::from(vec![Span::styled(
-                "Content Viewer:",
+            HelpLine::Heading("Global Keys:"),
+            HelpLine::Blank,
+            HelpLine::Key("Tab", "Switch focus between panels"),
+            HelpLine::Key("Ctrl+C", "Exit gview"),
+            HelpLine::Key("Ctrl+P", "Search files and lines"),
+            HelpLine::Key("Ctrl+^", "Switch to the previous commit"),
+            HelpLine::Key("Ctrl+S", "Save the screen to a file"),
+            HelpLine::Key("Ctrl+O", "Inspect the raw git object"),
+            HelpLine::Key("<", "Decrease left panel width"),
+            HelpLine::Key(">", "Increase left panel width"),
+            HelpLine::Key("?", "Show this help modal"),
+            HelpLine::Key("/ (in help)", "Search the keys as you type"),
+            HelpLine::Key("ESC", "Close help modal, or dismiss the shown error"),
+            HelpLine::Blank,
+            HelpLine::Heading("Filter Panel:"),
+            HelpLine::Blank,
+            HelpLine::Key("Enter", "Apply filter"),
+            HelpLine::Key("Ctrl+A", "Select all text"),
+            HelpLine::Key("Tab", "Complete the next path component"),
+            HelpLine::Key("←/→", "Move the cursor"),
+            HelpLine::Blank,
+            HelpLine::Heading("File List Panel:"),
+            HelpLine::Blank,
+            HelpLine::Key("↑/↓, j/k", "Navigate files"),
+            HelpLine::Key("Enter", "Select file"),
+            HelpLine::Key("Space", "Toggle reviewed mark (review mode)"),
+            HelpLine::Key("s", "Show diffstat of the file's directory"),
+            HelpLine::Key("S", "Toggle sparse checkout / all files"),
+            HelpLine::Key("t", "Toggle the directory tree"),
+            HelpLine::Key("c", "Toggle the files changed in this commit"),
+            HelpLine::Key("h/l, Enter", "Collapse/expand a directory (tree)"),
+            HelpLine::Key("y", "Copy the file path"),
+            HelpLine::Key("g", "Open the file's diff on the forge (review mode)"),
+            HelpLine::Key("gg", "Select the first file"),
+            HelpLine::Key("PgUp/PgDn", "Move a page (Ctrl+U/D: half a page)"),
+            HelpLine::Key("Home, G/End", "Select the first/last file"),
+            HelpLine::Key(":", "Scope the session to a directory"),
+            HelpLine::Blank,
+            HelpLine::Heading("Commit Panel:"),
+            HelpLine::Blank,
+            HelpLine::Key("o", "Open commit modal"),
+            HelpLine::Key("o (in modal)", "Jump to the picked/reverted commit"),
+            HelpLine::Key("t (in modal)", "Filter commits by trailer"),
+            HelpLine::Key("/ (in modal)", "Search commits by message/author"),
+            HelpLine::Key("d (in modal)", "Limit commits to a date range"),
+            HelpLine::Key("PgUp/PgDn", "Page through the modal (Ctrl+U/D: half)"),
+            HelpLine::Key("gg/G", "Newest/oldest commit in the modal"),
+            HelpLine::Key("j/k", "Scroll a long commit message"),
+            HelpLine::Key("←/→, Enter", "Select and go to a parent/child"),
+            HelpLine::Key("b", "Set base ref for review diff"),
+            HelpLine::Key("c", "Compare two refs (A..B)"),
+            HelpLine::Key("i", "Is this commit in a ref?"),
+            HelpLine::Key("m", "Merge-base with a ref"),
+            HelpLine::Key("C", "List the CI checks of the commit"),
+            HelpLine::Key("d", "Show/hide the commit details"),
+            HelpLine::Key("L", "Sum up the files by language"),
+            HelpLine::Key("I", "Open the next issue referenced in the message"),
+            HelpLine::Key("e", "Export review summary"),
+            HelpLine::Key("y", "Copy the commit hash"),
+            HelpLine::Blank,
+            HelpLine::Heading("Content Viewer:"),
+            HelpLine::Blank,
+            HelpLine::Key("↑/↓, j/k", "Scroll content vertically"),
+            HelpLine::Key("←/→, h/l", "Scroll content horizontally"),
+            HelpLine::Key("b", "Toggle blame view"),
+            HelpLine::Key("B", "Toggle repeated blame commits"),
+            HelpLine::Key("n", "Toggle line numbers"),
+            HelpLine::Key("/", "Search in the file"),
+            HelpLine::Key("n/N", "Next/previous match while searching"),
+            HelpLine::Key("d", "Toggle diff view"),
+            HelpLine::Key("D", "Toggle side-by-side diff view"),
+            HelpLine::Key("w", "Toggle working tree version"),
+            HelpLine::Key("i", "Toggle index (staged) version"),
+            HelpLine::Key("a", "Add note at the top line"),
+            HelpLine::Key("A", "Open notes panel"),
+            HelpLine::Key("R", "Choose the remote for browser links"),
+            HelpLine::Key("p", "Pin the top line across commits"),
+            HelpLine::Key("[ / ]", "Select a directory in the breadcrumb"),
+            HelpLine::Key("Enter", "Scope the file list to that directory"),
+            HelpLine::Key("y", "Copy the file path"),
+            HelpLine::Key("V", "Select lines from the top line"),
+            HelpLine::Key("Y", "Copy the top or selected lines"),
+            HelpLine::Key("v", "Print the content for native selection"),
+            HelpLine::Key("m", "Highlight my lines and commits"),
+            HelpLine::Key("Ctrl+]", "Jump to definition of the word"),
+            HelpLine::Key("Ctrl+T", "Jump back"),
+            HelpLine::Key("*", "Search the word across the commit"),
+            HelpLine::Key("o", "Open with configured external viewer"),
+            HelpLine::Key("g", "Go to GitHub (if available)"),
+            HelpLine::Key("gg", "Go to the first line"),
+            HelpLine::Key("PgUp/PgDn", "Scroll a page (Ctrl+U/D: half a page)"),
+            HelpLine::Key("Home, G/End", "Go to the first/last line"),
+            HelpLine::Key(":", "Go to a line number"),
+            HelpLine::Key("F", "Fetch a missing blob (partial clone)"),
+            HelpLine::Key(
+                "zz/zt/zb",
+                "Keep the current line centered/at top/at bottom",
+            ),
+            HelpLine::Blank,
+            HelpLine::Blank,
+            HelpLine::Hint,
+        ]
+    }
+
+    // The keys matching the query under their section headings, or all of the help.
+    fn visible_lines(&self) -> Vec<HelpLine> {
+        let lines = self.get_help_content();
+        if self.query.is_empty() {
+            return lines;
+        }
+        let query = self.query.to_ascii_lowercase();
+        let matches = |text: &str| text.to_ascii_lowercase().contains(&query);
+        let mut visible = vec![];
+        let mut heading = None;
+        for line in lines {
+            match line {
+                HelpLine::Heading(_) => heading = Some(line),
+                HelpLine::Key(key, description) if matches(key) || matches(description) => {
+                    if let Some(heading) = heading.take() {
+                        if !visible.is_empty() {
+                            visible.push(HelpLine::Blank);
+                        }
+                        visible.push(heading);
+                    }
+                    visible.push(line);
+                }
+                _ => {}
+            }
+        }
+        visible.extend([HelpLine::Blank, HelpLine::Hint]);
+        visible
+    }
+
+    fn create_item(&self, line: HelpLine) -> ListItem<'static> {
+        match line {
+            HelpLine::Heading(heading) => ListItem::new(Line::from(vec![Span::styled(
+                heading,
                 Style::default()
-                    .fg(Color::Cyan)
+                    .fg(self.theme.heading)
                     .add_modifier(Modifier::BOLD),
             )])),
-            ListItem::new(Line::from("")),
-            Self::create_key_line("↑/↓, j/k", "Scroll content vertically"),
-            Self::create_key_line("←/→, h/l", "Scroll content horizontally"),
-            Self::create_key_line("b", "Toggle blame view"),
-            Self::create_key_line("n", "Toggle line numbers"),
-            Self::create_key_line("g", "Go to GitHub (if available)"),
-            ListItem::new(Line::from("")),
-            ListItem::new(Line::from("")),
-            ListItem::new(Line::from(vec![
-                Span::styled("Use ", Style::default().fg(Color::Gray)),
-                Span::styled("↑/↓", Style::default().fg(Color::Yellow)),
-                Span::styled(" to scroll • Press ", Style::default().fg(Color::Gray)),
-                Span::styled("ESC", Style::default().fg(Color::Yellow)),
-                Span::styled(" to close", Style::default().fg(Color::Gray)),
+            HelpLine::Key(key, description) => self.create_key_line(key, description),
+            HelpLine::Blank => ListItem::new(Line::from("")),
+            HelpLine::Hint => ListItem::new(Line::from(vec![
+                Span::styled("Use ", Style::default().fg(self.theme.hint)),
+                Span::styled("↑/↓", Style::default().fg(self.theme.emphasis)),
+                Span::styled(" to scroll • ", Style::default().fg(self.theme.hint)),
+                Span::styled("/", Style::default().fg(self.theme.emphasis)),
+                Span::styled(" to search • Press ", Style::default().fg(self.theme.hint)),
+                Span::styled("ESC", Style::default().fg(self.theme.emphasis)),
+                Span::styled(" to close", Style::default().fg(self.theme.hint)),
             ])),
-        ]
+        }
+    }
+
+    fn create_key_line(&self, key: &'static str, description: &'static str) -> ListItem<'static> {
+        let key_style = Style::default().fg(self.theme.emphasis);
+        let mut spans = self.highlight(key, key_style);
+        spans.push(Span::styled(
+            " ".repeat(12usize.saturating_sub(key.chars().count())),
+            key_style,
+        ));
+        spans.push(Span::raw("  "));
+        spans.extend(self.highlight(description, Style::default().fg(self.theme.text)));
+        ListItem::new(Line::from(spans))
     }
 
-    fn create_key_line(key: &'static str, description: &'static str) -> ListItem<'static> {
-        ListItem::new(Line::from(vec![
-            Span::styled(format!("{:12}", key), Style::default().fg(Color::Yellow)),
-            Span::raw("  "),
-            Span::styled(description, Style::default().fg(Color::White)),
-        ]))
+    // `text` in `style`, with the matches of the query on the search match background.
+    fn highlight(&self, text: &'static str, style: Style) -> Vec<Span<'static>> {
+        if self.query.is_empty() {
+            return vec![Span::styled(text, style)];
+        }
+        let query = self.query.to_ascii_lowercase();
+        let mut spans = vec![];
+        let mut end = 0;
+        for (start, found) in text.to_ascii_lowercase().match_indices(&query) {
+            spans.push(Span::styled(&text[end..start], style));
+            end = start + found.len();
+            spans.push(Span::styled(
+                &text[start..end],
+                style.bg(self.theme.search_match),
+            ));
+        }
+        spans.push(Span::styled(&text[end..], style));
+        spans
     }
 
-    fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-        let popup_layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Percentage((100 - percent_y) / 2),
-                Constraint::Percentage(percent_y),
-                Constraint::Percentage((100 - percent_y) / 2),
-            ])
-            .split(r);
-
-        Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage((100 - percent_x) / 2),
-                Constraint::Percentage(percent_x),
-                Constraint::Percentage((100 - percent_x) / 2),
-            ])
-            .split(popup_layout[1])[1]
+    // Types into the query while searching; Enter keeps the matches, Esc drops them.
+    fn edit_query(&mut self, key_code: KeyCode) {
+        match key_code {
+            KeyCode::Char(c) => self.query.push(c),
+            KeyCode::Backspace => {
+                self.query.pop();
+            }
+            KeyCode::Enter => self.editing = false,
+            KeyCode::Esc => {
+                self.query.clear();
+                self.editing = false;
+            }
+            _ => return,
+        }
+        self.scroll_offset = 0;
     }
 }
 
@@ -132,18 +263,25 @@ impl OperatableComponent for HelpModal {
             return;
         }
 
-        let popup_area = Self::centered_rect(80, 80, rect);
+        let popup_area = centered_rect(80, 80, rect);
 
         // Clear the background
         frame.render_widget(Clear, popup_area);
 
-        let block = Block::default()
+        let mut block = Block::default()
             .title(" Key Configuration Help ")
             .borders(Borders::ALL)
             .border_type(BorderType::Double)
-            .style(Style::default().fg(Color::White));
+            .style(Style::default().fg(self.theme.text));
+        if self.editing || !self.query.is_empty() {
+            block = block.title_bottom(format!(
+                " /{}{} ",
+                self.query,
+                if self.editing { "▏" } else { "" }
+            ));
+        }
 
-        let help_content = Self::get_help_content();
+        let help_content = self.visible_lines();
 
         // Calculate visible area height (subtract 2 for borders)
         let inner_height = popup_area.height.saturating_sub(2) as usize;
@@ -153,11 +291,12 @@ impl OperatableComponent for HelpModal {
             .into_iter()
             .skip(self.scroll_offset)
             .take(inner_height)
+            .map(|line| self.create_item(line))
             .collect();
 
         let help_list = List::new(visible_content)
             .block(block)
-            .style(Style::default().fg(Color::White));
+            .style(Style::default().fg(self.theme.text));
 
         frame.render_widget(help_list, popup_area);
     }
@@ -171,14 +310,25 @@ impl OperatableComponent for HelpModal {
             return Message::NoAction;
         }
 
+        if self.editing && !matches!(key_code, KeyCode::Up | KeyCode::Down) {
+            self.edit_query(key_code);
+            return Message::NoAction;
+        }
+
         match key_code {
             KeyCode::Esc => Message::Once(OnceOperation::CloseHelpModal),
+            KeyCode::Char('/') => {
+                self.query.clear();
+                self.editing = true;
+                self.scroll_offset = 0;
+                Message::NoAction
+            }
             KeyCode::Up => {
                 self.scroll_offset = self.scroll_offset.saturating_sub(1);
                 Message::NoAction
             }
             KeyCode::Down => {
-                let help_content = Self::get_help_content();
+                let help_content = self.visible_lines();
                 let max_scroll = help_content.len().saturating_sub(1);
                 if self.scroll_offset < max_scroll {
                     self.scroll_offset += 1;
@@ -199,11 +349,21 @@ impl OperatableComponent for HelpModal {
                 self.visible = false;
                 self.focus = Focus::Off;
                 self.scroll_offset = 0; // Reset scroll when closing
+                self.query.clear();
+                self.editing = false;
             }
             _ => {}
         }
         Message::NoAction
     }
+
+    fn is_open(&self) -> bool {
+        self.visible
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
 }
 
 #[cfg(test)]
@@ -303,7 +463,7 @@ mod tests {
 
     #[test]
     fn test_help_modal_content() {
-        let help_content = HelpModal::get_help_content();
+        let help_content = HelpModal::new().get_help_content();
 
         // Should have content
         assert!(!help_content.is_empty());
@@ -319,10 +479,54 @@ mod tests {
         assert!(content_text.contains("ESC"));
     }
 
+    #[test]
+    fn test_help_modal_search() {
+        let mut help_modal = HelpModal::new();
+        help_modal.handle_message(&Message::Once(OnceOperation::ShowHelpModal));
+        for c in "/BLAME".chars() {
+            help_modal.process_events(KeyCode::Char(c));
+        }
+        assert!(help_modal.is_editing());
+        let lines = help_modal.visible_lines();
+        assert_eq!(
+            lines[..3],
+            [
+                HelpLine::Heading("Content Viewer:"),
+                HelpLine::Key("b", "Toggle blame view"),
+                HelpLine::Key("B", "Toggle repeated blame commits"),
+            ]
+        );
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal
+            .draw(|frame| help_modal.draw(frame, frame.size()))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let text = format!("{:?}", buffer);
+        assert!(text.contains("/BLAME▏"));
+        // `blame` is highlighted in `Toggle blame view`, whatever its case
+        assert_eq!(buffer.get(29, 4).bg, ratatui::style::Color::Reset);
+        assert_eq!(buffer.get(30, 4).symbol(), "b");
+        assert_eq!(buffer.get(30, 4).bg, Theme::default().search_match);
+        assert_eq!(buffer.get(34, 4).bg, Theme::default().search_match);
+        assert_eq!(buffer.get(35, 4).bg, ratatui::style::Color::Reset);
+
+        // Enter keeps the matches and lets keys act again; Esc still closes
+        help_modal.process_events(KeyCode::Enter);
+        assert!(!help_modal.is_editing());
+        assert_eq!(help_modal.visible_lines(), lines);
+        assert_eq!(
+            help_modal.process_events(KeyCode::Esc),
+            Message::Once(OnceOperation::CloseHelpModal)
+        );
+        help_modal.handle_message(&Message::Once(OnceOperation::CloseHelpModal));
+        assert_eq!(help_modal.visible_lines(), help_modal.get_help_content());
+    }
+
     #[test]
     fn test_help_modal_centered_rect() {
         let full_rect = Rect::new(0, 0, 100, 50);
-        let centered = HelpModal::centered_rect(80, 60, full_rect);
+        let centered = centered_rect(80, 60, full_rect);
 
         // Should be centered
         assert_eq!(centered.x, 10); // (100 - 80) / 2
@@ -369,16 +573,16 @@ mod tests {
                 "    ║                              ║    ",
                 "    ║Tab           Switch focus bet║    ",
                 "    ║Ctrl+C        Exit gview      ║    ",
+                "    ║Ctrl+P        Search files and║    ",
+                "    ║Ctrl+^        Switch to the pr║    ",
+                "    ║Ctrl+S        Save the screen ║    ",
+                "    ║Ctrl+O        Inspect the raw ║    ",
                 "    ║<             Decrease left pa║    ",
                 "    ║>             Increase left pa║    ",
                 "    ║?             Show this help m║    ",
+                "    ║/ (in help)   Search the keys ║    ",
                 "    ║ESC           Close help modal║    ",
                 "    ║                              ║    ",
-                "    ║Filter Panel:                 ║    ",
-                "    ║                              ║    ",
-                "    ║Enter         Apply filter    ║    ",
-                "    ║Ctrl+A        Select all text ║    ",
-                "    ║                              ║    ",
                 "    ╚══════════════════════════════╝    ",
                 "                                        ",
                 "                                        ",
@@ -418,12 +622,16 @@ mod tests {
                 x: 17, y: 10, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 36, y: 10, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 4, y: 11, fg: White, bg: Reset, underline: Reset, modifier: NONE,
+                x: 5, y: 11, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
+                x: 17, y: 11, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 36, y: 11, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 4, y: 12, fg: White, bg: Reset, underline: Reset, modifier: NONE,
-                x: 5, y: 12, fg: Cyan, bg: Reset, underline: Reset, modifier: BOLD,
-                x: 18, y: 12, fg: White, bg: Reset, underline: Reset, modifier: NONE,
+                x: 5, y: 12, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
+                x: 17, y: 12, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 36, y: 12, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 4, y: 13, fg: White, bg: Reset, underline: Reset, modifier: NONE,
+                x: 5, y: 13, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
+                x: 17, y: 13, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 36, y: 13, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 4, y: 14, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 5, y: 14, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
@@ -467,39 +675,39 @@ mod tests {
                 "               ║                                                                                                                      ║               ",
                 "               ║Tab           Switch focus between panels                                                                             ║               ",
                 "               ║Ctrl+C        Exit gview                                                                                              ║               ",
+                "               ║Ctrl+P        Search files and lines                                                                                  ║               ",
+                "               ║Ctrl+^        Switch to the previous commit                                                                           ║               ",
+                "               ║Ctrl+S        Save the screen to a file                                                                               ║               ",
+                "               ║Ctrl+O        Inspect the raw git object                                                                              ║               ",
                 "               ║<             Decrease left panel width                                                                               ║               ",
                 "               ║>             Increase left panel width                                                                               ║               ",
                 "               ║?             Show this help modal                                                                                    ║               ",
-                "               ║ESC           Close help modal                                                                                        ║               ",
+                "               ║/ (in help)   Search the keys as you type                                                                             ║               ",
+                "               ║ESC           Close help modal, or dismiss the shown error                                                            ║               ",
                 "               ║                                                                                                                      ║               ",
                 "               ║Filter Panel:                                                                                                         ║               ",
                 "               ║                                                                                                                      ║               ",
                 "               ║Enter         Apply filter                                                                                            ║               ",
                 "               ║Ctrl+A        Select all text                                                                                         ║               ",
+                "               ║Tab           Complete the next path component                                                                        ║               ",
+                "               ║←/→           Move the cursor                                                                                         ║               ",
                 "               ║                                                                                                                      ║               ",
                 "               ║File List Panel:                                                                                                      ║               ",
                 "               ║                                                                                                                      ║               ",
                 "               ║↑/↓, j/k      Navigate files                                                                                          ║               ",
                 "               ║Enter         Select file                                                                                             ║               ",
-                "               ║                                                                                                                      ║               ",
-                "               ║Commit Panel:                                                                                                         ║               ",
-                "               ║                                                                                                                      ║               ",
-                "               ║o             Open commit modal                                                                                       ║               ",
-                "               ║                                                                                                                      ║               ",
-                "               ║Content Viewer:                                                                                                       ║               ",
-                "               ║                                                                                                                      ║               ",
-                "               ║↑/↓, j/k      Scroll content vertically                                                                               ║               ",
-                "               ║←/→, h/l      Scroll content horizontally                                                                             ║               ",
-                "               ║b             Toggle blame view                                                                                       ║               ",
-                "               ║n             Toggle line numbers                                                                                     ║               ",
-                "               ║g             Go to GitHub (if available)                                                                             ║               ",
-                "               ║                                                                                                                      ║               ",
-                "               ║                                                                                                                      ║               ",
-                "               ║Use ↑/↓ to scroll • Press ESC to close                                                                                ║               ",
-                "               ║                                                                                                                      ║               ",
-                "               ║                                                                                                                      ║               ",
-                "               ║                                                                                                                      ║               ",
-                "               ║                                                                                                                      ║               ",
+                "               ║Space         Toggle reviewed mark (review mode)                                                                      ║               ",
+                "               ║s             Show diffstat of the file's directory                                                                   ║               ",
+                "               ║S             Toggle sparse checkout / all files                                                                      ║               ",
+                "               ║t             Toggle the directory tree                                                                               ║               ",
+                "               ║c             Toggle the files changed in this commit                                                                 ║               ",
+                "               ║h/l, Enter    Collapse/expand a directory (tree)                                                                      ║               ",
+                "               ║y             Copy the file path                                                                                      ║               ",
+                "               ║g             Open the file's diff on the forge (review mode)                                                         ║               ",
+                "               ║gg            Select the first file                                                                                   ║               ",
+                "               ║PgUp/PgDn     Move a page (Ctrl+U/D: half a page)                                                                     ║               ",
+                "               ║Home, G/End   Select the first/last file                                                                              ║               ",
+                "               ║:             Scope the session to a directory                                                                        ║               ",
                 "               ║                                                                                                                      ║               ",
                 "               ╚══════════════════════════════════════════════════════════════════════════════════════════════════════════════════════╝               ",
                 "                                                                                                                                                      ",
@@ -543,12 +751,16 @@ mod tests {
                 x: 28, y: 13, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 13, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 14, fg: White, bg: Reset, underline: Reset, modifier: NONE,
+                x: 16, y: 14, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
+                x: 28, y: 14, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 14, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 15, fg: White, bg: Reset, underline: Reset, modifier: NONE,
-                x: 16, y: 15, fg: Cyan, bg: Reset, underline: Reset, modifier: BOLD,
-                x: 29, y: 15, fg: White, bg: Reset, underline: Reset, modifier: NONE,
+                x: 16, y: 15, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
+                x: 28, y: 15, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 15, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 16, fg: White, bg: Reset, underline: Reset, modifier: NONE,
+                x: 16, y: 16, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
+                x: 28, y: 16, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 16, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 17, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 16, y: 17, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
@@ -562,7 +774,7 @@ mod tests {
                 x: 135, y: 19, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 20, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 16, y: 20, fg: Cyan, bg: Reset, underline: Reset, modifier: BOLD,
-                x: 32, y: 20, fg: White, bg: Reset, underline: Reset, modifier: NONE,
+                x: 29, y: 20, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 20, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 21, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 21, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
@@ -575,24 +787,28 @@ mod tests {
                 x: 28, y: 23, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 23, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 24, fg: White, bg: Reset, underline: Reset, modifier: NONE,
+                x: 16, y: 24, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
+                x: 28, y: 24, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 24, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 25, fg: White, bg: Reset, underline: Reset, modifier: NONE,
-                x: 16, y: 25, fg: Cyan, bg: Reset, underline: Reset, modifier: BOLD,
-                x: 29, y: 25, fg: White, bg: Reset, underline: Reset, modifier: NONE,
+                x: 16, y: 25, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
+                x: 28, y: 25, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 25, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 26, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 26, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 27, fg: White, bg: Reset, underline: Reset, modifier: NONE,
-                x: 16, y: 27, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
-                x: 28, y: 27, fg: White, bg: Reset, underline: Reset, modifier: NONE,
+                x: 16, y: 27, fg: Cyan, bg: Reset, underline: Reset, modifier: BOLD,
+                x: 32, y: 27, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 27, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 28, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 28, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 29, fg: White, bg: Reset, underline: Reset, modifier: NONE,
-                x: 16, y: 29, fg: Cyan, bg: Reset, underline: Reset, modifier: BOLD,
-                x: 31, y: 29, fg: White, bg: Reset, underline: Reset, modifier: NONE,
+                x: 16, y: 29, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
+                x: 28, y: 29, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 29, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 30, fg: White, bg: Reset, underline: Reset, modifier: NONE,
+                x: 16, y: 30, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
+                x: 28, y: 30, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 30, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 31, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 16, y: 31, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
@@ -615,24 +831,32 @@ mod tests {
                 x: 28, y: 35, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 35, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 36, fg: White, bg: Reset, underline: Reset, modifier: NONE,
+                x: 16, y: 36, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
+                x: 28, y: 36, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 36, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 37, fg: White, bg: Reset, underline: Reset, modifier: NONE,
+                x: 16, y: 37, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
+                x: 28, y: 37, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 37, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 38, fg: White, bg: Reset, underline: Reset, modifier: NONE,
-                x: 16, y: 38, fg: Gray, bg: Reset, underline: Reset, modifier: NONE,
-                x: 20, y: 38, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
-                x: 23, y: 38, fg: Gray, bg: Reset, underline: Reset, modifier: NONE,
-                x: 42, y: 38, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
-                x: 45, y: 38, fg: Gray, bg: Reset, underline: Reset, modifier: NONE,
-                x: 54, y: 38, fg: White, bg: Reset, underline: Reset, modifier: NONE,
+                x: 16, y: 38, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
+                x: 28, y: 38, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 38, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 39, fg: White, bg: Reset, underline: Reset, modifier: NONE,
+                x: 16, y: 39, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
+                x: 28, y: 39, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 39, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 40, fg: White, bg: Reset, underline: Reset, modifier: NONE,
+                x: 16, y: 40, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
+                x: 28, y: 40, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 40, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 41, fg: White, bg: Reset, underline: Reset, modifier: NONE,
+                x: 16, y: 41, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
+                x: 28, y: 41, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 41, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 42, fg: White, bg: Reset, underline: Reset, modifier: NONE,
+                x: 16, y: 42, fg: Yellow, bg: Reset, underline: Reset, modifier: NONE,
+                x: 28, y: 42, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 42, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
                 x: 15, y: 43, fg: White, bg: Reset, underline: Reset, modifier: NONE,
                 x: 135, y: 43, fg: Reset, bg: Reset, underline: Reset, modifier: NONE,
@@ -0,0 +1,161 @@
+use std::sync::{Arc, Mutex};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Block, Clear, Paragraph},
+    Frame,
+};
+
+use crate::{error::GviewError, repository::RepositoryInfo, theme::Theme};
+
+use super::{
+    centered_rect,
+    operatable_components::{Message, OnceOperation, OperatableComponent},
+};
+
+// Shows the raw git object of the selection, like `git cat-file -p`, for a closer look.
+pub struct InspectModal {
+    repository: Arc<Mutex<RepositoryInfo>>,
+    // heading and body of the inspected object, while open
+    object: Option<(String, String)>,
+    scroll: u16,
+    theme: Theme,
+}
+
+impl InspectModal {
+    pub fn new(repository: Arc<Mutex<RepositoryInfo>>) -> Self {
+        Self {
+            repository,
+            object: None,
+            scroll: 0,
+            theme: Theme::default(),
+        }
+    }
+
+    fn open(&mut self, path: Option<&str>) -> Message {
+        let Ok(repo) = self.repository.lock() else {
+            return Message::Error(GviewError::Lock);
+        };
+        match repo.inspect_object(path) {
+            Ok(object) => {
+                self.object = Some(object);
+                self.scroll = 0;
+                Message::NoAction
+            }
+            Err(e) => Message::Error(GviewError::new(
+                format!("Cannot inspect {}", path.unwrap_or("the commit")),
+                e,
+            )),
+        }
+    }
+}
+
+impl OperatableComponent for InspectModal {
+    fn draw(&mut self, frame: &mut Frame, rect: Rect) {
+        let Some((heading, body)) = &self.object else {
+            return;
+        };
+
+        let popup_area = centered_rect(80, 70, rect);
+        frame.render_widget(Clear, popup_area);
+
+        let block = Block::bordered()
+            .title(format!(" {} (↑/↓: scroll, Esc: close) ", heading))
+            .style(Style::default().fg(self.theme.text));
+        // a tab would move the terminal's cursor past the cells ratatui knows of
+        let lines: Vec<Line> = body
+            .lines()
+            .map(|line| Line::from(line.replace('\t', "    ")))
+            .collect();
+        let visible = popup_area.height.saturating_sub(2);
+        self.scroll = self
+            .scroll
+            .min((lines.len() as u16).saturating_sub(visible));
+        let paragraph = Paragraph::new(lines).block(block).scroll((self.scroll, 0));
+        frame.render_widget(paragraph, popup_area);
+    }
+
+    fn process_focus(&mut self) {}
+
+    fn process_events(&mut self, events: KeyCode) -> Message {
+        match events {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                return Message::Once(OnceOperation::CloseInspector)
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll = self.scroll.saturating_add(1),
+            _ => {}
+        }
+        Message::NoAction
+    }
+
+    fn handle_message(&mut self, message: &Message) -> Message {
+        match message {
+            Message::Once(OnceOperation::OpenInspector { path }) => self.open(path.as_deref()),
+            Message::Once(OnceOperation::CloseInspector) => {
+                self.object = None;
+                Message::NoAction
+            }
+            _ => Message::NoAction,
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        self.object.is_some()
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn create_mock_repo() -> Arc<Mutex<RepositoryInfo>> {
+        crate::components::create_test_repo("inspect_modal", &[("src/main.rs", "fn main() {}\n")])
+    }
+
+    #[test]
+    fn test_inspect_modal_open_and_close() {
+        let mut modal = InspectModal::new(create_mock_repo());
+        assert!(!modal.is_open());
+
+        let message = modal.handle_message(&Message::Once(OnceOperation::OpenInspector {
+            path: Some("no/such/file".to_owned()),
+        }));
+        assert!(matches!(message, Message::Error(_)));
+        assert!(!modal.is_open());
+
+        modal.handle_message(&Message::Once(OnceOperation::OpenInspector {
+            path: Some("src".to_owned()),
+        }));
+        assert!(modal.is_open());
+        assert!(modal.object.as_ref().is_some_and(
+            |(heading, body)| heading.starts_with("tree ") && body.ends_with("\tmain.rs")
+        ));
+        let message = modal.process_events(KeyCode::Esc);
+        assert_eq!(message, Message::Once(OnceOperation::CloseInspector));
+        modal.handle_message(&message);
+        assert!(!modal.is_open());
+    }
+
+    #[test]
+    fn test_inspect_modal_draw() {
+        let mut modal = InspectModal::new(create_mock_repo());
+        modal.handle_message(&Message::Once(OnceOperation::OpenInspector {
+            path: Some(String::new()),
+        }));
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal
+            .draw(|frame| modal.draw(frame, frame.size()))
+            .unwrap();
+        assert_snapshot!(format!("{:?}", terminal.backend().buffer()));
+    }
+}
@@ -0,0 +1,296 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+};
+
+use crossterm::event::KeyCode;
+use git2::Oid;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    error::GviewError,
+    progress::ProgressReporter,
+    repository::{LanguageStat, RepositoryInfo},
+    theme::Theme,
+};
+
+use super::{
+    centered_rect,
+    operatable_components::{Message, OnceOperation, OperatableComponent},
+};
+
+// Sums up the viewed commit's tree by language, like `linguist`: the files, lines and
+// bytes of each.
+pub struct LanguagesModal {
+    is_open: bool,
+    // the tree of the viewed commit when opened
+    tree: Option<Oid>,
+    // statistics of each tree counted, or why they could not be
+    results: HashMap<Oid, Result<Vec<LanguageStat>, String>>,
+    pending: Option<(Oid, JoinHandle<anyhow::Result<Vec<LanguageStat>>>)>,
+    scroll: u16,
+    repository: Arc<Mutex<RepositoryInfo>>,
+    progress: ProgressReporter,
+    theme: Theme,
+}
+
+impl LanguagesModal {
+    pub fn new(repository: Arc<Mutex<RepositoryInfo>>, progress: ProgressReporter) -> Self {
+        Self {
+            is_open: false,
+            tree: None,
+            results: HashMap::new(),
+            pending: None,
+            scroll: 0,
+            repository,
+            progress,
+            theme: Theme::default(),
+        }
+    }
+
+    // Shows the statistics of the viewed tree, counting them unless they are known.
+    fn open(&mut self) -> Message {
+        let Ok(repo) = self.repository.lock() else {
+            return Message::Error(GviewError::Lock);
+        };
+        let tree = match repo.viewed_tree_id() {
+            Ok(tree) => tree,
+            Err(e) => return Message::Error(GviewError::new("Cannot count the languages", e)),
+        };
+        if !self.results.contains_key(&tree)
+            && !matches!(&self.pending, Some((pending, _)) if *pending == tree)
+        {
+            match repo.cached_language_stats(tree) {
+                Some(stats) => {
+                    self.results.insert(tree, Ok(stats));
+                }
+                None => {
+                    self.pending =
+                        Some((tree, repo.spawn_language_stats(tree, self.progress.clone())))
+                }
+            }
+        }
+        self.tree = Some(tree);
+        self.scroll = 0;
+        self.is_open = true;
+        Message::NoAction
+    }
+
+    // Collects the statistics once the worker is done, and caches them; called once per tick.
+    pub fn poll(&mut self) {
+        if !self
+            .pending
+            .as_ref()
+            .is_some_and(|(_, handle)| handle.is_finished())
+        {
+            return;
+        }
+        let Some((tree, handle)) = self.pending.take() else {
+            return;
+        };
+        let result = match handle.join() {
+            Ok(Ok(stats)) => {
+                if let Ok(repo) = self.repository.lock() {
+                    repo.cache_language_stats(tree, &stats);
+                }
+                Ok(stats)
+            }
+            Ok(Err(e)) => Err(format!("Cannot count the languages: {}", e)),
+            Err(_) => Err("Cannot count the languages".to_owned()),
+        };
+        self.results.insert(tree, result);
+    }
+
+    fn lines(&self, stats: &[LanguageStat]) -> Vec<Line<'static>> {
+        let total = LanguageStat {
+            language: "Total".to_owned(),
+            files: stats.iter().map(|stat| stat.files).sum(),
+            lines: stats.iter().map(|stat| stat.lines).sum(),
+            bytes: stats.iter().map(|stat| stat.bytes).sum(),
+        };
+        let width = stats
+            .iter()
+            .map(|stat| stat.language.chars().count())
+            .max()
+            .unwrap_or_default()
+            .max("Language".len());
+        let row = |language: &str, files: &str, lines: &str, bytes: &str, share: &str| {
+            format!(
+                "{:<width$}  {:>6}  {:>9}  {:>11}  {:>6}",
+                language, files, lines, bytes, share
+            )
+        };
+        let stat_row = |stat: &LanguageStat| {
+            // shares are of the bytes, as linguist's are
+            let share = stat.bytes as f64 * 100.0 / total.bytes.max(1) as f64;
+            row(
+                &stat.language,
+                &stat.files.to_string(),
+                &stat.lines.to_string(),
+                &stat.bytes.to_string(),
+                &format!("{:.1}%", share),
+            )
+        };
+
+        let bold = Style::default()
+            .fg(self.theme.heading)
+            .add_modifier(Modifier::BOLD);
+        let mut lines = vec![Line::styled(
+            row("Language", "Files", "Lines", "Bytes", "Share"),
+            bold,
+        )];
+        lines.extend(
+            stats
+                .iter()
+                .map(|stat| Line::styled(stat_row(stat), Style::default().fg(self.theme.text))),
+        );
+        lines.push(Line::styled(stat_row(&total), bold));
+        lines
+    }
+}
+
+impl OperatableComponent for LanguagesModal {
+    fn draw(&mut self, frame: &mut Frame, rect: Rect) {
+        let Some(tree) = self.tree.filter(|_| self.is_open) else {
+            return;
+        };
+
+        let popup_area = centered_rect(70, 60, rect);
+        frame.render_widget(Clear, popup_area);
+
+        let tree_id = tree.to_string();
+        let block = Block::bordered()
+            .title(format!(
+                " Languages of tree {} (↑/↓: scroll, Esc: close) ",
+                &tree_id[..8]
+            ))
+            .style(Style::default().fg(self.theme.text));
+
+        let message = match self.results.get(&tree) {
+            None => Some("Counting the lines of every file…".to_owned()),
+            Some(Err(e)) => Some(e.to_owned()),
+            Some(Ok(stats)) if stats.is_empty() => Some("No files in this commit".to_owned()),
+            Some(Ok(_)) => None,
+        };
+        if let Some(message) = message {
+            let paragraph = Paragraph::new(message)
+                .block(block)
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true });
+            frame.render_widget(paragraph, popup_area);
+            return;
+        }
+
+        let lines = match self.results.get(&tree) {
+            Some(Ok(stats)) => self.lines(stats),
+            _ => vec![],
+        };
+        let visible = popup_area.height.saturating_sub(2);
+        self.scroll = self
+            .scroll
+            .min((lines.len() as u16).saturating_sub(visible));
+        let paragraph = Paragraph::new(lines).block(block).scroll((self.scroll, 0));
+        frame.render_widget(paragraph, popup_area);
+    }
+
+    fn process_focus(&mut self) {}
+
+    fn process_events(&mut self, events: KeyCode) -> Message {
+        match events {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                return Message::Once(OnceOperation::CloseLanguages)
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll = self.scroll.saturating_add(1),
+            _ => {}
+        }
+        Message::NoAction
+    }
+
+    fn handle_message(&mut self, message: &Message) -> Message {
+        match message {
+            Message::Once(OnceOperation::OpenLanguages) => self.open(),
+            Message::Once(OnceOperation::CloseLanguages) => {
+                self.is_open = false;
+                Message::NoAction
+            }
+            _ => Message::NoAction,
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress::ProgressTracker;
+    use insta::assert_snapshot;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn create_mock_repo() -> Arc<Mutex<RepositoryInfo>> {
+        crate::components::create_test_repo(
+            "languages_modal",
+            &[
+                ("src/main.rs", "fn main() {\n}\n"),
+                ("src/lib.rs", "pub mod app;\n"),
+                ("README.md", "# gview\n"),
+            ],
+        )
+    }
+
+    fn wait(modal: &mut LanguagesModal) {
+        while modal.pending.is_some() {
+            modal.poll();
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_languages_modal_open_and_close() {
+        let mut modal = LanguagesModal::new(create_mock_repo(), ProgressTracker::new().reporter());
+        assert!(!modal.is_open());
+
+        modal.handle_message(&Message::Once(OnceOperation::OpenLanguages));
+        assert!(modal.is_open());
+        wait(&mut modal);
+        let stats = modal.results[&modal.tree.unwrap()].as_ref().unwrap();
+        assert_eq!(stats[0].language, "Rust");
+        assert_eq!((stats[0].files, stats[0].lines), (2, 3));
+
+        let message = modal.process_events(KeyCode::Esc);
+        assert_eq!(message, Message::Once(OnceOperation::CloseLanguages));
+        modal.handle_message(&message);
+        assert!(!modal.is_open());
+
+        // opened again, the counted tree is shown without counting it anew
+        modal.handle_message(&Message::Once(OnceOperation::OpenLanguages));
+        assert!(modal.is_open());
+        assert!(modal.pending.is_none());
+    }
+
+    #[test]
+    fn test_languages_modal_draw() {
+        let mut modal = LanguagesModal::new(create_mock_repo(), ProgressTracker::new().reporter());
+        modal.handle_message(&Message::Once(OnceOperation::OpenLanguages));
+        wait(&mut modal);
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal
+            .draw(|frame| modal.draw(frame, frame.size()))
+            .unwrap();
+        assert_snapshot!(format!("{:?}", terminal.backend().buffer()));
+    }
+}
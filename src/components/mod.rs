@@ -1,7 +1,120 @@
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+
+pub mod checks_modal;
 pub mod commit_modal;
 pub mod commit_viewer;
 pub mod content_viewer;
 pub mod filer;
 pub mod filter;
 pub mod help_modal;
+pub mod inspect_modal;
+pub mod languages_modal;
+pub mod notes_modal;
 pub mod operatable_components;
+pub mod picker_modal;
+pub mod prompt_modal;
+pub mod remote_modal;
+pub mod search_modal;
+pub mod status_bar;
+pub mod whats_new_modal;
+
+// The middle `percent_x` by `percent_y` percent of `r`, where modals open.
+pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+// A repository in a fresh temporary directory named after `name`, with one commit of
+// `files`, each a path and its content.
+#[cfg(test)]
+pub fn init_test_repo(name: &str, files: &[(&str, &str)]) -> git2::Repository {
+    init_test_repo_with_commits(name, &[files])
+}
+
+// As `init_test_repo`, with one commit per entry of `commits`, each on top of the previous.
+#[cfg(test)]
+pub fn init_test_repo_with_commits(name: &str, commits: &[&[(&str, &str)]]) -> git2::Repository {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let test_dir = std::env::temp_dir().join(format!(
+        "gview_{}_test_{}_{}",
+        name,
+        timestamp,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&test_dir);
+    std::fs::create_dir_all(&test_dir).unwrap();
+
+    let repo = git2::Repository::init(&test_dir).unwrap();
+    let signature = git2::Signature::new(
+        "Test User",
+        "test@localhost",
+        &git2::Time::new(1234567890, 0),
+    )
+    .unwrap();
+    for (number, files) in commits.iter().enumerate() {
+        for (path, content) in *files {
+            let file = test_dir.join(path);
+            if let Some(parent) = file.parent() {
+                std::fs::create_dir_all(parent).unwrap();
+            }
+            std::fs::write(file, content).unwrap();
+        }
+        let tree_id = {
+            let mut index = repo.index().unwrap();
+            for (path, _) in *files {
+                index.add_path(std::path::Path::new(path)).unwrap();
+            }
+            index.write().unwrap();
+            index.write_tree().unwrap()
+        };
+        let tree = repo.find_tree(tree_id).unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<_> = parent.iter().collect();
+        let message = match number {
+            0 => "Initial commit".to_owned(),
+            _ => format!("Commit {}", number + 1),
+        };
+        let _ = repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            &message,
+            &tree,
+            &parents,
+        );
+    }
+    repo
+}
+
+// The repository of `init_test_repo`, viewed at its commit.
+#[cfg(test)]
+pub fn create_test_repo(
+    name: &str,
+    files: &[(&str, &str)],
+) -> std::sync::Arc<std::sync::Mutex<crate::repository::RepositoryInfo>> {
+    let repo = init_test_repo(name, files);
+    let oid = repo.head().unwrap().target().unwrap();
+    std::sync::Arc::new(std::sync::Mutex::new(
+        crate::repository::RepositoryInfo::_from_parts(repo, oid),
+    ))
+}
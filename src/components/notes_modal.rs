@@ -0,0 +1,304 @@
+use std::sync::{Arc, Mutex};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{error::GviewError, repository::RepositoryInfo, state::Note, theme::Theme};
+
+use super::{
+    centered_rect,
+    operatable_components::{
+        Focus, Message, MultipleTimesOperation, OnceOperation, OperatableComponent,
+    },
+    prompt_modal::PromptKind,
+};
+
+pub struct NotesModal {
+    focus: Focus,
+    is_open: bool,
+    notes: Vec<Note>,
+    list_state: ListState,
+    repository: Arc<Mutex<RepositoryInfo>>,
+    theme: Theme,
+}
+
+impl NotesModal {
+    pub fn new(repository: Arc<Mutex<RepositoryInfo>>) -> Self {
+        Self {
+            focus: Focus::Off,
+            is_open: false,
+            notes: Vec::new(),
+            list_state: ListState::default(),
+            repository,
+            theme: Theme::default(),
+        }
+    }
+
+    fn load_notes(&mut self) {
+        if let Ok(repo) = self.repository.lock() {
+            self.notes = repo.notes().to_vec();
+        }
+        let selected = self.list_state.selected().unwrap_or(0);
+        self.list_state.select(match self.notes.len() {
+            0 => None,
+            len => Some(selected.min(len - 1)),
+        });
+    }
+
+    fn open(&mut self) {
+        self.is_open = true;
+        self.focus = Focus::ON;
+        self.list_state.select(Some(0));
+        self.load_notes();
+    }
+
+    fn close(&mut self) {
+        self.is_open = false;
+        self.focus = Focus::Off;
+        self.list_state.select(None);
+    }
+
+    fn remove_selected(&mut self) -> Message {
+        let Some(selected) = self.list_state.selected() else {
+            return Message::NoAction;
+        };
+        let result = match self.repository.lock() {
+            Ok(mut repo) => repo
+                .remove_note(selected)
+                .map_err(|e| GviewError::new("Failed to remove note", e)),
+            Err(_) => Err(GviewError::Lock),
+        };
+        self.load_notes();
+        match result {
+            Ok(()) => Message::MultipleTimes(MultipleTimesOperation::ChangeNotes),
+            Err(e) => Message::Error(e),
+        }
+    }
+
+    fn _handle_message(&mut self, message: &Message) -> Message {
+        match message {
+            Message::Once(OnceOperation::OpenNotesModal) => self.open(),
+            Message::Once(OnceOperation::CloseNotesModal | OnceOperation::OpenLocation { .. }) => {
+                self.close()
+            }
+            Message::MultipleTimes(MultipleTimesOperation::ChangeNotes) if self.is_open => {
+                self.load_notes()
+            }
+            _ => {}
+        }
+        Message::NoAction
+    }
+}
+
+impl OperatableComponent for NotesModal {
+    fn draw(&mut self, frame: &mut Frame, rect: Rect) {
+        if !self.is_open {
+            return;
+        }
+
+        let popup_area = centered_rect(80, 60, rect);
+        frame.render_widget(Clear, popup_area);
+
+        let block = Block::bordered()
+            .title("Notes (Enter: jump, d: delete, e: export, Esc: close)")
+            .style(match self.focus {
+                Focus::ON => Style::default(),
+                Focus::Off => Style::default().fg(self.theme.unfocused),
+            });
+        let inner_area = block.inner(popup_area);
+        frame.render_widget(block, popup_area);
+
+        if self.notes.is_empty() {
+            let empty_msg = Paragraph::new("No notes yet (press a in the content viewer)")
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true });
+            frame.render_widget(empty_msg, inner_area);
+            return;
+        }
+
+        let items: Vec<ListItem> = self
+            .notes
+            .iter()
+            .map(|note| {
+                let short_id = &note.commit[..std::cmp::min(8, note.commit.len())];
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        short_id.to_owned(),
+                        Style::default().fg(self.theme.emphasis),
+                    ),
+                    Span::raw(" "),
+                    Span::styled(
+                        format!("{}:{}", note.path, note.line),
+                        Style::default().fg(self.theme.heading),
+                    ),
+                    Span::raw(" "),
+                    Span::raw(note.text.to_owned()),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(Style::default().bg(self.theme.selection))
+            .highlight_symbol("→ ");
+        frame.render_stateful_widget(list, inner_area, &mut self.list_state);
+    }
+
+    fn process_focus(&mut self) {
+        match self.focus {
+            Focus::Off => self.focus = Focus::ON,
+            Focus::ON => self.focus = Focus::Off,
+        }
+    }
+
+    fn process_events(&mut self, events: KeyCode) -> Message {
+        if !self.is_open {
+            return Message::NoAction;
+        }
+
+        match events {
+            KeyCode::Esc => return Message::Once(OnceOperation::CloseNotesModal),
+            KeyCode::Enter => {
+                if let Some(note) = self.list_state.selected().and_then(|i| self.notes.get(i)) {
+                    return Message::Once(OnceOperation::OpenLocation {
+                        commit_id: Some(note.commit.to_owned()),
+                        file: note.path.to_owned(),
+                        line: note.line,
+                    });
+                }
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                let selected = self.list_state.selected().unwrap_or(0);
+                self.list_state.select(Some(selected.saturating_sub(1)));
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if let Some(selected) = self.list_state.selected() {
+                    if selected + 1 < self.notes.len() {
+                        self.list_state.select(Some(selected + 1));
+                    }
+                }
+            }
+            KeyCode::Char('d') | KeyCode::Delete => return self.remove_selected(),
+            KeyCode::Char('e') => {
+                return Message::Once(OnceOperation::OpenPrompt {
+                    kind: PromptKind::ExportNotes,
+                })
+            }
+            _ => {}
+        }
+        Message::NoAction
+    }
+
+    fn handle_message(&mut self, message: &Message) -> Message {
+        match (message, self._handle_message(message)) {
+            (Message::MultipleTimes(_), Message::MultipleTimes(_)) => unreachable!(),
+            (Message::Once(_), Message::MultipleTimes(_)) => unreachable!(),
+            (Message::Once(_), Message::Once(_)) => unreachable!(),
+            (Message::NoAction, Message::MultipleTimes(_)) => unreachable!(),
+            (Message::NoAction, Message::Once(_)) => unreachable!(),
+            (_, new_message) => new_message,
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_mock_repo() -> Arc<Mutex<RepositoryInfo>> {
+        crate::components::create_test_repo("notes_modal", &[])
+    }
+
+    #[test]
+    fn test_notes_modal_open_lists_notes() {
+        let mock_repo = create_mock_repo();
+        mock_repo
+            .lock()
+            .unwrap()
+            .add_note("src/main.rs", 4, "hmm")
+            .unwrap();
+        let mut modal = NotesModal::new(Arc::clone(&mock_repo));
+        assert!(!modal.is_open());
+
+        modal.handle_message(&Message::Once(OnceOperation::OpenNotesModal));
+        assert!(modal.is_open());
+        assert_eq!(modal.notes.len(), 1);
+        assert_eq!(modal.list_state.selected(), Some(0));
+
+        let message = modal.process_events(KeyCode::Enter);
+        let commit_id = mock_repo.lock().unwrap().get_current_commit_id();
+        assert_eq!(
+            message,
+            Message::Once(OnceOperation::OpenLocation {
+                commit_id: Some(commit_id),
+                file: "src/main.rs".to_owned(),
+                line: 4,
+            })
+        );
+
+        // Jumping to a note closes the panel
+        modal.handle_message(&message);
+        assert!(!modal.is_open());
+    }
+
+    #[test]
+    fn test_notes_modal_delete_and_navigation() {
+        let mock_repo = create_mock_repo();
+        {
+            let mut repo = mock_repo.lock().unwrap();
+            repo.add_note("a.rs", 1, "one").unwrap();
+            repo.add_note("b.rs", 2, "two").unwrap();
+        }
+        let mut modal = NotesModal::new(Arc::clone(&mock_repo));
+        modal.handle_message(&Message::Once(OnceOperation::OpenNotesModal));
+
+        modal.process_events(KeyCode::Down);
+        modal.process_events(KeyCode::Down);
+        assert_eq!(modal.list_state.selected(), Some(1));
+
+        let message = modal.process_events(KeyCode::Char('d'));
+        assert_eq!(
+            message,
+            Message::MultipleTimes(MultipleTimesOperation::ChangeNotes)
+        );
+        assert_eq!(mock_repo.lock().unwrap().notes().len(), 1);
+        assert_eq!(modal.list_state.selected(), Some(0));
+
+        modal.process_events(KeyCode::Char('d'));
+        assert!(modal.notes.is_empty());
+        assert_eq!(modal.list_state.selected(), None);
+        assert_eq!(modal.process_events(KeyCode::Enter), Message::NoAction);
+    }
+
+    #[test]
+    fn test_notes_modal_keys() {
+        let mock_repo = create_mock_repo();
+        let mut modal = NotesModal::new(mock_repo);
+        assert_eq!(modal.process_events(KeyCode::Esc), Message::NoAction);
+
+        modal.handle_message(&Message::Once(OnceOperation::OpenNotesModal));
+        assert_eq!(
+            modal.process_events(KeyCode::Char('e')),
+            Message::Once(OnceOperation::OpenPrompt {
+                kind: PromptKind::ExportNotes
+            })
+        );
+        assert_eq!(
+            modal.process_events(KeyCode::Esc),
+            Message::Once(OnceOperation::CloseNotesModal)
+        );
+    }
+}
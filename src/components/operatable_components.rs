@@ -1,10 +1,16 @@
 use std::sync::{Arc, Mutex};
 
-use crate::repository::RepositoryInfo;
+use crate::{error::GviewError, repository::RepositoryInfo, theme::Theme};
 use crossterm::event::KeyCode;
-use ratatui::{layout::Rect, Frame};
+use ratatui::{
+    layout::Rect,
+    style::{Style, Stylize},
+    text::Line,
+    widgets::Block,
+    Frame,
+};
 
-use super::filter::FilterMode;
+use super::{filter::FilterMode, prompt_modal::PromptKind};
 
 // rust enum pass the operation command
 #[derive(Debug, PartialEq)]
@@ -12,7 +18,7 @@ pub enum Message {
     MultipleTimes(MultipleTimesOperation),
     Once(OnceOperation),
     NoAction,
-    Error { _message: String },
+    Error(GviewError),
 }
 
 #[derive(Debug)]
@@ -25,6 +31,11 @@ pub enum MultipleTimesOperation {
         repository: Arc<Mutex<RepositoryInfo>>,
     },
     ChangeShowCommit,
+    ChangeBase,
+    ChangeNotes,
+    ChangeScope,
+    ChangeHighlight,
+    ChangeDateRange,
 }
 
 impl PartialEq for MultipleTimesOperation {
@@ -44,22 +55,123 @@ impl PartialEq for MultipleTimesOperation {
                 MultipleTimesOperation::ChangeShowCommit,
                 MultipleTimesOperation::ChangeShowCommit,
             ) => true,
+            (MultipleTimesOperation::ChangeBase, MultipleTimesOperation::ChangeBase) => true,
+            (MultipleTimesOperation::ChangeNotes, MultipleTimesOperation::ChangeNotes) => true,
+            (MultipleTimesOperation::ChangeScope, MultipleTimesOperation::ChangeScope) => true,
+            (MultipleTimesOperation::ChangeHighlight, MultipleTimesOperation::ChangeHighlight) => {
+                true
+            }
+            (MultipleTimesOperation::ChangeDateRange, MultipleTimesOperation::ChangeDateRange) => {
+                true
+            }
             (MultipleTimesOperation::SetUp { .. }, MultipleTimesOperation::SetUp { .. }) => true, // Compare by type only
             _ => false,
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum OnceOperation {
-    ShowFile { file: String },
+    ShowFile {
+        file: String,
+    },
+    ShowDirectorySummary {
+        dir: String,
+    },
     JumpToContentView,
     JumpToFiler,
     OpenCommitModal,
     CloseCommitModal,
-    SetCommitById { commit_id: String },
+    SetCommitById {
+        commit_id: String,
+    },
+    PreviewCommit {
+        commit_id: String,
+    },
     ShowHelpModal,
     CloseHelpModal,
+    OpenPrompt {
+        kind: PromptKind,
+    },
+    ClosePrompt,
+    SubmitPrompt {
+        kind: PromptKind,
+        value: String,
+    },
+    OpenNotesModal,
+    CloseNotesModal,
+    OpenRemoteModal,
+    OpenChecksModal,
+    CloseChecksModal,
+    CloseRemoteModal,
+    SelectRemote {
+        name: String,
+    },
+    OpenLocation {
+        commit_id: Option<String>,
+        file: String,
+        line: usize,
+    },
+    OpenSearchModal {
+        word: String,
+    },
+    CloseSearchModal,
+    OpenPicker,
+    ClosePicker,
+    CloseWhatsNew,
+    // the object at `path` of the viewed commit, or the commit itself
+    OpenInspector {
+        path: Option<String>,
+    },
+    CloseInspector,
+    // files, lines and bytes per language of the viewed commit's tree
+    OpenLanguages,
+    CloseLanguages,
+    // reads the missing blob of `file` from the promisor remote of a partial clone
+    FetchBlob {
+        file: String,
+    },
+    ScopeTo {
+        prefix: String,
+    },
+    CopyToClipboard {
+        text: String,
+    },
+    // lines of a file exactly as stored, line endings included
+    CopyLines {
+        bytes: Vec<u8>,
+    },
+    PassThrough {
+        text: String,
+    },
+    OpenWithExternal {
+        file: String,
+    },
+    OpenInBrowser {
+        file: String,
+        line: usize,
+    },
+    OpenDiffInBrowser {
+        file: String,
+    },
+    OpenIssue {
+        reference: String,
+    },
+}
+
+impl OnceOperation {
+    // What operations that start another process do, to tell the user why they are
+    // refused when shelling out is disabled. None for the others.
+    pub fn spawned_action(&self) -> Option<&'static str> {
+        match self {
+            OnceOperation::OpenWithExternal { .. } => Some("Opening in an external program"),
+            OnceOperation::OpenInBrowser { .. } => Some("Opening in the browser"),
+            OnceOperation::OpenDiffInBrowser { .. } => Some("Opening the diff in the browser"),
+            OnceOperation::OpenIssue { .. } => Some("Opening the issue"),
+            OnceOperation::FetchBlob { .. } => Some("Fetching the missing file"),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -68,9 +180,51 @@ pub enum Focus {
     ON,
 }
 
+// The bordered block of a panel. Besides dropping the unfocused color, the focused one gets
+// a marker before its bold, reversed title and the theme's focus border, for palettes where
+// the gray hardly differs.
+pub fn panel_block<'a>(title: &str, focus: Focus, theme: &Theme) -> Block<'a> {
+    match focus {
+        Focus::ON => Block::bordered()
+            .title(
+                Line::from(match theme.focus_marker {
+                    Some(marker) => format!("{} {}", marker, title),
+                    None => title.to_owned(),
+                })
+                .bold()
+                .reversed(),
+            )
+            .border_type(theme.focus_border),
+        Focus::Off => Block::bordered()
+            .title(Line::from(title.to_owned()).bold())
+            .style(Style::default().fg(theme.unfocused)),
+    }
+}
+
 pub trait OperatableComponent {
     fn draw(&mut self, frame: &mut Frame, rect: Rect);
     fn process_focus(&mut self);
     fn process_events(&mut self, events: KeyCode) -> Message;
     fn handle_message(&mut self, message: &Message) -> Message;
+    // Panels are always shown; modals only while they are open.
+    fn is_open(&self) -> bool {
+        true
+    }
+    fn set_theme(&mut self, _theme: Theme) {}
+    // Rows the component needs to be of use, which the layout keeps for it when it can.
+    fn min_height(&self) -> u16 {
+        0
+    }
+    // Two-key chords such as `gg`. Their first key waits for the second instead of acting on
+    // its own, until another key or a timeout lets it through.
+    fn chords(&self) -> &'static [[char; 2]] {
+        &[]
+    }
+    fn process_chord(&mut self, _chord: [char; 2]) -> Message {
+        Message::NoAction
+    }
+    // Ctrl+D and Ctrl+U, half a page down or up in components that scroll.
+    fn scroll_half_page(&mut self, _down: bool) -> Message {
+        Message::NoAction
+    }
 }
@@ -0,0 +1,516 @@
+use std::{
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+};
+
+use crossterm::event::KeyCode;
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::{error::GviewError, progress::CancelToken, repository::RepositoryInfo, theme::Theme};
+
+use super::{
+    centered_rect,
+    operatable_components::{Focus, Message, OnceOperation, OperatableComponent},
+};
+
+const MAX_RESULTS: usize = 200;
+
+// A file (`line` is None) or one of its lines matching the query.
+#[derive(Debug, PartialEq)]
+struct PickerMatch {
+    path: String,
+    line: Option<usize>,
+    text: String,
+}
+
+pub struct PickerModal {
+    focus: Focus,
+    is_open: bool,
+    query: String,
+    // commit the files were read from, so jumps stay pinned to it
+    commit_id: String,
+    files: Arc<Vec<(String, String)>>,
+    matches: Vec<PickerMatch>,
+    // ranking of the latest query and the token that aborts it
+    pending: Option<(JoinHandle<Option<Vec<PickerMatch>>>, CancelToken)>,
+    list_state: ListState,
+    repository: Arc<Mutex<RepositoryInfo>>,
+    theme: Theme,
+}
+
+impl PickerModal {
+    pub fn new(repository: Arc<Mutex<RepositoryInfo>>) -> Self {
+        Self {
+            focus: Focus::Off,
+            is_open: false,
+            query: String::new(),
+            commit_id: String::new(),
+            files: Arc::new(Vec::new()),
+            matches: Vec::new(),
+            pending: None,
+            list_state: ListState::default(),
+            repository,
+            theme: Theme::default(),
+        }
+    }
+
+    fn open(&mut self) -> Message {
+        let loaded = match self.repository.lock() {
+            Ok(repo) => {
+                let commit_id = repo.get_current_commit_id();
+                // the files only change with the commit
+                if commit_id == self.commit_id {
+                    Ok(None)
+                } else {
+                    repo.text_blobs()
+                        .map(|files| Some((commit_id, files)))
+                        .map_err(|e| GviewError::new("Failed to read files", e))
+                }
+            }
+            Err(_) => Err(GviewError::Lock),
+        };
+        match loaded {
+            Ok(loaded) => {
+                if let Some((commit_id, files)) = loaded {
+                    self.commit_id = commit_id;
+                    self.files = Arc::new(files);
+                }
+                self.query.clear();
+                self.refresh();
+                self.is_open = true;
+                self.focus = Focus::ON;
+                Message::NoAction
+            }
+            Err(e) => Message::Error(e),
+        }
+    }
+
+    fn close(&mut self) {
+        self.cancel();
+        self.is_open = false;
+        self.focus = Focus::Off;
+        self.list_state.select(None);
+    }
+
+    // Ranks the files against the query. Only paths are matched without a query, which is
+    // cheap enough to do at once; line contents are matched on a worker, aborting the
+    // ranking of the previous query.
+    fn refresh(&mut self) {
+        self.cancel();
+        if self.query.is_empty() {
+            if let Some(matches) = rank(&self.files, "", &CancelToken::default()) {
+                self.show(matches);
+            }
+            return;
+        }
+        let cancel = CancelToken::default();
+        let files = Arc::clone(&self.files);
+        let query = self.query.to_owned();
+        let token = cancel.clone();
+        let handle = std::thread::spawn(move || rank(&files, &query, &token));
+        self.pending = Some((handle, cancel));
+    }
+
+    // Collects the ranking once the worker is done; called once per tick.
+    pub fn poll(&mut self) {
+        if !self
+            .pending
+            .as_ref()
+            .is_some_and(|(handle, _)| handle.is_finished())
+        {
+            return;
+        }
+        if let Some((handle, _)) = self.pending.take() {
+            if let Ok(Some(matches)) = handle.join() {
+                self.show(matches);
+            }
+        }
+    }
+
+    // Blocks until the running ranking, if any, is done.
+    pub fn wait(&mut self) {
+        while self.pending.is_some() {
+            self.poll();
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+    }
+
+    // Stops a running ranking; the worker notices the token and exits on its own.
+    fn cancel(&mut self) {
+        if let Some((_, cancel)) = self.pending.take() {
+            cancel.cancel();
+        }
+    }
+
+    fn show(&mut self, matches: Vec<PickerMatch>) {
+        self.matches = matches;
+        self.list_state.select(if self.matches.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+    }
+
+    fn selected(&self) -> Option<&PickerMatch> {
+        self.list_state.selected().and_then(|i| self.matches.get(i))
+    }
+
+    fn preview(&self, height: usize) -> Vec<Line<'static>> {
+        let Some(selected) = self.selected() else {
+            return vec![];
+        };
+        let Some((_, content)) = self.files.iter().find(|(path, _)| *path == selected.path) else {
+            return vec![];
+        };
+        let line = selected.line.unwrap_or(1);
+        // keep the match in the upper third of the pane
+        let start = line.saturating_sub(height / 3 + 1);
+        content
+            .lines()
+            .enumerate()
+            .skip(start)
+            .take(height)
+            .map(|(index, text)| {
+                let style = if selected.line == Some(index + 1) {
+                    Style::default().fg(self.theme.emphasis)
+                } else {
+                    Style::default()
+                };
+                Line::styled(format!("{:4} {}", index + 1, text), style)
+            })
+            .collect()
+    }
+
+    fn _handle_message(&mut self, message: &Message) -> Message {
+        match message {
+            Message::Once(OnceOperation::OpenPicker) => return self.open(),
+            Message::Once(OnceOperation::ClosePicker | OnceOperation::OpenLocation { .. }) => {
+                self.close()
+            }
+            _ => {}
+        }
+        Message::NoAction
+    }
+}
+
+impl OperatableComponent for PickerModal {
+    fn draw(&mut self, frame: &mut Frame, rect: Rect) {
+        if !self.is_open {
+            return;
+        }
+
+        let popup_area = centered_rect(90, 80, rect);
+        frame.render_widget(Clear, popup_area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(1)])
+            .split(popup_area);
+        let input = Paragraph::new(self.query.as_str()).block(
+            Block::bordered()
+                .title("Find files and lines (Enter: jump, Esc: close)")
+                .style(match self.focus {
+                    Focus::ON => Style::default(),
+                    Focus::Off => Style::default().fg(self.theme.unfocused),
+                }),
+        );
+        frame.render_widget(input, chunks[0]);
+
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(chunks[1]);
+        let results_block = Block::bordered().title(if self.pending.is_some() {
+            format!("{} matches (matching…)", self.matches.len())
+        } else {
+            format!("{} matches", self.matches.len())
+        });
+        if self.matches.is_empty() {
+            let empty_msg = Paragraph::new("No matches")
+                .alignment(Alignment::Center)
+                .block(results_block);
+            frame.render_widget(empty_msg, panes[0]);
+        } else {
+            let items: Vec<ListItem> = self
+                .matches
+                .iter()
+                .map(|found| match found.line {
+                    Some(line) => ListItem::new(Line::from(vec![
+                        Span::styled(
+                            format!("{}:{}", found.path, line),
+                            Style::default().fg(self.theme.heading),
+                        ),
+                        Span::raw(" "),
+                        Span::raw(found.text.to_owned()),
+                    ])),
+                    None => ListItem::new(Line::from(Span::styled(
+                        found.path.to_owned(),
+                        Style::default().fg(self.theme.heading),
+                    ))),
+                })
+                .collect();
+            let list = List::new(items)
+                .block(results_block)
+                .highlight_style(Style::default().bg(self.theme.selection))
+                .highlight_symbol("→ ");
+            frame.render_stateful_widget(list, panes[0], &mut self.list_state);
+        }
+
+        let preview_block = Block::bordered().title(
+            self.selected()
+                .map(|found| found.path.to_owned())
+                .unwrap_or_default(),
+        );
+        let height = preview_block.inner(panes[1]).height as usize;
+        let preview = Paragraph::new(self.preview(height)).block(preview_block);
+        frame.render_widget(preview, panes[1]);
+    }
+
+    fn process_focus(&mut self) {
+        match self.focus {
+            Focus::Off => self.focus = Focus::ON,
+            Focus::ON => self.focus = Focus::Off,
+        }
+    }
+
+    fn process_events(&mut self, events: KeyCode) -> Message {
+        if !self.is_open {
+            return Message::NoAction;
+        }
+
+        match events {
+            KeyCode::Esc => return Message::Once(OnceOperation::ClosePicker),
+            KeyCode::Enter => {
+                if let Some(found) = self.selected() {
+                    return Message::Once(OnceOperation::OpenLocation {
+                        commit_id: Some(self.commit_id.to_owned()),
+                        file: found.path.to_owned(),
+                        line: found.line.unwrap_or(1),
+                    });
+                }
+            }
+            KeyCode::Up => {
+                let selected = self.list_state.selected().unwrap_or(0);
+                self.list_state.select(Some(selected.saturating_sub(1)));
+            }
+            KeyCode::Down => {
+                if let Some(selected) = self.list_state.selected() {
+                    if selected + 1 < self.matches.len() {
+                        self.list_state.select(Some(selected + 1));
+                    }
+                }
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.refresh();
+            }
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.refresh();
+            }
+            _ => {}
+        }
+        Message::NoAction
+    }
+
+    fn handle_message(&mut self, message: &Message) -> Message {
+        match (message, self._handle_message(message)) {
+            (Message::MultipleTimes(_), Message::MultipleTimes(_)) => unreachable!(),
+            (Message::Once(_), Message::MultipleTimes(_)) => unreachable!(),
+            (Message::Once(_), Message::Once(_)) => unreachable!(),
+            (Message::NoAction, Message::MultipleTimes(_)) => unreachable!(),
+            (Message::NoAction, Message::Once(_)) => unreachable!(),
+            (_, new_message) => new_message,
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+}
+
+// Ranks paths and line contents against the query together, best first; None once canceled.
+fn rank(files: &[(String, String)], query: &str, cancel: &CancelToken) -> Option<Vec<PickerMatch>> {
+    let matcher = SkimMatcherV2::default();
+    let mut scored: Vec<(i64, PickerMatch)> = vec![];
+    for (path, content) in files {
+        if cancel.is_canceled() {
+            return None;
+        }
+        if let Some(score) = matcher.fuzzy_match(path, query) {
+            scored.push((
+                score,
+                PickerMatch {
+                    path: path.to_owned(),
+                    line: None,
+                    text: String::new(),
+                },
+            ));
+        }
+        if query.is_empty() {
+            continue;
+        }
+        for (index, line) in content.lines().enumerate() {
+            let text = line.trim();
+            if text.is_empty() {
+                continue;
+            }
+            if let Some(score) = matcher.fuzzy_match(text, query) {
+                scored.push((
+                    score,
+                    PickerMatch {
+                        path: path.to_owned(),
+                        line: Some(index + 1),
+                        text: text.to_owned(),
+                    },
+                ));
+            }
+        }
+    }
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    Some(
+        scored
+            .into_iter()
+            .take(MAX_RESULTS)
+            .map(|(_, found)| found)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_mock_repo() -> Arc<Mutex<RepositoryInfo>> {
+        crate::components::create_test_repo(
+            "picker_modal",
+            &[
+                ("main.rs", "fn main() {\n    render_frame();\n}\n"),
+                ("render.rs", "fn render_frame() {}\n"),
+            ],
+        )
+    }
+
+    fn type_query(modal: &mut PickerModal, query: &str) {
+        for c in query.chars() {
+            modal.process_events(KeyCode::Char(c));
+        }
+        modal.wait();
+    }
+
+    #[test]
+    fn test_picker_matches_paths_and_lines() {
+        let mock_repo = create_mock_repo();
+        let mut modal = PickerModal::new(Arc::clone(&mock_repo));
+        modal.handle_message(&Message::Once(OnceOperation::OpenPicker));
+        assert!(modal.is_open());
+
+        // Without a query every file is listed
+        assert_eq!(
+            modal
+                .matches
+                .iter()
+                .map(|found| found.path.as_str())
+                .collect::<Vec<_>>(),
+            vec!["main.rs", "render.rs"]
+        );
+
+        type_query(&mut modal, "render");
+        assert!(modal.matches.contains(&PickerMatch {
+            path: "render.rs".to_owned(),
+            line: None,
+            text: String::new(),
+        }));
+        assert!(modal.matches.contains(&PickerMatch {
+            path: "main.rs".to_owned(),
+            line: Some(2),
+            text: "render_frame();".to_owned(),
+        }));
+
+        type_query(&mut modal, "_frame()");
+        modal.process_events(KeyCode::Backspace);
+        modal.wait();
+        assert_eq!(modal.query, "render_frame(");
+        let index = modal
+            .matches
+            .iter()
+            .position(|found| found.path == "main.rs" && found.line == Some(2))
+            .unwrap();
+        for _ in 0..index {
+            modal.process_events(KeyCode::Down);
+        }
+        assert_eq!(
+            modal.preview(3),
+            vec![
+                Line::styled("   1 fn main() {", Style::default()),
+                Line::styled(
+                    "   2     render_frame();",
+                    Style::default().fg(Theme::dark().emphasis)
+                ),
+                Line::styled("   3 }", Style::default()),
+            ]
+        );
+
+        let message = modal.process_events(KeyCode::Enter);
+        let commit_id = mock_repo.lock().unwrap().get_current_commit_id();
+        assert_eq!(
+            message,
+            Message::Once(OnceOperation::OpenLocation {
+                commit_id: Some(commit_id),
+                file: "main.rs".to_owned(),
+                line: 2,
+            })
+        );
+        modal.handle_message(&message);
+        assert!(!modal.is_open());
+    }
+
+    #[test]
+    fn test_picker_without_matches() {
+        let mut modal = PickerModal::new(create_mock_repo());
+        modal.handle_message(&Message::Once(OnceOperation::OpenPicker));
+        type_query(&mut modal, "zzzz");
+        assert!(modal.matches.is_empty());
+        assert_eq!(modal.process_events(KeyCode::Enter), Message::NoAction);
+        assert_eq!(
+            modal.process_events(KeyCode::Esc),
+            Message::Once(OnceOperation::ClosePicker)
+        );
+    }
+
+    #[test]
+    fn test_picker_ranks_in_background() {
+        let mut modal = PickerModal::new(create_mock_repo());
+        modal.handle_message(&Message::Once(OnceOperation::OpenPicker));
+        modal.process_events(KeyCode::Char('z'));
+        assert!(modal.pending.is_some());
+
+        // a newer query replaces the ranking of the older one
+        let (_, older) = modal.pending.as_ref().unwrap();
+        let older = older.clone();
+        modal.process_events(KeyCode::Backspace);
+        assert!(older.is_canceled());
+        assert!(modal.pending.is_none());
+        assert_eq!(modal.matches.len(), 2);
+
+        let files = vec![("main.rs".to_owned(), "fn main() {}\n".to_owned())];
+        let cancel = CancelToken::default();
+        cancel.cancel();
+        assert_eq!(rank(&files, "main", &cancel), None);
+        assert_eq!(
+            rank(&files, "main", &CancelToken::default()).unwrap().len(),
+            2
+        );
+    }
+}
@@ -0,0 +1,281 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    widgets::{Block, Clear, Paragraph},
+    Frame,
+};
+
+use crate::theme::Theme;
+
+use super::operatable_components::{Focus, Message, OnceOperation, OperatableComponent};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PromptKind {
+    BaseRef,
+    Note,
+    ExportNotes,
+    ExportSummary,
+    CompareRefs,
+    Prefix,
+    ContainedIn,
+    MergeBase,
+    TrailerFilter,
+    DateRange,
+    ExportScreen,
+    GoToLine,
+}
+
+impl PromptKind {
+    fn title(self) -> &'static str {
+        match self {
+            PromptKind::BaseRef => "Base ref for review diff (empty to clear)",
+            PromptKind::Note => "Note for the current line",
+            PromptKind::ExportNotes => "Export notes as Markdown to (default: gview-notes.md)",
+            PromptKind::CompareRefs => "Compare refs as A..B (empty to clear)",
+            PromptKind::ContainedIn => "Is the current commit in (branch, tag or commit)",
+            PromptKind::MergeBase => "Merge-base of the current commit with",
+            PromptKind::TrailerFilter => "Show commits with a trailer matching (empty to clear)",
+            PromptKind::DateRange => {
+                "Show commits from SINCE..UNTIL as YYYY-MM-DD, either optional (empty to clear)"
+            }
+            PromptKind::Prefix => {
+                "cd: scope the session to a directory (empty for the whole repository)"
+            }
+            PromptKind::ExportSummary => {
+                "Export review summary as Markdown to (default: gview-review.md)"
+            }
+            PromptKind::ExportScreen => {
+                "Save the screen to (default: gview-screen.txt, *.ans with colors, + to copy)"
+            }
+            PromptKind::GoToLine => "Go to line",
+        }
+    }
+}
+
+pub struct PromptModal {
+    focus: Focus,
+    is_open: bool,
+    kind: PromptKind,
+    input: String,
+    error: Option<String>,
+    theme: Theme,
+}
+
+impl PromptModal {
+    pub fn new() -> Self {
+        Self {
+            focus: Focus::Off,
+            is_open: false,
+            kind: PromptKind::BaseRef,
+            input: "".to_owned(),
+            error: None,
+            theme: Theme::default(),
+        }
+    }
+
+    // Keeps the prompt open and shows why the submitted value was rejected.
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    fn open(&mut self, kind: PromptKind) {
+        self.is_open = true;
+        self.focus = Focus::ON;
+        self.kind = kind;
+        self.input.clear();
+        self.error = None;
+    }
+
+    fn close(&mut self) {
+        self.is_open = false;
+        self.focus = Focus::Off;
+        self.input.clear();
+        self.error = None;
+    }
+
+    fn _handle_message(&mut self, message: &Message) -> Message {
+        match message {
+            Message::Once(OnceOperation::OpenPrompt { kind }) => self.open(*kind),
+            Message::Once(OnceOperation::ClosePrompt) => self.close(),
+            _ => {}
+        }
+        Message::NoAction
+    }
+}
+
+impl OperatableComponent for PromptModal {
+    fn draw(&mut self, frame: &mut Frame, rect: Rect) {
+        if !self.is_open {
+            return;
+        }
+
+        let popup_area = centered_line(60, 3, rect);
+        frame.render_widget(Clear, popup_area);
+
+        let title = match &self.error {
+            Some(error) => format!("{} - {}", self.kind.title(), error),
+            None => self.kind.title().to_owned(),
+        };
+        let block = Block::bordered()
+            .title(title)
+            .border_style(match self.error {
+                Some(_) => Style::default().fg(self.theme.error),
+                None => Style::default(),
+            });
+        let inner_area = block.inner(popup_area);
+        frame.render_widget(block, popup_area);
+
+        let width = inner_area.width as usize;
+        let overflow = self.input.chars().count().saturating_sub(width);
+        let visible: String = self.input.chars().skip(overflow).collect();
+        let cursor = inner_area.x + visible.chars().count() as u16;
+        frame.render_widget(Paragraph::new(visible), inner_area);
+        frame.set_cursor(cursor.min(inner_area.right()), inner_area.y);
+    }
+
+    fn process_focus(&mut self) {
+        // The prompt always owns the focus while it is open
+    }
+
+    fn process_events(&mut self, events: KeyCode) -> Message {
+        if !self.is_open {
+            return Message::NoAction;
+        }
+
+        match events {
+            KeyCode::Esc => return Message::Once(OnceOperation::ClosePrompt),
+            KeyCode::Enter => {
+                return Message::Once(OnceOperation::SubmitPrompt {
+                    kind: self.kind,
+                    value: self.input.trim().to_owned(),
+                })
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                self.error = None;
+            }
+            KeyCode::Char(char) => {
+                self.input.push(char);
+                self.error = None;
+            }
+            _ => {}
+        }
+        Message::NoAction
+    }
+
+    fn handle_message(&mut self, message: &Message) -> Message {
+        match (message, self._handle_message(message)) {
+            (Message::MultipleTimes(_), Message::MultipleTimes(_)) => unreachable!(),
+            (Message::Once(_), Message::MultipleTimes(_)) => unreachable!(),
+            (Message::Once(_), Message::Once(_)) => unreachable!(),
+            (Message::NoAction, Message::MultipleTimes(_)) => unreachable!(),
+            (Message::NoAction, Message::Once(_)) => unreachable!(),
+            (_, new_message) => new_message,
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+}
+
+fn centered_line(percent_x: u16, height: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Fill(1),
+            Constraint::Length(height),
+            Constraint::Fill(1),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_modal_open_and_close() {
+        let mut prompt = PromptModal::new();
+        assert!(!prompt.is_open());
+
+        prompt.handle_message(&Message::Once(OnceOperation::OpenPrompt {
+            kind: PromptKind::BaseRef,
+        }));
+        assert!(prompt.is_open());
+        assert_eq!(prompt.focus, Focus::ON);
+
+        prompt.handle_message(&Message::Once(OnceOperation::ClosePrompt));
+        assert!(!prompt.is_open());
+        assert_eq!(prompt.focus, Focus::Off);
+    }
+
+    #[test]
+    fn test_prompt_modal_ignores_keys_when_closed() {
+        let mut prompt = PromptModal::new();
+        assert_eq!(prompt.process_events(KeyCode::Char('a')), Message::NoAction);
+        assert!(prompt.input.is_empty());
+    }
+
+    #[test]
+    fn test_prompt_modal_editing_and_submit() {
+        let mut prompt = PromptModal::new();
+        prompt.handle_message(&Message::Once(OnceOperation::OpenPrompt {
+            kind: PromptKind::BaseRef,
+        }));
+
+        for char in "mainx".chars() {
+            prompt.process_events(KeyCode::Char(char));
+        }
+        prompt.process_events(KeyCode::Backspace);
+        assert_eq!(prompt.input, "main");
+
+        let message = prompt.process_events(KeyCode::Enter);
+        assert_eq!(
+            message,
+            Message::Once(OnceOperation::SubmitPrompt {
+                kind: PromptKind::BaseRef,
+                value: "main".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_prompt_modal_escape_closes() {
+        let mut prompt = PromptModal::new();
+        prompt.handle_message(&Message::Once(OnceOperation::OpenPrompt {
+            kind: PromptKind::BaseRef,
+        }));
+        assert_eq!(
+            prompt.process_events(KeyCode::Esc),
+            Message::Once(OnceOperation::ClosePrompt)
+        );
+    }
+
+    #[test]
+    fn test_prompt_modal_error_is_cleared_on_edit() {
+        let mut prompt = PromptModal::new();
+        prompt.handle_message(&Message::Once(OnceOperation::OpenPrompt {
+            kind: PromptKind::BaseRef,
+        }));
+        prompt.set_error("not found".to_owned());
+        assert!(prompt.error.is_some());
+
+        prompt.process_events(KeyCode::Char('x'));
+        assert!(prompt.error.is_none());
+    }
+}
@@ -0,0 +1,234 @@
+use std::sync::{Arc, Mutex};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{error::GviewError, repository::RepositoryInfo, theme::Theme};
+
+use super::{
+    centered_rect,
+    operatable_components::{Focus, Message, OnceOperation, OperatableComponent},
+};
+
+pub struct RemoteModal {
+    focus: Focus,
+    is_open: bool,
+    // (name, URL) of each remote
+    remotes: Vec<(String, String)>,
+    // the remote browser links use now
+    current: String,
+    list_state: ListState,
+    repository: Arc<Mutex<RepositoryInfo>>,
+    theme: Theme,
+}
+
+impl RemoteModal {
+    pub fn new(repository: Arc<Mutex<RepositoryInfo>>) -> Self {
+        Self {
+            focus: Focus::Off,
+            is_open: false,
+            remotes: Vec::new(),
+            current: String::new(),
+            list_state: ListState::default(),
+            repository,
+            theme: Theme::default(),
+        }
+    }
+
+    fn open(&mut self) -> Message {
+        let loaded = match self.repository.lock() {
+            Ok(repo) => repo
+                .remotes()
+                .map(|remotes| (remotes, repo.remote_name()))
+                .map_err(|e| GviewError::new("Failed to list remotes", e)),
+            Err(_) => Err(GviewError::Lock),
+        };
+        let (remotes, current) = match loaded {
+            Ok(loaded) => loaded,
+            Err(e) => return Message::Error(e),
+        };
+        self.is_open = true;
+        self.focus = Focus::ON;
+        // start on the remote in use
+        let selected = remotes.iter().position(|(name, _)| *name == current);
+        self.list_state
+            .select(selected.or(if remotes.is_empty() { None } else { Some(0) }));
+        self.remotes = remotes;
+        self.current = current;
+        Message::NoAction
+    }
+
+    fn close(&mut self) {
+        self.is_open = false;
+        self.focus = Focus::Off;
+        self.list_state.select(None);
+    }
+
+    fn _handle_message(&mut self, message: &Message) -> Message {
+        match message {
+            Message::Once(OnceOperation::OpenRemoteModal) => return self.open(),
+            Message::Once(OnceOperation::CloseRemoteModal | OnceOperation::SelectRemote { .. }) => {
+                self.close()
+            }
+            _ => {}
+        }
+        Message::NoAction
+    }
+}
+
+impl OperatableComponent for RemoteModal {
+    fn draw(&mut self, frame: &mut Frame, rect: Rect) {
+        if !self.is_open {
+            return;
+        }
+
+        let popup_area = centered_rect(70, 40, rect);
+        frame.render_widget(Clear, popup_area);
+
+        let block = Block::bordered()
+            .title("Remote for browser links (Enter: use, Esc: close)")
+            .style(match self.focus {
+                Focus::ON => Style::default(),
+                Focus::Off => Style::default().fg(self.theme.unfocused),
+            });
+        let inner_area = block.inner(popup_area);
+        frame.render_widget(block, popup_area);
+
+        if self.remotes.is_empty() {
+            let empty_msg = Paragraph::new("No remotes configured")
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true });
+            frame.render_widget(empty_msg, inner_area);
+            return;
+        }
+
+        let items: Vec<ListItem> = self
+            .remotes
+            .iter()
+            .map(|(name, url)| {
+                let marker = if *name == self.current { "* " } else { "  " };
+                ListItem::new(Line::from(vec![
+                    Span::raw(marker),
+                    Span::styled(name.to_owned(), Style::default().fg(self.theme.emphasis)),
+                    Span::raw(" "),
+                    Span::styled(url.to_owned(), Style::default().fg(self.theme.heading)),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(Style::default().bg(self.theme.selection))
+            .highlight_symbol("→ ");
+        frame.render_stateful_widget(list, inner_area, &mut self.list_state);
+    }
+
+    fn process_focus(&mut self) {
+        match self.focus {
+            Focus::Off => self.focus = Focus::ON,
+            Focus::ON => self.focus = Focus::Off,
+        }
+    }
+
+    fn process_events(&mut self, events: KeyCode) -> Message {
+        if !self.is_open {
+            return Message::NoAction;
+        }
+
+        match events {
+            KeyCode::Esc => return Message::Once(OnceOperation::CloseRemoteModal),
+            KeyCode::Enter => {
+                if let Some((name, _)) =
+                    self.list_state.selected().and_then(|i| self.remotes.get(i))
+                {
+                    return Message::Once(OnceOperation::SelectRemote {
+                        name: name.to_owned(),
+                    });
+                }
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                let selected = self.list_state.selected().unwrap_or(0);
+                self.list_state.select(Some(selected.saturating_sub(1)));
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if let Some(selected) = self.list_state.selected() {
+                    if selected + 1 < self.remotes.len() {
+                        self.list_state.select(Some(selected + 1));
+                    }
+                }
+            }
+            _ => {}
+        }
+        Message::NoAction
+    }
+
+    fn handle_message(&mut self, message: &Message) -> Message {
+        match (message, self._handle_message(message)) {
+            (Message::MultipleTimes(_), Message::MultipleTimes(_)) => unreachable!(),
+            (Message::Once(_), Message::MultipleTimes(_)) => unreachable!(),
+            (Message::Once(_), Message::Once(_)) => unreachable!(),
+            (Message::NoAction, Message::MultipleTimes(_)) => unreachable!(),
+            (Message::NoAction, Message::Once(_)) => unreachable!(),
+            (_, new_message) => new_message,
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn create_mock_repo() -> Arc<Mutex<RepositoryInfo>> {
+        let repo = crate::components::init_test_repo("remote_modal", &[]);
+        repo.remote("origin", "git@github.com:me/gview.git")
+            .unwrap();
+        repo.remote("upstream", "https://github.com/kyoto7250/gview.git")
+            .unwrap();
+        let oid = repo.head().unwrap().target().unwrap();
+        Arc::new(Mutex::new(RepositoryInfo::_from_parts(repo, oid)))
+    }
+
+    #[test]
+    fn test_remote_modal_selects_remote() {
+        let mut modal = RemoteModal::new(create_mock_repo());
+        modal.handle_message(&Message::Once(OnceOperation::OpenRemoteModal));
+        assert!(modal.is_open());
+        assert_eq!(modal.current, "origin");
+        assert_eq!(modal.list_state.selected(), Some(0));
+
+        let mut terminal = Terminal::new(TestBackend::new(70, 10)).unwrap();
+        terminal
+            .draw(|frame| modal.draw(frame, frame.size()))
+            .unwrap();
+        assert_snapshot!(terminal.backend());
+
+        modal.process_events(KeyCode::Char('j'));
+        assert_eq!(
+            modal.process_events(KeyCode::Enter),
+            Message::Once(OnceOperation::SelectRemote {
+                name: "upstream".to_owned()
+            })
+        );
+        assert_eq!(
+            modal.process_events(KeyCode::Esc),
+            Message::Once(OnceOperation::CloseRemoteModal)
+        );
+        modal.handle_message(&Message::Once(OnceOperation::CloseRemoteModal));
+        assert!(!modal.is_open());
+    }
+}
@@ -0,0 +1,351 @@
+use std::{
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    error::GviewError,
+    progress::{CancelToken, ProgressReporter},
+    repository::{RepositoryInfo, SearchHit},
+    theme::Theme,
+};
+
+use super::{
+    centered_rect,
+    operatable_components::{Focus, Message, OnceOperation, OperatableComponent},
+};
+
+pub struct SearchModal {
+    focus: Focus,
+    is_open: bool,
+    word: String,
+    // commit the search ran at, so jumps stay pinned to it
+    commit_id: String,
+    hits: Vec<SearchHit>,
+    list_state: ListState,
+    // running search and the token that aborts it
+    pending: Option<(JoinHandle<anyhow::Result<Vec<SearchHit>>>, CancelToken)>,
+    error: Option<String>,
+    repository: Arc<Mutex<RepositoryInfo>>,
+    progress: ProgressReporter,
+    theme: Theme,
+}
+
+impl SearchModal {
+    pub fn new(repository: Arc<Mutex<RepositoryInfo>>, progress: ProgressReporter) -> Self {
+        Self {
+            focus: Focus::Off,
+            is_open: false,
+            word: String::new(),
+            commit_id: String::new(),
+            hits: Vec::new(),
+            list_state: ListState::default(),
+            pending: None,
+            error: None,
+            repository,
+            progress,
+            theme: Theme::default(),
+        }
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    fn open(&mut self, word: &str) -> Message {
+        self.cancel();
+        let cancel = CancelToken::default();
+        let (commit_id, handle) = match self.repository.lock() {
+            Ok(repo) => (
+                repo.get_current_commit_id(),
+                repo.spawn_search(word, self.progress.clone(), cancel.clone()),
+            ),
+            Err(_) => return Message::Error(GviewError::Lock),
+        };
+        word.clone_into(&mut self.word);
+        self.commit_id = commit_id;
+        self.hits.clear();
+        self.error = None;
+        self.list_state.select(None);
+        self.pending = Some((handle, cancel));
+        self.is_open = true;
+        self.focus = Focus::ON;
+        Message::NoAction
+    }
+
+    // Collects the result once the worker is done; called once per tick.
+    pub fn poll(&mut self) {
+        if !self
+            .pending
+            .as_ref()
+            .is_some_and(|(handle, _)| handle.is_finished())
+        {
+            return;
+        }
+        let Some((handle, _)) = self.pending.take() else {
+            return;
+        };
+        match handle.join() {
+            Ok(Ok(hits)) => {
+                self.list_state
+                    .select(if hits.is_empty() { None } else { Some(0) });
+                self.hits = hits;
+            }
+            Ok(Err(e)) => self.error = Some(format!("Search failed: {}", e)),
+            Err(_) => self.error = Some("Search failed".to_owned()),
+        }
+    }
+
+    // Blocks until the running search, if any, is done.
+    pub fn wait(&mut self) {
+        while self.pending.is_some() {
+            self.poll();
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+    }
+
+    // Stops a running search; the worker notices the token and exits on its own.
+    fn cancel(&mut self) {
+        if let Some((_, cancel)) = self.pending.take() {
+            cancel.cancel();
+        }
+    }
+
+    fn close(&mut self) {
+        self.cancel();
+        self.is_open = false;
+        self.focus = Focus::Off;
+        self.list_state.select(None);
+    }
+
+    fn _handle_message(&mut self, message: &Message) -> Message {
+        match message {
+            Message::Once(OnceOperation::OpenSearchModal { word }) => return self.open(word),
+            Message::Once(OnceOperation::CloseSearchModal | OnceOperation::OpenLocation { .. }) => {
+                self.close()
+            }
+            _ => {}
+        }
+        Message::NoAction
+    }
+}
+
+impl OperatableComponent for SearchModal {
+    fn draw(&mut self, frame: &mut Frame, rect: Rect) {
+        if !self.is_open {
+            return;
+        }
+
+        let popup_area = centered_rect(80, 60, rect);
+        frame.render_widget(Clear, popup_area);
+
+        let title = if self.is_searching() {
+            format!("Searching for `{}` (Esc: cancel)", self.word)
+        } else {
+            format!(
+                "{} matches of `{}` (Enter: jump, Esc: close)",
+                self.hits.len(),
+                self.word
+            )
+        };
+        let block = Block::bordered().title(title).style(match self.focus {
+            Focus::ON => Style::default(),
+            Focus::Off => Style::default().fg(self.theme.unfocused),
+        });
+        let inner_area = block.inner(popup_area);
+        frame.render_widget(block, popup_area);
+
+        if self.hits.is_empty() {
+            let empty_msg = Paragraph::new(match (&self.error, self.is_searching()) {
+                (Some(error), _) => error.as_str(),
+                (None, true) => "Searching...",
+                (None, false) => "No matches at this commit",
+            })
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+            frame.render_widget(empty_msg, inner_area);
+            return;
+        }
+
+        let items: Vec<ListItem> = self
+            .hits
+            .iter()
+            .map(|hit| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{}:{}", hit.path, hit.line),
+                        Style::default().fg(self.theme.heading),
+                    ),
+                    Span::raw(" "),
+                    Span::raw(hit.text.to_owned()),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(Style::default().bg(self.theme.selection))
+            .highlight_symbol("→ ");
+        frame.render_stateful_widget(list, inner_area, &mut self.list_state);
+    }
+
+    fn process_focus(&mut self) {
+        match self.focus {
+            Focus::Off => self.focus = Focus::ON,
+            Focus::ON => self.focus = Focus::Off,
+        }
+    }
+
+    fn process_events(&mut self, events: KeyCode) -> Message {
+        if !self.is_open {
+            return Message::NoAction;
+        }
+
+        match events {
+            KeyCode::Esc => return Message::Once(OnceOperation::CloseSearchModal),
+            KeyCode::Enter => {
+                if let Some(hit) = self.list_state.selected().and_then(|i| self.hits.get(i)) {
+                    return Message::Once(OnceOperation::OpenLocation {
+                        commit_id: Some(self.commit_id.to_owned()),
+                        file: hit.path.to_owned(),
+                        line: hit.line,
+                    });
+                }
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                let selected = self.list_state.selected().unwrap_or(0);
+                self.list_state.select(Some(selected.saturating_sub(1)));
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if let Some(selected) = self.list_state.selected() {
+                    if selected + 1 < self.hits.len() {
+                        self.list_state.select(Some(selected + 1));
+                    }
+                }
+            }
+            _ => {}
+        }
+        Message::NoAction
+    }
+
+    fn handle_message(&mut self, message: &Message) -> Message {
+        match (message, self._handle_message(message)) {
+            (Message::MultipleTimes(_), Message::MultipleTimes(_)) => unreachable!(),
+            (Message::Once(_), Message::MultipleTimes(_)) => unreachable!(),
+            (Message::Once(_), Message::Once(_)) => unreachable!(),
+            (Message::NoAction, Message::MultipleTimes(_)) => unreachable!(),
+            (Message::NoAction, Message::Once(_)) => unreachable!(),
+            (_, new_message) => new_message,
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress::ProgressTracker;
+
+    fn wait_for_search(modal: &mut SearchModal) {
+        while modal.is_searching() {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+            modal.poll();
+        }
+    }
+
+    fn create_mock_repo() -> Arc<Mutex<RepositoryInfo>> {
+        crate::components::create_test_repo(
+            "search_modal",
+            &[
+                ("main.rs", "fn main() {\n    helper();\n    helpers();\n}\n"),
+                ("util.rs", "fn helper() {}\n"),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_search_modal_lists_whole_word_matches() {
+        let mock_repo = create_mock_repo();
+        let mut modal = SearchModal::new(Arc::clone(&mock_repo), ProgressTracker::new().reporter());
+        assert_eq!(modal.process_events(KeyCode::Enter), Message::NoAction);
+
+        modal.handle_message(&Message::Once(OnceOperation::OpenSearchModal {
+            word: "helper".to_owned(),
+        }));
+        assert!(modal.is_open());
+        wait_for_search(&mut modal);
+        assert_eq!(
+            modal
+                .hits
+                .iter()
+                .map(|hit| (hit.path.as_str(), hit.line))
+                .collect::<Vec<_>>(),
+            vec![("main.rs", 2), ("util.rs", 1)]
+        );
+        assert_eq!(modal.hits[0].text, "helper();");
+
+        modal.process_events(KeyCode::Down);
+        modal.process_events(KeyCode::Down);
+        let message = modal.process_events(KeyCode::Enter);
+        let commit_id = mock_repo.lock().unwrap().get_current_commit_id();
+        assert_eq!(
+            message,
+            Message::Once(OnceOperation::OpenLocation {
+                commit_id: Some(commit_id),
+                file: "util.rs".to_owned(),
+                line: 1,
+            })
+        );
+
+        modal.handle_message(&message);
+        assert!(!modal.is_open());
+    }
+
+    #[test]
+    fn test_search_modal_esc_cancels_running_search() {
+        let mut modal = SearchModal::new(create_mock_repo(), ProgressTracker::new().reporter());
+        modal.handle_message(&Message::Once(OnceOperation::OpenSearchModal {
+            word: "helper".to_owned(),
+        }));
+        let (_, cancel) = modal.pending.as_ref().unwrap();
+        let cancel = cancel.clone();
+
+        let message = modal.process_events(KeyCode::Esc);
+        assert_eq!(message, Message::Once(OnceOperation::CloseSearchModal));
+        modal.handle_message(&message);
+        assert!(cancel.is_canceled());
+        assert!(!modal.is_searching());
+        assert!(!modal.is_open());
+    }
+
+    #[test]
+    fn test_search_modal_without_matches() {
+        let mut modal = SearchModal::new(create_mock_repo(), ProgressTracker::new().reporter());
+        modal.handle_message(&Message::Once(OnceOperation::OpenSearchModal {
+            word: "missing".to_owned(),
+        }));
+        assert!(modal.is_open());
+        wait_for_search(&mut modal);
+        assert_eq!(modal.list_state.selected(), None);
+        assert_eq!(modal.process_events(KeyCode::Enter), Message::NoAction);
+        assert_eq!(
+            modal.process_events(KeyCode::Esc),
+            Message::Once(OnceOperation::CloseSearchModal)
+        );
+    }
+}
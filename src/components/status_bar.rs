@@ -0,0 +1,336 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::Style,
+    widgets::Paragraph,
+    Frame,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    progress::{ProgressReporter, ProgressTracker},
+    theme::Theme,
+};
+
+use super::operatable_components::{Message, OperatableComponent};
+
+// How long a notice, or each error in turn, stays in the status bar.
+const NOTICE_DURATION: Duration = Duration::from_secs(8);
+// Errors kept waiting beyond the shown one; a failure repeating in a loop adds no more.
+const MAX_ERRORS: usize = 20;
+
+/// Where the user is, shown on the right of the status bar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewPosition {
+    pub commit: String,
+    // the shown file, its viewer mode and how far down it is scrolled
+    pub file: Option<(String, &'static str, usize)>,
+}
+
+impl ViewPosition {
+    // The longest description that fits in `room` columns: the commit and file with the mode
+    // and scroll percentage, then without the file path, then the commit alone.
+    fn describe(&self, room: usize) -> Option<String> {
+        let mut candidates = vec![];
+        if let Some((file, mode, percent)) = &self.file {
+            candidates.push(format!("{}  {}  {}  {}%", self.commit, file, mode, percent));
+            candidates.push(format!("{}  {}  {}%", self.commit, mode, percent));
+        }
+        candidates.push(self.commit.clone());
+        candidates
+            .into_iter()
+            .find(|candidate| candidate.width() <= room)
+    }
+}
+
+pub struct StatusBar {
+    progress: ProgressTracker,
+    // answer to the last query and when it was given
+    notice: Option<(String, Instant)>,
+    // errors shown one after another ahead of the notice, and when the first one came up
+    errors: VecDeque<String>,
+    error_since: Instant,
+    // first key of a chord waiting for the second
+    pending_key: Option<char>,
+    position: Option<ViewPosition>,
+    theme: Theme,
+}
+
+impl StatusBar {
+    pub fn new() -> Self {
+        Self {
+            progress: ProgressTracker::new(),
+            notice: None,
+            errors: VecDeque::new(),
+            error_since: Instant::now(),
+            pending_key: None,
+            position: None,
+            theme: Theme::default(),
+        }
+    }
+
+    pub fn notify(&mut self, notice: String) {
+        self.notice = Some((notice, Instant::now()));
+    }
+
+    // Queues `notice` to be shown in the color of removed lines, as something went wrong,
+    // once the errors before it are dismissed or have had their time.
+    pub fn notify_error(&mut self, notice: String) {
+        if self.errors.len() > MAX_ERRORS {
+            return;
+        }
+        if self.errors.is_empty() {
+            self.error_since = Instant::now();
+        }
+        self.errors.push_back(notice);
+    }
+
+    // Drops the shown error for the next one, if there is an error to drop.
+    pub fn dismiss_error(&mut self) -> bool {
+        let dismissed = self.errors.pop_front().is_some();
+        self.error_since = Instant::now();
+        dismissed
+    }
+
+    // The shown error, or else the notice.
+    pub fn notice(&self) -> Option<&str> {
+        self.errors
+            .front()
+            .or(self.notice.as_ref().map(|(notice, _)| notice))
+            .map(String::as_str)
+    }
+
+    pub fn set_pending_key(&mut self, key: Option<char>) {
+        self.pending_key = key;
+    }
+
+    pub fn set_position(&mut self, position: Option<ViewPosition>) {
+        self.position = position;
+    }
+
+    pub fn reporter(&self) -> ProgressReporter {
+        self.progress.reporter()
+    }
+
+    pub fn tick(&mut self) {
+        self.progress.tick();
+        if !self.errors.is_empty() && self.error_since.elapsed() >= NOTICE_DURATION {
+            self.dismiss_error();
+        }
+        if let Some((_, since)) = &self.notice {
+            if since.elapsed() >= NOTICE_DURATION {
+                self.notice = None;
+            }
+        }
+    }
+}
+
+impl OperatableComponent for StatusBar {
+    fn draw(&mut self, frame: &mut Frame, rect: Rect) {
+        let (status, color) = match (self.pending_key, self.progress.describe(), self.notice()) {
+            (Some(key), _, _) => (format!("{}-", key), self.theme.emphasis),
+            (None, Some(progress), _) => (progress, self.theme.emphasis),
+            // waiting errors are counted in front of the shown one
+            (None, None, Some(error)) if self.errors.len() > 1 => (
+                format!("[1/{}] {}", self.errors.len(), error),
+                self.theme.removed,
+            ),
+            (None, None, Some(error)) if !self.errors.is_empty() => {
+                (error.to_owned(), self.theme.removed)
+            }
+            (None, None, Some(notice)) => (notice.to_owned(), self.theme.heading),
+            (None, None, None) => ("Press ? for help".to_owned(), self.theme.unfocused),
+        };
+        // the position takes what the status leaves, keeping two columns between them
+        let room = (rect.width as usize).saturating_sub(status.width() + 2);
+        if let Some(position) = self.position.as_ref().and_then(|p| p.describe(room)) {
+            let position = Paragraph::new(position)
+                .alignment(Alignment::Right)
+                .style(Style::default().fg(self.theme.unfocused));
+            frame.render_widget(position, rect);
+        }
+        let status_area = Rect {
+            width: rect.width.min(status.width() as u16),
+            ..rect
+        };
+        frame.render_widget(
+            Paragraph::new(status).style(Style::default().fg(color)),
+            status_area,
+        );
+    }
+
+    // The status bar never takes focus or keys.
+    fn process_focus(&mut self) {}
+
+    fn process_events(&mut self, _events: KeyCode) -> Message {
+        Message::NoAction
+    }
+
+    fn handle_message(&mut self, _message: &Message) -> Message {
+        Message::NoAction
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn render(status_bar: &mut StatusBar) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(40, 1)).unwrap();
+        terminal
+            .draw(|frame| status_bar.draw(frame, frame.size()))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..40)
+            .map(|x| buffer.get(x, 0).symbol().to_owned())
+            .collect::<String>()
+            .trim_end()
+            .to_owned()
+    }
+
+    #[test]
+    fn test_status_bar_shows_progress() {
+        let mut status_bar = StatusBar::new();
+        assert_eq!(render(&mut status_bar), "Press ? for help");
+
+        let reporter = status_bar.reporter();
+        reporter.update("loading commits", 42, None);
+        status_bar.tick();
+        assert_eq!(render(&mut status_bar), "⠙ loading commits 42");
+
+        reporter.finish("loading commits");
+        status_bar.tick();
+        assert_eq!(render(&mut status_bar), "Press ? for help");
+    }
+
+    #[test]
+    fn test_status_bar_shows_notice() {
+        let mut status_bar = StatusBar::new();
+        status_bar.notify("abc12345 is in main".to_owned());
+        status_bar.tick();
+        assert_eq!(render(&mut status_bar), "abc12345 is in main");
+
+        // progress takes precedence while a task runs
+        let reporter = status_bar.reporter();
+        reporter.update("walking tree", 1, Some(2));
+        status_bar.tick();
+        assert_eq!(render(&mut status_bar), "⠹ walking tree 1/2");
+
+        reporter.finish("walking tree");
+        status_bar.notice = status_bar
+            .notice
+            .map(|(notice, since)| (notice, since - NOTICE_DURATION));
+        status_bar.tick();
+        assert_eq!(render(&mut status_bar), "Press ? for help");
+    }
+
+    #[test]
+    fn test_status_bar_shows_pending_key() {
+        let mut status_bar = StatusBar::new();
+        status_bar.notify("abc12345 is in main".to_owned());
+        status_bar.set_pending_key(Some('g'));
+        assert_eq!(render(&mut status_bar), "g-");
+        status_bar.set_pending_key(None);
+        assert_eq!(render(&mut status_bar), "abc12345 is in main");
+    }
+
+    #[test]
+    fn test_status_bar_shows_position() {
+        let mut status_bar = StatusBar::new();
+        status_bar.set_position(Some(ViewPosition {
+            commit: "abc12345".to_owned(),
+            file: Some(("src/main.rs".to_owned(), "blame", 42)),
+        }));
+        assert_eq!(
+            render(&mut status_bar),
+            "Press ? for help    abc12345  blame  42%"
+        );
+
+        // a longer status leaves less room, down to the commit alone
+        status_bar.notify("Copied 12 lines".to_owned());
+        assert_eq!(
+            render(&mut status_bar),
+            "Copied 12 lines     abc12345  blame  42%"
+        );
+        status_bar.notify("Browser links use upstream".to_owned());
+        assert_eq!(
+            render(&mut status_bar),
+            "Browser links use upstream      abc12345"
+        );
+
+        // with no file shown, only the commit
+        status_bar.set_position(Some(ViewPosition {
+            commit: "abc12345".to_owned(),
+            file: None,
+        }));
+        status_bar.notice = None;
+        assert_eq!(
+            render(&mut status_bar),
+            "Press ? for help                abc12345"
+        );
+        status_bar.set_position(Some(ViewPosition {
+            commit: "abc12345".to_owned(),
+            file: Some(("a.rs".to_owned(), "lines", 7)),
+        }));
+        let mut terminal = Terminal::new(TestBackend::new(50, 1)).unwrap();
+        terminal
+            .draw(|frame| status_bar.draw(frame, frame.size()))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let text: String = (0..50).map(|x| buffer.get(x, 0).symbol()).collect();
+        assert_eq!(text, "Press ? for help         abc12345  a.rs  lines  7%");
+    }
+
+    #[test]
+    fn test_status_bar_shows_error() {
+        let mut status_bar = StatusBar::new();
+        status_bar.notify_error("Failed to copy: no terminal".to_owned());
+        let mut terminal = Terminal::new(TestBackend::new(40, 1)).unwrap();
+        terminal
+            .draw(|frame| status_bar.draw(frame, frame.size()))
+            .unwrap();
+        assert_eq!(
+            terminal.backend().buffer().get(0, 0).fg,
+            Theme::default().removed
+        );
+
+        // the error outlasts later notices, which show once it goes
+        status_bar.notify("Copied abc".to_owned());
+        assert_eq!(render(&mut status_bar), "Failed to copy: no terminal");
+        assert!(status_bar.dismiss_error());
+        assert_eq!(render(&mut status_bar), "Copied abc");
+        assert!(!status_bar.dismiss_error());
+    }
+
+    #[test]
+    fn test_status_bar_queues_errors() {
+        let mut status_bar = StatusBar::new();
+        status_bar.notify_error("Failed to copy".to_owned());
+        status_bar.notify_error("Failed to open".to_owned());
+        assert_eq!(render(&mut status_bar), "[1/2] Failed to copy");
+
+        // each one gets its time before the next comes up
+        status_bar.tick();
+        assert_eq!(status_bar.notice(), Some("Failed to copy"));
+        status_bar.error_since -= NOTICE_DURATION;
+        status_bar.tick();
+        assert_eq!(render(&mut status_bar), "Failed to open");
+        status_bar.dismiss_error();
+        assert_eq!(render(&mut status_bar), "Press ? for help");
+
+        for _ in 0..100 {
+            status_bar.notify_error("Failed again".to_owned());
+        }
+        assert_eq!(status_bar.errors.len(), MAX_ERRORS + 1);
+    }
+}
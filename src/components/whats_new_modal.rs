@@ -0,0 +1,155 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{changelog::Release, theme::Theme};
+
+use super::{
+    centered_rect,
+    operatable_components::{Message, OnceOperation, OperatableComponent},
+};
+
+// Lists the changes of the releases since the last run, once after an upgrade.
+pub struct WhatsNewModal {
+    releases: Vec<Release>,
+    scroll: u16,
+    theme: Theme,
+}
+
+impl WhatsNewModal {
+    pub fn new() -> Self {
+        Self {
+            releases: vec![],
+            scroll: 0,
+            theme: Theme::default(),
+        }
+    }
+
+    pub fn open(&mut self, releases: Vec<Release>) {
+        self.releases = releases;
+        self.scroll = 0;
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let mut lines = vec![];
+        for release in &self.releases {
+            if !lines.is_empty() {
+                lines.push(Line::from(""));
+            }
+            lines.push(Line::from(Span::styled(
+                format!("gview {}", release.version),
+                Style::default()
+                    .fg(self.theme.heading)
+                    .add_modifier(Modifier::BOLD),
+            )));
+            lines.extend(release.changes.iter().map(|change| {
+                Line::from(vec![
+                    Span::styled("• ", Style::default().fg(self.theme.emphasis)),
+                    Span::styled(change.to_owned(), Style::default().fg(self.theme.text)),
+                ])
+            }));
+        }
+        lines
+    }
+}
+
+impl OperatableComponent for WhatsNewModal {
+    fn draw(&mut self, frame: &mut Frame, rect: Rect) {
+        if self.releases.is_empty() {
+            return;
+        }
+
+        let popup_area = centered_rect(70, 60, rect);
+        frame.render_widget(Clear, popup_area);
+
+        let block = Block::bordered()
+            .title(" What's new (↑/↓: scroll, Esc: close) ")
+            .style(Style::default().fg(self.theme.text));
+        let lines = self.lines();
+        // wrapped lines can take more rows, so this only keeps the last line in reach
+        self.scroll = self.scroll.min((lines.len() as u16).saturating_sub(1));
+        let paragraph = Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0));
+        frame.render_widget(paragraph, popup_area);
+    }
+
+    fn process_focus(&mut self) {}
+
+    fn process_events(&mut self, events: KeyCode) -> Message {
+        match events {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                return Message::Once(OnceOperation::CloseWhatsNew)
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll = self.scroll.saturating_add(1),
+            _ => {}
+        }
+        Message::NoAction
+    }
+
+    fn handle_message(&mut self, message: &Message) -> Message {
+        if let Message::Once(OnceOperation::CloseWhatsNew) = message {
+            self.releases.clear();
+        }
+        Message::NoAction
+    }
+
+    fn is_open(&self) -> bool {
+        !self.releases.is_empty()
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn release(version: &str, changes: &[&str]) -> Release {
+        Release {
+            version: version.to_owned(),
+            changes: changes.iter().map(|change| change.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_whats_new_modal_open_and_close() {
+        let mut modal = WhatsNewModal::new();
+        assert!(!modal.is_open());
+        modal.open(vec![]);
+        assert!(!modal.is_open());
+
+        modal.open(vec![release("0.2.0", &["`x` does this"])]);
+        assert!(modal.is_open());
+        assert_eq!(modal.process_events(KeyCode::Char('j')), Message::NoAction);
+        let message = modal.process_events(KeyCode::Esc);
+        assert_eq!(message, Message::Once(OnceOperation::CloseWhatsNew));
+        modal.handle_message(&message);
+        assert!(!modal.is_open());
+    }
+
+    #[test]
+    fn test_whats_new_modal_draw() {
+        let mut modal = WhatsNewModal::new();
+        modal.open(vec![
+            release("0.3.0", &["`x` does this", "`y` does that"]),
+            release("0.2.0", &["`z` does more"]),
+        ]);
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        terminal
+            .draw(|frame| modal.draw(frame, frame.size()))
+            .unwrap();
+        assert_snapshot!(format!("{:?}", terminal.backend().buffer()));
+    }
+}
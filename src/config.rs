@@ -0,0 +1,330 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use serde::Deserialize;
+
+use crate::{
+    cache,
+    issues::{self, IssuePattern},
+    repository,
+    theme::Theme,
+};
+
+// Directory for user configuration, following the XDG base directory spec.
+pub fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("gview"))
+}
+
+// Values to check URL templates against when the config is read.
+const URL_TEMPLATE_EXAMPLE: [(&str, &str); 5] = [
+    ("host", "example.com"),
+    ("repo", "owner/repo"),
+    ("sha", "0000000"),
+    ("path", "README.md"),
+    ("line", "1"),
+];
+
+/// User settings read from `config.toml` in the config directory.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    // file extension -> command line; `{}` is replaced by the file path
+    open_with: HashMap<String, String>,
+    // size limit of the on-disk cache in megabytes
+    cache_size_mb: Option<u64>,
+    // show the file under the file list selection without pressing Enter
+    live_preview: Option<bool>,
+    // render `:shortcode:`s in commit messages as emoji
+    emoji: Option<bool>,
+    theme: ThemeConfig,
+    // remote host -> forge URL template with {host}, {repo}, {sha}, {path} and {line}
+    url_template: HashMap<String, String>,
+    // former spelling of `browser.remote`, still read
+    remote: Option<String>,
+    browser: BrowserConfig,
+    // regex of issue references in commit messages -> URL template with {id}, {host} and {repo}
+    issue_url: HashMap<String, String>,
+    checks: ChecksConfig,
+    // look for a later gview on crates.io at startup
+    update_check: Option<bool>,
+    // lines of context the content viewer keeps around the current line
+    scrolloff: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeConfig {
+    // built-in theme to start from, `dark` or `light`
+    name: Option<String>,
+    // role -> color, overriding the built-in theme
+    colors: HashMap<String, String>,
+    // glyph before the title of the focused panel, empty for none
+    focus_marker: Option<String>,
+    // border of the focused panel: `plain`, `rounded`, `double` or `thick`
+    focus_border: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct BrowserConfig {
+    // remote whose URL browser links use, `origin` when unset
+    remote: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ChecksConfig {
+    // ask the forge for the CI status of the shown commit
+    enabled: Option<bool>,
+    // GitHub token for the requests, instead of the `gh auth` login
+    token: Option<String>,
+}
+
+impl Config {
+    pub fn parse(content: &str) -> anyhow::Result<Self> {
+        let config: Self = toml::from_str(content)?;
+        config.theme()?;
+        for template in config.url_template.values() {
+            repository::url_from_template(template, &URL_TEMPLATE_EXAMPLE)?;
+        }
+        for (pattern, template) in &config.issue_url {
+            IssuePattern::new(pattern, template)?;
+        }
+        Ok(config)
+    }
+
+    // A missing config file is not an error, an unreadable or invalid one is.
+    pub fn load() -> anyhow::Result<Self> {
+        let Some(path) = config_dir().map(|dir| dir.join("config.toml")) else {
+            return Ok(Self::default());
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => Self::parse(&content).map_err(|e| e.context(path.display().to_string())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn cache_limit(&self) -> u64 {
+        self.cache_size_mb.map_or(cache::DEFAULT_LIMIT, |size| {
+            size.saturating_mul(1024 * 1024)
+        })
+    }
+
+    pub fn live_preview(&self) -> bool {
+        self.live_preview.unwrap_or(true)
+    }
+
+    pub fn emoji(&self) -> bool {
+        self.emoji.unwrap_or(true)
+    }
+
+    pub fn url_templates(&self) -> HashMap<String, String> {
+        self.url_template.clone()
+    }
+
+    pub fn remote(&self) -> Option<&str> {
+        self.browser.remote.as_deref().or(self.remote.as_deref())
+    }
+
+    // The configured issue patterns, followed by `#123` linking to the forge's issues.
+    pub fn issue_patterns(&self) -> Vec<IssuePattern> {
+        self.issue_url
+            .iter()
+            .map(|(pattern, template)| (pattern.as_str(), template.as_str()))
+            .chain([(issues::DEFAULT_PATTERN, issues::DEFAULT_TEMPLATE)])
+            .filter_map(|(pattern, template)| IssuePattern::new(pattern, template).ok())
+            .collect()
+    }
+
+    pub fn checks_enabled(&self) -> bool {
+        self.checks.enabled.unwrap_or(false)
+    }
+
+    pub fn checks_token(&self) -> Option<String> {
+        self.checks.token.clone()
+    }
+
+    pub fn update_check(&self) -> bool {
+        self.update_check.unwrap_or(false)
+    }
+
+    pub fn scrolloff(&self) -> usize {
+        self.scrolloff.unwrap_or(0)
+    }
+
+    // Overrides the configured theme name, from the command line.
+    pub fn set_theme_name(&mut self, name: String) {
+        self.theme.name = Some(name);
+    }
+
+    pub fn theme(&self) -> anyhow::Result<Theme> {
+        let mut theme = Theme::by_name(self.theme.name.as_deref().unwrap_or("dark"))?;
+        for (role, color) in &self.theme.colors {
+            theme.set(role, color)?;
+        }
+        if let Some(marker) = &self.theme.focus_marker {
+            theme.set_focus_marker(marker)?;
+        }
+        if let Some(border) = &self.theme.focus_border {
+            theme.set_focus_border(border)?;
+        }
+        Ok(theme)
+    }
+
+    // The configured command for `file`, split into program and arguments.
+    pub fn open_with(&self, file: &str, path: &str) -> Option<Vec<String>> {
+        let extension = std::path::Path::new(file)
+            .extension()?
+            .to_str()?
+            .to_lowercase();
+        let command = self
+            .open_with
+            .iter()
+            .find(|(key, _)| key.trim_start_matches('.').to_lowercase() == extension)?
+            .1;
+        let mut args: Vec<String> = command
+            .split_whitespace()
+            .map(|arg| arg.replace("{}", path))
+            .collect();
+        if args.is_empty() {
+            return None;
+        }
+        if !command.contains("{}") {
+            args.push(path.to_owned());
+        }
+        Some(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_open_with() {
+        let config = Config::parse(
+            "[open_with]\n\
+             png = \"imgcat\"\n\
+             \".ipynb\" = \"nbpreview --theme dark {}\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.open_with("assets/logo.PNG", "/tmp/logo.PNG"),
+            Some(vec!["imgcat".to_owned(), "/tmp/logo.PNG".to_owned()])
+        );
+        assert_eq!(
+            config.open_with("notebook.ipynb", "/tmp/notebook.ipynb"),
+            Some(vec![
+                "nbpreview".to_owned(),
+                "--theme".to_owned(),
+                "dark".to_owned(),
+                "/tmp/notebook.ipynb".to_owned()
+            ])
+        );
+        assert_eq!(config.open_with("src/main.rs", "/tmp/main.rs"), None);
+        assert_eq!(config.open_with("Makefile", "/tmp/Makefile"), None);
+    }
+
+    #[test]
+    fn test_parse_theme() {
+        assert_eq!(Config::parse("").unwrap().theme().unwrap(), Theme::dark());
+        let mut config = Config::parse(
+            "[theme]\n\
+             name = \"light\"\n\
+             [theme.colors]\n\
+             selection = \"#204a87\"\n",
+        )
+        .unwrap();
+        let theme = config.theme().unwrap();
+        assert_eq!(
+            theme.selection,
+            ratatui::style::Color::Rgb(0x20, 0x4a, 0x87)
+        );
+        assert_eq!(theme.unfocused, Theme::light().unfocused);
+
+        // the command line picks the base theme, the configured colors still apply
+        config.set_theme_name("dark".to_owned());
+        let theme = config.theme().unwrap();
+        assert_eq!(
+            theme.selection,
+            ratatui::style::Color::Rgb(0x20, 0x4a, 0x87)
+        );
+        assert_eq!(theme.unfocused, Theme::dark().unfocused);
+
+        let theme = Config::parse("[theme]\nfocus_marker = \"\"\nfocus_border = \"double\"\n")
+            .unwrap()
+            .theme()
+            .unwrap();
+        assert_eq!(theme.focus_marker, None);
+        assert_eq!(theme.focus_border, ratatui::widgets::BorderType::Double);
+        assert!(Config::parse("[theme]\nfocus_border = \"dotted\"\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_invalid_config() {
+        assert!(Config::parse("").unwrap().open_with.is_empty());
+        assert_eq!(
+            Config::parse("").unwrap().cache_limit(),
+            cache::DEFAULT_LIMIT
+        );
+        assert_eq!(
+            Config::parse("cache_size_mb = 8\n").unwrap().cache_limit(),
+            8 * 1024 * 1024
+        );
+        assert!(Config::parse("").unwrap().live_preview());
+        assert!(!Config::parse("live_preview = false\n")
+            .unwrap()
+            .live_preview());
+        assert!(Config::parse("").unwrap().emoji());
+        assert!(!Config::parse("emoji = false\n").unwrap().emoji());
+        assert!(Config::parse("unknown = 1\n").is_err());
+        assert!(Config::parse("[theme]\nname = \"solarized\"\n").is_err());
+        assert!(
+            Config::parse("[url_template]\n\"git.corp\" = \"https://{host}/{branch}\"\n").is_err()
+        );
+        assert_eq!(
+            Config::parse("[url_template]\n\"git.corp\" = \"https://{host}/{repo}\"\n")
+                .unwrap()
+                .url_templates()
+                .len(),
+            1
+        );
+        assert_eq!(Config::parse("").unwrap().remote(), None);
+        assert_eq!(Config::parse("").unwrap().issue_patterns().len(), 1);
+        assert_eq!(
+            Config::parse("[issue_url]\n'JIRA-\\d+' = \"https://jira.corp/browse/{id}\"\n")
+                .unwrap()
+                .issue_patterns()
+                .len(),
+            2
+        );
+        assert!(Config::parse("[issue_url]\n'(' = \"https://{id}\"\n").is_err());
+        assert!(!Config::parse("").unwrap().checks_enabled());
+        let config = Config::parse("[checks]\nenabled = true\ntoken = \"secret\"\n").unwrap();
+        assert!(config.checks_enabled());
+        assert_eq!(config.checks_token().as_deref(), Some("secret"));
+        assert!(!Config::parse("").unwrap().update_check());
+        assert!(Config::parse("update_check = true\n")
+            .unwrap()
+            .update_check());
+        assert_eq!(Config::parse("").unwrap().scrolloff(), 0);
+        assert_eq!(Config::parse("scrolloff = 5\n").unwrap().scrolloff(), 5);
+        assert_eq!(
+            Config::parse("remote = \"upstream\"\n").unwrap().remote(),
+            Some("upstream")
+        );
+        assert_eq!(
+            Config::parse("remote = \"origin\"\n[browser]\nremote = \"upstream\"\n")
+                .unwrap()
+                .remote(),
+            Some("upstream")
+        );
+        assert!(Config::parse("[theme.colors]\nborder = \"red\"\n").is_err());
+        assert!(Config::parse("[open_with]\npng = 1\n").is_err());
+    }
+}
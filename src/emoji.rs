@@ -0,0 +1,155 @@
+// Shortcodes of gitmoji (https://gitmoji.dev) and a few other common ones.
+const SHORTCODES: &[(&str, &str)] = &[
+    ("+1", "👍"),
+    ("-1", "👎"),
+    ("adhesive_bandage", "🩹"),
+    ("airplane", "✈️"),
+    ("alembic", "⚗️"),
+    ("alien", "👽️"),
+    ("ambulance", "🚑️"),
+    ("apple", "🍎"),
+    ("arrow_down", "⬇️"),
+    ("arrow_up", "⬆️"),
+    ("art", "🎨"),
+    ("beers", "🍻"),
+    ("bento", "🍱"),
+    ("bookmark", "🔖"),
+    ("books", "📚"),
+    ("boom", "💥"),
+    ("bricks", "🧱"),
+    ("bug", "🐛"),
+    ("building_construction", "🏗️"),
+    ("bulb", "💡"),
+    ("busts_in_silhouette", "👥"),
+    ("camera_flash", "📸"),
+    ("card_file_box", "🗃️"),
+    ("chart_with_upwards_trend", "📈"),
+    ("checkered_flag", "🏁"),
+    ("children_crossing", "🚸"),
+    ("closed_lock_with_key", "🔐"),
+    ("clown_face", "🤡"),
+    ("coffin", "⚰️"),
+    ("construction", "🚧"),
+    ("construction_worker", "👷"),
+    ("dizzy", "💫"),
+    ("egg", "🥚"),
+    ("eyes", "👀"),
+    ("fire", "🔥"),
+    ("globe_with_meridians", "🌐"),
+    ("goal_net", "🥅"),
+    ("green_heart", "💚"),
+    ("hammer", "🔨"),
+    ("heart", "❤️"),
+    ("heavy_check_mark", "✔️"),
+    ("heavy_minus_sign", "➖"),
+    ("heavy_plus_sign", "➕"),
+    ("iphone", "📱"),
+    ("key", "🔑"),
+    ("label", "🏷️"),
+    ("lipstick", "💄"),
+    ("lock", "🔒️"),
+    ("loud_sound", "🔊"),
+    ("mag", "🔍️"),
+    ("memo", "📝"),
+    ("money_with_wings", "💸"),
+    ("monocle_face", "🧐"),
+    ("mute", "🔇"),
+    ("necktie", "👔"),
+    ("package", "📦️"),
+    ("page_facing_up", "📄"),
+    ("passport_control", "🛂"),
+    ("pencil", "📝"),
+    ("pencil2", "✏️"),
+    ("penguin", "🐧"),
+    ("poop", "💩"),
+    ("pushpin", "📌"),
+    ("recycle", "♻️"),
+    ("rewind", "⏪️"),
+    ("robot", "🤖"),
+    ("rocket", "🚀"),
+    ("rotating_light", "🚨"),
+    ("safety_vest", "🦺"),
+    ("see_no_evil", "🙈"),
+    ("seedling", "🌱"),
+    ("shirt", "👕"),
+    ("sparkles", "✨"),
+    ("speech_balloon", "💬"),
+    ("star", "⭐"),
+    ("stethoscope", "🩺"),
+    ("tada", "🎉"),
+    ("technologist", "🧑‍💻"),
+    ("test_tube", "🧪"),
+    ("thread", "🧵"),
+    ("triangular_flag_on_post", "🚩"),
+    ("truck", "🚚"),
+    ("twisted_rightwards_arrows", "🔀"),
+    ("warning", "⚠️"),
+    ("wastebasket", "🗑️"),
+    ("wheelchair", "♿️"),
+    ("white_check_mark", "✅"),
+    ("wrench", "🔧"),
+    ("x", "❌"),
+    ("zap", "⚡️"),
+];
+
+fn lookup(shortcode: &str) -> Option<&'static str> {
+    SHORTCODES
+        .binary_search_by(|(name, _)| (*name).cmp(shortcode))
+        .ok()
+        .map(|index| SHORTCODES[index].1)
+}
+
+/// Replaces the known `:shortcode:`s of `text` with their emoji; unknown ones are kept as is.
+pub fn replace_shortcodes(text: &str) -> String {
+    let mut replaced = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(':') {
+        replaced.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let emoji = after
+            .find(':')
+            .and_then(|end| Some((lookup(&after[..end])?, end)));
+        match emoji {
+            Some((emoji, end)) => {
+                replaced.push_str(emoji);
+                rest = &after[end + 1..];
+            }
+            // the closing colon may open the next shortcode
+            None => {
+                replaced.push(':');
+                rest = after;
+            }
+        }
+    }
+    replaced.push_str(rest);
+    replaced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shortcodes_are_sorted() {
+        assert!(SHORTCODES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn test_replace_shortcodes() {
+        assert_eq!(
+            replace_shortcodes(":sparkles: Add emoji :bug:fix"),
+            "✨ Add emoji 🐛fix"
+        );
+        assert_eq!(
+            replace_shortcodes("abc123: :tada: Initial commit"),
+            "abc123: 🎉 Initial commit"
+        );
+        // unknown shortcodes and stray colons are left alone
+        assert_eq!(
+            replace_shortcodes(":unknown: at 12:30:00 :+1:"),
+            ":unknown: at 12:30:00 👍"
+        );
+        assert_eq!(replace_shortcodes("no emoji"), "no emoji");
+        assert_eq!(replace_shortcodes("trailing :"), "trailing :");
+    }
+}
@@ -0,0 +1,158 @@
+use std::{
+    fmt,
+    fs::OpenOptions,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::state;
+
+/// Where the app logs the errors of a session, next to the other per-user state.
+pub fn log_path() -> Option<PathBuf> {
+    state::state_dir().map(|dir| dir.join("errors.log"))
+}
+
+/// What went wrong, with what gview was doing at the time. Components hand it to the app in
+/// `Message::Error`, which shows it in the status bar and logs it; on the command line it
+/// decides the exit code.
+#[derive(Debug)]
+pub enum GviewError {
+    /// The repository is poisoned: another thread panicked while holding it.
+    Lock,
+    /// git could not answer, e.g. for a missing commit or object.
+    Git {
+        context: String,
+        source: git2::Error,
+    },
+    /// A file could not be read or written.
+    Io { context: String, source: io::Error },
+    /// Any other failure, such as an invalid config or an unreachable forge.
+    Other {
+        context: String,
+        source: anyhow::Error,
+    },
+    /// The action has nothing to act on, such as copying without a selection.
+    Unavailable(String),
+}
+
+impl GviewError {
+    /// Wraps `error` with `context`, keeping git and I/O errors apart from the others.
+    pub fn new(context: impl Into<String>, error: impl Into<anyhow::Error>) -> Self {
+        let context = context.into();
+        let error = match error.into().downcast::<git2::Error>() {
+            Ok(source) => return Self::Git { context, source },
+            Err(error) => error,
+        };
+        match error.downcast::<io::Error>() {
+            Ok(source) => Self::Io { context, source },
+            Err(source) => Self::Other { context, source },
+        }
+    }
+
+    pub fn unavailable(message: impl Into<String>) -> Self {
+        Self::Unavailable(message.into())
+    }
+
+    /// The exit code gview ends with when the error stops it: 2 for git errors, 74 and 70
+    /// for I/O and internal errors as in sysexits.h, and 1 otherwise.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Git { .. } => 2,
+            Self::Io { .. } => 74,
+            Self::Lock => 70,
+            Self::Other { .. } | Self::Unavailable(_) => 1,
+        }
+    }
+
+    /// Appends the error to the log at `path` with the time it happened. Logging must not
+    /// fail the action that failed, so its own errors are ignored.
+    pub fn log(&self, path: &Path) {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) else {
+            return;
+        };
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        let _ = writeln!(file, "{} {}", time, self);
+    }
+}
+
+impl fmt::Display for GviewError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lock => write!(f, "Failed to acquire repository lock"),
+            Self::Git { context, source } => write!(f, "{}: {}", context, source.message()),
+            Self::Io { context, source } => write!(f, "{}: {}", context, source),
+            Self::Other { context, source } => write!(f, "{}: {:#}", context, source),
+            Self::Unavailable(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for GviewError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Git { source, .. } => Some(source),
+            Self::Io { source, .. } => Some(source),
+            Self::Other { source, .. } => Some(source.as_ref()),
+            Self::Lock | Self::Unavailable(_) => None,
+        }
+    }
+}
+
+// Errors compare by kind and by what they say, so that messages carrying them can be
+// compared in tests.
+impl PartialEq for GviewError {
+    fn eq(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+            && self.to_string() == other.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kinds_and_log() {
+        let git = GviewError::new(
+            "Commit not found",
+            git2::Error::from_str("object not found"),
+        );
+        assert!(matches!(git, GviewError::Git { .. }));
+        assert_eq!(git.to_string(), "Commit not found: object not found");
+        assert_eq!(git.exit_code(), 2);
+
+        let missing = io::Error::new(io::ErrorKind::NotFound, "no such file");
+        let io = GviewError::new("Failed to save", anyhow::Error::from(missing));
+        assert!(matches!(io, GviewError::Io { .. }));
+        assert!(std::error::Error::source(&io).is_some());
+
+        let other = GviewError::new(
+            "Invalid config",
+            anyhow::anyhow!("expected a table").context("parsing [theme]"),
+        );
+        assert!(matches!(other, GviewError::Other { .. }));
+        // the causes are kept, outermost first
+        assert_eq!(
+            other.to_string(),
+            "Invalid config: parsing [theme]: expected a table"
+        );
+        assert_eq!(GviewError::Lock.exit_code(), 70);
+
+        let path = std::env::temp_dir()
+            .join(format!("gview_error_test_{}", std::process::id()))
+            .join("errors.log");
+        other.log(&path);
+        GviewError::unavailable("No file lines to copy").log(&path);
+        let logged = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = logged.lines().collect();
+        assert!(lines[0].ends_with(" Invalid config: parsing [theme]: expected a table"));
+        assert!(lines[1].ends_with(" No file lines to copy"));
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
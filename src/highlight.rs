@@ -0,0 +1,179 @@
+use std::{path::Path, sync::OnceLock};
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::Span,
+};
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{FontStyle, ThemeSet},
+    parsing::{SyntaxReference, SyntaxSet},
+};
+
+// Lines highlighted per draw at most, so that no keypress waits on a long file.
+const STEP: usize = 1_000;
+
+// Loading the bundled syntaxes takes a moment, so it happens on the first highlighted file.
+fn assets() -> &'static (SyntaxSet, ThemeSet) {
+    static ASSETS: OnceLock<(SyntaxSet, ThemeSet)> = OnceLock::new();
+    ASSETS.get_or_init(|| {
+        (
+            SyntaxSet::load_defaults_newlines(),
+            ThemeSet::load_defaults(),
+        )
+    })
+}
+
+// The syntax of `file` by its extension, or its name for files like `Makefile`.
+fn syntax_by_name<'a>(syntaxes: &'a SyntaxSet, file: &str) -> Option<&'a SyntaxReference> {
+    let path = Path::new(file);
+    path.extension()
+        .or_else(|| path.file_name())
+        .and_then(|name| syntaxes.find_syntax_by_extension(&name.to_string_lossy()))
+}
+
+/// The language `file` is written in by its extension or name, as syntect names it. None
+/// for plain text and files it does not know.
+pub fn language(file: &str) -> Option<&'static str> {
+    let (syntaxes, _) = assets();
+    syntax_by_name(syntaxes, file)
+        .filter(|syntax| syntax.name != syntaxes.find_syntax_plain_text().name)
+        .map(|syntax| syntax.name.as_str())
+}
+
+/// Colors the lines of a file as they come into view. Each line's colors depend on every
+/// line before it, so lines are highlighted in order, up to the furthest one shown yet.
+pub struct Highlighter {
+    lines: Vec<String>,
+    state: HighlightLines<'static>,
+    // text in the theme's default color keeps the terminal's own
+    default: Option<syntect::highlighting::Color>,
+    spans: Vec<Vec<Span<'static>>>,
+}
+
+impl Highlighter {
+    /// Picks the syntax of `file` by extension (or file name) and then by the first line,
+    /// as for scripts with a shebang, coloring it with the syntect theme `theme`. None for
+    /// plain text.
+    pub fn new(file: &str, lines: &[&str], theme: &str) -> Option<Self> {
+        let (syntaxes, themes) = assets();
+        let theme = themes.themes.get(theme)?;
+        let syntax = syntax_by_name(syntaxes, file)
+            .or_else(|| syntaxes.find_syntax_by_first_line(lines.first()?))
+            .filter(|syntax| syntax.name != syntaxes.find_syntax_plain_text().name)?;
+        Some(Self {
+            lines: lines.iter().map(|line| format!("{}\n", line)).collect(),
+            state: HighlightLines::new(syntax, theme),
+            default: theme.settings.foreground,
+            spans: vec![],
+        })
+    }
+
+    /// Highlights the lines before `end`, but at most `STEP` more than so far: a view
+    /// scrolled far into a long file gets its colors over a few draws.
+    pub fn advance(&mut self, end: usize) {
+        let (syntaxes, _) = assets();
+        let end = end.min(self.lines.len()).min(self.spans.len() + STEP);
+        while self.spans.len() < end {
+            let Ok(regions) = self
+                .state
+                .highlight_line(&self.lines[self.spans.len()], syntaxes)
+            else {
+                // the lines after one syntect cannot parse stay plain
+                self.lines.truncate(self.spans.len());
+                return;
+            };
+            let spans = regions
+                .into_iter()
+                .map(|(style, text)| {
+                    let mut span_style = Style::default();
+                    if Some(style.foreground) != self.default {
+                        let color = style.foreground;
+                        span_style = span_style.fg(Color::Rgb(color.r, color.g, color.b));
+                    }
+                    if style.font_style.contains(FontStyle::BOLD) {
+                        span_style = span_style.add_modifier(Modifier::BOLD);
+                    }
+                    if style.font_style.contains(FontStyle::ITALIC) {
+                        span_style = span_style.add_modifier(Modifier::ITALIC);
+                    }
+                    Span::styled(text.trim_end_matches('\n').to_owned(), span_style)
+                })
+                .filter(|span| !span.content.is_empty())
+                .collect();
+            self.spans.push(spans);
+        }
+    }
+
+    /// The colored spans of line `index`, once it is highlighted.
+    pub fn line(&self, index: usize) -> Option<&[Span<'static>]> {
+        self.spans.get(index).map(Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DARK: &str = "base16-ocean.dark";
+
+    #[test]
+    fn test_highlight_rust() {
+        let mut highlighter = Highlighter::new(
+            "src/main.rs",
+            &["fn main() {", "    let x = 42;", "}"],
+            DARK,
+        )
+        .unwrap();
+        assert!(highlighter.line(0).is_none());
+        highlighter.advance(10);
+        assert!(highlighter.line(3).is_none());
+        // the spans cover each line exactly
+        let text: Vec<String> = (0..3)
+            .map(|index| {
+                let spans = highlighter.line(index).unwrap();
+                spans.iter().map(|span| span.content.as_ref()).collect()
+            })
+            .collect();
+        assert_eq!(text, vec!["fn main() {", "    let x = 42;", "}"]);
+        let keyword = highlighter
+            .line(0)
+            .unwrap()
+            .iter()
+            .find(|span| span.content == "fn")
+            .unwrap();
+        assert!(matches!(keyword.style.fg, Some(Color::Rgb(..))));
+    }
+
+    #[test]
+    fn test_highlight_by_first_line_and_plain_text() {
+        assert!(Highlighter::new("scripts/build", &["#!/bin/bash", "echo hi"], DARK).is_some());
+        assert!(Highlighter::new("notes.txt", &["just text"], DARK).is_none());
+        assert!(Highlighter::new("LICENSE", &["MIT License"], DARK).is_none());
+        // both built-in themes name a bundled syntect theme
+        for theme in [crate::theme::Theme::dark(), crate::theme::Theme::light()] {
+            assert!(Highlighter::new("main.rs", &["fn main() {}"], theme.syntax).is_some());
+        }
+        assert!(Highlighter::new("main.rs", &["fn main() {}"], "no such theme").is_none());
+    }
+
+    #[test]
+    fn test_highlight_in_steps() {
+        let lines = vec!["let x = 1;"; STEP * 3];
+        let mut highlighter = Highlighter::new("main.rs", &lines, DARK).unwrap();
+        highlighter.advance(lines.len());
+        assert!(highlighter.line(STEP - 1).is_some());
+        assert!(highlighter.line(STEP).is_none());
+        highlighter.advance(lines.len());
+        highlighter.advance(lines.len());
+        assert!(highlighter.line(lines.len() - 1).is_some());
+    }
+
+    #[test]
+    fn test_language() {
+        assert_eq!(language("src/main.rs"), Some("Rust"));
+        assert_eq!(language("Makefile"), Some("Makefile"));
+        assert_eq!(language("notes.txt"), None);
+        assert_eq!(language("data.unknown"), None);
+    }
+}
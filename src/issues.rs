@@ -0,0 +1,121 @@
+use std::ops::Range;
+
+use regex::Regex;
+
+use crate::repository;
+
+// `#123`, linked to the issues of the forge repository unless configured otherwise.
+pub const DEFAULT_PATTERN: &str = r"\B#(\d+)\b";
+pub const DEFAULT_TEMPLATE: &str = "https://{host}/{repo}/issues/{id}";
+
+// Values to check templates against when they are configured.
+const TEMPLATE_EXAMPLE: [(&str, &str); 3] =
+    [("host", "example.com"), ("repo", "owner/repo"), ("id", "1")];
+
+/// Issue references a commit message may contain, such as `#123` or `JIRA-456`, and the URL
+/// each is linked to. The template may use `{id}`, the first group of the pattern or else the
+/// whole reference, and `{host}` and `{repo}` of the forge remote.
+#[derive(Debug, Clone)]
+pub struct IssuePattern {
+    regex: Regex,
+    template: String,
+}
+
+impl IssuePattern {
+    pub fn new(pattern: &str, template: &str) -> anyhow::Result<Self> {
+        let regex = Regex::new(pattern)?;
+        repository::url_from_template(template, &TEMPLATE_EXAMPLE)?;
+        Ok(Self {
+            regex,
+            template: template.to_owned(),
+        })
+    }
+
+    pub fn uses_remote(&self) -> bool {
+        self.template.contains("{host}") || self.template.contains("{repo}")
+    }
+}
+
+/// Byte ranges of the issue references in `text`. Where matches of several patterns
+/// overlap, the one starting first, then the longest, is kept.
+pub fn find(text: &str, patterns: &[IssuePattern]) -> Vec<Range<usize>> {
+    let mut matches: Vec<Range<usize>> = patterns
+        .iter()
+        .flat_map(|pattern| pattern.regex.find_iter(text).map(|found| found.range()))
+        .filter(|range| !range.is_empty())
+        .collect();
+    matches.sort_by_key(|range| (range.start, usize::MAX - range.end));
+    let mut kept: Vec<Range<usize>> = Vec::new();
+    for range in matches {
+        if kept.last().is_none_or(|last| last.end <= range.start) {
+            kept.push(range);
+        }
+    }
+    kept
+}
+
+/// The URL of `reference`, a whole match of one of `patterns`. `remote` gives the host and
+/// repository path of the forge, for the templates that use them.
+pub fn url(
+    reference: &str,
+    patterns: &[IssuePattern],
+    remote: impl FnOnce() -> anyhow::Result<(String, String)>,
+) -> anyhow::Result<String> {
+    let (pattern, captures) = patterns
+        .iter()
+        .find_map(|pattern| {
+            pattern
+                .regex
+                .captures(reference)
+                .filter(|captures| captures.get(0).is_some_and(|m| m.len() == reference.len()))
+                .map(|captures| (pattern, captures))
+        })
+        .ok_or_else(|| anyhow::anyhow!("{} is not an issue reference", reference))?;
+    let id = captures
+        .get(1)
+        .map_or(reference, |group| group.as_str())
+        .to_owned();
+    let (host, repo) = match pattern.uses_remote() {
+        true => remote()?,
+        false => (String::new(), String::new()),
+    };
+    repository::url_from_template(
+        &pattern.template,
+        &[("host", &host), ("repo", &repo), ("id", &id)],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_and_link_issue_references() {
+        let patterns = vec![
+            IssuePattern::new(r"\bJIRA-\d+\b", "https://jira.corp/browse/{id}").unwrap(),
+            IssuePattern::new(DEFAULT_PATTERN, DEFAULT_TEMPLATE).unwrap(),
+        ];
+        let message = "Fix crash (#12, JIRA-456)\n\nSee issue#3 and NOJIRA-7.";
+        let found: Vec<&str> = find(message, &patterns)
+            .into_iter()
+            .map(|range| &message[range])
+            .collect();
+        assert_eq!(found, vec!["#12", "JIRA-456"]);
+
+        let remote = || Ok(("github.com".to_owned(), "owner/repo".to_owned()));
+        assert_eq!(
+            url("#12", &patterns, remote).unwrap(),
+            "https://github.com/owner/repo/issues/12"
+        );
+        // templates without the remote do not need one
+        assert_eq!(
+            url("JIRA-456", &patterns, || Err(anyhow::anyhow!("no remote"))).unwrap(),
+            "https://jira.corp/browse/JIRA-456"
+        );
+        assert!(url("#12", &patterns, || Err(anyhow::anyhow!("no remote"))).is_err());
+        assert!(url("#12x", &patterns, remote).is_err());
+
+        assert!(IssuePattern::new("(", "https://{id}").is_err());
+        assert!(IssuePattern::new("#(\\d+)", "https://{number}").is_err());
+    }
+}
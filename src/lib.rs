@@ -0,0 +1,55 @@
+pub mod app;
+pub mod bench;
+pub mod cache;
+mod changelog;
+mod checks;
+mod clipboard;
+mod components;
+pub mod config;
+mod emoji;
+pub mod error;
+mod highlight;
+mod issues;
+mod progress;
+pub mod repository;
+mod screen;
+pub mod script;
+mod sparse;
+mod state;
+mod summary;
+mod tags;
+pub mod theme;
+mod trailers;
+mod update;
+
+use std::io::{self, stdout};
+
+use app::Tui;
+use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
+    ExecutableCommand,
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    crossterm::terminal::{
+        disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+    },
+    terminal::Terminal,
+};
+
+/// Initialize the terminal and enter alternate screen mode.
+pub fn init_terminal() -> io::Result<Tui> {
+    enable_raw_mode()?;
+    stdout().execute(EnterAlternateScreen)?;
+    stdout().execute(EnableMouseCapture)?;
+    let backend = CrosstermBackend::new(stdout());
+    Terminal::new(backend)
+}
+
+/// Restore the terminal to its original state.
+pub fn restore_terminal() -> io::Result<()> {
+    disable_raw_mode()?;
+    stdout().execute(DisableMouseCapture)?;
+    stdout().execute(LeaveAlternateScreen)?;
+    Ok(())
+}
@@ -1,12 +1,11 @@
-mod app;
-mod components;
-mod repository;
-use std::{
-    io::{self, stdout},
-    panic,
-};
+use std::panic;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use gview::{
+    app, bench, cache, config,
+    error::{self, GviewError},
+    init_terminal, repository, restore_terminal, script,
+};
 
 #[derive(Parser)]
 #[command(name = "gview")]
@@ -18,24 +17,80 @@ struct Args {
     #[arg(short, long)]
     commit: Option<String>,
 
+    /// Scope the session to a directory of the repository
+    #[arg(long)]
+    prefix: Option<String>,
+
+    /// List only commits made on or after this date (YYYY-MM-DD)
+    #[arg(long, value_name = "DATE")]
+    since: Option<String>,
+
+    /// List only commits made on or before this date (YYYY-MM-DD)
+    #[arg(long, value_name = "DATE")]
+    until: Option<String>,
+
+    /// Disable actions that spawn external processes (browser, external viewers)
+    #[arg(long)]
+    no_shell_out: bool,
+
+    /// List only the files inside the sparse checkout, if the repository uses one
+    #[arg(long)]
+    sparse: bool,
+
+    /// Color theme, dark or light, overriding the config
+    #[arg(long, value_name = "NAME")]
+    theme: Option<String>,
+
+    /// Play the keys of a script without a terminal and print the final screen
+    #[arg(long, value_name = "FILE")]
+    script: Option<String>,
+
+    /// Screen size for --script
+    #[arg(long, value_name = "WIDTHxHEIGHT", default_value = "80x24")]
+    size: String,
+
     /// Print version
     #[arg(short = 'v', long = "version", action = clap::ArgAction::Version)]
     _version: (),
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage the on-disk cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Time the tree walk, filtering, blame and history loading, with nothing cached
+    #[command(hide = true)]
+    Bench {
+        /// Measure a generated repository of FILESxCOMMITS instead of the current one
+        #[arg(long, value_name = "FILESxCOMMITS")]
+        synthetic: Option<String>,
+
+        /// File to blame, by default the first one listed
+        #[arg(long)]
+        file: Option<String>,
+
+        /// Runs of each measurement, whose median is reported
+        #[arg(long, default_value_t = 10)]
+        runs: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Remove the cached data of every repository
+    Clear,
 }
 
-use app::Tui;
 use color_eyre::{
     config::{EyreHook, HookBuilder, PanicHook},
     eyre,
 };
-use crossterm::ExecutableCommand;
-use ratatui::{
-    backend::CrosstermBackend,
-    crossterm::terminal::{
-        disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
-    },
-    terminal::Terminal,
-};
 
 pub fn install_hooks() -> color_eyre::Result<()> {
     let (panic_hook, eyre_hook) = HookBuilder::default().into_hooks();
@@ -63,42 +118,139 @@ fn install_error_hook(eyre_hook: EyreHook) -> color_eyre::Result<()> {
     Ok(())
 }
 
-/// Initialize the terminal and enter alternate screen mode.
-pub fn init_terminal() -> io::Result<Tui> {
-    enable_raw_mode()?;
-    stdout().execute(EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout());
-    Terminal::new(backend)
+fn run_bench(synthetic: Option<&str>, file: Option<String>, runs: usize) -> anyhow::Result<()> {
+    let path = match synthetic {
+        Some(size) => {
+            let (files, commits) = size
+                .split_once('x')
+                .and_then(|(files, commits)| Some((files.parse().ok()?, commits.parse().ok()?)))
+                .ok_or_else(|| anyhow::anyhow!("expected --synthetic FILESxCOMMITS"))?;
+            let dir = std::env::temp_dir().join(format!("gview-bench-{}", std::process::id()));
+            println!(
+                "Generating {} files and {} commits in {}",
+                files,
+                commits,
+                dir.display()
+            );
+            bench::synthetic_repository(&dir, files, commits)?;
+            dir
+        }
+        None => std::env::current_dir()?,
+    };
+    let measured = bench::Workload::new(&path, file).and_then(|workload| workload.measure(runs));
+    if synthetic.is_some() {
+        let _ = std::fs::remove_dir_all(&path);
+    }
+    for (name, time) in measured? {
+        println!("{:<40} {:>10.2?}", name, time);
+    }
+    Ok(())
 }
 
-/// Restore the terminal to its original state.
-pub fn restore_terminal() -> io::Result<()> {
-    disable_raw_mode()?;
-    stdout().execute(LeaveAlternateScreen)?;
-    Ok(())
+// Reports an error that stops gview before its screen opens, and exits with its code.
+fn fail(error: GviewError) -> ! {
+    eprintln!("{}", error);
+    std::process::exit(error.exit_code())
 }
 
 fn main() -> color_eyre::Result<()> {
     let args = Args::parse();
 
-    let repository_info = repository::RepositoryInfo::new();
-    if repository_info.is_err() {
-        return Ok(());
+    match args.command {
+        Some(Command::Cache {
+            action: CacheAction::Clear,
+        }) => {
+            match cache::clear() {
+                Ok(Some(dir)) => println!("Removed {}", dir.display()),
+                Ok(None) => println!("Cache is already empty"),
+                Err(e) => fail(GviewError::new("Failed to clear the cache", e)),
+            }
+            return Ok(());
+        }
+        Some(Command::Bench {
+            synthetic,
+            file,
+            runs,
+        }) => {
+            if let Err(e) = run_bench(synthetic.as_deref(), file, runs) {
+                fail(GviewError::new("Benchmark failed", e));
+            }
+            return Ok(());
+        }
+        None => {}
     }
 
-    let mut repo_info = repository_info.unwrap();
+    let mut repo_info = match repository::RepositoryInfo::new() {
+        Ok(repo_info) => repo_info,
+        Err(e) => fail(GviewError::new("Cannot open the repository", e)),
+    };
+
+    let mut config = match config::Config::load() {
+        Ok(config) => config,
+        Err(e) => fail(GviewError::new("Invalid config", e)),
+    };
+    if let Some(theme) = args.theme {
+        config.set_theme_name(theme);
+        if let Err(e) = config.theme() {
+            fail(GviewError::new("Invalid theme", e));
+        }
+    }
+    repo_info.set_cache_limit(config.cache_limit());
+    repo_info.set_url_templates(config.url_templates());
+    repo_info.set_issue_patterns(config.issue_patterns());
+    if let Some(remote) = config.remote() {
+        repo_info.set_remote(remote);
+    }
+    repo_info.set_sparse_only(args.sparse);
 
     // If a commit ID is provided, try to set it
     if let Some(commit_id) = args.commit {
-        if repo_info.set_commit_by_id(&commit_id).is_err() {
-            eprintln!("Commit not found: {}", commit_id);
-            return Ok(());
+        if let Err(e) = repo_info.set_commit_by_id(&commit_id) {
+            fail(GviewError::new("Invalid --commit", e));
+        }
+    }
+
+    if let Some(prefix) = args.prefix {
+        if let Err(e) = repo_info.set_prefix(&prefix) {
+            fail(GviewError::new("Invalid --prefix", e));
         }
     }
 
+    match repository::DateRange::new(args.since.as_deref(), args.until.as_deref()) {
+        Ok(range) => repo_info.set_date_range(range),
+        Err(e) => fail(GviewError::new("Invalid --since/--until", e)),
+    }
+
+    if let Some(path) = args.script {
+        let keys = std::fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|script| script::parse(&script));
+        let (keys, (width, height)) = match (keys, script::parse_size(&args.size)) {
+            (Ok(keys), Ok(size)) => (keys, size),
+            (Err(e), _) | (_, Err(e)) => {
+                fail(GviewError::new(format!("Invalid script {}", path), e))
+            }
+        };
+        let mut app = app::App::new(repo_info, config);
+        // scripted runs never leave the process
+        app.disable_shell_out();
+        print!("{}", app.run_script(&keys, width, height));
+        return Ok(());
+    }
+
     install_hooks()?;
     let mut terminal = init_terminal()?;
-    let mut app = app::App::new(repo_info);
+    // scripted runs above wait for blame and listings so their output is the same every time
+    repo_info.set_background(true);
+    let mut app = app::App::new(repo_info, config);
+    app.set_error_log(error::log_path());
+    app.show_whats_new();
+    app.restore_layout();
+    if args.no_shell_out {
+        app.disable_shell_out();
+    }
+    app.start_update_check();
+    app.start_checks();
     app.run(&mut terminal)?;
     restore_terminal()?;
     Ok(())
@@ -0,0 +1,147 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+};
+
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+#[derive(Debug, PartialEq)]
+pub enum ProgressEvent {
+    Update {
+        task: &'static str,
+        done: usize,
+        total: Option<usize>,
+    },
+    Finish {
+        task: &'static str,
+    },
+}
+
+/// Sending half handed to worker threads. Reports are dropped once the UI is gone.
+#[derive(Clone, Debug)]
+pub struct ProgressReporter {
+    sender: mpsc::Sender<ProgressEvent>,
+}
+
+impl ProgressReporter {
+    pub fn update(&self, task: &'static str, done: usize, total: Option<usize>) {
+        let _ = self
+            .sender
+            .send(ProgressEvent::Update { task, done, total });
+    }
+
+    pub fn finish(&self, task: &'static str) {
+        let _ = self.sender.send(ProgressEvent::Finish { task });
+    }
+}
+
+/// Flag shared with a worker thread, which checks it regularly and stops early once set.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_canceled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Frame `frame` of the spinner, for panels showing their own work.
+pub fn spinner(frame: usize) -> &'static str {
+    SPINNER[frame % SPINNER.len()]
+}
+
+/// Collects progress of running tasks for the status bar.
+#[derive(Debug)]
+pub struct ProgressTracker {
+    sender: mpsc::Sender<ProgressEvent>,
+    receiver: mpsc::Receiver<ProgressEvent>,
+    tasks: BTreeMap<&'static str, (usize, Option<usize>)>,
+    frame: usize,
+}
+
+impl ProgressTracker {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            sender,
+            receiver,
+            tasks: BTreeMap::new(),
+            frame: 0,
+        }
+    }
+
+    pub fn reporter(&self) -> ProgressReporter {
+        ProgressReporter {
+            sender: self.sender.clone(),
+        }
+    }
+
+    // Applies pending reports and advances the spinner; called once per tick.
+    pub fn tick(&mut self) {
+        while let Ok(event) = self.receiver.try_recv() {
+            match event {
+                ProgressEvent::Update { task, done, total } => {
+                    self.tasks.insert(task, (done, total));
+                }
+                ProgressEvent::Finish { task } => {
+                    self.tasks.remove(task);
+                }
+            }
+        }
+        self.frame = (self.frame + 1) % SPINNER.len();
+    }
+
+    // Spinner followed by every running task, or None when idle.
+    pub fn describe(&self) -> Option<String> {
+        if self.tasks.is_empty() {
+            return None;
+        }
+        let tasks: Vec<String> = self
+            .tasks
+            .iter()
+            .map(|(task, (done, total))| match total {
+                Some(total) => format!("{} {}/{}", task, done, total),
+                None => format!("{} {}", task, done),
+            })
+            .collect();
+        Some(format!("{} {}", SPINNER[self.frame], tasks.join(", ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_tracker() {
+        let mut tracker = ProgressTracker::new();
+        let reporter = tracker.reporter();
+        assert_eq!(tracker.describe(), None);
+
+        reporter.update("loading commits", 120, None);
+        reporter.update("walking tree", 3, Some(40));
+        // Nothing shows up until the next tick
+        assert_eq!(tracker.describe(), None);
+
+        tracker.tick();
+        assert_eq!(
+            tracker.describe(),
+            Some("⠙ loading commits 120, walking tree 3/40".to_owned())
+        );
+
+        reporter.finish("loading commits");
+        tracker.tick();
+        assert_eq!(tracker.describe(), Some("⠹ walking tree 3/40".to_owned()));
+
+        reporter.finish("walking tree");
+        tracker.tick();
+        assert_eq!(tracker.describe(), None);
+    }
+}
@@ -1,12 +1,63 @@
-use git2::{Commit, ObjectType, Oid, Repository, TreeWalkMode, TreeWalkResult};
+use git2::{
+    BlameOptions, Blob, Commit, Delta, Diff, DiffFindOptions, DiffOptions, ObjectType, Oid, Patch,
+    Repository, Tree, TreeWalkMode, TreeWalkResult,
+};
+use regex::Regex;
+use sha2::{Digest, Sha256};
 use std::{
-    io::{BufRead, BufReader},
+    collections::{BTreeSet, HashMap, HashSet},
+    ops::RangeInclusive,
     path::{Path, PathBuf},
-    process::Command,
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use crate::{
+    cache::Cache,
+    highlight,
+    issues::{self, IssuePattern},
+    progress::{CancelToken, ProgressReporter},
+    sparse::SparsePatterns,
+    state::{self, Note, NoteStore},
+    summary,
+    tags::TagIndex,
+    trailers,
 };
 
+// Larger files are left out of the search and definition indexes.
 const MAX_FILE_SIZE: usize = 16 * 1024; // 16KB
 
+// Larger files are read a window of lines at a time, without blame.
+const LAZY_FILE_SIZE: usize = 256 * 1024; // 256KB
+pub const WINDOW_LINES: usize = 2000;
+const PRELOAD_COMMITS: usize = 500;
+
+static SCRATCH_DIRS: AtomicUsize = AtomicUsize::new(0);
+
+// Directory for materialized blobs, removed together with its owner on exit.
+#[derive(Debug)]
+struct ScratchDir {
+    path: PathBuf,
+}
+
+impl ScratchDir {
+    fn new() -> Self {
+        let count = SCRATCH_DIRS.fetch_add(1, Ordering::Relaxed);
+        Self {
+            path: std::env::temp_dir().join(format!("gview-{}-{}", std::process::id(), count)),
+        }
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CommitRow {
     pub _author: String,
@@ -26,693 +77,4836 @@ impl CommitRow {
     }
 }
 
-pub struct RepositoryInfo {
-    repository: Repository,
-    oid: Oid,
+// Consecutive lines last changed by the same commit, as reported by blame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlameHunk {
+    // first line of the hunk, starting at 1
+    pub start: usize,
+    pub len: usize,
+    pub commit: Oid,
+    pub author: String,
+    pub email: String,
+    // commit time in seconds since the epoch
+    pub time: i64,
 }
 
-impl std::fmt::Debug for RepositoryInfo {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("RepositoryInfo")
-            .field("oid", &self.oid)
-            .finish()
+// `user.name` and `user.email` from git config, to pick out one's own changes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Identity {
+    pub name: Option<String>,
+    pub email: Option<String>,
+}
+
+impl Identity {
+    fn from_config(repository: &Repository) -> Self {
+        let config = repository.config().ok();
+        let get = |key: &str| {
+            config
+                .as_ref()
+                .and_then(|config| config.get_string(key).ok())
+                .filter(|value| !value.is_empty())
+        };
+        Self {
+            name: get("user.name"),
+            email: get("user.email"),
+        }
+    }
+
+    // Either the email (ignoring case) or the name is enough, as both tend to vary over time.
+    pub fn matches(&self, name: &str, email: &str) -> bool {
+        self.email
+            .as_deref()
+            .is_some_and(|mine| mine.eq_ignore_ascii_case(email))
+            || self.name.as_deref() == Some(name)
     }
 }
 
-impl RepositoryInfo {
-    pub fn new() -> anyhow::Result<Self> {
-        let repo_path = std::env::current_dir()?;
-        let repository = Repository::discover(repo_path)?;
-        let oid = repository.head()?.target().unwrap();
-        Ok(Self { repository, oid })
+// Lines of a committed file together with the blame hunks covering them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileContent {
+    pub lines: Vec<String>,
+    pub hunks: Vec<BlameHunk>,
+    // why blame failed, in which case the lines are still shown without it
+    pub blame_error: Option<String>,
+    // the last line is not terminated by a newline
+    pub missing_newline: bool,
+    // where the lines are in a file too large to read whole
+    pub window: Option<Window>,
+    // blame still to be worked out in the background, see `RepositoryInfo::spawn_blame`
+    pub pending_blame: Option<BlameRequest>,
+}
+
+/// A file whose blame is still to be worked out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlameRequest {
+    // cache key of the file at the commit
+    key: String,
+    // the viewed commit, whose version of the file is blamed
+    commit: Oid,
+    pub path: String,
+    // lines of the file, which no hunk may reach past
+    count: usize,
+}
+
+/// The files of a tree listed on a worker thread, handed back to
+/// `RepositoryInfo::finish_walk` once it is finished.
+pub struct WalkJob {
+    tree: Oid,
+    // every tree listed on the way, see `list_tree`
+    handle: std::thread::JoinHandle<anyhow::Result<HashMap<Oid, Vec<String>>>>,
+}
+
+impl WalkJob {
+    pub fn tree(&self) -> Oid {
+        self.tree
     }
 
-    // NOTE: this function should only be used during testing.
-    pub fn _from_parts(repository: Repository, oid: Oid) -> Self {
-        Self { repository, oid }
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
     }
+}
 
-    pub fn current_commit(&mut self) -> anyhow::Result<(String, String)> {
-        let commit = self.repository.find_commit(self.oid)?;
-        let commit_message = commit.message().unwrap_or("No commit message");
-        Ok((self.oid.to_string(), commit_message.to_owned()))
+const WALK_TASK: &str = "listing directories";
+
+/// The children of every commit reachable from HEAD found on a worker thread, handed back
+/// to `RepositoryInfo::finish_children` once it is finished.
+pub struct ChildrenJob {
+    head: Oid,
+    handle: std::thread::JoinHandle<anyhow::Result<HashMap<Oid, Vec<Oid>>>>,
+}
+
+impl ChildrenJob {
+    pub fn head(&self) -> Oid {
+        self.head
     }
 
-    pub fn set_parent_commit(&mut self) {
-        let commit = self.repository.find_commit(self.oid).unwrap();
-        if commit.parent_count() > 0 {
-            self.oid = commit.parent(0).unwrap().id();
-        }
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
     }
+}
 
-    pub fn set_next_commit(&mut self) -> anyhow::Result<(String, String)> {
-        let next_commit_id = {
-            let next_commit = self.find_next_commit()?;
-            next_commit.map(|next_commit| next_commit.id())
-        };
+const CHILDREN_TASK: &str = "finding children";
+const TRAILER_TASK: &str = "filtering by trailer";
 
-        if let Some(next_commit_id) = next_commit_id {
-            self.oid = next_commit_id;
-        }
-        self.current_commit()
+/// Blame of a file worked out on a worker thread, handed back to
+/// `RepositoryInfo::finish_blame` once it is finished.
+pub struct BlameJob {
+    request: BlameRequest,
+    handle: std::thread::JoinHandle<anyhow::Result<Vec<BlameHunk>>>,
+}
+
+impl BlameJob {
+    pub fn request(&self) -> &BlameRequest {
+        &self.request
     }
 
-    fn find_next_commit(&mut self) -> anyhow::Result<Option<Commit>> {
-        let commit = self.repository.find_commit(self.oid)?;
-        let mut revwalk = self.repository.revwalk()?;
-        revwalk.push_head()?;
-        revwalk.set_sorting(git2::Sort::REVERSE)?;
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+}
 
-        let mut next_commit_found = false;
-        for oid_result in revwalk {
-            let oid = oid_result?;
-            let rev_commit = self.repository.find_commit(oid)?;
+// A run of lines read from a large file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Window {
+    // lines of the file before the first one read
+    pub start: usize,
+    // lines of the whole file
+    pub total: usize,
+}
 
-            if next_commit_found {
-                return Ok(Some(rev_commit));
-            }
+impl FileContent {
+    pub fn hunk_at(&self, number: usize) -> Option<&BlameHunk> {
+        self.hunks
+            .iter()
+            .find(|hunk| hunk.start <= number && number < hunk.start + hunk.len)
+    }
 
-            if rev_commit.id() == commit.id() {
-                next_commit_found = true;
-            }
-        }
+    // One row per line, for views that render line by line; unblamed lines carry a zero id.
+    pub fn rows(&self) -> Vec<CommitRow> {
+        self.lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let (author, commit) = match self.hunk_at(i + 1) {
+                    Some(hunk) => (hunk.author.to_owned(), hunk.commit),
+                    None => ("".to_owned(), Oid::zero()),
+                };
+                let start = self.window.map_or(0, |window| window.start);
+                CommitRow::new(author, commit, start + i + 1, line.to_owned())
+            })
+            .collect()
+    }
+}
 
-        Ok(None)
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffRow {
+    pub origin: char,
+    pub old_number: Option<u32>,
+    pub new_number: Option<u32>,
+    pub line: String,
+}
+
+// A changed region between two versions of a file. Starts are 1-based; an empty side starts
+// at the line it follows, as in the `@@` header of a hunk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+}
+
+// Both versions of a file and the hunks between them, for the side-by-side diff.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SplitDiff {
+    pub old: Vec<String>,
+    pub new: Vec<String>,
+    pub hunks: Vec<DiffHunk>,
+}
+
+// Where a file's content is read from: the viewed commit, the index, or the checkout.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Source {
+    Commit,
+    Index,
+    WorkingTree,
+}
+
+impl Source {
+    pub fn label(self) -> &'static str {
+        match self {
+            Source::Commit => "COMMIT",
+            Source::Index => "INDEX",
+            Source::WorkingTree => "WORKING TREE",
+        }
     }
+}
 
-    pub fn get_content(&mut self, filename: String) -> anyhow::Result<Vec<CommitRow>> {
-        if filename == *"not found" {
-            return Ok(vec![]);
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rename {
+    pub from: String,
+    pub similarity: u16,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileStat {
+    pub path: String,
+    pub additions: usize,
+    pub deletions: usize,
+    pub rename: Option<Rename>,
+}
+
+// Where a commit came from, according to the lines git adds to its message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Origin {
+    // `(cherry picked from commit <id>)`, added by `git cherry-pick -x`
+    CherryPick(String),
+    // `Revert "..."`, usually followed by `This reverts commit <id>.`
+    Revert(Option<String>),
+}
+
+impl Origin {
+    fn parse(message: &str) -> Option<Self> {
+        let commit_after = |marker: &str| {
+            message.lines().find_map(|line| {
+                let rest = line.trim().strip_prefix(marker)?;
+                let id: String = rest.chars().take_while(char::is_ascii_hexdigit).collect();
+                (id.len() >= 7).then_some(id)
+            })
+        };
+        if let Some(id) = commit_after("(cherry picked from commit ") {
+            return Some(Origin::CherryPick(id));
         }
-        let path = Path::new(&filename);
-        let blame = self.repository.blame_file(path, None)?;
-        let commit = self.repository.head()?.peel_to_commit()?;
-        let tree = commit.tree()?;
-        let blob = tree
-            .get_path(path)?
-            .to_object(&self.repository)?
-            .peel_to_blob()?;
-        let reader = BufReader::new(blob.content());
-        let mut content = vec![];
-        for (i, line) in reader.lines().enumerate() {
-            if let (Ok(line), Some(hunk)) = (line, blame.get_line(i + 1)) {
-                let signature = hunk.orig_signature();
-                let author = signature.name().unwrap_or("Unknown");
-                let commit_id = hunk.final_commit_id();
-                // let line_number = hunk.final_start_line();
-                let row = CommitRow::new(author.to_owned(), commit_id, i + 1, line);
-                content.push(row);
-            }
+        let reverted = commit_after("This reverts commit ");
+        if reverted.is_some() || message.starts_with("Revert \"") {
+            return Some(Origin::Revert(reverted));
         }
+        None
+    }
 
-        Ok(content)
+    // Stored in the history cache next to the subject.
+    fn encode(origin: Option<&Origin>) -> String {
+        match origin {
+            Some(Origin::CherryPick(id)) => format!("pick:{}", id),
+            Some(Origin::Revert(id)) => format!("revert:{}", id.as_deref().unwrap_or("")),
+            None => String::new(),
+        }
     }
-    pub fn get_commit_history(&self) -> anyhow::Result<Vec<(String, String)>> {
-        let mut revwalk = self.repository.revwalk()?;
-        revwalk.push_head()?;
-        revwalk.set_sorting(git2::Sort::TIME)?;
 
-        let mut commits = Vec::new();
-        for oid_result in revwalk {
-            let oid = oid_result?;
-            let commit = self.repository.find_commit(oid)?;
-            let commit_message = commit
-                .message()
-                .unwrap_or("No commit message")
-                .lines()
-                .next()
-                .unwrap_or("")
-                .to_string();
-            commits.push((oid.to_string(), commit_message));
+    fn decode(field: &str) -> Option<Origin> {
+        match field.split_once(':')? {
+            ("pick", id) => Some(Origin::CherryPick(id.to_owned())),
+            ("revert", "") => Some(Origin::Revert(None)),
+            ("revert", id) => Some(Origin::Revert(Some(id.to_owned()))),
+            _ => None,
         }
+    }
 
-        Ok(commits)
+    pub fn label(&self) -> &'static str {
+        match self {
+            Origin::CherryPick(_) => "cherry-pick",
+            Origin::Revert(_) => "revert",
+        }
     }
 
-    pub fn get_current_commit_id(&self) -> String {
-        self.oid.to_string()
+    pub fn commit(&self) -> Option<&str> {
+        match self {
+            Origin::CherryPick(id) => Some(id),
+            Origin::Revert(id) => id.as_deref(),
+        }
     }
+}
 
-    pub fn set_commit_by_id(&mut self, commit_id: &str) -> anyhow::Result<()> {
-        let oid = if commit_id.len() == 40 {
-            // Full commit ID
-            git2::Oid::from_str(commit_id)?
-        } else {
-            // Short commit ID - need to resolve it
-            let mut revwalk = self.repository.revwalk()?;
-            revwalk.push_head()?;
+// The window of commit times the commit history is limited to, open at either end.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DateRange {
+    since: Option<(i64, String)>,
+    // the day given is included, so the window ends at the start of the next one
+    until: Option<(i64, String)>,
+}
 
-            let mut found_oid = None;
-            let mut match_count = 0;
-            for oid_result in revwalk {
-                let oid = oid_result?;
-                let oid_str = oid.to_string();
-                if oid_str.starts_with(commit_id) {
-                    found_oid = Some(oid);
-                    match_count += 1;
-                    if match_count > 1 {
-                        return Err(anyhow::anyhow!(
-                            "Ambiguous commit ID: multiple commits match '{}'",
-                            commit_id
-                        ));
-                    }
-                }
+impl DateRange {
+    // Dates are YYYY-MM-DD, in UTC.
+    pub fn new(since: Option<&str>, until: Option<&str>) -> anyhow::Result<Self> {
+        let parse = |date: Option<&str>, days: i64| -> anyhow::Result<Option<(i64, String)>> {
+            match date.map(str::trim).filter(|date| !date.is_empty()) {
+                Some(date) => Ok(Some((
+                    summary::parse_date(date)? + days * 86400,
+                    date.to_owned(),
+                ))),
+                None => Ok(None),
             }
-
-            found_oid.ok_or_else(|| anyhow::anyhow!(format!("Commit '{}' not found", commit_id)))?
         };
+        let range = Self {
+            since: parse(since, 0)?,
+            until: parse(until, 1)?,
+        };
+        if let (Some((since, _)), Some((until, _))) = (&range.since, &range.until) {
+            if since >= until {
+                return Err(anyhow::anyhow!(
+                    "{} is not before {}",
+                    range.start(),
+                    range.end()
+                ));
+            }
+        }
+        Ok(range)
+    }
 
-        // Verify the commit exists before setting it
-        self.repository.find_commit(oid)?;
-        self.oid = oid;
-        Ok(())
+    // Reads `SINCE..UNTIL`, where either date may be left out.
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        match text.split_once("..") {
+            Some((since, until)) => Self::new(Some(since), Some(until)),
+            None if text.trim().is_empty() => Ok(Self::default()),
+            None => Err(anyhow::anyhow!("expected dates as SINCE..UNTIL")),
+        }
     }
 
-    pub fn recursive_walk(&mut self) -> anyhow::Result<Vec<String>> {
-        let head = self.repository.find_commit(self.oid)?;
-        let tree = head.tree()?;
-        let mut results: Vec<String> = vec![];
-        let mut path_stack: Vec<PathBuf> = vec![PathBuf::new()];
-        let _ = tree.walk(TreeWalkMode::PreOrder, |root, entry| {
-            if let Some(name) = entry.name() {
-                let mut current_path = PathBuf::from(root);
-                current_path.push(name);
-
-                if let Ok(obj) = entry.to_object(&self.repository) {
-                    match obj.kind() {
-                        Some(ObjectType::Blob) => {
-                            let blob = obj.peel_to_blob().unwrap();
-                            let content = blob.content();
-                            if content.len() < MAX_FILE_SIZE && content.is_ascii() {
-                                results.push(current_path.to_string_lossy().to_string());
-                            }
-                        }
-                        Some(ObjectType::Tree) => {
-                            path_stack.push(current_path.clone());
-                        }
-                        _ => (),
-                    }
-                }
-            }
-            TreeWalkResult::Ok
-        });
+    pub fn is_set(&self) -> bool {
+        self.since.is_some() || self.until.is_some()
+    }
 
-        Ok(results)
+    fn contains(&self, time: i64) -> bool {
+        self.since.as_ref().is_none_or(|(since, _)| time >= *since)
+            && self.until.as_ref().is_none_or(|(until, _)| time < *until)
     }
 
-    pub fn get_origin_url(&self) -> anyhow::Result<String> {
-        let config = self.repository.config()?;
-        let url = config.get_string("remote.origin.url")?;
-        Ok(url)
+    fn start(&self) -> &str {
+        self.since.as_ref().map_or("", |(_, date)| date)
     }
 
-    pub fn open_file_in_browser(&self, file_path: &str, line_number: usize) -> anyhow::Result<()> {
-        let origin_url = self.get_origin_url()?;
-        let github_url = self.construct_github_url(&origin_url, file_path, line_number)?;
+    fn end(&self) -> &str {
+        self.until.as_ref().map_or("", |(_, date)| date)
+    }
+}
 
-        self.open_url_in_browser(&github_url)?;
-        Ok(())
+impl std::fmt::Display for DateRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.start(), self.end())
     }
+}
 
-    fn construct_github_url(
-        &self,
-        origin_url: &str,
-        file_path: &str,
-        line_number: usize,
-    ) -> anyhow::Result<String> {
-        let (base_url, repo_path) = if origin_url.starts_with("git@") {
-            // SSH format: git@github.com:owner/repo.git
-            let url_without_prefix = origin_url.strip_prefix("git@").unwrap();
-            let parts: Vec<&str> = url_without_prefix.split(':').collect();
-            if parts.len() != 2 {
-                return Err(anyhow::anyhow!("Invalid SSH URL format"));
-            }
-            let host = parts[0];
-            let repo_path = parts[1].strip_suffix(".git").unwrap_or(parts[1]);
-            (format!("https://{}", host), repo_path.to_string())
-        } else if origin_url.starts_with("https://") {
-            // HTTPS format: https://github.com/owner/repo.git
-            let url_without_https = origin_url.strip_prefix("https://").unwrap();
-            let parts: Vec<&str> = url_without_https.splitn(2, '/').collect();
-            if parts.len() != 2 {
-                return Err(anyhow::anyhow!("Invalid HTTPS URL format"));
+// Size of a commit's change against its first parent.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CommitStats {
+    pub files: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl std::fmt::Display for CommitStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} file{} changed, +{} −{}",
+            self.files,
+            if self.files == 1 { "" } else { "s" },
+            self.insertions,
+            self.deletions
+        )
+    }
+}
+
+// Files, lines and bytes of one language in a tree, as `count_languages` tallies them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageStat {
+    // the language's name, the extension of text files of no known language, or "Binary"
+    pub language: String,
+    pub files: usize,
+    pub lines: usize,
+    pub bytes: usize,
+}
+
+// What `file` is counted as: its language, else its extension, else "Other".
+fn language_of(file: &str, content: &[u8]) -> String {
+    if !is_text(content) {
+        return "Binary".to_owned();
+    }
+    if let Some(language) = highlight::language(file) {
+        return language.to_owned();
+    }
+    match Path::new(file).extension() {
+        Some(extension) => format!(".{}", extension.to_string_lossy()),
+        None => "Other".to_owned(),
+    }
+}
+
+// Tallies the blobs of `tree` by language, the most bytes first, like `linguist` does.
+fn count_languages(
+    repository: &Repository,
+    tree: Oid,
+    progress: &ProgressReporter,
+) -> anyhow::Result<Vec<LanguageStat>> {
+    let mut blobs = vec![];
+    repository
+        .find_tree(tree)?
+        .walk(TreeWalkMode::PreOrder, |root, entry| {
+            if let (Some(ObjectType::Blob), Some(name)) = (entry.kind(), entry.name()) {
+                blobs.push((format!("{}{}", root, name), entry.id()));
             }
-            let host = parts[0];
-            let repo_path = parts[1].strip_suffix(".git").unwrap_or(parts[1]);
-            (format!("https://{}", host), repo_path.to_string())
+            TreeWalkResult::Ok
+        })?;
+    let mut stats: HashMap<String, LanguageStat> = HashMap::new();
+    for (index, (path, oid)) in blobs.iter().enumerate() {
+        progress.update("counting languages", index + 1, Some(blobs.len()));
+        let blob = repository.find_blob(*oid)?;
+        let content = blob.content();
+        let language = language_of(path, content);
+        let lines = if is_text(content) {
+            split_lines(content).0.len()
         } else {
-            return Err(anyhow::anyhow!("Unsupported URL format"));
+            0
         };
+        let stat = stats
+            .entry(language.clone())
+            .or_insert_with(|| LanguageStat {
+                language,
+                files: 0,
+                lines: 0,
+                bytes: 0,
+            });
+        stat.files += 1;
+        stat.lines += lines;
+        stat.bytes += content.len();
+    }
+    let mut stats: Vec<LanguageStat> = stats.into_values().collect();
+    stats.sort_by(|a, b| b.bytes.cmp(&a.bytes).then(a.language.cmp(&b.language)));
+    Ok(stats)
+}
 
-        let commit_id = self.oid.to_string();
-        let url = format!(
-            "{}/{}/blob/{}/{}#L{}",
-            base_url, repo_path, commit_id, file_path, line_number
-        );
-        Ok(url)
+// Splits blob content into lines without their `\n` or `\r\n` ending, keeping undecodable
+// lines (lossily) so line numbers stay aligned with blame. Also tells whether the final
+// newline is missing.
+fn split_lines(content: &[u8]) -> (Vec<String>, bool) {
+    let missing_newline = !content.is_empty() && !content.ends_with(b"\n");
+    let content = content.strip_suffix(b"\n").unwrap_or(content);
+    if content.is_empty() && !missing_newline {
+        return (vec![], false);
     }
+    let lines = content
+        .split(|byte| *byte == b'\n')
+        .map(|line| {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            String::from_utf8_lossy(line).into_owned()
+        })
+        .collect();
+    (lines, missing_newline)
+}
 
-    fn open_url_in_browser(&self, url: &str) -> anyhow::Result<()> {
-        #[cfg(target_os = "macos")]
-        {
-            Command::new("open").arg(url).spawn()?;
-        }
-        #[cfg(target_os = "linux")]
-        {
-            Command::new("xdg-open").arg(url).spawn()?;
-        }
-        #[cfg(target_os = "windows")]
-        {
-            Command::new("cmd").args(["/c", "start", url]).spawn()?;
+// Rows of `content` split as `split_lines` does, without blame, and whether the final newline
+// is missing.
+fn unblamed_rows(content: &[u8]) -> (Vec<CommitRow>, bool) {
+    let (lines, missing_newline) = split_lines(content);
+    let rows = lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| CommitRow::new("".to_owned(), Oid::zero(), i + 1, line))
+        .collect();
+    (rows, missing_newline)
+}
+
+// Lines `lines` (1-based) of `content` byte for byte with their line endings, which
+// `split_lines` normalizes for display.
+fn line_bytes(content: &[u8], lines: RangeInclusive<usize>) -> &[u8] {
+    let mut starts = std::iter::once(0).chain(
+        content
+            .iter()
+            .enumerate()
+            .filter(|(_, byte)| **byte == b'\n')
+            .map(|(index, _)| index + 1),
+    );
+    let start = starts
+        .nth(lines.start().saturating_sub(1))
+        .unwrap_or(content.len());
+    let end = starts
+        .nth(lines.end().saturating_sub(*lines.start()))
+        .unwrap_or(content.len());
+    &content[start..end]
+}
+
+// Hunks without context between `old` and `new`, so everything outside them is unchanged.
+fn split_diff(old_path: &str, old: &[u8], new_path: &str, new: &[u8]) -> anyhow::Result<SplitDiff> {
+    if old.contains(&0) || new.contains(&0) {
+        return Err(anyhow::anyhow!("binary files cannot be shown side by side"));
+    }
+    let mut options = DiffOptions::new();
+    options.context_lines(0);
+    let patch = Patch::from_buffers(
+        old,
+        Some(Path::new(old_path)),
+        new,
+        Some(Path::new(new_path)),
+        Some(&mut options),
+    )?;
+    let hunks = (0..patch.num_hunks())
+        .map(|index| {
+            patch.hunk(index).map(|(hunk, _)| DiffHunk {
+                old_start: hunk.old_start() as usize,
+                old_lines: hunk.old_lines() as usize,
+                new_start: hunk.new_start() as usize,
+                new_lines: hunk.new_lines() as usize,
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(SplitDiff {
+        old: split_lines(old).0,
+        new: split_lines(new).0,
+        hunks,
+    })
+}
+
+// Files of `tree`, relative to it. Subtrees are listed once per id and remembered in
+// `listed`, since most of them are shared between neighbouring commits.
+fn list_tree(
+    repository: &Repository,
+    tree: &Tree<'_>,
+    listed: &mut HashMap<Oid, Vec<String>>,
+    progress: Option<&ProgressReporter>,
+) -> anyhow::Result<Vec<String>> {
+    if let Some(files) = listed.get(&tree.id()) {
+        return Ok(files.clone());
+    }
+    let mut files = vec![];
+    for entry in tree.iter() {
+        let Some(name) = entry.name() else {
+            continue;
+        };
+        match entry.kind() {
+            // binary files are listed too, and shown as a hex dump
+            Some(ObjectType::Blob) => files.push(name.to_owned()),
+            Some(ObjectType::Tree) => {
+                let subtree = repository.find_tree(entry.id())?;
+                for path in list_tree(repository, &subtree, listed, progress)? {
+                    files.push(format!("{}/{}", name, path));
+                }
+            }
+            _ => (),
         }
-        Ok(())
     }
+    listed.insert(tree.id(), files.clone());
+    if let Some(progress) = progress {
+        progress.update(WALK_TASK, listed.len(), None);
+    }
+    Ok(files)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::env;
-    use std::fs;
-    use std::io::Write;
+// Blame hunks of `path` at `commit`, leaving out any reaching past its `count` lines.
+fn blame_file_hunks(
+    repository: &Repository,
+    commit: Oid,
+    path: &Path,
+    count: usize,
+) -> anyhow::Result<Vec<BlameHunk>> {
+    let blame = repository.blame_file(path, Some(BlameOptions::new().newest_commit(commit)))?;
+    Ok(blame
+        .iter()
+        .map(|hunk| {
+            let signature = hunk.orig_signature();
+            BlameHunk {
+                start: hunk.final_start_line(),
+                len: hunk.lines_in_hunk(),
+                commit: hunk.final_commit_id(),
+                author: signature.name().unwrap_or("Unknown").to_owned(),
+                email: signature.email().unwrap_or("").to_owned(),
+                time: signature.when().seconds(),
+            }
+        })
+        .filter(|hunk| hunk.start + hunk.len <= count + 1)
+        .collect())
+}
 
-    fn setup_test_repo_with_file() -> (Repository, String) {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos();
-        let test_dir = env::temp_dir().join(format!("gview_test_repo_{}", timestamp));
-        let _ = fs::remove_dir_all(&test_dir);
-        fs::create_dir_all(&test_dir).unwrap();
+// Blobs without a NUL byte in their first 8000 bytes, the check git uses, count as text
+// whatever their encoding.
+fn is_text(content: &[u8]) -> bool {
+    !content[..content.len().min(8000)].contains(&0)
+}
 
-        let repo = Repository::init(&test_dir).unwrap();
+// Up to `WINDOW_LINES` lines from line `start` on; only they are decoded, so a large file
+// costs no more than its shown part on top of the blob itself.
+fn content_window(content: &[u8], start: usize) -> FileContent {
+    let missing_newline = !content.is_empty() && !content.ends_with(b"\n");
+    let body = content.strip_suffix(b"\n").unwrap_or(content);
+    let mut newlines = body
+        .iter()
+        .enumerate()
+        .filter(|(_, byte)| **byte == b'\n')
+        .map(|(index, _)| index);
+    let total = match content.is_empty() {
+        true => 0,
+        false => body.iter().filter(|byte| **byte == b'\n').count() + 1,
+    };
+    let start = start.min(total);
+    let lines: Vec<String> = if start == total {
+        vec![]
+    } else {
+        let from = match start {
+            0 => 0,
+            _ => newlines
+                .nth(start - 1)
+                .map_or(body.len(), |index| index + 1),
+        };
+        let to = newlines.nth(WINDOW_LINES - 1).unwrap_or(body.len());
+        body[from..to]
+            .split(|byte| *byte == b'\n')
+            .map(|line| {
+                let line = line.strip_suffix(b"\r").unwrap_or(line);
+                String::from_utf8_lossy(line).into_owned()
+            })
+            .collect()
+    };
+    FileContent {
+        missing_newline: missing_newline && start + lines.len() == total,
+        lines,
+        hunks: vec![],
+        blame_error: Some("the file is too large".to_owned()),
+        window: Some(Window { start, total }),
+        pending_blame: None,
+    }
+}
 
-        // Create a test file
-        let test_file_path = test_dir.join("test.txt");
-        let mut file = fs::File::create(&test_file_path).unwrap();
-        file.write_all(b"line 1\nline 2\nline 3\n").unwrap();
+fn with_forward_slashes(path: &str, separator: char) -> String {
+    match separator {
+        '/' => path.to_owned(),
+        _ => path.replace(separator, "/"),
+    }
+}
 
-        // Add and commit the file
+// URL templates of the forges' file pages by layout name, which a configured template
+// can be instead of a URL.
+const FORGE_LAYOUTS: [(&str, &str); 5] = [
+    ("github", "https://{host}/{repo}/blob/{sha}/{path}#L{line}"),
+    (
+        "gitlab",
+        "https://{host}/{repo}/-/blob/{sha}/{path}#L{line}",
+    ),
+    (
+        "bitbucket",
+        "https://{host}/{repo}/src/{sha}/{path}#lines-{line}",
+    ),
+    (
+        "gitea",
+        "https://{host}/{repo}/src/commit/{sha}/{path}#L{line}",
+    ),
+    (
+        "sourcehut",
+        "https://{host}/{repo}/tree/{sha}/item/{path}#L{line}",
+    ),
+];
+
+// Public hosts and their layouts; any other host is taken for GitHub unless configured.
+const FORGE_HOSTS: [(&str, &str); 6] = [
+    ("github.com", "github"),
+    ("gitlab.com", "gitlab"),
+    ("bitbucket.org", "bitbucket"),
+    ("codeberg.org", "gitea"),
+    ("gitea.com", "gitea"),
+    ("git.sr.ht", "sourcehut"),
+];
+
+// The file page template of the forge layout `name`, e.g. `gitlab`.
+fn forge_layout(name: &str) -> Option<&'static str> {
+    FORGE_LAYOUTS
+        .iter()
+        .find(|(layout, _)| *layout == name)
+        .map(|(_, template)| *template)
+}
+
+/// Fills the `{host}`, `{repo}`, `{sha}`, `{path}` and `{line}` placeholders of a forge URL
+/// template from `fields`, failing on any other placeholder.
+pub fn url_from_template(template: &str, fields: &[(&str, &str)]) -> anyhow::Result<String> {
+    let mut url = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        url.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .map(|end| start + end)
+            .ok_or_else(|| anyhow::anyhow!("unclosed {{ in {}", template))?;
+        let name = &rest[start + 1..end];
+        let (_, value) = fields
+            .iter()
+            .find(|(field, _)| *field == name)
+            .ok_or_else(|| anyhow::anyhow!("unknown placeholder {{{}}} in {}", name, template))?;
+        url.push_str(value);
+        rest = &rest[end + 1..];
+    }
+    url.push_str(rest);
+    Ok(url)
+}
+
+/// A path as git writes it, with `/` between components whatever the platform's
+/// separator is. Paths are kept this way everywhere, so that filtering, prefixes and forge
+/// URLs work the same on Windows.
+pub fn git_path(path: &Path) -> String {
+    with_forward_slashes(&path.to_string_lossy(), std::path::MAIN_SEPARATOR)
+}
+
+/// Whether `error` is the object database failing to give an object: missing, as in partial
+/// clones whose promisor remote was not asked and in pruned repositories, or corrupt.
+/// A path that is not in a tree is not one of these.
+pub fn is_unavailable_object(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<git2::Error>().is_some_and(|error| {
+        matches!(
+            error.class(),
+            git2::ErrorClass::Odb | git2::ErrorClass::Zlib
+        )
+    })
+}
+
+// What the terminal session can reach a browser through.
+#[derive(Debug, Clone, Copy, Default)]
+struct Session {
+    // logged in over SSH, where a browser would open on the remote machine if at all
+    ssh: bool,
+    // inside WSL, where Windows' browser is reached through wslview
+    wsl: bool,
+    // an X11 or Wayland display, needed by xdg-open
+    display: bool,
+}
+
+impl Session {
+    fn current() -> Self {
+        let set = |name: &str| std::env::var_os(name).is_some_and(|value| !value.is_empty());
+        Self {
+            ssh: set("SSH_CONNECTION") || set("SSH_TTY"),
+            wsl: set("WSL_DISTRO_NAME"),
+            display: set("DISPLAY") || set("WAYLAND_DISPLAY"),
+        }
+    }
+}
+
+// The command opening `url` in the default browser, if the session has one. On Windows,
+// `cmd /c start` would split URLs at `&`, so the URL handler is called directly.
+fn browser_command(url: &str, session: Session) -> Option<Command> {
+    let (program, args): (&str, &[&str]) = if session.ssh {
+        return None;
+    } else if session.wsl {
+        ("wslview", &[])
+    } else if cfg!(target_os = "windows") {
+        ("rundll32", &["url.dll,FileProtocolHandler"])
+    } else if cfg!(target_os = "macos") {
+        ("open", &[])
+    } else if session.display {
+        ("xdg-open", &[])
+    } else {
+        return None;
+    };
+    let mut command = Command::new(program);
+    command.args(args).arg(url);
+    Some(command)
+}
+
+/// Opens `url` in the default browser; fails when the session has no browser to open, as
+/// over SSH or in WSL without wslview.
+pub fn open_in_browser(url: &str) -> anyhow::Result<()> {
+    let Some(mut command) = browser_command(url, Session::current()) else {
+        return Err(anyhow::anyhow!("no browser available"));
+    };
+    command.spawn()?;
+    Ok(())
+}
+
+fn delta_path(diff: &Diff<'_>, index: usize) -> Option<String> {
+    let delta = diff.get_delta(index)?;
+    delta
+        .new_file()
+        .path()
+        .or(delta.old_file().path())
+        .map(git_path)
+}
+
+// git2 does not expose the similarity score, so it is read from the patch header.
+fn rename_at(diff: &Diff<'_>, index: usize) -> anyhow::Result<Option<Rename>> {
+    let delta = match diff.get_delta(index) {
+        Some(delta) if delta.status() == Delta::Renamed => delta,
+        _ => return Ok(None),
+    };
+    let from = match delta.old_file().path() {
+        Some(path) => git_path(path),
+        None => return Ok(None),
+    };
+    let header = match Patch::from_diff(diff, index)? {
+        Some(mut patch) => String::from_utf8_lossy(&patch.to_buf()?).to_string(),
+        None => String::new(),
+    };
+    let similarity = header
+        .lines()
+        .find_map(|line| line.strip_prefix("similarity index "))
+        .and_then(|value| value.trim_end_matches('%').parse().ok())
+        .unwrap_or(100);
+    Ok(Some(Rename { from, similarity }))
+}
+
+// Rows of the patch for `filename` in `diff`, or nothing if the file is unchanged.
+fn diff_rows(diff: &Diff<'_>, filename: &str) -> anyhow::Result<Vec<DiffRow>> {
+    let index = match (0..diff.deltas().len())
+        .find(|index| delta_path(diff, *index).as_deref() == Some(filename))
+    {
+        Some(index) => index,
+        None => return Ok(vec![]),
+    };
+
+    let mut rows = vec![];
+    if let Some(rename) = rename_at(diff, index)? {
+        rows.push(DiffRow {
+            origin: 'H',
+            old_number: None,
+            new_number: None,
+            line: format!(
+                "renamed from {} ({}% similar)",
+                rename.from, rename.similarity
+            ),
+        });
+    }
+    let mut patch = match Patch::from_diff(diff, index)? {
+        Some(patch) => patch,
+        None => return Ok(rows),
+    };
+    patch.print(&mut |_, _, line| {
+        if matches!(line.origin(), '+' | '-' | ' ' | 'H') {
+            let content = String::from_utf8_lossy(line.content());
+            rows.push(DiffRow {
+                origin: line.origin(),
+                old_number: line.old_lineno(),
+                new_number: line.new_lineno(),
+                line: content.trim_end_matches(['\r', '\n']).to_owned(),
+            });
+        }
+        true
+    })?;
+    Ok(rows)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub path: String,
+    pub line: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitInfo {
+    pub id: String,
+    pub author: String,
+    pub email: String,
+    pub date: String,
+    pub message: String,
+}
+
+impl CommitInfo {
+    fn from_commit(commit: &Commit) -> Self {
+        let author = commit.author();
+        Self {
+            id: commit.id().to_string(),
+            author: author.name().unwrap_or("unknown").to_owned(),
+            email: author.email().unwrap_or("").to_owned(),
+            date: summary::format_time(author.when()),
+            message: commit.message().unwrap_or("No commit message").to_owned(),
+        }
+    }
+}
+
+/// A commit of the history as the commit modal lists it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub id: String,
+    pub subject: String,
+    pub author: String,
+    pub email: String,
+    pub origin: Option<Origin>,
+}
+
+/// Commits read from one part of the history, see `RepositoryInfo::history_page`.
+#[derive(Debug, Default)]
+pub struct HistoryPage {
+    pub entries: Vec<HistoryEntry>,
+    // where the next page starts, unless the history ends here
+    pub next: Option<usize>,
+}
+
+// Text blobs at commit `oid`, limited to the directory `prefix` when given.
+fn text_blobs_at(
+    repository: &Repository,
+    oid: Oid,
+    prefix: Option<&str>,
+    cancel: &CancelToken,
+) -> anyhow::Result<Vec<(String, String)>> {
+    let mut tree = repository.find_commit(oid)?.tree()?;
+    if let Some(prefix) = prefix {
+        // the directory may not exist at every commit
+        match tree.get_path(Path::new(prefix)) {
+            Ok(entry) => tree = entry.to_object(repository)?.peel_to_tree()?,
+            Err(_) => return Ok(vec![]),
+        }
+    }
+    let base = prefix
+        .map(|prefix| format!("{}/", prefix))
+        .unwrap_or_default();
+    let mut results: Vec<(String, String)> = vec![];
+    let walked = tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+        if cancel.is_canceled() {
+            return TreeWalkResult::Abort;
+        }
+        if entry.kind() == Some(ObjectType::Blob) {
+            if let (Some(name), Ok(blob)) = (entry.name(), repository.find_blob(entry.id())) {
+                let content = blob.content();
+                if content.len() < MAX_FILE_SIZE && is_text(content) {
+                    results.push((
+                        format!("{}{}{}", base, root, name),
+                        String::from_utf8_lossy(content).to_string(),
+                    ));
+                }
+            }
+        }
+        TreeWalkResult::Ok
+    });
+    if cancel.is_canceled() {
+        return Err(anyhow::anyhow!("canceled"));
+    }
+    walked?;
+    Ok(results)
+}
+
+// Lines at commit `oid` containing `word` as a whole word.
+fn search_word(
+    repository: &Repository,
+    oid: Oid,
+    prefix: Option<&str>,
+    word: &str,
+    progress: &ProgressReporter,
+    cancel: &CancelToken,
+) -> anyhow::Result<Vec<SearchHit>> {
+    let pattern = Regex::new(&format!(r"\b{}\b", regex::escape(word)))?;
+    let blobs = text_blobs_at(repository, oid, prefix, cancel)?;
+    let mut hits: Vec<SearchHit> = vec![];
+    for (index, (path, content)) in blobs.iter().enumerate() {
+        if cancel.is_canceled() {
+            return Err(anyhow::anyhow!("canceled"));
+        }
+        progress.update("searching", index + 1, Some(blobs.len()));
+        for (number, line) in content.lines().enumerate() {
+            if pattern.is_match(line) {
+                hits.push(SearchHit {
+                    path: path.to_owned(),
+                    line: number + 1,
+                    text: line.trim().to_owned(),
+                });
+            }
+        }
+    }
+    Ok(hits)
+}
+
+// Children of each commit reachable from `head`; the walk stops early once `cancel` is set.
+fn child_graph(
+    repository: &Repository,
+    head: Oid,
+    progress: Option<&ProgressReporter>,
+    cancel: &CancelToken,
+) -> anyhow::Result<HashMap<Oid, Vec<Oid>>> {
+    let mut graph: HashMap<Oid, Vec<Oid>> = HashMap::new();
+    let mut revwalk = repository.revwalk()?;
+    revwalk.push(head)?;
+    for (index, oid) in revwalk.enumerate() {
+        if cancel.is_canceled() {
+            return Err(anyhow::anyhow!("canceled"));
+        }
+        let oid = oid?;
+        for parent in repository.find_commit(oid)?.parent_ids() {
+            graph.entry(parent).or_default().push(oid);
+        }
+        if let Some(progress) = progress {
+            progress.update(CHILDREN_TASK, index + 1, None);
+        }
+    }
+    Ok(graph)
+}
+
+// Commits from HEAD with a trailer whose `Key: value` text contains `query`, ignoring case;
+// the walk stops early once `cancel` is set.
+fn commits_with_trailer(
+    repository: &Repository,
+    query: &str,
+    progress: &ProgressReporter,
+    cancel: &CancelToken,
+) -> anyhow::Result<HashSet<String>> {
+    let query = query.to_lowercase();
+    let mut revwalk = repository.revwalk()?;
+    revwalk.push_head()?;
+    let mut matching = HashSet::new();
+    for (index, oid) in revwalk.enumerate() {
+        if cancel.is_canceled() {
+            return Err(anyhow::anyhow!("canceled"));
+        }
+        let oid = oid?;
+        let commit = repository.find_commit(oid)?;
+        let (_, trailers) = trailers::split(commit.message().unwrap_or(""));
+        if trailers
+            .iter()
+            .any(|trailer| trailer.to_string().to_lowercase().contains(&query))
+        {
+            matching.insert(oid.to_string());
+        }
+        progress.update(TRAILER_TASK, index + 1, None);
+    }
+    Ok(matching)
+}
+
+fn load_commit_metadata(
+    path: &Path,
+    metadata: &Mutex<HashMap<Oid, CommitInfo>>,
+    progress: &ProgressReporter,
+) -> anyhow::Result<()> {
+    let repository = Repository::open(path)?;
+    let mut revwalk = repository.revwalk()?;
+    revwalk.push_head()?;
+    revwalk.set_sorting(git2::Sort::TIME)?;
+    for (index, oid) in revwalk.take(PRELOAD_COMMITS).enumerate() {
+        let oid = oid?;
+        let info = CommitInfo::from_commit(&repository.find_commit(oid)?);
+        metadata
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Failed to acquire metadata lock"))?
+            .insert(oid, info);
+        progress.update("loading commits", index + 1, None);
+    }
+    Ok(())
+}
+
+pub struct RepositoryInfo {
+    repository: Repository,
+    oid: Oid,
+    base: Option<Oid>,
+    target: Option<Oid>,
+    notes: NoteStore,
+    tags: Option<(Oid, TagIndex)>,
+    scratch: ScratchDir,
+    cache: Cache,
+    // filled in the background by `preload_commit_metadata`
+    metadata: Arc<Mutex<HashMap<Oid, CommitInfo>>>,
+    // files per tree id, see `list_tree`
+    listed_trees: HashMap<Oid, Vec<String>>,
+    // blame hunks, or why blame failed, per file at a commit as keyed in the cache
+    blamed: HashMap<String, Result<Vec<BlameHunk>, String>>,
+    // leave blame missing from `get_content` and listings missing from `listed_files` to
+    // worker threads rather than waiting for them
+    background: bool,
+    sparse: Option<SparsePatterns>,
+    // list only the files inside the sparse checkout
+    sparse_only: bool,
+    // directory the session is scoped to, without a trailing slash
+    prefix: Option<String>,
+    // commit times the history is limited to
+    date_range: DateRange,
+    // highlight the user's own blame lines and commits
+    highlight_mine: bool,
+    // children of each commit reachable from HEAD, rebuilt when HEAD moves
+    children: Option<(Oid, HashMap<Oid, Vec<Oid>>)>,
+    // remote host -> forge URL template, see `url_from_template`
+    url_templates: HashMap<String, String>,
+    // remote whose URL browser links use, `origin` when unset or missing
+    remote: Option<String>,
+    // issue references linked from commit messages, see `issues::find`
+    issue_patterns: Vec<IssuePattern>,
+}
+
+impl std::fmt::Debug for RepositoryInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RepositoryInfo")
+            .field("oid", &self.oid)
+            .field("base", &self.base)
+            .field("target", &self.target)
+            .finish()
+    }
+}
+
+impl RepositoryInfo {
+    pub fn new() -> anyhow::Result<Self> {
+        let repo_path = std::env::current_dir()?;
+        let repository = Repository::discover(repo_path)?;
+        let oid = repository.head()?.target().unwrap();
+        let key = state::storage_key(repository.path());
+        let notes = NoteStore::for_repository(&key);
+        let cache = Cache::for_repository(&key);
+        Ok(Self {
+            oid,
+            base: None,
+            target: None,
+            notes,
+            tags: None,
+            scratch: ScratchDir::new(),
+            cache,
+            metadata: Arc::new(Mutex::new(HashMap::new())),
+            listed_trees: HashMap::new(),
+            blamed: HashMap::new(),
+            background: false,
+            sparse: SparsePatterns::from_repository(&repository),
+            sparse_only: false,
+            prefix: None,
+            date_range: DateRange::default(),
+            highlight_mine: false,
+            children: None,
+            url_templates: HashMap::new(),
+            remote: None,
+            issue_patterns: IssuePattern::new(issues::DEFAULT_PATTERN, issues::DEFAULT_TEMPLATE)
+                .into_iter()
+                .collect(),
+            repository,
+        })
+    }
+
+    // Opens the repository at `path` without the on-disk cache or the notes, so that every
+    // read goes to git, as when measuring it.
+    pub fn open_uncached(path: &Path) -> anyhow::Result<Self> {
+        let repository = Repository::discover(path)?;
+        let oid = repository.head()?.peel_to_commit()?.id();
+        Ok(Self::_from_parts(repository, oid))
+    }
+
+    // NOTE: this function should only be used during testing.
+    pub fn _from_parts(repository: Repository, oid: Oid) -> Self {
+        Self {
+            oid,
+            base: None,
+            target: None,
+            notes: NoteStore::default(),
+            tags: None,
+            scratch: ScratchDir::new(),
+            cache: Cache::default(),
+            metadata: Arc::new(Mutex::new(HashMap::new())),
+            listed_trees: HashMap::new(),
+            blamed: HashMap::new(),
+            background: false,
+            sparse: SparsePatterns::from_repository(&repository),
+            sparse_only: false,
+            prefix: None,
+            date_range: DateRange::default(),
+            highlight_mine: false,
+            children: None,
+            url_templates: HashMap::new(),
+            remote: None,
+            issue_patterns: IssuePattern::new(issues::DEFAULT_PATTERN, issues::DEFAULT_TEMPLATE)
+                .into_iter()
+                .collect(),
+            repository,
+        }
+    }
+
+    pub fn current_commit(&mut self) -> anyhow::Result<(String, String)> {
+        let commit = self.repository.find_commit(self.oid)?;
+        let commit_message = commit.message().unwrap_or("No commit message");
+        Ok((self.oid.to_string(), commit_message.to_owned()))
+    }
+
+    pub fn set_parent_commit(&mut self) {
+        let commit = self.repository.find_commit(self.oid).unwrap();
+        if commit.parent_count() > 0 {
+            self.oid = commit.parent(0).unwrap().id();
+        }
+    }
+
+    pub fn set_next_commit(&mut self) -> anyhow::Result<(String, String)> {
+        let next_commit_id = {
+            let next_commit = self.find_next_commit()?;
+            next_commit.map(|next_commit| next_commit.id())
+        };
+
+        if let Some(next_commit_id) = next_commit_id {
+            self.oid = next_commit_id;
+        }
+        self.current_commit()
+    }
+
+    fn subject_of(&self, oid: Oid) -> anyhow::Result<(String, String)> {
+        let commit = self.repository.find_commit(oid)?;
+        let subject = commit.summary().unwrap_or("").to_owned();
+        Ok((oid.to_string(), subject))
+    }
+
+    // Parents of the current commit as (id, subject), the first parent first.
+    pub fn parents(&self) -> anyhow::Result<Vec<(String, String)>> {
+        let commit = self.repository.find_commit(self.oid)?;
+        commit
+            .parent_ids()
+            .map(|parent| self.subject_of(parent))
+            .collect()
+    }
+
+    pub fn head_id(&self) -> anyhow::Result<Oid> {
+        Ok(self.repository.head()?.peel_to_commit()?.id())
+    }
+
+    /// Children of the current commit among the commits reachable from HEAD, as (id,
+    /// subject), or None when HEAD moved since they were found and slow work goes to worker
+    /// threads: `spawn_children` finds them then.
+    pub fn children(&mut self) -> Option<anyhow::Result<Vec<(String, String)>>> {
+        let head = match self.head_id() {
+            Ok(head) => head,
+            Err(e) => return Some(Err(e)),
+        };
+        if !matches!(&self.children, Some((oid, _)) if *oid == head) {
+            if self.background {
+                return None;
+            }
+            match child_graph(&self.repository, head, None, &CancelToken::default()) {
+                Ok(graph) => self.children = Some((head, graph)),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        let children = match &self.children {
+            Some((_, graph)) => graph.get(&self.oid).cloned().unwrap_or_default(),
+            None => Vec::new(),
+        };
+        Some(
+            children
+                .into_iter()
+                .map(|child| self.subject_of(child))
+                .collect(),
+        )
+    }
+
+    /// Finds the children of every commit reachable from HEAD on a worker thread with its own
+    /// handle to the repository; the walk stops early once `cancel` is set.
+    pub fn spawn_children(
+        &self,
+        progress: ProgressReporter,
+        cancel: CancelToken,
+    ) -> anyhow::Result<ChildrenJob> {
+        let head = self.head_id()?;
+        let path = self.repository.path().to_path_buf();
+        let handle = std::thread::spawn(move || {
+            let result = Repository::open(path)
+                .map_err(anyhow::Error::from)
+                .and_then(|repository| child_graph(&repository, head, Some(&progress), &cancel));
+            progress.finish(CHILDREN_TASK);
+            result
+        });
+        Ok(ChildrenJob { head, handle })
+    }
+
+    // Keeps the children found by a finished `job` for `children` to pick up.
+    pub fn finish_children(&mut self, job: ChildrenJob) -> anyhow::Result<()> {
+        let graph = job
+            .handle
+            .join()
+            .map_err(|_| anyhow::anyhow!("finding children stopped unexpectedly"))??;
+        self.children = Some((job.head, graph));
+        Ok(())
+    }
+
+    fn find_next_commit(&mut self) -> anyhow::Result<Option<Commit<'_>>> {
+        let commit = self.repository.find_commit(self.oid)?;
+        let mut revwalk = self.repository.revwalk()?;
+        revwalk.push_head()?;
+        revwalk.set_sorting(git2::Sort::REVERSE)?;
+
+        let mut next_commit_found = false;
+        for oid_result in revwalk {
+            let oid = oid_result?;
+            let rev_commit = self.repository.find_commit(oid)?;
+
+            if next_commit_found {
+                return Ok(Some(rev_commit));
+            }
+
+            if rev_commit.id() == commit.id() {
+                next_commit_found = true;
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub fn get_content(&mut self, filename: String) -> anyhow::Result<FileContent> {
+        if filename == *"not found" {
+            return Ok(FileContent::default());
+        }
+        let path = Path::new(&filename);
+        let (lines, missing_newline) = {
+            let blob = self.viewed_blob(&filename)?;
+            if blob.size() > LAZY_FILE_SIZE {
+                return Ok(content_window(blob.content(), 0));
+            }
+            split_lines(blob.content())
+        };
+        let request = BlameRequest {
+            key: format!("{}-{}", self.oid, state::storage_key(path)),
+            commit: self.oid,
+            path: filename.to_owned(),
+            count: lines.len(),
+        };
+        let mut pending_blame = None;
+        let blamed = match self.known_blame_hunks(&request) {
+            Some(blamed) => blamed,
+            None if self.background => {
+                pending_blame = Some(request);
+                Ok(vec![])
+            }
+            None => {
+                let blamed = blame_file_hunks(&self.repository, self.oid, path, lines.len())
+                    .map_err(|e| e.to_string());
+                self.store_blame_hunks(&request, blamed.clone());
+                blamed
+            }
+        };
+        let (hunks, blame_error) = match blamed {
+            Ok(hunks) => (hunks, None),
+            Err(e) => (vec![], Some(e)),
+        };
+        Ok(FileContent {
+            lines,
+            hunks,
+            blame_error,
+            missing_newline,
+            window: None,
+            pending_blame,
+        })
+    }
+
+    // Leaves blame and file listings that are not known yet to `spawn_blame` and
+    // `spawn_walk`, so files and commits show without waiting.
+    pub fn set_background(&mut self, background: bool) {
+        self.background = background;
+    }
+
+    /// Works out the blame of `request` on a worker thread with its own handle to the
+    /// repository.
+    pub fn spawn_blame(&self, request: BlameRequest) -> BlameJob {
+        let (git_dir, commit, path, count) = (
+            self.repository.path().to_path_buf(),
+            request.commit,
+            request.path.clone(),
+            request.count,
+        );
+        let handle = std::thread::spawn(move || {
+            let repository = Repository::open(git_dir)?;
+            blame_file_hunks(&repository, commit, Path::new(&path), count)
+        });
+        BlameJob { request, handle }
+    }
+
+    /// The remote that objects missing from a partial clone come from, if this is one.
+    pub fn promisor_remote(&self) -> Option<String> {
+        let config = self.repository.config().ok()?;
+        if let Ok(remote) = config.get_string("extensions.partialclone") {
+            return Some(remote);
+        }
+        let mut entries = config.entries(Some(r"remote\..*\.promisor")).ok()?;
+        while let Some(Ok(entry)) = entries.next() {
+            let remote = entry
+                .name()
+                .and_then(|name| name.strip_prefix("remote."))
+                .and_then(|name| name.strip_suffix(".promisor"));
+            if let (Some(remote), Ok(true)) = (remote, config.get_bool(entry.name()?)) {
+                return Some(remote.to_owned());
+            }
+        }
+        None
+    }
+
+    /// Fetches the blob of `filename` at the viewed commit on a worker thread. libgit2 cannot
+    /// fetch from a promisor remote, so this reads the blob through git, which fetches the
+    /// objects a partial clone is missing as they are read.
+    pub fn spawn_blob_fetch(
+        &self,
+        filename: &str,
+    ) -> anyhow::Result<std::thread::JoinHandle<anyhow::Result<()>>> {
+        let blob = self
+            .repository
+            .find_commit(self.oid)?
+            .tree()?
+            .get_path(Path::new(filename))?
+            .id();
+        let git_dir = self.repository.path().to_path_buf();
+        Ok(std::thread::spawn(move || {
+            let output = Command::new("git")
+                .arg("--git-dir")
+                .arg(git_dir)
+                .args(["cat-file", "blob", &blob.to_string()])
+                .stdout(Stdio::null())
+                .output()
+                .map_err(|e| anyhow::anyhow!("cannot run git: {}", e))?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(anyhow::anyhow!("{}", stderr.trim()));
+            }
+            Ok(())
+        }))
+    }
+
+    // Keeps the outcome of a finished `job`, for `get_content` to pick up.
+    pub fn finish_blame(&mut self, job: BlameJob) {
+        let blamed = match job.handle.join() {
+            Ok(blamed) => blamed.map_err(|e| e.to_string()),
+            Err(_) => Err("blame stopped unexpectedly".to_owned()),
+        };
+        self.store_blame_hunks(&job.request, blamed);
+    }
+
+    // `WINDOW_LINES` lines of a large file from line `start` (0-based), as `get_content` reads
+    // the top of it.
+    pub fn get_content_window(&self, filename: &str, start: usize) -> anyhow::Result<FileContent> {
+        Ok(content_window(self.viewed_blob(filename)?.content(), start))
+    }
+
+    // The bytes of `filename` at the viewed commit, for files that are not text.
+    pub fn get_raw_blob(&self, filename: &str) -> anyhow::Result<Vec<u8>> {
+        Ok(self.viewed_blob(filename)?.content().to_vec())
+    }
+
+    // Lines `lines` (1-based) of `filename` at the viewed commit exactly as stored, for copies.
+    pub fn get_raw_lines(
+        &self,
+        filename: &str,
+        lines: RangeInclusive<usize>,
+    ) -> anyhow::Result<Vec<u8>> {
+        Ok(line_bytes(self.viewed_blob(filename)?.content(), lines).to_vec())
+    }
+
+    // Whether `filename` at the viewed commit holds binary data; unreadable files are left to
+    // `get_content`.
+    pub fn is_binary_file(&self, filename: &str) -> bool {
+        self.viewed_blob(filename)
+            .is_ok_and(|blob| !is_text(blob.content()))
+    }
+
+    /// The viewed commit, or the object at `path` in its tree (the root tree for an empty
+    /// path), printed the way `git cat-file -p` would: a heading of its kind and id, then the
+    /// commit's headers and message, the tree's entries with their modes and ids, or the
+    /// blob's size. Blob contents are left to the viewer.
+    pub fn inspect_object(&self, path: Option<&str>) -> anyhow::Result<(String, String)> {
+        let commit = self.repository.find_commit(self.oid)?;
+        let object = match path {
+            None => commit.into_object(),
+            Some("") => commit.tree()?.into_object(),
+            Some(path) => commit
+                .tree()?
+                .get_path(Path::new(path))?
+                .to_object(&self.repository)?,
+        };
+        let kind = object.kind().map_or("object", |kind| kind.str());
+        let heading = format!("{} {}", kind, object.id());
+        let body = if let Some(commit) = object.as_commit() {
+            format!(
+                "{}\n\n{}",
+                String::from_utf8_lossy(commit.raw_header_bytes()).trim_end(),
+                String::from_utf8_lossy(commit.message_raw_bytes()).trim_end()
+            )
+        } else if let Some(tree) = object.as_tree() {
+            tree.iter()
+                .map(|entry| {
+                    format!(
+                        "{:06o} {} {}\t{}",
+                        entry.filemode(),
+                        entry.kind().map_or("object", |kind| kind.str()),
+                        entry.id(),
+                        String::from_utf8_lossy(entry.name_bytes())
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        } else if let Some(blob) = object.as_blob() {
+            format!(
+                "size {}\nbinary {}",
+                blob.size(),
+                if blob.is_binary() { "yes" } else { "no" }
+            )
+        } else {
+            String::new()
+        };
+        Ok((heading, body))
+    }
+
+    pub fn viewed_tree_id(&self) -> anyhow::Result<Oid> {
+        Ok(self.repository.find_commit(self.oid)?.tree_id())
+    }
+
+    // Language statistics of `tree` from an earlier run, see `cache_language_stats`.
+    pub fn cached_language_stats(&self, tree: Oid) -> Option<Vec<LanguageStat>> {
+        let cached = self.cache.get("languages", &tree.to_string())?;
+        cached
+            .lines()
+            .map(|line| {
+                let mut fields = line.splitn(4, '\t');
+                Some(LanguageStat {
+                    files: fields.next()?.parse().ok()?,
+                    lines: fields.next()?.parse().ok()?,
+                    bytes: fields.next()?.parse().ok()?,
+                    language: fields.next()?.to_owned(),
+                })
+            })
+            .collect()
+    }
+
+    // Cached per tree, as reading every blob of a large tree takes a while.
+    pub fn cache_language_stats(&self, tree: Oid, stats: &[LanguageStat]) {
+        let serialized: Vec<String> = stats
+            .iter()
+            .map(|stat| {
+                format!(
+                    "{}\t{}\t{}\t{}",
+                    stat.files, stat.lines, stat.bytes, stat.language
+                )
+            })
+            .collect();
+        self.cache
+            .put("languages", &tree.to_string(), &serialized.join("\n"));
+    }
+
+    /// Tallies the files, lines and bytes of each language in `tree` on a worker thread with
+    /// its own handle to the repository.
+    pub fn spawn_language_stats(
+        &self,
+        tree: Oid,
+        progress: ProgressReporter,
+    ) -> std::thread::JoinHandle<anyhow::Result<Vec<LanguageStat>>> {
+        let path = self.repository.path().to_path_buf();
+        std::thread::spawn(move || {
+            let result = Repository::open(path)
+                .map_err(anyhow::Error::from)
+                .and_then(|repository| count_languages(&repository, tree, &progress));
+            progress.finish("counting languages");
+            result
+        })
+    }
+
+    fn viewed_blob(&self, filename: &str) -> anyhow::Result<Blob<'_>> {
+        Ok(self
+            .repository
+            .find_commit(self.oid)?
+            .tree()?
+            .get_path(Path::new(filename))?
+            .to_object(&self.repository)?
+            .peel_to_blob()?)
+    }
+
+    // Blame of `request` worked out before, in this run or cached from an earlier one, since
+    // blame is slow.
+    fn known_blame_hunks(&self, request: &BlameRequest) -> Option<Result<Vec<BlameHunk>, String>> {
+        if let Some(blamed) = self.blamed.get(&request.key) {
+            return Some(blamed.clone());
+        }
+        let count = request.count;
+        if let Some(cached) = self.cache.get("hunks", &request.key) {
+            let hunks: Option<Vec<BlameHunk>> = cached
+                .lines()
+                .map(|line| {
+                    let mut fields = line.splitn(6, '\t');
+                    Some(BlameHunk {
+                        start: fields.next()?.parse().ok()?,
+                        len: fields.next()?.parse().ok()?,
+                        commit: Oid::from_str(fields.next()?).ok()?,
+                        time: fields.next()?.parse().ok()?,
+                        email: fields.next()?.to_owned(),
+                        author: fields.next()?.to_owned(),
+                    })
+                })
+                .collect();
+            if let Some(hunks) =
+                hunks.filter(|hunks| hunks.iter().all(|hunk| hunk.start + hunk.len <= count + 1))
+            {
+                return Some(Ok(hunks));
+            }
+        }
+        None
+    }
+
+    // Remembers the blame of `request` for this run, and caches it when it worked out.
+    fn store_blame_hunks(
+        &mut self,
+        request: &BlameRequest,
+        blamed: Result<Vec<BlameHunk>, String>,
+    ) {
+        if let Ok(hunks) = &blamed {
+            let serialized: Vec<String> = hunks
+                .iter()
+                .map(|hunk| {
+                    format!(
+                        "{}\t{}\t{}\t{}\t{}\t{}",
+                        hunk.start, hunk.len, hunk.commit, hunk.time, hunk.email, hunk.author
+                    )
+                })
+                .collect();
+            self.cache
+                .put("hunks", &request.key, &serialized.join("\n"));
+        }
+        self.blamed.insert(request.key.clone(), blamed);
+    }
+
+    pub fn identity(&self) -> Identity {
+        Identity::from_config(&self.repository)
+    }
+
+    pub fn highlight_mine(&self) -> bool {
+        self.highlight_mine
+    }
+
+    pub fn toggle_highlight_mine(&mut self) {
+        self.highlight_mine = !self.highlight_mine;
+    }
+
+    // Finds the commits from HEAD with a trailer containing `query` on a worker thread with
+    // its own handle to the repository; the walk stops early once `cancel` is set.
+    pub fn spawn_trailer_filter(
+        &self,
+        query: &str,
+        progress: ProgressReporter,
+        cancel: CancelToken,
+    ) -> std::thread::JoinHandle<anyhow::Result<HashSet<String>>> {
+        let (path, query) = (self.repository.path().to_path_buf(), query.to_owned());
+        std::thread::spawn(move || {
+            let result = Repository::open(path)
+                .map_err(anyhow::Error::from)
+                .and_then(|repository| {
+                    commits_with_trailer(&repository, &query, &progress, &cancel)
+                });
+            progress.finish(TRAILER_TASK);
+            result
+        })
+    }
+
+    pub fn prefix(&self) -> Option<&str> {
+        self.prefix.as_deref()
+    }
+
+    // Scopes file lists, searches, diffs and history to the directory `prefix`; an empty
+    // prefix restores the whole repository.
+    pub fn set_prefix(&mut self, prefix: &str) -> anyhow::Result<()> {
+        let prefix = git_path(Path::new(prefix.trim()));
+        let prefix = prefix.strip_prefix("./").unwrap_or(&prefix);
+        let prefix = prefix.trim_matches('/');
+        if prefix.is_empty() {
+            self.prefix = None;
+            self.tags = None;
+            return Ok(());
+        }
+        let tree = self.repository.find_commit(self.oid)?.tree()?;
+        let entry = tree
+            .get_path(Path::new(prefix))
+            .map_err(|_| anyhow::anyhow!("{} does not exist", prefix))?;
+        if entry.kind() != Some(ObjectType::Tree) {
+            return Err(anyhow::anyhow!("{} is not a directory", prefix));
+        }
+        self.prefix = Some(prefix.to_owned());
+        // definitions are looked up within the scope too
+        self.tags = None;
+        Ok(())
+    }
+
+    pub fn date_range(&self) -> &DateRange {
+        &self.date_range
+    }
+
+    // Limits the commit history to `range`.
+    pub fn set_date_range(&mut self, range: DateRange) {
+        self.date_range = range;
+    }
+
+    fn in_scope(&self, path: &str) -> bool {
+        match &self.prefix {
+            Some(prefix) => path
+                .strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.starts_with('/')),
+            None => true,
+        }
+    }
+
+    // Whether `commit` changed anything under the prefix, compared to its first parent.
+    fn touches_prefix(&self, commit: &Commit<'_>, prefix: &str) -> anyhow::Result<bool> {
+        let entry_id = |commit: &Commit<'_>| -> anyhow::Result<Option<Oid>> {
+            Ok(commit
+                .tree()?
+                .get_path(Path::new(prefix))
+                .ok()
+                .map(|entry| entry.id()))
+        };
+        let parent = match commit.parent(0) {
+            Ok(parent) => entry_id(&parent)?,
+            Err(_) => None,
+        };
+        Ok(entry_id(commit)? != parent)
+    }
+
+    pub fn get_commit_history(&self) -> anyhow::Result<Vec<(String, String)>> {
+        Ok(self
+            .history()?
+            .into_iter()
+            .map(|(id, subject, _)| (id, subject))
+            .collect())
+    }
+
+    // Cherry-picked and reverted commits of the history, by id.
+    pub fn commit_origins(&self) -> anyhow::Result<HashMap<String, Origin>> {
+        Ok(self
+            .history()?
+            .into_iter()
+            .filter_map(|(id, _, origin)| Some((id, origin?)))
+            .collect())
+    }
+
+    /// Up to `count` commits from HEAD within the date range and prefix, in the order of
+    /// `get_commit_history`, from position `from` of the walk on: 0 for the first page, then
+    /// the `next` of the page before. Only the listed commits are read, so the first page
+    /// comes quickly however long the history is.
+    pub fn history_page(&self, from: usize, count: usize) -> anyhow::Result<HistoryPage> {
+        let mut page = HistoryPage::default();
+        for found in self.filtered_history(from)? {
+            let (position, commit) = found?;
+            if page.entries.len() == count {
+                page.next = Some(position);
+                break;
+            }
+            let message = commit.message().unwrap_or("No commit message");
+            let author = commit.author();
+            page.entries.push(HistoryEntry {
+                id: commit.id().to_string(),
+                subject: message.lines().next().unwrap_or("").to_owned(),
+                author: author.name().unwrap_or("").to_owned(),
+                email: author.email().unwrap_or("").to_owned(),
+                origin: Origin::parse(message),
+            });
+        }
+        Ok(page)
+    }
+
+    // Commits from HEAD within the date range and prefix, newest first, from position `from`
+    // of the walk on. Each comes with its position, where a later walk can pick up.
+    fn filtered_history(
+        &self,
+        from: usize,
+    ) -> anyhow::Result<impl Iterator<Item = anyhow::Result<(usize, Commit<'_>)>> + '_> {
+        let mut revwalk = self.repository.revwalk()?;
+        revwalk.push_head()?;
+        revwalk.set_sorting(git2::Sort::TIME)?;
+        Ok(revwalk
+            .enumerate()
+            .skip(from)
+            .filter_map(move |(position, oid)| {
+                let kept = || -> anyhow::Result<Option<(usize, Commit<'_>)>> {
+                    let commit = self.repository.find_commit(oid?)?;
+                    if !self.date_range.contains(commit.time().seconds()) {
+                        return Ok(None);
+                    }
+                    if let Some(prefix) = &self.prefix {
+                        if !self.touches_prefix(&commit, prefix)? {
+                            return Ok(None);
+                        }
+                    }
+                    Ok(Some((position, commit)))
+                };
+                kept().transpose()
+            }))
+    }
+
+    // Id, subject and origin of every commit from HEAD, cached as one line per commit.
+    fn history(&self) -> anyhow::Result<Vec<(String, String, Option<Origin>)>> {
+        let mut head = self.repository.head()?.peel_to_commit()?.id().to_string();
+        if let Some(prefix) = &self.prefix {
+            head = format!("{}-{}", head, state::storage_key(Path::new(prefix)));
+        }
+        if self.date_range.is_set() {
+            head = format!(
+                "{}-{}-{}",
+                head,
+                self.date_range.start(),
+                self.date_range.end()
+            );
+        }
+        if let Some(history) = self.cache.get("history", &head) {
+            // entries written before origins were recorded are read again
+            let parsed: Option<Vec<_>> = history
+                .lines()
+                .map(|line| {
+                    let mut fields = line.splitn(3, '\t');
+                    let (id, subject) = (fields.next()?, fields.next()?);
+                    Some((
+                        id.to_owned(),
+                        subject.to_owned(),
+                        Origin::decode(fields.next()?),
+                    ))
+                })
+                .collect();
+            if let Some(parsed) = parsed {
+                return Ok(parsed);
+            }
+        }
+
+        let mut commits = Vec::new();
+        for found in self.filtered_history(0)? {
+            let (_, commit) = found?;
+            let message = commit.message().unwrap_or("No commit message");
+            let commit_message = message.lines().next().unwrap_or("").to_string();
+            commits.push((
+                commit.id().to_string(),
+                commit_message,
+                Origin::parse(message),
+            ));
+        }
+
+        let history: Vec<String> = commits
+            .iter()
+            .map(|(id, subject, origin)| {
+                format!("{}\t{}\t{}", id, subject, Origin::encode(origin.as_ref()))
+            })
+            .collect();
+        self.cache.put("history", &head, &history.join("\n"));
+        Ok(commits)
+    }
+
+    pub fn current_oid(&self) -> Oid {
+        self.oid
+    }
+
+    pub fn get_current_commit_id(&self) -> String {
+        self.oid.to_string()
+    }
+
+    pub fn set_commit_by_id(&mut self, commit_id: &str) -> anyhow::Result<()> {
+        let oid = if commit_id.len() == 40 {
+            // Full commit ID
+            git2::Oid::from_str(commit_id)?
+        } else {
+            // Short commit ID - need to resolve it
+            let mut revwalk = self.repository.revwalk()?;
+            revwalk.push_head()?;
+
+            let mut found_oid = None;
+            let mut match_count = 0;
+            for oid_result in revwalk {
+                let oid = oid_result?;
+                let oid_str = oid.to_string();
+                if oid_str.starts_with(commit_id) {
+                    found_oid = Some(oid);
+                    match_count += 1;
+                    if match_count > 1 {
+                        return Err(anyhow::anyhow!(
+                            "Ambiguous commit ID: multiple commits match '{}'",
+                            commit_id
+                        ));
+                    }
+                }
+            }
+
+            found_oid.ok_or_else(|| anyhow::anyhow!(format!("Commit '{}' not found", commit_id)))?
+        };
+
+        // Verify the commit exists before setting it
+        self.repository.find_commit(oid)?;
+        self.oid = oid;
+        Ok(())
+    }
+
+    pub fn set_url_templates(&mut self, templates: HashMap<String, String>) {
+        self.url_templates = templates;
+    }
+
+    // The file page template of `host`: the configured one, which may name a layout, else
+    // that of a known public forge, else GitHub's.
+    fn url_template(&self, host: &str) -> &str {
+        if let Some(template) = self.url_templates.get(host) {
+            return forge_layout(template).unwrap_or(template.as_str());
+        }
+        FORGE_HOSTS
+            .iter()
+            .find(|(known, _)| *known == host)
+            .and_then(|(_, layout)| forge_layout(layout))
+            .unwrap_or(FORGE_LAYOUTS[0].1)
+    }
+
+    pub fn set_issue_patterns(&mut self, patterns: Vec<IssuePattern>) {
+        self.issue_patterns = patterns;
+    }
+
+    // Byte ranges of the issue references in `text`.
+    pub fn issue_references(&self, text: &str) -> Vec<std::ops::Range<usize>> {
+        issues::find(text, &self.issue_patterns)
+    }
+
+    // The URL `reference` is linked to, such as the forge's page of issue `#123`.
+    pub fn issue_url(&self, reference: &str) -> anyhow::Result<String> {
+        issues::url(reference, &self.issue_patterns, || self.remote_repository())
+    }
+
+    // Writes `path` as of commit `oid` into the scratch directory, so external tools, editors
+    // and diff programs can be pointed at historical versions. Files keep their repository
+    // path under a directory per commit.
+    pub fn materialize_blob(&self, path: &str, oid: Oid) -> anyhow::Result<PathBuf> {
+        let relative = Path::new(path);
+        if !relative
+            .components()
+            .all(|component| matches!(component, std::path::Component::Normal(_)))
+        {
+            return Err(anyhow::anyhow!("invalid repository path: {}", path));
+        }
+        let blob = self
+            .repository
+            .find_commit(oid)?
+            .tree()?
+            .get_path(relative)?
+            .to_object(&self.repository)?
+            .peel_to_blob()?;
+        let target = self.scratch.path.join(oid.to_string()).join(relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&target, blob.content())?;
+        Ok(target)
+    }
+
+    pub fn set_cache_limit(&mut self, limit: u64) {
+        self.cache.set_limit(limit);
+    }
+
+    pub fn has_sparse_checkout(&self) -> bool {
+        self.sparse.is_some()
+    }
+
+    // Whether the file list is limited to the sparse checkout.
+    pub fn sparse_only(&self) -> bool {
+        self.sparse_only && self.sparse.is_some()
+    }
+
+    pub fn set_sparse_only(&mut self, sparse_only: bool) {
+        self.sparse_only = sparse_only;
+    }
+
+    pub fn recursive_walk(&mut self) -> anyhow::Result<Vec<String>> {
+        let files = self.walk_tree()?;
+        Ok(self.scoped(files))
+    }
+
+    /// What `recursive_walk` lists, or None when the viewed tree has not been listed before
+    /// and slow work goes to worker threads: `spawn_walk` lists it then.
+    pub fn listed_files(&mut self) -> Option<anyhow::Result<Vec<String>>> {
+        if self.background {
+            let tree = match self.viewed_tree_id() {
+                Ok(tree) => tree,
+                Err(e) => return Some(Err(e)),
+            };
+            let files = self.known_listing(tree)?;
+            return Some(Ok(self.scoped(files)));
+        }
+        Some(self.recursive_walk())
+    }
+
+    /// Lists the files of the viewed tree on a worker thread with its own handle to the
+    /// repository.
+    pub fn spawn_walk(&self, progress: ProgressReporter) -> anyhow::Result<WalkJob> {
+        let tree = self.viewed_tree_id()?;
+        let path = self.repository.path().to_path_buf();
+        let handle = std::thread::spawn(move || {
+            let result = Repository::open(path)
+                .map_err(anyhow::Error::from)
+                .and_then(|repository| {
+                    let mut listed = HashMap::new();
+                    list_tree(
+                        &repository,
+                        &repository.find_tree(tree)?,
+                        &mut listed,
+                        Some(&progress),
+                    )?;
+                    Ok(listed)
+                });
+            progress.finish(WALK_TASK);
+            result
+        });
+        Ok(WalkJob { tree, handle })
+    }
+
+    // Keeps the listing of a finished `job` for `listed_files` to pick up, and caches it.
+    pub fn finish_walk(&mut self, job: WalkJob) -> anyhow::Result<()> {
+        let listed = job
+            .handle
+            .join()
+            .map_err(|_| anyhow::anyhow!("listing stopped unexpectedly"))??;
+        if let Some(files) = listed.get(&job.tree) {
+            self.cache
+                .put("listing", &job.tree.to_string(), &files.join("\n"));
+        }
+        self.listed_trees.extend(listed);
+        Ok(())
+    }
+
+    // `files` without those outside the sparse checkout, when limited to it, or the scope.
+    fn scoped(&self, mut files: Vec<String>) -> Vec<String> {
+        if let (true, Some(sparse)) = (self.sparse_only, &self.sparse) {
+            files.retain(|file| sparse.includes(file));
+        }
+        files.retain(|file| self.in_scope(file));
+        files
+    }
+
+    // The files of `tree` when listed before, in this run or cached from an earlier one.
+    fn known_listing(&mut self, tree: Oid) -> Option<Vec<String>> {
+        // commits visited before in this run are listed from memory
+        if let Some(files) = self.listed_trees.get(&tree) {
+            return Some(files.clone());
+        }
+        // not `files`, where older versions cached listings without non-ASCII text
+        let files: Vec<String> = self
+            .cache
+            .get("listing", &tree.to_string())?
+            .lines()
+            .map(str::to_owned)
+            .collect();
+        self.listed_trees.insert(tree, files.clone());
+        Some(files)
+    }
+
+    fn walk_tree(&mut self) -> anyhow::Result<Vec<String>> {
+        if let Some(files) = self.known_listing(self.viewed_tree_id()?) {
+            return Ok(files);
+        }
+
+        let tree = self.repository.find_commit(self.oid)?.tree()?;
+        let results = list_tree(&self.repository, &tree, &mut self.listed_trees, None)?;
+
+        self.cache
+            .put("listing", &tree.id().to_string(), &results.join("\n"));
+        Ok(results)
+    }
+
+    // Text blobs at the current commit, with the same limits as `recursive_walk`.
+    pub fn text_blobs(&self) -> anyhow::Result<Vec<(String, String)>> {
+        text_blobs_at(
+            &self.repository,
+            self.oid,
+            self.prefix.as_deref(),
+            &CancelToken::default(),
+        )
+    }
+
+    // Searches the current commit for `word` on a worker thread with its own handle to the
+    // repository; the search stops early once `cancel` is set.
+    pub fn spawn_search(
+        &self,
+        word: &str,
+        progress: ProgressReporter,
+        cancel: CancelToken,
+    ) -> std::thread::JoinHandle<anyhow::Result<Vec<SearchHit>>> {
+        let (path, oid, prefix, word) = (
+            self.repository.path().to_path_buf(),
+            self.oid,
+            self.prefix.clone(),
+            word.to_owned(),
+        );
+        std::thread::spawn(move || {
+            let result = Repository::open(path)
+                .map_err(anyhow::Error::from)
+                .and_then(|repository| {
+                    search_word(
+                        &repository,
+                        oid,
+                        prefix.as_deref(),
+                        &word,
+                        &progress,
+                        &cancel,
+                    )
+                });
+            progress.finish("searching");
+            result
+        })
+    }
+
+    // Definitions of `name` at the current commit; the index is rebuilt when the commit changes.
+    pub fn find_definition(&mut self, name: &str) -> anyhow::Result<Vec<(String, usize)>> {
+        if !matches!(&self.tags, Some((oid, _)) if *oid == self.oid) {
+            let blobs = self.text_blobs()?;
+            let index = TagIndex::build(
+                blobs
+                    .iter()
+                    .map(|(path, content)| (path.as_str(), content.as_str())),
+            );
+            self.tags = Some((self.oid, index));
+        }
+        Ok(self
+            .tags
+            .as_ref()
+            .map(|(_, index)| index.lookup(name).to_vec())
+            .unwrap_or_default())
+    }
+
+    fn resolve_commit(&self, spec: &str) -> anyhow::Result<Oid> {
+        Ok(self
+            .repository
+            .revparse_single(spec)?
+            .peel_to_commit()?
+            .id())
+    }
+
+    // Whether the viewed commit is reachable from `spec`, e.g. already merged into main.
+    pub fn is_contained_in(&self, spec: &str) -> anyhow::Result<bool> {
+        let target = self.resolve_commit(spec)?;
+        Ok(target == self.oid || self.repository.graph_descendant_of(target, self.oid)?)
+    }
+
+    // Id and subject of the best common ancestor of the viewed commit and `spec`.
+    pub fn merge_base_with(&self, spec: &str) -> anyhow::Result<(String, String)> {
+        let other = self.resolve_commit(spec)?;
+        let base = self
+            .repository
+            .find_commit(self.repository.merge_base(self.oid, other)?)?;
+        Ok((
+            base.id().to_string(),
+            base.summary().unwrap_or("").to_owned(),
+        ))
+    }
+
+    pub fn set_base_ref(&mut self, spec: &str) -> anyhow::Result<()> {
+        self.base = Some(self.resolve_commit(spec)?);
+        self.target = None;
+        Ok(())
+    }
+
+    // Compares two arbitrary refs instead of the base and the current commit.
+    pub fn set_compare_refs(&mut self, from: &str, to: &str) -> anyhow::Result<()> {
+        let from = self.resolve_commit(from)?;
+        let to = self.resolve_commit(to)?;
+        self.base = Some(from);
+        self.target = Some(to);
+        Ok(())
+    }
+
+    pub fn clear_base_ref(&mut self) {
+        self.base = None;
+        self.target = None;
+    }
+
+    pub fn base_ref(&self) -> Option<String> {
+        self.base.map(|base| base.to_string())
+    }
+
+    pub fn compare_target(&self) -> Option<String> {
+        self.target.map(|target| target.to_string())
+    }
+
+    // The commit diffs are taken to: the compare target when set, otherwise the current commit.
+    fn diff_new_tree(&self) -> anyhow::Result<Tree<'_>> {
+        Ok(self
+            .repository
+            .find_commit(self.target.unwrap_or(self.oid))?
+            .tree()?)
+    }
+
+    // The tree diffs are taken against: the base ref when set, otherwise the first parent.
+    // A review starts where the commit forked from the base, so the base's later commits
+    // do not show up as reverted; two compared refs are diffed as they are.
+    fn diff_old_tree(&self) -> anyhow::Result<Option<Tree<'_>>> {
+        if let Some(base) = self.base {
+            let old = match self.target {
+                Some(_) => base,
+                // unrelated histories have no merge-base to start from
+                None => self.repository.merge_base(base, self.oid).unwrap_or(base),
+            };
+            return Ok(Some(self.repository.find_commit(old)?.tree()?));
+        }
+        let commit = self.repository.find_commit(self.oid)?;
+        if commit.parent_count() > 0 {
+            Ok(Some(commit.parent(0)?.tree()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    // Tree-to-tree diff with rename detection, so a moved file is a single entry.
+    fn tree_diff(&self) -> anyhow::Result<Diff<'_>> {
+        let new_tree = self.diff_new_tree()?;
+        let old_tree = self.diff_old_tree()?;
+        let mut options = DiffOptions::new();
+        if let Some(prefix) = &self.prefix {
+            options.pathspec(format!("{}/", prefix));
+        }
+        let mut diff = self.repository.diff_tree_to_tree(
+            old_tree.as_ref(),
+            Some(&new_tree),
+            Some(&mut options),
+        )?;
+        diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+        Ok(diff)
+    }
+
+    pub fn changed_files(&self) -> anyhow::Result<Vec<String>> {
+        let diff = self.tree_diff()?;
+        Ok((0..diff.deltas().len())
+            .filter_map(|index| delta_path(&diff, index))
+            .collect())
+    }
+
+    // Renamed files keyed by their new path.
+    pub fn renamed_files(&self) -> anyhow::Result<HashMap<String, Rename>> {
+        let diff = self.tree_diff()?;
+        let mut renames = HashMap::new();
+        for index in 0..diff.deltas().len() {
+            if let (Some(path), Some(rename)) = (delta_path(&diff, index), rename_at(&diff, index)?)
+            {
+                renames.insert(path, rename);
+            }
+        }
+        Ok(renames)
+    }
+
+    // One-letter status of each changed file, keyed by its new path: A, M, D, R, C or T.
+    pub fn change_statuses(&self) -> anyhow::Result<HashMap<String, char>> {
+        let diff = self.tree_diff()?;
+        Ok(diff
+            .deltas()
+            .enumerate()
+            .filter_map(|(index, delta)| {
+                let status = match delta.status() {
+                    Delta::Added => 'A',
+                    Delta::Deleted => 'D',
+                    Delta::Renamed => 'R',
+                    Delta::Copied => 'C',
+                    Delta::Typechange => 'T',
+                    _ => 'M',
+                };
+                delta_path(&diff, index).map(|path| (path, status))
+            })
+            .collect())
+    }
+
+    pub fn changed_file_stats(&self) -> anyhow::Result<Vec<FileStat>> {
+        let diff = self.tree_diff()?;
+
+        let mut stats = vec![];
+        for index in 0..diff.deltas().len() {
+            let path = match delta_path(&diff, index) {
+                Some(path) => path,
+                None => continue,
+            };
+            // binary files have no patch and count as no line changes
+            let (additions, deletions) = match Patch::from_diff(&diff, index)? {
+                Some(patch) => {
+                    let (_, additions, deletions) = patch.line_stats()?;
+                    (additions, deletions)
+                }
+                None => (0, 0),
+            };
+            stats.push(FileStat {
+                path,
+                additions,
+                deletions,
+                rename: rename_at(&diff, index)?,
+            });
+        }
+        Ok(stats)
+    }
+
+    // Cached per commit, as they are asked for again and again while moving through history.
+    pub fn commit_stats(&self, commit_id: &str) -> anyhow::Result<CommitStats> {
+        if let Some(cached) = self.cache.get("stats", commit_id) {
+            let fields: Vec<usize> = cached
+                .split('\t')
+                .filter_map(|field| field.parse().ok())
+                .collect();
+            if let [files, insertions, deletions] = fields[..] {
+                return Ok(CommitStats {
+                    files,
+                    insertions,
+                    deletions,
+                });
+            }
+        }
+
+        let commit = self.repository.find_commit(Oid::from_str(commit_id)?)?;
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        };
+        let diff =
+            self.repository
+                .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+        let stats = diff.stats()?;
+        let stats = CommitStats {
+            files: stats.files_changed(),
+            insertions: stats.insertions(),
+            deletions: stats.deletions(),
+        };
+        self.cache.put(
+            "stats",
+            commit_id,
+            &format!("{}\t{}\t{}", stats.files, stats.insertions, stats.deletions),
+        );
+        Ok(stats)
+    }
+
+    pub fn commit_info(&self) -> anyhow::Result<CommitInfo> {
+        self.info_of(self.oid)
+    }
+
+    // Author, date and message of any commit, such as the one a blamed line comes from.
+    pub fn commit_info_of(&self, commit_id: &str) -> anyhow::Result<CommitInfo> {
+        self.info_of(Oid::from_str(commit_id)?)
+    }
+
+    fn info_of(&self, oid: Oid) -> anyhow::Result<CommitInfo> {
+        let preloaded = self
+            .metadata
+            .lock()
+            .ok()
+            .and_then(|metadata| metadata.get(&oid).cloned());
+        match preloaded {
+            Some(info) => Ok(info),
+            None => Ok(CommitInfo::from_commit(&self.repository.find_commit(oid)?)),
+        }
+    }
+
+    // Name, email and date of whoever committed the current commit, who differs from the
+    // author after a rebase, an amend by someone else or a patch applied by a maintainer.
+    pub fn committer(&self) -> anyhow::Result<(String, String, String)> {
+        let commit = self.repository.find_commit(self.oid)?;
+        let committer = commit.committer();
+        Ok((
+            committer.name().unwrap_or("unknown").to_owned(),
+            committer.email().unwrap_or("").to_owned(),
+            summary::format_time(committer.when()),
+        ))
+    }
+
+    // Reads the metadata of the most recent commits on a separate thread with its own
+    // handle to the repository, so the first views needing it do not walk the history.
+    pub fn preload_commit_metadata(
+        &self,
+        progress: ProgressReporter,
+    ) -> std::thread::JoinHandle<()> {
+        let path = self.repository.path().to_path_buf();
+        let metadata = Arc::clone(&self.metadata);
+        std::thread::spawn(move || {
+            let _ = load_commit_metadata(&path, &metadata, &progress);
+            progress.finish("loading commits");
+        })
+    }
+
+    pub fn review_summary(&self) -> anyhow::Result<String> {
+        Ok(summary::review_summary(
+            &self.commit_info()?,
+            self.base_ref().as_deref(),
+            &self.changed_file_stats()?,
+            self.notes(),
+        ))
+    }
+
+    pub fn get_diff(&self, filename: &str) -> anyhow::Result<Vec<DiffRow>> {
+        if filename == "not found" {
+            return Ok(vec![]);
+        }
+        diff_rows(&self.tree_diff()?, filename)
+    }
+
+    // The file on both sides of the diff view for `source`, following a rename back to its old
+    // path. A side without the file is empty.
+    pub fn get_split_diff(&self, filename: &str, source: Source) -> anyhow::Result<SplitDiff> {
+        if filename == "not found" {
+            return Ok(SplitDiff::default());
+        }
+        let read = |tree: Option<&Tree<'_>>, path: &str| -> anyhow::Result<Vec<u8>> {
+            match tree.and_then(|tree| tree.get_path(Path::new(path)).ok()) {
+                Some(entry) => Ok(entry
+                    .to_object(&self.repository)?
+                    .peel_to_blob()?
+                    .content()
+                    .to_vec()),
+                None => Ok(vec![]),
+            }
+        };
+        let (old_path, old, new) = match source {
+            Source::Commit => {
+                let old_path = self
+                    .renamed_files()?
+                    .remove(filename)
+                    .map_or(filename.to_owned(), |rename| rename.from);
+                let old = read(self.diff_old_tree()?.as_ref(), &old_path)?;
+                let new = read(Some(&self.diff_new_tree()?), filename)?;
+                (old_path, old, new)
+            }
+            Source::Index | Source::WorkingTree => {
+                let tree = self.repository.find_commit(self.oid)?.tree()?;
+                let old = read(Some(&tree), filename)?;
+                let new = if source == Source::Index {
+                    let index = self.repository.index()?;
+                    match index.get_path(Path::new(filename), 0) {
+                        Some(entry) => self.repository.find_blob(entry.id)?.content().to_vec(),
+                        None => vec![],
+                    }
+                } else {
+                    let workdir = self
+                        .repository
+                        .workdir()
+                        .ok_or_else(|| anyhow::anyhow!("repository has no working tree"))?;
+                    std::fs::read(workdir.join(filename)).unwrap_or_default()
+                };
+                (filename.to_owned(), old, new)
+            }
+        };
+        split_diff(&old_path, &old, filename, &new)
+    }
+
+    // Reads the checked-out version of a file; blame does not apply, so rows carry a zero id.
+    // Also tells whether the final newline is missing.
+    pub fn get_worktree_content(&self, filename: &str) -> anyhow::Result<(Vec<CommitRow>, bool)> {
+        if filename == "not found" {
+            return Ok((vec![], false));
+        }
+        let workdir = self
+            .repository
+            .workdir()
+            .ok_or_else(|| anyhow::anyhow!("repository has no working tree"))?;
+        let content = std::fs::read(workdir.join(filename))?;
+        Ok(unblamed_rows(&content))
+    }
+
+    fn blob_at(&self, commit_id: &str, filename: &str) -> anyhow::Result<Blob<'_>> {
+        let commit = self.repository.find_commit(Oid::from_str(commit_id)?)?;
+        let blob = commit
+            .tree()?
+            .get_path(Path::new(filename))?
+            .to_object(&self.repository)?
+            .peel_to_blob()?;
+        Ok(blob)
+    }
+
+    // Reads a file as of any commit, without blame, to preview history quickly.
+    pub fn get_content_at(
+        &self,
+        commit_id: &str,
+        filename: &str,
+    ) -> anyhow::Result<Vec<CommitRow>> {
+        let commit = Oid::from_str(commit_id)?;
+        Ok(split_lines(self.blob_at(commit_id, filename)?.content())
+            .0
+            .into_iter()
+            .enumerate()
+            .map(|(i, line)| CommitRow::new("".to_owned(), commit, i + 1, line))
+            .collect())
+    }
+
+    // Where `line` of `filename` at `from` ended up at `to`, following the diff between them.
+    // Changed lines map to the same offset in the replacing hunk, deleted ones to the line before.
+    pub fn map_line(
+        &self,
+        from: &str,
+        to: &str,
+        filename: &str,
+        line: usize,
+    ) -> anyhow::Result<usize> {
+        let old = self.blob_at(from, filename)?;
+        let new = self.blob_at(to, filename)?;
+        let mut options = DiffOptions::new();
+        options.context_lines(0);
+        let patch = Patch::from_blobs(&old, None, &new, None, Some(&mut options))?;
+
+        let mut offset: isize = 0;
+        for index in 0..patch.num_hunks() {
+            let (hunk, _) = patch.hunk(index)?;
+            let (old_start, old_lines) = (hunk.old_start() as usize, hunk.old_lines() as usize);
+            let (new_start, new_lines) = (hunk.new_start() as usize, hunk.new_lines() as usize);
+            if old_lines == 0 {
+                // pure insertion after `old_start`
+                if line <= old_start {
+                    break;
+                }
+            } else if line < old_start {
+                break;
+            } else if line < old_start + old_lines {
+                if new_lines == 0 {
+                    return Ok(new_start.max(1));
+                }
+                return Ok(new_start + (line - old_start).min(new_lines - 1));
+            }
+            offset += new_lines as isize - old_lines as isize;
+        }
+        Ok(line.saturating_add_signed(offset).max(1))
+    }
+
+    // Reads the staged version of a file; like the working tree, it has no blame. Also tells
+    // whether the final newline is missing.
+    pub fn get_index_content(&self, filename: &str) -> anyhow::Result<(Vec<CommitRow>, bool)> {
+        if filename == "not found" {
+            return Ok((vec![], false));
+        }
+        let index = self.repository.index()?;
+        let entry = index
+            .get_path(Path::new(filename), 0)
+            .ok_or_else(|| anyhow::anyhow!("{} is not in the index", filename))?;
+        let blob = self.repository.find_blob(entry.id)?;
+        Ok(unblamed_rows(blob.content()))
+    }
+
+    // Diff from the viewed commit to the staged version of a file.
+    pub fn get_index_diff(&self, filename: &str) -> anyhow::Result<Vec<DiffRow>> {
+        if filename == "not found" {
+            return Ok(vec![]);
+        }
+        let tree = self.repository.find_commit(self.oid)?.tree()?;
+        let mut options = DiffOptions::new();
+        options.pathspec(filename).disable_pathspec_match(true);
+        let diff = self
+            .repository
+            .diff_tree_to_index(Some(&tree), None, Some(&mut options))?;
+        diff_rows(&diff, filename)
+    }
+
+    // Diff from the viewed commit to the working tree version of a file.
+    pub fn get_worktree_diff(&self, filename: &str) -> anyhow::Result<Vec<DiffRow>> {
+        if filename == "not found" {
+            return Ok(vec![]);
+        }
+        let tree = self.repository.find_commit(self.oid)?.tree()?;
+        let mut options = DiffOptions::new();
+        options.pathspec(filename).disable_pathspec_match(true);
+        let diff = self
+            .repository
+            .diff_tree_to_workdir(Some(&tree), Some(&mut options))?;
+        diff_rows(&diff, filename)
+    }
+
+    pub fn notes(&self) -> &[Note] {
+        self.notes.notes()
+    }
+
+    pub fn add_note(&mut self, path: &str, line: usize, text: &str) -> anyhow::Result<()> {
+        self.notes.add(Note {
+            commit: self.oid.to_string(),
+            path: path.to_owned(),
+            line,
+            text: text.to_owned(),
+        })?;
+        Ok(())
+    }
+
+    pub fn remove_note(&mut self, index: usize) -> anyhow::Result<()> {
+        self.notes.remove(index)?;
+        Ok(())
+    }
+
+    // Lines of `path` carrying a note at the current commit.
+    pub fn noted_lines(&self, path: &str) -> BTreeSet<usize> {
+        let commit = self.oid.to_string();
+        self.notes
+            .notes()
+            .iter()
+            .filter(|note| note.commit == commit && note.path == path)
+            .map(|note| note.line)
+            .collect()
+    }
+
+    /// Names and URLs of the repository's remotes.
+    pub fn remotes(&self) -> anyhow::Result<Vec<(String, String)>> {
+        let names = self.repository.remotes()?;
+        Ok(names
+            .iter()
+            .flatten()
+            .filter_map(|name| {
+                let remote = self.repository.find_remote(name).ok()?;
+                Some((name.to_owned(), remote.url()?.to_owned()))
+            })
+            .collect())
+    }
+
+    // Prefers `remote` for browser links; a name missing from this repository, as a
+    // configured default can be, falls back to `origin`.
+    pub fn set_remote(&mut self, remote: &str) {
+        self.remote = Some(remote.to_owned());
+    }
+
+    // The remote browser links use: the chosen one, else `origin`, else the only remote.
+    pub fn remote_name(&self) -> String {
+        match &self.remote {
+            Some(remote) if self.repository.find_remote(remote).is_ok() => remote.to_owned(),
+            _ if self.repository.find_remote("origin").is_ok() => "origin".to_owned(),
+            _ => match self.repository.remotes() {
+                Ok(names) if names.len() == 1 => names
+                    .get(0)
+                    .map_or_else(|| "origin".to_owned(), str::to_owned),
+                _ => "origin".to_owned(),
+            },
+        }
+    }
+
+    /// Whether browser links need a remote picked first: none is chosen or it is gone,
+    /// there is no `origin`, and there are several remotes to choose from.
+    pub fn remote_is_ambiguous(&self) -> bool {
+        self.repository.find_remote(&self.remote_name()).is_err()
+            && self.repository.remotes().is_ok_and(|names| names.len() > 1)
+    }
+
+    pub fn get_origin_url(&self) -> anyhow::Result<String> {
+        let remote = self.repository.find_remote(&self.remote_name())?;
+        remote
+            .url()
+            .map(|url| url.to_owned())
+            .ok_or_else(|| anyhow::anyhow!("the remote URL is not valid UTF-8"))
+    }
+
+    /// The forge URL of `file_path` at `line_number` in the viewed commit.
+    pub fn file_url(&self, file_path: &str, line_number: usize) -> anyhow::Result<String> {
+        let origin_url = self.get_origin_url()?;
+        self.construct_file_url(&origin_url, file_path, line_number)
+    }
+
+    /// The forge page of the changes to `file_path`: the compare view from the base ref
+    /// when one is set, otherwise the viewed commit, anchored to the file's diff.
+    pub fn diff_url(&self, file_path: &str) -> anyhow::Result<String> {
+        let origin_url = self.get_origin_url()?;
+        self.construct_diff_url(&origin_url, file_path)
+    }
+
+    /// The forge host and `owner/repo` path of the remote browser links use.
+    pub fn remote_repository(&self) -> anyhow::Result<(String, String)> {
+        let (base_url, repo_path) = forge_repository(&self.get_origin_url()?)?;
+        Ok((
+            base_url.trim_start_matches("https://").to_owned(),
+            repo_path,
+        ))
+    }
+
+    fn construct_diff_url(&self, origin_url: &str, file_path: &str) -> anyhow::Result<String> {
+        let (base_url, repo_path) = forge_repository(origin_url)?;
+        let host = base_url.trim_start_matches("https://");
+        if forge_layout("github") != Some(self.url_template(host)) {
+            return Err(anyhow::anyhow!("diff links are not supported for {}", host));
+        }
+        let page = match self.base {
+            Some(base) => format!("compare/{}...{}", base, self.target.unwrap_or(self.oid)),
+            None => format!("commit/{}", self.oid),
+        };
+        // GitHub anchors each file's diff by the SHA-256 of its path
+        let anchor: String = Sha256::digest(git_path(Path::new(file_path)).as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        Ok(format!(
+            "{}/{}/{}#diff-{}",
+            base_url, repo_path, page, anchor
+        ))
+    }
+
+    fn construct_file_url(
+        &self,
+        origin_url: &str,
+        file_path: &str,
+        line_number: usize,
+    ) -> anyhow::Result<String> {
+        let (base_url, repo_path) = forge_repository(origin_url)?;
+        let host = base_url.trim_start_matches("https://");
+        url_from_template(
+            self.url_template(host),
+            &[
+                ("host", host),
+                ("repo", &repo_path),
+                ("sha", &self.oid.to_string()),
+                ("path", &git_path(Path::new(file_path))),
+                ("line", &line_number.to_string()),
+            ],
+        )
+    }
+}
+
+// The forge's base URL and `owner/repo` path of a remote URL.
+fn forge_repository(origin_url: &str) -> anyhow::Result<(String, String)> {
+    let (base_url, repo_path) = if let Some(url_without_prefix) = origin_url.strip_prefix("git@") {
+        // SSH format: git@github.com:owner/repo.git
+        let parts: Vec<&str> = url_without_prefix.split(':').collect();
+        if parts.len() != 2 {
+            return Err(anyhow::anyhow!("Invalid SSH URL format"));
+        }
+        let host = parts[0];
+        let repo_path = parts[1].strip_suffix(".git").unwrap_or(parts[1]);
+        (format!("https://{}", host), repo_path.to_string())
+    } else if let Some(url_without_https) = origin_url.strip_prefix("https://") {
+        // HTTPS format: https://github.com/owner/repo.git
+        let parts: Vec<&str> = url_without_https.splitn(2, '/').collect();
+        if parts.len() != 2 {
+            return Err(anyhow::anyhow!("Invalid HTTPS URL format"));
+        }
+        let host = parts[0];
+        let repo_path = parts[1].strip_suffix(".git").unwrap_or(parts[1]);
+        (format!("https://{}", host), repo_path.to_string())
+    } else {
+        return Err(anyhow::anyhow!("Unsupported URL format"));
+    };
+    Ok((base_url, repo_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::io::Write;
+
+    fn setup_test_repo_with_file() -> (Repository, String) {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let test_dir = env::temp_dir().join(format!("gview_test_repo_{}", timestamp));
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).unwrap();
+
+        let repo = Repository::init(&test_dir).unwrap();
+
+        // Create a test file
+        let test_file_path = test_dir.join("test.txt");
+        let mut file = fs::File::create(&test_file_path).unwrap();
+        file.write_all(b"line 1\nline 2\nline 3\n").unwrap();
+
+        // Add and commit the file
         let mut index = repo.index().unwrap();
         index.add_path(std::path::Path::new("test.txt")).unwrap();
         index.write().unwrap();
 
-        let signature = git2::Signature::new(
-            "Test User",
-            "test@example.com",
-            &git2::Time::new(1234567890, 0),
-        )
-        .unwrap();
-        let tree_id = index.write_tree().unwrap();
-        let tree = repo.find_tree(tree_id).unwrap();
+        let signature = git2::Signature::new(
+            "Test User",
+            "test@example.com",
+            &git2::Time::new(1234567890, 0),
+        )
+        .unwrap();
+        let tree_id = index.write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+
+        let _ = repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "Add test file",
+            &tree,
+            &[],
+        );
+
+        drop(tree);
+        (repo, "test.txt".to_string())
+    }
+
+    // Two commits on test.txt: "line 1..3" and then line 2 replaced; HEAD is the second one.
+    fn setup_test_repo_with_history() -> (Repository, Oid, Oid) {
+        let (repo, filename) = setup_test_repo_with_file();
+        let first = repo.head().unwrap().target().unwrap();
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        fs::write(workdir.join(&filename), "line 1\nline two\nline 3\n").unwrap();
+        fs::write(workdir.join("added.txt"), "new file\n").unwrap();
+
+        let signature = git2::Signature::new(
+            "Test User",
+            "test@example.com",
+            &git2::Time::new(1234567990, 0),
+        )
+        .unwrap();
+        let second = {
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new(&filename)).unwrap();
+            index.add_path(Path::new("added.txt")).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parent = repo.find_commit(first).unwrap();
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "Change line 2",
+                &tree,
+                &[&parent],
+            )
+            .unwrap()
+        };
+        (repo, first, second)
+    }
+
+    fn setup_empty_repo() -> Repository {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let test_dir = env::temp_dir().join(format!("gview_empty_repo_{}", timestamp));
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).unwrap();
+
+        let repo = Repository::init(&test_dir).unwrap();
+
+        let signature = git2::Signature::new(
+            "Test User",
+            "test@example.com",
+            &git2::Time::new(1234567890, 0),
+        )
+        .unwrap();
+        let tree_id = {
+            let mut index = repo.index().unwrap();
+            index.write_tree().unwrap()
+        };
+        let tree = repo.find_tree(tree_id).unwrap();
+
+        let _ = repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "Initial commit",
+            &tree,
+            &[],
+        );
+
+        drop(tree);
+        repo
+    }
+
+    #[test]
+    fn test_commit_row_new() {
+        let oid = Oid::from_str("0123456789abcdef0123456789abcdef01234567").unwrap();
+        let row = CommitRow::new(
+            "test_author".to_string(),
+            oid,
+            42,
+            "println!(\"Hello, world!\");".to_string(),
+        );
+
+        assert_eq!(row._author, "test_author");
+        assert_eq!(row.commit, oid);
+        assert_eq!(row.number, 42);
+        assert_eq!(row.line, "println!(\"Hello, world!\");");
+    }
+
+    #[test]
+    fn test_repository_info_current_commit() {
+        let repo = setup_empty_repo();
+        let head_commit = repo.head().unwrap().target().unwrap();
+
+        let mut repo_info = RepositoryInfo::_from_parts(repo, head_commit);
+
+        let result = repo_info.current_commit().unwrap();
+        assert_eq!(result.0.len(), 40); // SHA length
+        assert_eq!(result.1, "Initial commit");
+    }
+
+    #[test]
+    fn test_set_commit_by_id_full_hash() {
+        let (repo, _) = setup_test_repo_with_file();
+        let head_commit = repo.head().unwrap().target().unwrap();
+        let head_commit_str = head_commit.to_string();
+
+        let mut repo_info = RepositoryInfo::_from_parts(repo, head_commit);
+
+        // Test setting by full commit ID
+        let result = repo_info.set_commit_by_id(&head_commit_str);
+        assert!(result.is_ok());
+        assert_eq!(repo_info.oid, head_commit);
+    }
+
+    #[test]
+    fn test_set_commit_by_id_short_hash() {
+        let (repo, _) = setup_test_repo_with_file();
+        let head_commit = repo.head().unwrap().target().unwrap();
+        let head_commit_str = head_commit.to_string();
+        let short_commit = &head_commit_str[..7]; // Use 7 characters
+
+        let mut repo_info = RepositoryInfo::_from_parts(repo, head_commit);
+
+        // Test setting by short commit ID
+        let result = repo_info.set_commit_by_id(short_commit);
+        assert!(result.is_ok());
+        assert_eq!(repo_info.oid, head_commit);
+    }
+
+    #[test]
+    fn test_set_commit_by_id_invalid() {
+        let (repo, _) = setup_test_repo_with_file();
+        let head_commit = repo.head().unwrap().target().unwrap();
+
+        let mut repo_info = RepositoryInfo::_from_parts(repo, head_commit);
+
+        // Test setting by invalid commit ID
+        let result = repo_info.set_commit_by_id("invalid123");
+        assert!(result.is_err());
+        assert_eq!(repo_info.oid, head_commit); // Should remain unchanged
+    }
+
+    #[test]
+    fn test_repository_info_set_parent_commit() {
+        let (repo, _) = setup_test_repo_with_file();
+        let _head_commit = repo.head().unwrap().target().unwrap();
+
+        // Create a second commit
+        let signature = git2::Signature::new(
+            "Test User",
+            "test@example.com",
+            &git2::Time::new(1234567890, 0),
+        )
+        .unwrap();
+        let tree = repo
+            .head()
+            .unwrap()
+            .peel_to_commit()
+            .unwrap()
+            .tree()
+            .unwrap();
+        let parent_commit = repo.head().unwrap().peel_to_commit().unwrap();
+        let second_commit_oid = repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "Second commit",
+                &tree,
+                &[&parent_commit],
+            )
+            .unwrap();
+
+        drop(tree);
+        drop(parent_commit);
+
+        let mut repo_info = RepositoryInfo::_from_parts(repo, second_commit_oid);
+
+        let original_oid = repo_info.oid;
+        repo_info.set_parent_commit();
+
+        // Should now be pointing to the parent commit
+        assert_ne!(original_oid, repo_info.oid);
+    }
+
+    #[test]
+    fn test_repository_info_set_parent_commit_no_parent() {
+        let repo = setup_empty_repo();
+        let head_commit = repo.head().unwrap().target().unwrap();
+
+        let mut repo_info = RepositoryInfo::_from_parts(repo, head_commit);
+
+        let original_oid = repo_info.oid;
+        repo_info.set_parent_commit();
+
+        // Should remain the same as it has no parent
+        assert_eq!(original_oid, repo_info.oid);
+    }
+
+    #[test]
+    fn test_get_content_not_found_special_case() {
+        let repo = setup_empty_repo();
+        let head_commit = repo.head().unwrap().target().unwrap();
+
+        let mut repo_info = RepositoryInfo::_from_parts(repo, head_commit);
+
+        let result = repo_info.get_content("not found".to_string()).unwrap();
+        assert_eq!(result, FileContent::default());
+    }
+
+    #[test]
+    fn test_split_lines() {
+        assert_eq!(split_lines(b""), (vec![], false));
+        assert_eq!(
+            split_lines(b"a\nb\n"),
+            (vec!["a".to_owned(), "b".to_owned()], false)
+        );
+        assert_eq!(
+            split_lines(b"a\r\nb"),
+            (vec!["a".to_owned(), "b".to_owned()], true)
+        );
+        // an empty line before the final newline is kept
+        assert_eq!(
+            split_lines(b"a\n\n"),
+            (vec!["a".to_owned(), "".to_owned()], false)
+        );
+        assert_eq!(
+            split_lines(b"\xff\nok\n"),
+            (vec!["\u{fffd}".to_owned(), "ok".to_owned()], false)
+        );
+    }
+
+    #[test]
+    fn test_line_bytes() {
+        // line endings and undecodable bytes are kept as they are
+        assert_eq!(line_bytes(b"a\r\nb\r\nc", 1..=1), b"a\r\n");
+        assert_eq!(line_bytes(b"a\r\nb\r\nc", 2..=3), b"b\r\nc");
+        assert_eq!(line_bytes(b"\xff\nok\n", 1..=2), b"\xff\nok\n");
+        assert_eq!(line_bytes(b"a\n", 2..=2), b"");
+    }
+
+    #[test]
+    fn test_file_content_rows_without_blame() {
+        let content = FileContent {
+            lines: vec!["line 1".to_owned(), "line 2".to_owned()],
+            hunks: vec![],
+            blame_error: Some("blame failed".to_owned()),
+            missing_newline: false,
+            window: None,
+            pending_blame: None,
+        };
+        let rows = content.rows();
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|row| row.commit.is_zero()));
+        assert_eq!(rows[1].line, "line 2");
+    }
+
+    #[test]
+    fn test_content_window() {
+        let content: String = (1..=WINDOW_LINES + 10)
+            .map(|number| format!("line {}\r\n", number))
+            .collect();
+        let window = content_window(content.as_bytes(), WINDOW_LINES);
+        assert_eq!(
+            window.window,
+            Some(Window {
+                start: WINDOW_LINES,
+                total: WINDOW_LINES + 10
+            })
+        );
+        assert_eq!(window.lines.len(), 10);
+        assert_eq!(window.rows()[0].number, WINDOW_LINES + 1);
+        assert_eq!(window.lines[9], format!("line {}", WINDOW_LINES + 10));
+        assert!(!window.missing_newline);
+
+        let window = content_window(b"a\n\nb", 1);
+        assert_eq!(window.lines, vec!["", "b"]);
+        assert!(window.missing_newline);
+        assert!(content_window(b"a\n", 5).lines.is_empty());
+        assert!(content_window(b"", 0).lines.is_empty());
+    }
+
+    #[test]
+    fn test_get_content_with_file() {
+        let (repo, filename) = setup_test_repo_with_file();
+        let head_commit = repo.head().unwrap().target().unwrap();
+
+        let mut repo_info = RepositoryInfo::_from_parts(repo, head_commit);
+
+        let content = repo_info.get_content(filename).unwrap();
+        assert_eq!(content.blame_error, None);
+        assert!(!content.missing_newline);
+        assert_eq!(content.hunks.len(), 1);
+        assert_eq!(content.hunks[0].start, 1);
+        assert_eq!(content.hunks[0].len, 3);
+        assert_eq!(content.hunks[0].author, "Test User");
+        assert_eq!(content.hunks[0].email, "test@example.com");
+        assert_eq!(content.hunks[0].time, 1234567890);
+
+        let result = content.rows();
+        assert_eq!(result.len(), 3); // 3 lines
+        assert_eq!(result[0].line, "line 1");
+        assert_eq!(result[1].line, "line 2");
+        assert_eq!(result[2].line, "line 3");
+        assert_eq!(result[0].number, 1);
+        assert_eq!(result[1].number, 2);
+        assert_eq!(result[2].number, 3);
+    }
+
+    #[test]
+    fn test_recursive_walk_empty_repo() {
+        let repo = setup_empty_repo();
+        let head_commit = repo.head().unwrap().target().unwrap();
+
+        let mut repo_info = RepositoryInfo::_from_parts(repo, head_commit);
+
+        let result = repo_info.recursive_walk().unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn test_recursive_walk_with_file() {
+        let (repo, _) = setup_test_repo_with_file();
+        let head_commit = repo.head().unwrap().target().unwrap();
+
+        let mut repo_info = RepositoryInfo::_from_parts(repo, head_commit);
+
+        let result = repo_info.recursive_walk().unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0], "test.txt");
+    }
+
+    #[test]
+    fn test_prefix_scopes_session() {
+        let (repo, _) = setup_test_repo_with_file();
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        let signature = git2::Signature::new(
+            "Test User",
+            "test@example.com",
+            &git2::Time::new(1234567990, 0),
+        )
+        .unwrap();
+        let commit = |files: &[(&str, &str)], message: &str| {
+            let mut index = repo.index().unwrap();
+            for (path, content) in files {
+                fs::create_dir_all(workdir.join(path).parent().unwrap()).unwrap();
+                fs::write(workdir.join(path), content).unwrap();
+                index.add_path(Path::new(path)).unwrap();
+            }
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parent = repo
+                .find_commit(repo.head().unwrap().target().unwrap())
+                .unwrap();
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                message,
+                &tree,
+                &[&parent],
+            )
+            .unwrap()
+        };
+        let second = commit(
+            &[("src/lib.rs", "pub fn f() {}\n"), ("src/deep/mod.rs", "\n")],
+            "Add sources",
+        );
+        let third = commit(&[("test.txt", "changed\n")], "Change test file");
+        let mut repo_info = RepositoryInfo::_from_parts(repo, third);
+
+        assert!(repo_info.set_prefix("test.txt").is_err());
+        assert!(repo_info.set_prefix("missing").is_err());
+        repo_info.set_prefix("./src/").unwrap();
+        assert_eq!(repo_info.prefix(), Some("src"));
+
+        assert_eq!(
+            repo_info.recursive_walk().unwrap(),
+            ["src/deep/mod.rs", "src/lib.rs"]
+        );
+        assert_eq!(
+            repo_info
+                .text_blobs()
+                .unwrap()
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect::<Vec<_>>(),
+            ["src/deep/mod.rs", "src/lib.rs"]
+        );
+        assert_eq!(
+            repo_info.get_commit_history().unwrap(),
+            [(second.to_string(), "Add sources".to_owned())]
+        );
+        // the viewed commit changed nothing under src
+        assert!(repo_info.changed_files().unwrap().is_empty());
+        repo_info.set_commit_by_id(&second.to_string()).unwrap();
+        assert_eq!(
+            repo_info.changed_files().unwrap(),
+            ["src/deep/mod.rs", "src/lib.rs"]
+        );
+
+        repo_info.set_prefix("").unwrap();
+        assert_eq!(repo_info.prefix(), None);
+        assert_eq!(repo_info.recursive_walk().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_recursive_walk_sparse_only() {
+        let (repo, _, second) = setup_test_repo_with_history();
+        repo.config()
+            .unwrap()
+            .set_bool("core.sparseCheckout", true)
+            .unwrap();
+        fs::write(repo.path().join("info/sparse-checkout"), "added.txt\n").unwrap();
+        let mut repo_info = RepositoryInfo::_from_parts(repo, second);
+        assert!(repo_info.has_sparse_checkout());
+
+        assert_eq!(
+            repo_info.recursive_walk().unwrap(),
+            ["added.txt", "test.txt"]
+        );
+        repo_info.set_sparse_only(true);
+        assert!(repo_info.sparse_only());
+        assert_eq!(repo_info.recursive_walk().unwrap(), ["added.txt"]);
+    }
+
+    #[test]
+    fn test_recursive_walk_reuses_unchanged_subtrees() {
+        let (repo, _) = setup_test_repo_with_file();
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        let signature = git2::Signature::new(
+            "Test User",
+            "test@example.com",
+            &git2::Time::new(1234567990, 0),
+        )
+        .unwrap();
+        let commit = |files: &[(&str, &str)]| {
+            let mut index = repo.index().unwrap();
+            for (path, content) in files {
+                fs::create_dir_all(workdir.join(path).parent().unwrap()).unwrap();
+                fs::write(workdir.join(path), content).unwrap();
+                index.add_path(Path::new(path)).unwrap();
+            }
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parent = repo
+                .find_commit(repo.head().unwrap().target().unwrap())
+                .unwrap();
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "Update",
+                &tree,
+                &[&parent],
+            )
+            .unwrap()
+        };
+        let first = commit(&[("src/lib.rs", "pub fn f() {}\n"), ("src/deep/mod.rs", "\n")]);
+        let second = commit(&[("test.txt", "changed\n")]);
+        let mut repo_info = RepositoryInfo::_from_parts(repo, second);
+
+        let files = vec!["src/deep/mod.rs", "src/lib.rs", "test.txt"];
+        assert_eq!(repo_info.recursive_walk().unwrap(), files);
+        // root, src and src/deep
+        assert_eq!(repo_info.listed_trees.len(), 3);
+
+        // Only the root differs in the first commit
+        repo_info.set_commit_by_id(&first.to_string()).unwrap();
+        assert_eq!(repo_info.recursive_walk().unwrap(), files);
+        assert_eq!(repo_info.listed_trees.len(), 4);
+    }
+
+    #[test]
+    fn test_get_content_blame_in_background() {
+        let (repo, filename) = setup_test_repo_with_file();
+        let head_commit = repo.head().unwrap().target().unwrap();
+        let mut repo_info = RepositoryInfo::_from_parts(repo, head_commit);
+        repo_info.set_background(true);
+
+        let content = repo_info.get_content(filename.clone()).unwrap();
+        assert_eq!(content.lines.len(), 3);
+        assert!(content.hunks.is_empty());
+        let request = content.pending_blame.unwrap();
+        assert_eq!(request.path, filename);
+
+        let job = repo_info.spawn_blame(request);
+        while !job.is_finished() {
+            std::thread::yield_now();
+        }
+        repo_info.finish_blame(job);
+        let content = repo_info.get_content(filename).unwrap();
+        assert_eq!(content.pending_blame, None);
+        assert_eq!(content.hunks.len(), 1);
+        assert_eq!(content.hunks[0].author, "Test User");
+    }
+
+    #[test]
+    fn test_get_content_at_viewed_commit() {
+        let (repo, first, second) = setup_test_repo_with_history();
+        // HEAD stays on the second commit while the first is viewed
+        let mut repo_info = RepositoryInfo::_from_parts(repo, first);
+
+        let content = repo_info.get_content("test.txt".to_owned()).unwrap();
+        assert_eq!(content.lines, vec!["line 1", "line 2", "line 3"]);
+        assert!(content.hunks.iter().all(|hunk| hunk.commit == first));
+        assert!(repo_info.get_content("added.txt".to_owned()).is_err());
+        assert!(!repo_info.is_binary_file("test.txt"));
+
+        repo_info.set_commit_by_id(&second.to_string()).unwrap();
+        let content = repo_info.get_content("test.txt".to_owned()).unwrap();
+        assert_eq!(content.lines[1], "line two");
+        assert!(content.hunks.iter().any(|hunk| hunk.commit == second));
+
+        // the blame on a worker thread follows the viewed commit too
+        let (repo, first, _) = setup_test_repo_with_history();
+        let mut repo_info = RepositoryInfo::_from_parts(repo, first);
+        repo_info.set_background(true);
+        let request = repo_info
+            .get_content("test.txt".to_owned())
+            .unwrap()
+            .pending_blame
+            .unwrap();
+        let job = repo_info.spawn_blame(request);
+        while !job.is_finished() {
+            std::thread::yield_now();
+        }
+        repo_info.finish_blame(job);
+        let content = repo_info.get_content("test.txt".to_owned()).unwrap();
+        assert_eq!(content.hunks.len(), 1);
+        assert_eq!(content.hunks[0].commit, first);
+    }
+
+    #[test]
+    fn test_fetch_blob_of_partial_clone() {
+        let (origin, filename) = setup_test_repo_with_file();
+        origin
+            .config()
+            .unwrap()
+            .set_bool("uploadpack.allowFilter", true)
+            .unwrap();
+        let clone_dir = origin.workdir().unwrap().with_extension("partial");
+        let _ = fs::remove_dir_all(&clone_dir);
+        let cloned = Command::new("git")
+            .args(["clone", "--quiet", "--no-checkout", "--filter=blob:none"])
+            .arg(format!("file://{}", origin.workdir().unwrap().display()))
+            .arg(&clone_dir)
+            .status()
+            .unwrap();
+        assert!(cloned.success());
+
+        let repo = Repository::open(&clone_dir).unwrap();
+        let head = repo.head().unwrap().target().unwrap();
+        let mut repo_info = RepositoryInfo::_from_parts(repo, head);
+        assert_eq!(repo_info.promisor_remote(), Some("origin".to_owned()));
+        let error = repo_info.get_content(filename.clone()).unwrap_err();
+        assert!(is_unavailable_object(&error));
+
+        let fetch = repo_info.spawn_blob_fetch(&filename).unwrap();
+        assert!(fetch.join().unwrap().is_ok());
+        let content = repo_info.get_content(filename).unwrap();
+        assert_eq!(content.lines, vec!["line 1", "line 2", "line 3"]);
+
+        // a full clone has nothing to fetch from
+        let (repo, _) = setup_test_repo_with_file();
+        let head = repo.head().unwrap().target().unwrap();
+        assert_eq!(
+            RepositoryInfo::_from_parts(repo, head).promisor_remote(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_recursive_walk_reads_no_blobs() {
+        let (repo, filename) = setup_test_repo_with_file();
+        let head = repo.head().unwrap().target().unwrap();
+        let blob = repo
+            .find_commit(head)
+            .unwrap()
+            .tree()
+            .unwrap()
+            .get_path(Path::new(&filename))
+            .unwrap()
+            .id()
+            .to_string();
+        // the listing comes from tree entries alone, so it survives a missing blob
+        let git_dir = repo.path().to_path_buf();
+        fs::remove_file(git_dir.join("objects").join(&blob[..2]).join(&blob[2..])).unwrap();
+        let repo = Repository::open(&git_dir).unwrap();
+        let mut repo_info = RepositoryInfo::_from_parts(repo, head);
+        assert_eq!(repo_info.recursive_walk().unwrap(), [filename.as_str()]);
+        let error = repo_info.get_content(filename.clone()).unwrap_err();
+        assert!(is_unavailable_object(&error));
+        // a path missing from the tree is told apart from a missing object
+        let error = repo_info.get_content("missing.txt".to_owned()).unwrap_err();
+        assert!(!is_unavailable_object(&error));
+    }
+
+    #[test]
+    fn test_recursive_walk_lists_non_ascii_text() {
+        let (repo, filename) = setup_test_repo_with_file();
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        fs::write(workdir.join("greeting.rs"), "// こんにちは 🎉 café\n").unwrap();
+        fs::write(workdir.join("data.bin"), [b'a', 0, 0xff, b'\n']).unwrap();
+        let signature = git2::Signature::new(
+            "Test User",
+            "test@example.com",
+            &git2::Time::new(1234567990, 0),
+        )
+        .unwrap();
+        let oid = {
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new("greeting.rs")).unwrap();
+            index.add_path(Path::new("data.bin")).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parent = repo
+                .find_commit(repo.head().unwrap().target().unwrap())
+                .unwrap();
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "Add greeting",
+                &tree,
+                &[&parent],
+            )
+            .unwrap()
+        };
+        let mut repo_info = RepositoryInfo::_from_parts(repo, oid);
+
+        // UTF-8 text is indexed for search, a blob with NUL bytes is only listed
+        assert_eq!(
+            repo_info.recursive_walk().unwrap(),
+            vec!["data.bin".to_owned(), "greeting.rs".to_owned(), filename]
+        );
+        let text_blobs = repo_info.text_blobs().unwrap();
+        assert!(text_blobs.iter().any(|(file, _)| file == "greeting.rs"));
+        assert!(!text_blobs.iter().any(|(file, _)| file == "data.bin"));
+        assert_eq!(
+            repo_info
+                .get_content("greeting.rs".to_owned())
+                .unwrap()
+                .lines,
+            vec!["// こんにちは 🎉 café"]
+        );
+        assert_eq!(
+            repo_info
+                .get_content_at(&oid.to_string(), "greeting.rs")
+                .unwrap()[0]
+                .line,
+            "// こんにちは 🎉 café"
+        );
+    }
+
+    #[test]
+    fn test_binary_files_listed_and_temp_blob() {
+        let (repo, filename) = setup_test_repo_with_file();
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        let png = [0x89, b'P', b'N', b'G', 0, 1, 2];
+        fs::write(workdir.join("logo.png"), png).unwrap();
+        let signature = git2::Signature::new(
+            "Test User",
+            "test@example.com",
+            &git2::Time::new(1234567990, 0),
+        )
+        .unwrap();
+        let oid = {
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new("logo.png")).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parent = repo
+                .find_commit(repo.head().unwrap().target().unwrap())
+                .unwrap();
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "Add logo",
+                &tree,
+                &[&parent],
+            )
+            .unwrap()
+        };
+        let mut repo_info = RepositoryInfo::_from_parts(repo, oid);
+
+        // Binary files are listed next to text and read as raw bytes
+        assert_eq!(
+            repo_info.recursive_walk().unwrap(),
+            vec!["logo.png".to_owned(), filename.clone()]
+        );
+        assert!(repo_info.is_binary_file("logo.png"));
+        assert!(!repo_info.is_binary_file(&filename));
+        assert_eq!(repo_info.get_raw_blob("logo.png").unwrap(), png);
+
+        let path = repo_info.materialize_blob("logo.png", oid).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), png);
+    }
+
+    #[test]
+    fn test_history_in_date_range() {
+        // both commits are from 2009-02-13, in UTC
+        let (repo, _, second) = setup_test_repo_with_history();
+        let mut repo_info = RepositoryInfo::_from_parts(repo, second);
+        // paging through the history finds the same commits as reading it whole
+        let count = |repo_info: &mut RepositoryInfo, range: &str| {
+            repo_info.set_date_range(DateRange::parse(range).unwrap());
+            let page = repo_info.history_page(0, 10).unwrap();
+            let history = repo_info.get_commit_history().unwrap();
+            assert_eq!(page.entries.len(), history.len());
+            assert_eq!(page.next, None);
+            history.len()
+        };
+        assert_eq!(count(&mut repo_info, "2009-02-13..2009-02-13"), 2);
+        assert_eq!(count(&mut repo_info, "2009-02-14.."), 0);
+        assert_eq!(count(&mut repo_info, "..2009-02-12"), 0);
+        assert_eq!(count(&mut repo_info, ""), 2);
+        assert!(!repo_info.date_range().is_set());
+
+        let range = DateRange::new(Some("2009-01-01"), None).unwrap();
+        assert_eq!(range.to_string(), "2009-01-01..");
+        assert!(DateRange::parse("2009-02-14..2009-02-13").is_err());
+        assert!(DateRange::parse("2009-02-14").is_err());
+        assert!(DateRange::parse("last week..").is_err());
+    }
+
+    #[test]
+    fn test_walk_in_background() {
+        let (repo, _, second) = setup_test_repo_with_history();
+        let mut repo_info = RepositoryInfo::_from_parts(repo, second);
+        repo_info.set_background(true);
+        // a tree not listed before is left to a worker thread
+        assert!(repo_info.listed_files().is_none());
+
+        let mut tracker = crate::progress::ProgressTracker::new();
+        let job = repo_info.spawn_walk(tracker.reporter()).unwrap();
+        while !job.is_finished() {
+            std::thread::yield_now();
+        }
+        repo_info.finish_walk(job).unwrap();
+        tracker.tick();
+        assert_eq!(tracker.describe(), None);
+        assert_eq!(
+            repo_info.listed_files().unwrap().unwrap(),
+            vec!["added.txt", "test.txt"]
+        );
+    }
+
+    #[test]
+    fn test_cached_file_list_history_and_blame() {
+        let (repo, _, second) = setup_test_repo_with_history();
+        let tree = repo.find_commit(second).unwrap().tree_id();
+        let mut repo_info = RepositoryInfo::_from_parts(repo, second);
+        let dir = env::temp_dir().join(format!(
+            "gview_repository_cache_{}_{}",
+            second,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        repo_info.cache = Cache::new(Some(dir.clone()), crate::cache::DEFAULT_LIMIT);
+
+        let files = repo_info.recursive_walk().unwrap();
+        let history = repo_info.get_commit_history().unwrap();
+        let content = repo_info.get_content("test.txt".to_owned()).unwrap();
+        assert_eq!(files, vec!["added.txt", "test.txt"]);
+        assert_eq!(history.len(), 2);
+
+        // Later calls are answered from the cache
+        assert_eq!(repo_info.recursive_walk().unwrap(), files);
+        assert_eq!(repo_info.get_commit_history().unwrap(), history);
+        let cached = repo_info.get_content("test.txt".to_owned()).unwrap();
+        assert_eq!(cached, content);
+        assert!(fs::read_dir(dir.join("listing")).unwrap().any(|entry| entry
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .starts_with(&tree.to_string())));
+        assert_eq!(fs::read_dir(dir.join("hunks")).unwrap().count(), 1);
+
+        // once listed, a commit is listed from memory rather than the cache
+        for entry in fs::read_dir(dir.join("listing")).unwrap() {
+            fs::write(entry.unwrap().path(), "stale.txt").unwrap();
+        }
+        assert_eq!(repo_info.recursive_walk().unwrap(), files);
+    }
+
+    #[test]
+    fn test_identity() {
+        let (repo, _, second) = setup_test_repo_with_history();
+        let signature = git2::Signature::new(
+            "Someone Else",
+            "else@example.com",
+            &git2::Time::new(1234568090, 0),
+        )
+        .unwrap();
+        let tree = repo.find_commit(second).unwrap().tree().unwrap();
+        let parent = repo.find_commit(second).unwrap();
+        let third = repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "Empty change",
+                &tree,
+                &[&parent],
+            )
+            .unwrap();
+        drop((tree, parent));
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Renamed User").unwrap();
+        config.set_str("user.email", "TEST@example.com").unwrap();
+        let repo_info = RepositoryInfo::_from_parts(repo, third);
+
+        let identity = repo_info.identity();
+        assert_eq!(identity.name.as_deref(), Some("Renamed User"));
+        assert!(identity.matches("Test User", "test@example.com"));
+        assert!(identity.matches("Renamed User", "old@example.com"));
+        assert!(!identity.matches("Someone Else", "else@example.com"));
+        assert!(!Identity::default().matches("", ""));
+    }
+
+    #[test]
+    fn test_get_content_at() {
+        let (repo, first, second) = setup_test_repo_with_history();
+        let repo_info = RepositoryInfo::_from_parts(repo, second);
+        let lines = |commit: Oid| -> Vec<String> {
+            repo_info
+                .get_content_at(&commit.to_string(), "test.txt")
+                .unwrap()
+                .into_iter()
+                .map(|row| row.line)
+                .collect()
+        };
+        assert_eq!(lines(first), vec!["line 1", "line 2", "line 3"]);
+        assert_eq!(lines(second), vec!["line 1", "line two", "line 3"]);
+        // added.txt only exists from the second commit on
+        assert!(repo_info
+            .get_content_at(&first.to_string(), "added.txt")
+            .is_err());
+    }
+
+    #[test]
+    fn test_map_line() {
+        let (repo, first, second) = setup_test_repo_with_history();
+        let third = {
+            let workdir = repo.workdir().unwrap().to_path_buf();
+            // insert a line on top and drop "line two"
+            fs::write(workdir.join("test.txt"), "line 0\nline 1\nline 3\n").unwrap();
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new("test.txt")).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parent = repo.find_commit(second).unwrap();
+            let signature = git2::Signature::new(
+                "Test User",
+                "test@example.com",
+                &git2::Time::new(1234568090, 0),
+            )
+            .unwrap();
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "Reshuffle lines",
+                &tree,
+                &[&parent],
+            )
+            .unwrap()
+        };
+        let repo_info = RepositoryInfo::_from_parts(repo, third);
+        let (first, second, third) = (first.to_string(), second.to_string(), third.to_string());
+        let map = |from: &str, to: &str, line: usize| {
+            repo_info.map_line(from, to, "test.txt", line).unwrap()
+        };
+
+        // a changed line stays in place
+        assert_eq!(map(&first, &second, 2), 2);
+        assert_eq!(map(&second, &third, 1), 2);
+        // the deleted line lands on the line before it
+        assert_eq!(map(&second, &third, 2), 2);
+        assert_eq!(map(&second, &third, 3), 3);
+        assert_eq!(map(&third, &second, 1), 1);
+        assert_eq!(map(&third, &second, 3), 3);
+        assert!(repo_info.map_line(&first, &third, "added.txt", 1).is_err());
+    }
+
+    #[test]
+    fn test_preload_commit_metadata() {
+        let (repo, first, second) = setup_test_repo_with_history();
+        let repo_info = RepositoryInfo::_from_parts(repo, second);
+        let expected = repo_info.commit_info().unwrap();
+
+        let mut tracker = crate::progress::ProgressTracker::new();
+        repo_info
+            .preload_commit_metadata(tracker.reporter())
+            .join()
+            .unwrap();
+        // the loader reports its end, so the status bar goes idle again
+        tracker.tick();
+        assert_eq!(tracker.describe(), None);
+        {
+            let metadata = repo_info.metadata.lock().unwrap();
+            assert_eq!(metadata.len(), 2);
+            assert_eq!(metadata[&first].message, "Add test file");
+        }
+        assert_eq!(repo_info.commit_info().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_spawn_search_and_cancel() {
+        let (repo, _, second) = setup_test_repo_with_history();
+        let repo_info = RepositoryInfo::_from_parts(repo, second);
+        let mut tracker = crate::progress::ProgressTracker::new();
+
+        let hits = repo_info
+            .spawn_search("two", tracker.reporter(), CancelToken::default())
+            .join()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            hits,
+            vec![SearchHit {
+                path: "test.txt".to_owned(),
+                line: 2,
+                text: "line two".to_owned(),
+            }]
+        );
+        tracker.tick();
+        assert_eq!(tracker.describe(), None);
+
+        let cancel = CancelToken::default();
+        cancel.cancel();
+        let result = repo_info
+            .spawn_search("two", tracker.reporter(), cancel)
+            .join()
+            .unwrap();
+        assert_eq!(result.unwrap_err().to_string(), "canceled");
+    }
 
-        let _ = repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            "Add test file",
-            &tree,
-            &[],
+    #[test]
+    fn test_materialize_blob() {
+        let (repo, first, second) = setup_test_repo_with_history();
+        let repo_info = RepositoryInfo::_from_parts(repo, second);
+
+        let old = repo_info.materialize_blob("test.txt", first).unwrap();
+        let new = repo_info.materialize_blob("test.txt", second).unwrap();
+        assert!(old.ends_with(format!("{}/test.txt", first)));
+        assert!(fs::read_to_string(&old).unwrap().contains("line 2"));
+        assert!(fs::read_to_string(&new).unwrap().contains("line two"));
+
+        assert!(repo_info.materialize_blob("added.txt", first).is_err());
+        assert!(repo_info.materialize_blob("../test.txt", second).is_err());
+
+        // The scratch directory goes away with the repository
+        drop(repo_info);
+        assert!(!old.exists());
+        assert!(!new.exists());
+    }
+
+    #[test]
+    fn test_find_next_commit_no_next() {
+        let repo = setup_empty_repo();
+        let head_commit = repo.head().unwrap().target().unwrap();
+
+        let mut repo_info = RepositoryInfo::_from_parts(repo, head_commit);
+
+        let result = repo_info.find_next_commit().unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_set_next_commit_no_next() {
+        let repo = setup_empty_repo();
+        let head_commit = repo.head().unwrap().target().unwrap();
+
+        let mut repo_info = RepositoryInfo::_from_parts(repo, head_commit);
+
+        let original_oid = repo_info.oid;
+        let result = repo_info.set_next_commit().unwrap();
+
+        // Should remain the same as there's no next commit
+        assert_eq!(original_oid, repo_info.oid);
+        assert_eq!(result.1, "Initial commit");
+    }
+
+    #[test]
+    fn test_construct_file_url_ssh() {
+        let repo = setup_empty_repo();
+        let head_commit = repo.head().unwrap().target().unwrap();
+
+        let repo_info = RepositoryInfo::_from_parts(repo, head_commit);
+
+        let ssh_url = "git@github.com:owner/repo.git";
+        let result = repo_info
+            .construct_file_url(ssh_url, "src/main.rs", 42)
+            .unwrap();
+        let expected = format!(
+            "https://github.com/owner/repo/blob/{}/src/main.rs#L42",
+            head_commit
         );
+        assert_eq!(result, expected);
+    }
 
-        drop(tree);
-        (repo, "test.txt".to_string())
+    #[test]
+    fn test_construct_file_url_https() {
+        let repo = setup_empty_repo();
+        let head_commit = repo.head().unwrap().target().unwrap();
+
+        let repo_info = RepositoryInfo::_from_parts(repo, head_commit);
+
+        let https_url = "https://github.com/owner/repo.git";
+        let result = repo_info
+            .construct_file_url(https_url, "README.md", 1)
+            .unwrap();
+        let expected = format!(
+            "https://github.com/owner/repo/blob/{}/README.md#L1",
+            head_commit
+        );
+        assert_eq!(result, expected);
     }
 
-    fn setup_empty_repo() -> Repository {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos();
-        let test_dir = env::temp_dir().join(format!("gview_empty_repo_{}", timestamp));
-        let _ = fs::remove_dir_all(&test_dir);
-        fs::create_dir_all(&test_dir).unwrap();
+    #[test]
+    fn test_construct_file_url_enterprise() {
+        let repo = setup_empty_repo();
+        let head_commit = repo.head().unwrap().target().unwrap();
 
-        let repo = Repository::init(&test_dir).unwrap();
+        let repo_info = RepositoryInfo::_from_parts(repo, head_commit);
 
-        let signature = git2::Signature::new(
-            "Test User",
-            "test@example.com",
-            &git2::Time::new(1234567890, 0),
-        )
-        .unwrap();
-        let tree_id = {
-            let mut index = repo.index().unwrap();
-            index.write_tree().unwrap()
+        let enterprise_url = "git@github.enterprise.com:team/project.git";
+        let result = repo_info
+            .construct_file_url(enterprise_url, "lib/utils.rs", 100)
+            .unwrap();
+        let expected = format!(
+            "https://github.enterprise.com/team/project/blob/{}/lib/utils.rs#L100",
+            head_commit
+        );
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_construct_file_url_without_git_suffix() {
+        let repo = setup_empty_repo();
+        let head_commit = repo.head().unwrap().target().unwrap();
+
+        let repo_info = RepositoryInfo::_from_parts(repo, head_commit);
+
+        let url_without_git = "git@github.com:owner/repo";
+        let result = repo_info
+            .construct_file_url(url_without_git, "test.py", 5)
+            .unwrap();
+        let expected = format!(
+            "https://github.com/owner/repo/blob/{}/test.py#L5",
+            head_commit
+        );
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_construct_file_url_on_other_forges() {
+        let repo = setup_empty_repo();
+        let head = repo.head().unwrap().target().unwrap();
+        let mut repo_info = RepositoryInfo::_from_parts(repo, head);
+        let url = |repo_info: &RepositoryInfo, origin: &str| {
+            repo_info
+                .construct_file_url(origin, "src/lib.rs", 10)
+                .unwrap()
+                .replace(&head.to_string(), "SHA")
         };
-        let tree = repo.find_tree(tree_id).unwrap();
 
-        let _ = repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            "Initial commit",
-            &tree,
-            &[],
+        assert_eq!(
+            url(&repo_info, "git@gitlab.com:group/project.git"),
+            "https://gitlab.com/group/project/-/blob/SHA/src/lib.rs#L10"
+        );
+        assert_eq!(
+            url(&repo_info, "https://bitbucket.org/team/repo.git"),
+            "https://bitbucket.org/team/repo/src/SHA/src/lib.rs#lines-10"
+        );
+        assert_eq!(
+            url(&repo_info, "https://codeberg.org/owner/repo.git"),
+            "https://codeberg.org/owner/repo/src/commit/SHA/src/lib.rs#L10"
+        );
+        assert_eq!(
+            url(&repo_info, "git@git.sr.ht:~user/repo"),
+            "https://git.sr.ht/~user/repo/tree/SHA/item/src/lib.rs#L10"
         );
 
-        drop(tree);
-        repo
+        // a self-hosted forge can name its layout instead of spelling out a template
+        repo_info.set_url_templates(HashMap::from([(
+            "gitea.corp".to_owned(),
+            "gitea".to_owned(),
+        )]));
+        assert_eq!(
+            url(&repo_info, "git@gitea.corp:team/service.git"),
+            "https://gitea.corp/team/service/src/commit/SHA/src/lib.rs#L10"
+        );
+        assert!(repo_info
+            .construct_diff_url("git@gitlab.com:group/project.git", "src/lib.rs")
+            .is_err());
+        assert!(forge_layout("github").is_some());
+        assert!(forge_layout("svn").is_none());
     }
 
     #[test]
-    fn test_commit_row_new() {
-        let oid = Oid::from_str("0123456789abcdef0123456789abcdef01234567").unwrap();
-        let row = CommitRow::new(
-            "test_author".to_string(),
-            oid,
-            42,
-            "println!(\"Hello, world!\");".to_string(),
+    fn test_construct_url_from_host_template() {
+        let repo = setup_empty_repo();
+        let head_commit = repo.head().unwrap().target().unwrap();
+
+        let mut repo_info = RepositoryInfo::_from_parts(repo, head_commit);
+        repo_info.set_url_templates(HashMap::from([(
+            "git.corp".to_owned(),
+            "https://git.corp/{repo}/-/blob/{sha}/{path}#L{line}".to_owned(),
+        )]));
+
+        let result = repo_info
+            .construct_file_url("git@git.corp:team/service.git", "src/lib.rs", 7)
+            .unwrap();
+        assert_eq!(
+            result,
+            format!(
+                "https://git.corp/team/service/-/blob/{}/src/lib.rs#L7",
+                head_commit
+            )
+        );
+        // other hosts keep the GitHub layout
+        let result = repo_info
+            .construct_file_url("https://github.com/owner/repo.git", "a.rs", 1)
+            .unwrap();
+        assert_eq!(
+            result,
+            format!("https://github.com/owner/repo/blob/{}/a.rs#L1", head_commit)
         );
 
-        assert_eq!(row._author, "test_author");
-        assert_eq!(row.commit, oid);
-        assert_eq!(row.number, 42);
-        assert_eq!(row.line, "println!(\"Hello, world!\");");
+        assert_eq!(
+            url_from_template("https://{host}/x/{nope}", &[("host", "h")])
+                .unwrap_err()
+                .to_string(),
+            "unknown placeholder {nope} in https://{host}/x/{nope}"
+        );
+        assert!(url_from_template("https://{host", &[("host", "h")]).is_err());
     }
 
     #[test]
-    fn test_repository_info_current_commit() {
+    fn test_select_remote() {
         let repo = setup_empty_repo();
+        repo.remote("origin", "git@github.com:me/repo.git").unwrap();
+        repo.remote("upstream", "https://github.com/owner/repo.git")
+            .unwrap();
         let head_commit = repo.head().unwrap().target().unwrap();
 
-        let mut repo_info = RepositoryInfo {
-            repository: repo,
-            oid: head_commit,
-        };
+        let mut repo_info = RepositoryInfo::_from_parts(repo, head_commit);
+        assert_eq!(
+            repo_info.remotes().unwrap(),
+            vec![
+                ("origin".to_owned(), "git@github.com:me/repo.git".to_owned()),
+                (
+                    "upstream".to_owned(),
+                    "https://github.com/owner/repo.git".to_owned()
+                ),
+            ]
+        );
+        assert_eq!(repo_info.remote_name(), "origin");
 
-        let result = repo_info.current_commit().unwrap();
-        assert_eq!(result.0.len(), 40); // SHA length
-        assert_eq!(result.1, "Initial commit");
+        repo_info.set_remote("upstream");
+        assert_eq!(
+            repo_info.get_origin_url().unwrap(),
+            "https://github.com/owner/repo.git"
+        );
+        // a remote this repository lacks falls back to origin
+        repo_info.set_remote("fork");
+        assert_eq!(repo_info.remote_name(), "origin");
+        assert!(!repo_info.remote_is_ambiguous());
     }
 
     #[test]
-    fn test_set_commit_by_id_full_hash() {
-        let (repo, _) = setup_test_repo_with_file();
+    fn test_remote_without_origin() {
+        let repo = setup_empty_repo();
+        repo.remote("upstream", "https://github.com/owner/repo.git")
+            .unwrap();
         let head_commit = repo.head().unwrap().target().unwrap();
-        let head_commit_str = head_commit.to_string();
+        let mut repo_info = RepositoryInfo::_from_parts(repo, head_commit);
+        // the only remote stands in for origin
+        assert_eq!(repo_info.remote_name(), "upstream");
+        assert!(!repo_info.remote_is_ambiguous());
+
+        repo_info
+            .repository
+            .remote("fork", "https://github.com/me/repo.git")
+            .unwrap();
+        assert!(repo_info.remote_is_ambiguous());
+        repo_info.set_remote("fork");
+        assert!(!repo_info.remote_is_ambiguous());
+        assert_eq!(
+            repo_info.get_origin_url().unwrap(),
+            "https://github.com/me/repo.git"
+        );
+    }
+
+    #[test]
+    fn test_construct_file_url_invalid_format() {
+        let repo = setup_empty_repo();
+        let head_commit = repo.head().unwrap().target().unwrap();
+
+        let repo_info = RepositoryInfo::_from_parts(repo, head_commit);
+
+        let invalid_url = "invalid-url-format";
+        let result = repo_info.construct_file_url(invalid_url, "file.txt", 1);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_windows_paths_and_browser_command() {
+        assert_eq!(
+            with_forward_slashes("src\\components\\filer.rs", '\\'),
+            "src/components/filer.rs"
+        );
+        // a backslash is part of the name where it is not a separator
+        assert_eq!(with_forward_slashes("odd\\name", '/'), "odd\\name");
+        assert_eq!(git_path(Path::new("src/main.rs")), "src/main.rs");
 
-        let mut repo_info = RepositoryInfo {
-            repository: repo,
-            oid: head_commit,
+        // the URL is passed as a single argument, never through a shell
+        let url = "https://github.com/owner/repo/blob/abc/a&b.rs#L1";
+        let local = Session {
+            display: true,
+            ..Session::default()
         };
+        let command = browser_command(url, local).unwrap();
+        assert_eq!(command.get_args().last(), Some(std::ffi::OsStr::new(url)));
+    }
 
-        // Test setting by full commit ID
-        let result = repo_info.set_commit_by_id(&head_commit_str);
-        assert!(result.is_ok());
-        assert_eq!(repo_info.oid, head_commit);
+    #[test]
+    fn test_browser_command_by_session() {
+        let url = "https://github.com/owner/repo";
+        let program =
+            |session| browser_command(url, session).map(|command| command.get_program().to_owned());
+        let ssh = Session {
+            ssh: true,
+            display: true,
+            ..Session::default()
+        };
+        assert_eq!(program(ssh), None);
+        let wsl = Session {
+            wsl: true,
+            ..Session::default()
+        };
+        assert_eq!(program(wsl), Some("wslview".into()));
+        if cfg!(not(any(target_os = "windows", target_os = "macos"))) {
+            // xdg-open needs a display
+            assert_eq!(program(Session::default()), None);
+        }
     }
 
     #[test]
-    fn test_set_commit_by_id_short_hash() {
-        let (repo, _) = setup_test_repo_with_file();
-        let head_commit = repo.head().unwrap().target().unwrap();
-        let head_commit_str = head_commit.to_string();
-        let short_commit = &head_commit_str[..7]; // Use 7 characters
+    fn test_set_base_ref_and_clear() {
+        let (repo, first, second) = setup_test_repo_with_history();
+        let mut repo_info = RepositoryInfo::_from_parts(repo, second);
+        assert_eq!(repo_info.base_ref(), None);
+
+        repo_info.set_base_ref("HEAD~1").unwrap();
+        assert_eq!(repo_info.base_ref(), Some(first.to_string()));
+
+        repo_info.clear_base_ref();
+        assert_eq!(repo_info.base_ref(), None);
+    }
+
+    #[test]
+    fn test_construct_diff_url() {
+        let (repo, first, second) = setup_test_repo_with_history();
+        let mut repo_info = RepositoryInfo::_from_parts(repo, second);
+        let anchor = "b1a35a68f14e696205874893c07fd24fdb88882b47c23cc0e0c80a30c7d53759";
+
+        assert_eq!(
+            repo_info
+                .construct_diff_url("git@github.com:owner/repo.git", "src/lib.rs")
+                .unwrap(),
+            format!(
+                "https://github.com/owner/repo/commit/{}#diff-{}",
+                second, anchor
+            )
+        );
+        repo_info.set_base_ref("HEAD~1").unwrap();
+        assert_eq!(
+            repo_info
+                .construct_diff_url("https://github.com/owner/repo.git", "src/lib.rs")
+                .unwrap(),
+            format!(
+                "https://github.com/owner/repo/compare/{}...{}#diff-{}",
+                first, second, anchor
+            )
+        );
+
+        repo_info.set_url_templates(HashMap::from([(
+            "git.corp".to_owned(),
+            "https://git.corp/{repo}/-/blob/{sha}/{path}#L{line}".to_owned(),
+        )]));
+        assert!(repo_info
+            .construct_diff_url("git@git.corp:team/service.git", "src/lib.rs")
+            .is_err());
+    }
+
+    #[test]
+    fn test_set_base_ref_invalid() {
+        let (repo, _, second) = setup_test_repo_with_history();
+        let mut repo_info = RepositoryInfo::_from_parts(repo, second);
+        assert!(repo_info.set_base_ref("no-such-branch").is_err());
+        assert_eq!(repo_info.base_ref(), None);
+    }
+
+    #[test]
+    fn test_changed_files_against_parent_and_base() {
+        let (repo, first, second) = setup_test_repo_with_history();
+        let mut repo_info = RepositoryInfo::_from_parts(repo, second);
+
+        let mut files = repo_info.changed_files().unwrap();
+        files.sort();
+        assert_eq!(files, vec!["added.txt", "test.txt"]);
+        let statuses = repo_info.change_statuses().unwrap();
+        assert_eq!(statuses["added.txt"], 'A');
+        assert_eq!(statuses["test.txt"], 'M');
 
-        let mut repo_info = RepositoryInfo {
-            repository: repo,
-            oid: head_commit,
+        // Against itself nothing changed
+        repo_info.set_base_ref(&second.to_string()).unwrap();
+        assert!(repo_info.changed_files().unwrap().is_empty());
+
+        // A base that moved on is reviewed from the merge-base, so its own commit does not
+        // show up as a deletion
+        let side = {
+            let repo = &repo_info.repository;
+            let blob = repo.blob(b"side\n").unwrap();
+            let mut builder = repo
+                .treebuilder(Some(&repo.find_commit(first).unwrap().tree().unwrap()))
+                .unwrap();
+            builder.insert("side.txt", blob, 0o100644).unwrap();
+            let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+            let signature = git2::Signature::new(
+                "Test User",
+                "test@example.com",
+                &git2::Time::new(1234568090, 0),
+            )
+            .unwrap();
+            repo.commit(
+                None,
+                &signature,
+                &signature,
+                "Add side file",
+                &tree,
+                &[&repo.find_commit(first).unwrap()],
+            )
+            .unwrap()
         };
+        repo_info.set_base_ref(&side.to_string()).unwrap();
+        let mut files = repo_info.changed_files().unwrap();
+        files.sort();
+        assert_eq!(files, vec!["added.txt", "test.txt"]);
 
-        // Test setting by short commit ID
-        let result = repo_info.set_commit_by_id(short_commit);
-        assert!(result.is_ok());
-        assert_eq!(repo_info.oid, head_commit);
+        // The root commit is diffed against the empty tree
+        let repo_info = RepositoryInfo::_from_parts(repo_info.repository, first);
+        assert_eq!(repo_info.changed_files().unwrap(), vec!["test.txt"]);
+        assert_eq!(repo_info.change_statuses().unwrap()["test.txt"], 'A');
+    }
+
+    #[test]
+    fn test_get_diff_rows() {
+        let (repo, _, second) = setup_test_repo_with_history();
+        let repo_info = RepositoryInfo::_from_parts(repo, second);
+
+        let rows = repo_info.get_diff("test.txt").unwrap();
+        assert_eq!(rows[0].origin, 'H');
+        assert!(rows[0].line.starts_with("@@"));
+        let changes: Vec<(char, &str)> = rows[1..]
+            .iter()
+            .map(|row| (row.origin, row.line.as_str()))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (' ', "line 1"),
+                ('-', "line 2"),
+                ('+', "line two"),
+                (' ', "line 3")
+            ]
+        );
+        assert_eq!(rows[2].old_number, Some(2));
+        assert_eq!(rows[2].new_number, None);
+        assert_eq!(rows[3].new_number, Some(2));
+    }
+
+    #[test]
+    fn test_get_diff_root_commit() {
+        let (repo, first, _) = setup_test_repo_with_history();
+        let repo_info = RepositoryInfo::_from_parts(repo, first);
+
+        // without a parent, every line is an addition
+        let rows = repo_info.get_diff("test.txt").unwrap();
+        assert_eq!(rows[0].origin, 'H');
+        assert!(rows[1..].iter().all(|row| row.origin == '+'));
+        assert_eq!(rows.len(), 4);
+    }
+
+    #[test]
+    fn test_get_split_diff() {
+        let (repo, first, second) = setup_test_repo_with_history();
+        let repo_info = RepositoryInfo::_from_parts(repo, second);
+        let split = repo_info
+            .get_split_diff("test.txt", Source::Commit)
+            .unwrap();
+        assert_eq!(split.old, vec!["line 1", "line 2", "line 3"]);
+        assert_eq!(split.new, vec!["line 1", "line two", "line 3"]);
+        assert_eq!(
+            split.hunks,
+            vec![DiffHunk {
+                old_start: 2,
+                old_lines: 1,
+                new_start: 2,
+                new_lines: 1
+            }]
+        );
+
+        // an added file has nothing on the old side
+        let split = repo_info
+            .get_split_diff("added.txt", Source::Commit)
+            .unwrap();
+        assert!(split.old.is_empty());
+        assert_eq!(split.new, vec!["new file"]);
+        assert_eq!(split.hunks[0].old_lines, 0);
+
+        let repo_info = RepositoryInfo::_from_parts(repo_info.repository, first);
+        let split = repo_info
+            .get_split_diff("test.txt", Source::Commit)
+            .unwrap();
+        assert!(split.old.is_empty());
+        assert_eq!(split.new.len(), 3);
+    }
+
+    #[test]
+    fn test_get_diff_not_found_and_unchanged() {
+        let (repo, _, second) = setup_test_repo_with_history();
+        let mut repo_info = RepositoryInfo::_from_parts(repo, second);
+        assert!(repo_info.get_diff("not found").unwrap().is_empty());
+
+        repo_info.set_base_ref("HEAD").unwrap();
+        assert!(repo_info.get_diff("test.txt").unwrap().is_empty());
     }
 
     #[test]
-    fn test_set_commit_by_id_invalid() {
-        let (repo, _) = setup_test_repo_with_file();
-        let head_commit = repo.head().unwrap().target().unwrap();
+    fn test_compare_refs() {
+        let (repo, first, second) = setup_test_repo_with_history();
+        // The current commit does not matter once two refs are compared
+        let mut repo_info = RepositoryInfo::_from_parts(repo, first);
+        repo_info
+            .set_compare_refs(&first.to_string(), &second.to_string())
+            .unwrap();
+        assert_eq!(repo_info.compare_target(), Some(second.to_string()));
 
-        let mut repo_info = RepositoryInfo {
-            repository: repo,
-            oid: head_commit,
-        };
+        let mut files = repo_info.changed_files().unwrap();
+        files.sort();
+        assert_eq!(files, vec!["added.txt", "test.txt"]);
+        assert!(repo_info
+            .get_diff("test.txt")
+            .unwrap()
+            .iter()
+            .any(|row| row.origin == '+' && row.line == "line two"));
 
-        // Test setting by invalid commit ID
-        let result = repo_info.set_commit_by_id("invalid123");
-        assert!(result.is_err());
-        assert_eq!(repo_info.oid, head_commit); // Should remain unchanged
+        assert!(repo_info.set_compare_refs("HEAD", "no-such-ref").is_err());
+
+        // Setting a plain base ref leaves compare mode
+        repo_info.set_base_ref(&first.to_string()).unwrap();
+        assert_eq!(repo_info.compare_target(), None);
+        assert!(repo_info.changed_files().unwrap().is_empty());
     }
 
     #[test]
-    fn test_repository_info_set_parent_commit() {
-        let (repo, _) = setup_test_repo_with_file();
-        let _head_commit = repo.head().unwrap().target().unwrap();
-
-        // Create a second commit
+    fn test_renames_are_detected() {
+        let (repo, _, second) = setup_test_repo_with_history();
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        fs::remove_file(workdir.join("test.txt")).unwrap();
+        fs::write(
+            workdir.join("moved.txt"),
+            "line 1\nline two\nline 3\nline 4\n",
+        )
+        .unwrap();
         let signature = git2::Signature::new(
             "Test User",
             "test@example.com",
-            &git2::Time::new(1234567890, 0),
+            &git2::Time::new(1234568090, 0),
         )
         .unwrap();
-        let tree = repo
-            .head()
-            .unwrap()
-            .peel_to_commit()
-            .unwrap()
-            .tree()
-            .unwrap();
-        let parent_commit = repo.head().unwrap().peel_to_commit().unwrap();
-        let second_commit_oid = repo
-            .commit(
+        let third = {
+            let mut index = repo.index().unwrap();
+            index.remove_path(Path::new("test.txt")).unwrap();
+            index.add_path(Path::new("moved.txt")).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parent = repo.find_commit(second).unwrap();
+            repo.commit(
                 Some("HEAD"),
                 &signature,
                 &signature,
-                "Second commit",
+                "Move test.txt",
                 &tree,
-                &[&parent_commit],
+                &[&parent],
             )
-            .unwrap();
-
-        drop(tree);
-        drop(parent_commit);
-
-        let mut repo_info = RepositoryInfo {
-            repository: repo,
-            oid: second_commit_oid,
+            .unwrap()
         };
+        let repo_info = RepositoryInfo::_from_parts(repo, third);
 
-        let original_oid = repo_info.oid;
-        repo_info.set_parent_commit();
+        assert_eq!(repo_info.changed_files().unwrap(), vec!["moved.txt"]);
+        let rename = repo_info.renamed_files().unwrap()["moved.txt"].clone();
+        assert_eq!(rename.from, "test.txt");
+        assert!(rename.similarity >= 50 && rename.similarity < 100);
 
-        // Should now be pointing to the parent commit
-        assert_ne!(original_oid, repo_info.oid);
+        let stats = repo_info.changed_file_stats().unwrap();
+        assert_eq!(stats[0].additions, 1);
+        assert_eq!(stats[0].rename, Some(rename.clone()));
+
+        let rows = repo_info.get_diff("moved.txt").unwrap();
+        assert_eq!(
+            rows[0].line,
+            format!("renamed from test.txt ({}% similar)", rename.similarity)
+        );
+        assert!(rows
+            .iter()
+            .any(|row| row.origin == '+' && row.line == "line 4"));
     }
 
     #[test]
-    fn test_repository_info_set_parent_commit_no_parent() {
-        let repo = setup_empty_repo();
-        let head_commit = repo.head().unwrap().target().unwrap();
-
-        let mut repo_info = RepositoryInfo {
-            repository: repo,
-            oid: head_commit,
+    fn test_find_definition_follows_current_commit() {
+        let (repo, _, second) = setup_test_repo_with_history();
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        fs::create_dir_all(workdir.join("src")).unwrap();
+        fs::write(
+            workdir.join("src/defs.rs"),
+            "// helpers\npub fn helper() {}\n",
+        )
+        .unwrap();
+        let signature = git2::Signature::new(
+            "Test User",
+            "test@example.com",
+            &git2::Time::new(1234568090, 0),
+        )
+        .unwrap();
+        let third = {
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new("src/defs.rs")).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parent = repo.find_commit(second).unwrap();
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "Add helper",
+                &tree,
+                &[&parent],
+            )
+            .unwrap()
         };
+        let mut repo_info = RepositoryInfo::_from_parts(repo, third);
 
-        let original_oid = repo_info.oid;
-        repo_info.set_parent_commit();
+        assert_eq!(
+            repo_info.find_definition("helper").unwrap(),
+            vec![("src/defs.rs".to_owned(), 2)]
+        );
+        assert!(repo_info.find_definition("missing").unwrap().is_empty());
 
-        // Should remain the same as it has no parent
-        assert_eq!(original_oid, repo_info.oid);
+        // The index is rebuilt for the parent commit, where the helper does not exist yet
+        repo_info.set_parent_commit();
+        assert!(repo_info.find_definition("helper").unwrap().is_empty());
     }
 
     #[test]
-    fn test_get_content_not_found_special_case() {
-        let repo = setup_empty_repo();
-        let head_commit = repo.head().unwrap().target().unwrap();
+    fn test_worktree_content_and_diff() {
+        let (repo, _, second) = setup_test_repo_with_history();
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        fs::write(
+            workdir.join("test.txt"),
+            "line 1\nline two\nline 3\nuncommitted\n",
+        )
+        .unwrap();
+        let repo_info = RepositoryInfo::_from_parts(repo, second);
 
-        let mut repo_info = RepositoryInfo {
-            repository: repo,
-            oid: head_commit,
-        };
+        let (rows, missing_newline) = repo_info.get_worktree_content("test.txt").unwrap();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[3].line, "uncommitted");
+        assert_eq!(rows[3].commit, Oid::zero());
+        assert!(!missing_newline);
 
-        let result = repo_info.get_content("not found".to_string()).unwrap();
-        assert!(result.is_empty());
+        let changes: Vec<(char, String)> = repo_info
+            .get_worktree_diff("test.txt")
+            .unwrap()
+            .into_iter()
+            .filter(|row| row.origin == '+' || row.origin == '-')
+            .map(|row| (row.origin, row.line))
+            .collect();
+        assert_eq!(changes, vec![('+', "uncommitted".to_owned())]);
+        assert!(repo_info.get_worktree_diff("added.txt").unwrap().is_empty());
+
+        assert!(repo_info.get_worktree_content("missing.txt").is_err());
+
+        // a line that is not UTF-8 keeps the lines after it, and CRLF endings are dropped
+        fs::write(
+            workdir.join("test.txt"),
+            b"line 1\r\nbad \xff byte\r\nline 3\r\nlast",
+        )
+        .unwrap();
+        let (rows, missing_newline) = repo_info.get_worktree_content("test.txt").unwrap();
+        let lines: Vec<&str> = rows.iter().map(|row| row.line.as_str()).collect();
+        assert_eq!(lines, vec!["line 1", "bad \u{fffd} byte", "line 3", "last"]);
+        assert!(missing_newline);
     }
 
     #[test]
-    fn test_get_content_with_file() {
-        let (repo, filename) = setup_test_repo_with_file();
-        let head_commit = repo.head().unwrap().target().unwrap();
+    fn test_index_content_and_diff() {
+        let (repo, first, second) = setup_test_repo_with_history();
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        fs::write(workdir.join("test.txt"), "line 1\nstaged\nline 3\n").unwrap();
+        {
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new("test.txt")).unwrap();
+            index.write().unwrap();
+        }
+        // Unstaged edits are not part of the index version
+        fs::write(
+            workdir.join("test.txt"),
+            "line 1\nstaged\nline 3\nunstaged\n",
+        )
+        .unwrap();
+        let mut repo_info = RepositoryInfo::_from_parts(repo, second);
 
-        let mut repo_info = RepositoryInfo {
-            repository: repo,
-            oid: head_commit,
-        };
+        let (rows, missing_newline) = repo_info.get_index_content("test.txt").unwrap();
+        let lines: Vec<&str> = rows.iter().map(|row| row.line.as_str()).collect();
+        assert_eq!(lines, vec!["line 1", "staged", "line 3"]);
+        assert!(!missing_newline);
+        assert!(repo_info.get_index_content("missing.txt").is_err());
 
-        let result = repo_info.get_content(filename).unwrap();
-        assert_eq!(result.len(), 3); // 3 lines
-        assert_eq!(result[0].line, "line 1");
-        assert_eq!(result[1].line, "line 2");
-        assert_eq!(result[2].line, "line 3");
-        assert_eq!(result[0].number, 1);
-        assert_eq!(result[1].number, 2);
-        assert_eq!(result[2].number, 3);
+        let changes: Vec<(char, String)> = repo_info
+            .get_index_diff("test.txt")
+            .unwrap()
+            .into_iter()
+            .filter(|row| row.origin == '+' || row.origin == '-')
+            .map(|row| (row.origin, row.line))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![('-', "line two".to_owned()), ('+', "staged".to_owned())]
+        );
+
+        // Against an older commit the index also contains later committed files
+        repo_info.set_commit_by_id(&first.to_string()).unwrap();
+        assert!(!repo_info.get_index_diff("added.txt").unwrap().is_empty());
+
+        // a staged line that is not UTF-8 keeps the lines after it
+        fs::write(workdir.join("test.txt"), b"line 1\nbad \xff byte\nline 3").unwrap();
+        {
+            let mut index = repo_info.repository.index().unwrap();
+            index.add_path(Path::new("test.txt")).unwrap();
+            index.write().unwrap();
+        }
+        let (rows, missing_newline) = repo_info.get_index_content("test.txt").unwrap();
+        let lines: Vec<&str> = rows.iter().map(|row| row.line.as_str()).collect();
+        assert_eq!(lines, vec!["line 1", "bad \u{fffd} byte", "line 3"]);
+        assert!(missing_newline);
     }
 
     #[test]
-    fn test_recursive_walk_empty_repo() {
-        let repo = setup_empty_repo();
-        let head_commit = repo.head().unwrap().target().unwrap();
+    fn test_containment_and_merge_base() {
+        let (repo, first, second) = setup_test_repo_with_history();
+        // a side branch forking at the first commit
+        let signature = git2::Signature::new(
+            "Test User",
+            "test@example.com",
+            &git2::Time::new(1234568090, 0),
+        )
+        .unwrap();
+        let parent = repo.find_commit(first).unwrap();
+        let side = repo
+            .commit(
+                None,
+                &signature,
+                &signature,
+                "Side change",
+                &parent.tree().unwrap(),
+                &[&parent],
+            )
+            .unwrap();
+        repo.branch("side", &repo.find_commit(side).unwrap(), false)
+            .unwrap();
+        drop(parent);
+        let mut repo_info = RepositoryInfo::_from_parts(repo, second);
 
-        let mut repo_info = RepositoryInfo {
-            repository: repo,
-            oid: head_commit,
-        };
+        assert!(repo_info.is_contained_in("HEAD").unwrap());
+        assert!(!repo_info.is_contained_in("side").unwrap());
+        assert!(repo_info.is_contained_in("no-such-branch").is_err());
+        assert_eq!(
+            repo_info.merge_base_with("side").unwrap(),
+            (first.to_string(), "Add test file".to_owned())
+        );
 
-        let result = repo_info.recursive_walk().unwrap();
-        assert!(result.is_empty());
+        repo_info.set_commit_by_id(&first.to_string()).unwrap();
+        assert!(repo_info.is_contained_in("side").unwrap());
     }
 
     #[test]
-    fn test_recursive_walk_with_file() {
-        let (repo, _) = setup_test_repo_with_file();
-        let head_commit = repo.head().unwrap().target().unwrap();
+    fn test_commit_stats() {
+        let (repo, first, second) = setup_test_repo_with_history();
+        let repo_info = RepositoryInfo::_from_parts(repo, second);
+        let stats = repo_info.commit_stats(&second.to_string()).unwrap();
+        assert_eq!(
+            stats,
+            CommitStats {
+                files: 2,
+                insertions: 2,
+                deletions: 1
+            }
+        );
+        assert_eq!(stats.to_string(), "2 files changed, +2 −1");
+        // the root commit is compared against the empty tree
+        assert_eq!(
+            repo_info
+                .commit_stats(&first.to_string())
+                .unwrap()
+                .to_string(),
+            "1 file changed, +3 −0"
+        );
+        assert!(repo_info.commit_stats("not a commit").is_err());
+    }
+
+    #[test]
+    fn test_language_stats() {
+        let (repo, _, second) = setup_test_repo_with_history();
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        fs::create_dir_all(workdir.join("src")).unwrap();
+        fs::write(workdir.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(workdir.join("logo.png"), [0x89, b'P', 0, 1]).unwrap();
+        fs::write(workdir.join("LICENSE"), "MIT\n").unwrap();
+        let signature = git2::Signature::new(
+            "Test User",
+            "test@example.com",
+            &git2::Time::new(1234568090, 0),
+        )
+        .unwrap();
+        let third = {
+            let mut index = repo.index().unwrap();
+            for path in ["src/main.rs", "logo.png", "LICENSE"] {
+                index.add_path(Path::new(path)).unwrap();
+            }
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parent = repo.find_commit(second).unwrap();
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "Add more files",
+                &tree,
+                &[&parent],
+            )
+            .unwrap()
+        };
+        let mut repo_info = RepositoryInfo::_from_parts(repo, third);
+        let dir = env::temp_dir().join(format!(
+            "gview_repository_languages_{}_{}",
+            third,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        repo_info.cache = Cache::new(Some(dir.clone()), crate::cache::DEFAULT_LIMIT);
 
-        let mut repo_info = RepositoryInfo {
-            repository: repo,
-            oid: head_commit,
+        let tree = repo_info.viewed_tree_id().unwrap();
+        assert_eq!(repo_info.cached_language_stats(tree), None);
+        let mut tracker = crate::progress::ProgressTracker::new();
+        let stats = repo_info
+            .spawn_language_stats(tree, tracker.reporter())
+            .join()
+            .unwrap()
+            .unwrap();
+        let stat = |language: &str, files, lines, bytes| LanguageStat {
+            language: language.to_owned(),
+            files,
+            lines,
+            bytes,
         };
+        // text of no known language goes by its extension, binary files count no lines
+        assert_eq!(
+            stats,
+            vec![
+                stat(".txt", 2, 4, 32),
+                stat("Rust", 1, 1, 13),
+                stat("Binary", 1, 0, 4),
+                stat("Other", 1, 1, 4),
+            ]
+        );
+        tracker.tick();
+        assert_eq!(tracker.describe(), None);
 
-        let result = repo_info.recursive_walk().unwrap();
-        assert_eq!(result.len(), 1);
-        assert_eq!(result[0], "test.txt");
+        repo_info.cache_language_stats(tree, &stats);
+        assert_eq!(repo_info.cached_language_stats(tree), Some(stats));
+        let _ = fs::remove_dir_all(&dir);
     }
 
     #[test]
-    fn test_find_next_commit_no_next() {
-        let repo = setup_empty_repo();
-        let head_commit = repo.head().unwrap().target().unwrap();
+    fn test_parse_origin() {
+        assert_eq!(
+            Origin::parse("Fix typo\n\n(cherry picked from commit 0123456789abcdef)\n"),
+            Some(Origin::CherryPick("0123456789abcdef".to_owned()))
+        );
+        assert_eq!(
+            Origin::parse("Revert \"Fix typo\"\n\nThis reverts commit abcdef0123.\n"),
+            Some(Origin::Revert(Some("abcdef0123".to_owned())))
+        );
+        assert_eq!(
+            Origin::parse("Revert \"Fix typo\""),
+            Some(Origin::Revert(None))
+        );
+        assert_eq!(Origin::parse("Revert the revert of the typo fix"), None);
+        assert_eq!(Origin::parse("Fix typo"), None);
+        for origin in [
+            Origin::CherryPick("abc1234".to_owned()),
+            Origin::Revert(Some("abc1234".to_owned())),
+            Origin::Revert(None),
+        ] {
+            assert_eq!(Origin::decode(&Origin::encode(Some(&origin))), Some(origin));
+        }
+        assert_eq!(Origin::decode(&Origin::encode(None)), None);
+    }
 
-        let mut repo_info = RepositoryInfo {
-            repository: repo,
-            oid: head_commit,
+    #[test]
+    fn test_commits_with_trailer() {
+        let (repo, _, second) = setup_test_repo_with_history();
+        let signature = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let reviewed = {
+            let tree = repo.find_commit(second).unwrap().tree().unwrap();
+            let parent = repo.find_commit(second).unwrap();
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "Tidy up\n\nReviewed-by: Ann <ann@example.com>\n",
+                &tree,
+                &[&parent],
+            )
+            .unwrap()
+        };
+        let repo_info = RepositoryInfo::_from_parts(repo, reviewed);
+        let mut tracker = crate::progress::ProgressTracker::new();
+        let filter = |query: &str, cancel: CancelToken| {
+            repo_info
+                .spawn_trailer_filter(query, tracker.reporter(), cancel)
+                .join()
+                .unwrap()
         };
+        assert_eq!(
+            filter("reviewed-by: ann", CancelToken::default()).unwrap(),
+            HashSet::from([reviewed.to_string()])
+        );
+        assert_eq!(
+            filter("ann@example", CancelToken::default()).unwrap().len(),
+            1
+        );
+        assert!(filter("Bob", CancelToken::default()).unwrap().is_empty());
 
-        let result = repo_info.find_next_commit().unwrap();
-        assert!(result.is_none());
+        let cancel = CancelToken::default();
+        cancel.cancel();
+        assert!(filter("ann", cancel).is_err());
+        tracker.tick();
+        assert_eq!(tracker.describe(), None);
     }
 
     #[test]
-    fn test_set_next_commit_no_next() {
-        let repo = setup_empty_repo();
-        let head_commit = repo.head().unwrap().target().unwrap();
-
-        let mut repo_info = RepositoryInfo {
-            repository: repo,
-            oid: head_commit,
+    fn test_parents_and_children() {
+        let (repo, first, second) = setup_test_repo_with_history();
+        let signature = git2::Signature::now("Test User", "test@example.com").unwrap();
+        // a merge of a side branch from `first` into `second`
+        let side = {
+            let tree = repo.find_commit(first).unwrap().tree().unwrap();
+            let parent = repo.find_commit(first).unwrap();
+            repo.commit(None, &signature, &signature, "Side", &tree, &[&parent])
+                .unwrap()
         };
+        let merge = {
+            let tree = repo.find_commit(second).unwrap().tree().unwrap();
+            let parents = [
+                &repo.find_commit(second).unwrap(),
+                &repo.find_commit(side).unwrap(),
+            ];
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "Merge side",
+                &tree,
+                &parents,
+            )
+            .unwrap()
+        };
+        let mut repo_info = RepositoryInfo::_from_parts(repo, merge);
+        let ids = |entries: Vec<(String, String)>| -> Vec<String> {
+            entries.into_iter().map(|(id, _)| id).collect()
+        };
+        assert_eq!(
+            repo_info.parents().unwrap(),
+            vec![
+                (second.to_string(), "Change line 2".to_owned()),
+                (side.to_string(), "Side".to_owned())
+            ]
+        );
+        assert!(repo_info.children().unwrap().unwrap().is_empty());
 
-        let original_oid = repo_info.oid;
-        let result = repo_info.set_next_commit().unwrap();
-
-        // Should remain the same as there's no next commit
-        assert_eq!(original_oid, repo_info.oid);
-        assert_eq!(result.1, "Initial commit");
+        repo_info.set_commit_by_id(&first.to_string()).unwrap();
+        assert!(repo_info.parents().unwrap().is_empty());
+        let mut children = ids(repo_info.children().unwrap().unwrap());
+        children.sort();
+        let mut expected = vec![second.to_string(), side.to_string()];
+        expected.sort();
+        assert_eq!(children, expected);
     }
 
     #[test]
-    fn test_construct_github_url_ssh() {
-        let repo = setup_empty_repo();
-        let head_commit = repo.head().unwrap().target().unwrap();
+    fn test_children_in_background() {
+        let (repo, first, second) = setup_test_repo_with_history();
+        let mut repo_info = RepositoryInfo::_from_parts(repo, first);
+        repo_info.set_background(true);
+        // the history is left to a worker thread
+        assert!(repo_info.children().is_none());
 
-        let repo_info = RepositoryInfo {
-            repository: repo,
-            oid: head_commit,
-        };
+        let mut tracker = crate::progress::ProgressTracker::new();
+        let canceled = CancelToken::default();
+        canceled.cancel();
+        let job = repo_info
+            .spawn_children(tracker.reporter(), canceled)
+            .unwrap();
+        while !job.is_finished() {
+            std::thread::yield_now();
+        }
+        assert!(repo_info.finish_children(job).is_err());
+        assert!(repo_info.children().is_none());
 
-        let ssh_url = "git@github.com:owner/repo.git";
-        let result = repo_info
-            .construct_github_url(ssh_url, "src/main.rs", 42)
+        let job = repo_info
+            .spawn_children(tracker.reporter(), CancelToken::default())
             .unwrap();
-        let expected = format!(
-            "https://github.com/owner/repo/blob/{}/src/main.rs#L42",
-            head_commit
+        assert_eq!(job.head(), second);
+        while !job.is_finished() {
+            std::thread::yield_now();
+        }
+        repo_info.finish_children(job).unwrap();
+        tracker.tick();
+        assert_eq!(tracker.describe(), None);
+        assert_eq!(
+            repo_info.children().unwrap().unwrap(),
+            vec![(second.to_string(), "Change line 2".to_owned())]
         );
-        assert_eq!(result, expected);
     }
 
     #[test]
-    fn test_construct_github_url_https() {
-        let repo = setup_empty_repo();
-        let head_commit = repo.head().unwrap().target().unwrap();
-
-        let repo_info = RepositoryInfo {
-            repository: repo,
-            oid: head_commit,
+    fn test_commit_origins() {
+        let (repo, first, second) = setup_test_repo_with_history();
+        let signature = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let revert = {
+            let tree = repo.find_commit(first).unwrap().tree().unwrap();
+            let parent = repo.find_commit(second).unwrap();
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                &format!(
+                    "Revert \"Change line 2\"\n\nThis reverts commit {}.\n",
+                    second
+                ),
+                &tree,
+                &[&parent],
+            )
+            .unwrap()
         };
-
-        let https_url = "https://github.com/owner/repo.git";
-        let result = repo_info
-            .construct_github_url(https_url, "README.md", 1)
-            .unwrap();
-        let expected = format!(
-            "https://github.com/owner/repo/blob/{}/README.md#L1",
-            head_commit
+        let repo_info = RepositoryInfo::_from_parts(repo, revert);
+        let origins = repo_info.commit_origins().unwrap();
+        assert_eq!(origins.len(), 1);
+        assert_eq!(
+            origins[&revert.to_string()],
+            Origin::Revert(Some(second.to_string()))
+        );
+        // the subjects are listed as before
+        assert_eq!(
+            repo_info.get_commit_history().unwrap()[0],
+            (revert.to_string(), "Revert \"Change line 2\"".to_owned())
         );
-        assert_eq!(result, expected);
     }
 
     #[test]
-    fn test_construct_github_url_enterprise() {
-        let repo = setup_empty_repo();
-        let head_commit = repo.head().unwrap().target().unwrap();
-
-        let repo_info = RepositoryInfo {
-            repository: repo,
-            oid: head_commit,
+    fn test_history_page() {
+        let (repo, first, second) = setup_test_repo_with_history();
+        let repo_info = RepositoryInfo::_from_parts(repo, second);
+        let ids = |page: &HistoryPage| -> Vec<String> {
+            page.entries.iter().map(|entry| entry.id.clone()).collect()
         };
 
-        let enterprise_url = "git@github.enterprise.com:team/project.git";
-        let result = repo_info
-            .construct_github_url(enterprise_url, "lib/utils.rs", 100)
-            .unwrap();
-        let expected = format!(
-            "https://github.enterprise.com/team/project/blob/{}/lib/utils.rs#L100",
-            head_commit
+        let page = repo_info.history_page(0, 1).unwrap();
+        assert_eq!(ids(&page), vec![second.to_string()]);
+        assert_eq!(page.entries[0].author, "Test User");
+        assert_eq!(page.next, Some(1));
+        let page = repo_info.history_page(1, 1).unwrap();
+        assert_eq!(ids(&page), vec![first.to_string()]);
+        assert_eq!(page.next, None);
+
+        // the pages follow the whole history
+        let whole = repo_info.history_page(0, 10).unwrap();
+        assert_eq!(
+            whole
+                .entries
+                .into_iter()
+                .map(|entry| (entry.id, entry.subject))
+                .collect::<Vec<_>>(),
+            repo_info.get_commit_history().unwrap()
         );
-        assert_eq!(result, expected);
     }
 
     #[test]
-    fn test_construct_github_url_without_git_suffix() {
-        let repo = setup_empty_repo();
-        let head_commit = repo.head().unwrap().target().unwrap();
+    fn test_inspect_object() {
+        let (repo, first, second) = setup_test_repo_with_history();
+        let blob = repo.blob("new file\n".as_bytes()).unwrap();
+        let repo_info = RepositoryInfo::_from_parts(repo, second);
 
-        let repo_info = RepositoryInfo {
-            repository: repo,
-            oid: head_commit,
-        };
+        let (heading, body) = repo_info.inspect_object(None).unwrap();
+        assert_eq!(heading, format!("commit {}", second));
+        assert!(body.contains(&format!("\nparent {}\n", first)));
+        assert!(body.ends_with("\n\nChange line 2"));
 
-        let url_without_git = "git@github.com:owner/repo";
-        let result = repo_info
-            .construct_github_url(url_without_git, "test.py", 5)
-            .unwrap();
-        let expected = format!(
-            "https://github.com/owner/repo/blob/{}/test.py#L5",
-            head_commit
+        let (heading, body) = repo_info.inspect_object(Some("")).unwrap();
+        assert!(heading.starts_with("tree "));
+        assert!(body
+            .lines()
+            .any(|line| line == format!("100644 blob {}\tadded.txt", blob)));
+
+        let (heading, body) = repo_info.inspect_object(Some("added.txt")).unwrap();
+        assert_eq!(heading, format!("blob {}", blob));
+        assert_eq!(body, "size 9\nbinary no");
+
+        assert!(repo_info.inspect_object(Some("missing.txt")).is_err());
+    }
+
+    #[test]
+    fn test_changed_file_stats_and_commit_info() {
+        let (repo, _, second) = setup_test_repo_with_history();
+        let repo_info = RepositoryInfo::_from_parts(repo, second);
+
+        let mut stats = repo_info.changed_file_stats().unwrap();
+        stats.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(
+            stats,
+            vec![
+                FileStat {
+                    path: "added.txt".to_owned(),
+                    additions: 1,
+                    deletions: 0,
+                    rename: None,
+                },
+                FileStat {
+                    path: "test.txt".to_owned(),
+                    additions: 1,
+                    deletions: 1,
+                    rename: None,
+                },
+            ]
         );
-        assert_eq!(result, expected);
+
+        let info = repo_info.commit_info().unwrap();
+        assert_eq!(info.id, second.to_string());
+        assert_eq!(info.author, "Test User");
+
+        let summary = repo_info.review_summary().unwrap();
+        assert!(summary.contains("## Changed files (2 files, +2 -1)"));
     }
 
     #[test]
-    fn test_construct_github_url_invalid_format() {
-        let repo = setup_empty_repo();
-        let head_commit = repo.head().unwrap().target().unwrap();
+    fn test_notes_at_current_commit() {
+        let (repo, first, second) = setup_test_repo_with_history();
+        let mut repo_info = RepositoryInfo::_from_parts(repo, second);
+        repo_info.add_note("test.txt", 2, "why?").unwrap();
+        repo_info.add_note("test.txt", 3, "ok").unwrap();
+        repo_info.add_note("added.txt", 1, "new").unwrap();
 
-        let repo_info = RepositoryInfo {
-            repository: repo,
-            oid: head_commit,
-        };
+        assert_eq!(repo_info.notes().len(), 3);
+        assert_eq!(repo_info.notes()[0].commit, second.to_string());
+        assert_eq!(repo_info.noted_lines("test.txt"), BTreeSet::from([2, 3]));
 
-        let invalid_url = "invalid-url-format";
-        let result = repo_info.construct_github_url(invalid_url, "file.txt", 1);
-        assert!(result.is_err());
+        repo_info.remove_note(0).unwrap();
+        assert_eq!(repo_info.noted_lines("test.txt"), BTreeSet::from([3]));
+
+        // Notes belong to the commit they were written on
+        repo_info.set_commit_by_id(&first.to_string()).unwrap();
+        assert!(repo_info.noted_lines("test.txt").is_empty());
     }
 }
@@ -0,0 +1,123 @@
+use std::fmt::Write;
+
+use ratatui::{
+    buffer::{Buffer, Cell},
+    style::{Color, Modifier, Style},
+};
+use unicode_width::UnicodeWidthStr;
+
+// The symbols of each row, leaving out the cells covered by a wide character before them.
+fn rows(buffer: &Buffer) -> impl Iterator<Item = Vec<&Cell>> {
+    let width = buffer.area.width as usize;
+    buffer.content.chunks(width.max(1)).map(|row| {
+        let mut cells = Vec::with_capacity(row.len());
+        let mut skip = 0;
+        for cell in row {
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            skip = cell.symbol().width().saturating_sub(1);
+            cells.push(cell);
+        }
+        cells
+    })
+}
+
+/// The screen as plain text, one line per row without trailing spaces.
+pub fn to_text(buffer: &Buffer) -> String {
+    rows(buffer)
+        .map(|cells| {
+            let line: String = cells.iter().map(|cell| cell.symbol()).collect();
+            line.trim_end().to_owned() + "\n"
+        })
+        .collect()
+}
+
+fn color_code(color: Color, base: u8) -> Option<String> {
+    let code = match color {
+        Color::Reset => return None,
+        Color::Black => base,
+        Color::Red => base + 1,
+        Color::Green => base + 2,
+        Color::Yellow => base + 3,
+        Color::Blue => base + 4,
+        Color::Magenta => base + 5,
+        Color::Cyan => base + 6,
+        Color::Gray => base + 7,
+        Color::DarkGray => base + 60,
+        Color::LightRed => base + 61,
+        Color::LightGreen => base + 62,
+        Color::LightYellow => base + 63,
+        Color::LightBlue => base + 64,
+        Color::LightMagenta => base + 65,
+        Color::LightCyan => base + 66,
+        Color::White => base + 67,
+        Color::Indexed(index) => return Some(format!("{};5;{}", base + 8, index)),
+        Color::Rgb(r, g, b) => return Some(format!("{};2;{};{};{}", base + 8, r, g, b)),
+    };
+    Some(code.to_string())
+}
+
+fn sgr(style: Style) -> String {
+    let mut codes = vec!["0".to_owned()];
+    for (modifier, code) in [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::CROSSED_OUT, "9"),
+    ] {
+        if style.add_modifier.contains(modifier) {
+            codes.push(code.to_owned());
+        }
+    }
+    codes.extend(style.fg.and_then(|color| color_code(color, 30)));
+    codes.extend(style.bg.and_then(|color| color_code(color, 40)));
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+/// The screen with its colors as ANSI escape sequences, for `cat` or `less -R`.
+pub fn to_ansi(buffer: &Buffer) -> String {
+    let mut ansi = String::new();
+    for cells in rows(buffer) {
+        let mut current = Style::default();
+        for cell in cells {
+            let style = cell.style();
+            if style != current {
+                ansi.push_str(&sgr(style));
+                current = style;
+            }
+            ansi.push_str(cell.symbol());
+        }
+        let _ = writeln!(ansi, "\x1b[0m");
+    }
+    ansi
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::layout::Rect;
+
+    fn buffer() -> Buffer {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 8, 2));
+        buffer.set_string(0, 0, "ab", Style::default().fg(Color::Red));
+        buffer.set_string(0, 1, "日本x", Style::default().add_modifier(Modifier::BOLD));
+        buffer
+    }
+
+    #[test]
+    fn test_to_text() {
+        assert_eq!(to_text(&buffer()), "ab\n日本x\n");
+    }
+
+    #[test]
+    fn test_to_ansi() {
+        assert_eq!(
+            to_ansi(&buffer()),
+            "\x1b[0;31mab\x1b[0m      \x1b[0m\n\x1b[0;1m日本x\x1b[0m   \x1b[0m\n"
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use anyhow::{anyhow, bail};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+fn named_key(name: &str) -> Option<KeyCode> {
+    let code = match name.to_ascii_lowercase().as_str() {
+        "enter" | "cr" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backtab" | "s-tab" => KeyCode::BackTab,
+        "bs" | "backspace" => KeyCode::Backspace,
+        "del" | "delete" => KeyCode::Delete,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "space" => KeyCode::Char(' '),
+        "lt" => KeyCode::Char('<'),
+        _ => return None,
+    };
+    Some(code)
+}
+
+fn parse_key(name: &str) -> anyhow::Result<KeyEvent> {
+    if let Some(code) = named_key(name) {
+        return Ok(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+    let mut chars = name.chars();
+    match (chars.next(), chars.next(), chars.next(), chars.next()) {
+        (Some('C' | 'c'), Some('-'), Some(char), None) => {
+            Ok(KeyEvent::new(KeyCode::Char(char), KeyModifiers::CONTROL))
+        }
+        _ => Err(anyhow!("unknown key <{}>", name)),
+    }
+}
+
+/// Reads keys written in vim's notation: characters are typed as they are, and special keys
+/// go in angle brackets, like `<Enter>`, `<Tab>`, `<C-p>` or `<lt>` for `<` itself. Line
+/// breaks are ignored and lines starting with `#` are comments.
+pub fn parse(script: &str) -> anyhow::Result<Vec<KeyEvent>> {
+    let mut keys = Vec::new();
+    for (number, line) in script.lines().enumerate() {
+        if line.starts_with('#') {
+            continue;
+        }
+        let mut rest = line;
+        while let Some(char) = rest.chars().next() {
+            if char == '<' {
+                let Some(end) = rest.find('>') else {
+                    bail!("line {}: unclosed <", number + 1);
+                };
+                let key =
+                    parse_key(&rest[1..end]).map_err(|e| anyhow!("line {}: {}", number + 1, e))?;
+                keys.push(key);
+                rest = &rest[end + 1..];
+            } else {
+                keys.push(KeyEvent::new(KeyCode::Char(char), KeyModifiers::NONE));
+                rest = &rest[char.len_utf8()..];
+            }
+        }
+    }
+    Ok(keys)
+}
+
+/// Parses a screen size given as `WIDTHxHEIGHT`.
+pub fn parse_size(size: &str) -> anyhow::Result<(u16, u16)> {
+    size.split_once('x')
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+        .filter(|(width, height)| *width > 0 && *height > 0)
+        .ok_or_else(|| anyhow!("expected a size as WIDTHxHEIGHT, like 80x24"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_script() {
+        let keys = parse("# open the picker\n<C-p>main<Enter>\n<lt>j <Esc>").unwrap();
+        let codes: Vec<(KeyCode, KeyModifiers)> =
+            keys.iter().map(|key| (key.code, key.modifiers)).collect();
+        assert_eq!(
+            codes,
+            vec![
+                (KeyCode::Char('p'), KeyModifiers::CONTROL),
+                (KeyCode::Char('m'), KeyModifiers::NONE),
+                (KeyCode::Char('a'), KeyModifiers::NONE),
+                (KeyCode::Char('i'), KeyModifiers::NONE),
+                (KeyCode::Char('n'), KeyModifiers::NONE),
+                (KeyCode::Enter, KeyModifiers::NONE),
+                (KeyCode::Char('<'), KeyModifiers::NONE),
+                (KeyCode::Char('j'), KeyModifiers::NONE),
+                (KeyCode::Char(' '), KeyModifiers::NONE),
+                (KeyCode::Esc, KeyModifiers::NONE),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_script_errors() {
+        assert_eq!(
+            parse("j\n<Enter").unwrap_err().to_string(),
+            "line 2: unclosed <"
+        );
+        assert_eq!(
+            parse("<Foo>").unwrap_err().to_string(),
+            "line 1: unknown key <Foo>"
+        );
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("120x40").unwrap(), (120, 40));
+        assert!(parse_size("120").is_err());
+        assert!(parse_size("0x40").is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use git2::Repository;
+use regex::Regex;
+
+struct Pattern {
+    negated: bool,
+    // only matches directories, written with a trailing `/`
+    directory: bool,
+    regex: Regex,
+}
+
+/// Patterns of `.git/info/sparse-checkout`, matched like gitignore patterns: the last pattern
+/// matching a path or one of its parent directories decides. This covers cone mode too, whose
+/// patterns are a restricted form of the same syntax.
+pub struct SparsePatterns {
+    patterns: Vec<Pattern>,
+}
+
+impl SparsePatterns {
+    /// Patterns of the repository when sparse checkout is enabled.
+    pub fn from_repository(repository: &Repository) -> Option<Self> {
+        let enabled = repository
+            .config()
+            .and_then(|config| config.get_bool("core.sparseCheckout"))
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+        let content =
+            std::fs::read_to_string(repository.path().join("info/sparse-checkout")).ok()?;
+        Some(Self::parse(&content))
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let patterns = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let (negated, line) = match line.strip_prefix('!') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                let (directory, line) = match line.strip_suffix('/') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                // a slash anywhere but at the end anchors the pattern to the root
+                let anchored = line.contains('/');
+                let line = line.trim_start_matches('/');
+                let mut regex = String::from(if anchored { "^" } else { "(^|/)" });
+                let mut chars = line.chars().peekable();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '*' if chars.peek() == Some(&'*') => {
+                            chars.next();
+                            regex.push_str(".*");
+                        }
+                        '*' => regex.push_str("[^/]*"),
+                        '?' => regex.push_str("[^/]"),
+                        c => regex.push_str(&regex::escape(&c.to_string())),
+                    }
+                }
+                regex.push('$');
+                Some(Pattern {
+                    negated,
+                    directory,
+                    regex: Regex::new(&regex).ok()?,
+                })
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    /// Whether `path`, a file relative to the repository root, is checked out.
+    pub fn includes(&self, path: &str) -> bool {
+        let mut included = false;
+        for pattern in &self.patterns {
+            let matches_directory = path
+                .match_indices('/')
+                .any(|(end, _)| pattern.regex.is_match(&path[..end]));
+            let matches_file = !pattern.directory && pattern.regex.is_match(path);
+            if matches_directory || matches_file {
+                included = !pattern.negated;
+            }
+        }
+        included
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cone_patterns() {
+        // written by `git sparse-checkout set src/app`
+        let sparse = SparsePatterns::parse("/*\n!/*/\n/src/\n!/src/*/\n/src/app/\n");
+        assert!(sparse.includes("README.md"));
+        assert!(sparse.includes("src/main.rs"));
+        assert!(sparse.includes("src/app/mod.rs"));
+        assert!(sparse.includes("src/app/views/list.rs"));
+        assert!(!sparse.includes("src/other/mod.rs"));
+        assert!(!sparse.includes("docs/index.md"));
+    }
+
+    #[test]
+    fn test_non_cone_patterns() {
+        let sparse = SparsePatterns::parse("# docs only\n*.md\n!drafts/\nsrc/**/*.rs\n");
+        assert!(sparse.includes("README.md"));
+        assert!(sparse.includes("docs/guide.md"));
+        assert!(!sparse.includes("drafts/todo.md"));
+        assert!(sparse.includes("src/a/b/c.rs"));
+        assert!(!sparse.includes("src/a/b/c.toml"));
+        assert!(!SparsePatterns::parse("").includes("README.md"));
+    }
+}
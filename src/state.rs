@@ -0,0 +1,406 @@
+use std::{
+    collections::BTreeSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+// Directory for persistent per-user state, following the XDG base directory spec.
+pub fn state_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_STATE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state")))
+        .map(|dir| dir.join("gview"))
+}
+
+// Stable directory name for per-repository state (FNV-1a of the repository path).
+pub fn storage_key(path: &Path) -> String {
+    let hash = path
+        .to_string_lossy()
+        .bytes()
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+    format!("{:016x}", hash)
+}
+
+fn write_lines<'a>(path: &Path, lines: impl Iterator<Item = &'a String>) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let content: Vec<&str> = lines.map(String::as_str).collect();
+    fs::write(path, content.join("\n"))
+}
+
+/// Files marked as reviewed for one base/commit pair.
+#[derive(Debug, Default)]
+pub struct ReviewState {
+    path: Option<PathBuf>,
+    reviewed: BTreeSet<String>,
+}
+
+impl ReviewState {
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let reviewed = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|content| {
+                content
+                    .lines()
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { path, reviewed }
+    }
+
+    pub fn for_pair(base: &str, commit: &str) -> Self {
+        Self::load(state_dir().map(|dir| dir.join("reviews").join(format!("{}..{}", base, commit))))
+    }
+
+    pub fn is_reviewed(&self, file: &str) -> bool {
+        self.reviewed.contains(file)
+    }
+
+    pub fn count_reviewed(&self, files: &[String]) -> usize {
+        files.iter().filter(|file| self.is_reviewed(file)).count()
+    }
+
+    // Flips the mark and persists it right away so progress survives a crash.
+    pub fn toggle(&mut self, file: &str) -> io::Result<()> {
+        if !self.reviewed.remove(file) {
+            self.reviewed.insert(file.to_owned());
+        }
+        match &self.path {
+            Some(path) => write_lines(path, self.reviewed.iter()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The gview version of the last run, to tell what is new since.
+#[derive(Debug, Default)]
+pub struct LastRun {
+    path: Option<PathBuf>,
+    version: Option<String>,
+}
+
+impl LastRun {
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let version = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|content| content.trim().to_owned())
+            .filter(|version| !version.is_empty());
+        Self { path, version }
+    }
+
+    pub fn for_user() -> Self {
+        Self::load(state_dir().map(|dir| dir.join("last_version")))
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    pub fn record(&mut self, version: &str) -> io::Result<()> {
+        self.version = Some(version.to_owned());
+        match &self.path {
+            Some(path) => write_lines(path, self.version.iter()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Panel sizes set by dragging the dividers between panels, as percentages.
+#[derive(Debug, Default)]
+pub struct PanelLayout {
+    path: Option<PathBuf>,
+    // of the width, taken by the left column
+    pub left: Option<u16>,
+    // of the right column's height, taken by the commit panel
+    pub commit: Option<u16>,
+}
+
+impl PanelLayout {
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let mut layout = Self {
+            path,
+            ..Self::default()
+        };
+        let content = layout
+            .path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .unwrap_or_default();
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once(' ') else {
+                continue;
+            };
+            let value = value.trim().parse().ok();
+            match key {
+                "left" => layout.left = value,
+                "commit" => layout.commit = value,
+                _ => {}
+            }
+        }
+        layout
+    }
+
+    pub fn for_user() -> Self {
+        Self::load(state_dir().map(|dir| dir.join("layout")))
+    }
+
+    pub fn record(&self) -> io::Result<()> {
+        let lines: Vec<String> = [("left", self.left), ("commit", self.commit)]
+            .into_iter()
+            .filter_map(|(key, value)| Some(format!("{} {}", key, value?)))
+            .collect();
+        match &self.path {
+            Some(path) => write_lines(path, lines.iter()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A local annotation attached to a line of a file at a specific commit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Note {
+    pub commit: String,
+    pub path: String,
+    pub line: usize,
+    pub text: String,
+}
+
+impl Note {
+    fn to_record(&self) -> String {
+        let text = self.text.replace(['\t', '\n', '\r'], " ");
+        format!("{}\t{}\t{}\t{}", self.commit, self.path, self.line, text)
+    }
+
+    fn from_record(record: &str) -> Option<Note> {
+        let mut fields = record.splitn(4, '\t');
+        Some(Note {
+            commit: fields.next()?.to_owned(),
+            path: fields.next()?.to_owned(),
+            line: fields.next()?.parse().ok()?,
+            text: fields.next()?.to_owned(),
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct NoteStore {
+    path: Option<PathBuf>,
+    notes: Vec<Note>,
+}
+
+impl NoteStore {
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let notes = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|content| content.lines().filter_map(Note::from_record).collect())
+            .unwrap_or_default();
+        Self { path, notes }
+    }
+
+    pub fn for_repository(key: &str) -> Self {
+        Self::load(state_dir().map(|dir| dir.join("notes").join(format!("{}.tsv", key))))
+    }
+
+    pub fn notes(&self) -> &[Note] {
+        &self.notes
+    }
+
+    pub fn add(&mut self, note: Note) -> io::Result<()> {
+        self.notes.push(note);
+        self.save()
+    }
+
+    pub fn remove(&mut self, index: usize) -> io::Result<()> {
+        if index < self.notes.len() {
+            self.notes.remove(index);
+        }
+        self.save()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        match &self.path {
+            Some(path) => {
+                let records: Vec<String> = self.notes.iter().map(Note::to_record).collect();
+                write_lines(path, records.iter())
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+// Renders notes grouped by file, ready to paste into a review comment.
+pub fn notes_to_markdown(notes: &[Note]) -> String {
+    let mut paths: Vec<&str> = notes.iter().map(|note| note.path.as_str()).collect();
+    paths.sort();
+    paths.dedup();
+
+    let mut markdown = String::from("## Review notes\n");
+    if notes.is_empty() {
+        markdown.push_str("\nNo notes.\n");
+    }
+    for path in paths {
+        markdown.push_str(&format!("\n### `{}`\n\n", path));
+        let mut file_notes: Vec<&Note> = notes.iter().filter(|note| note.path == path).collect();
+        file_notes.sort_by_key(|note| note.line);
+        for note in file_notes {
+            markdown.push_str(&format!(
+                "- L{} (`{}`): {}\n",
+                note.line,
+                &note.commit[..std::cmp::min(8, note.commit.len())],
+                note.text
+            ));
+        }
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn temp_state_file(name: &str) -> PathBuf {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        env::temp_dir()
+            .join(format!(
+                "gview_state_test_{}_{}",
+                timestamp,
+                std::process::id()
+            ))
+            .join(name)
+    }
+
+    #[test]
+    fn test_review_state_toggle_and_reload() {
+        let path = temp_state_file("review");
+        let mut state = ReviewState::load(Some(path.clone()));
+        assert!(!state.is_reviewed("src/main.rs"));
+
+        state.toggle("src/main.rs").unwrap();
+        state.toggle("README.md").unwrap();
+        assert!(state.is_reviewed("src/main.rs"));
+
+        let reloaded = ReviewState::load(Some(path.clone()));
+        assert!(reloaded.is_reviewed("src/main.rs"));
+        assert!(reloaded.is_reviewed("README.md"));
+
+        state.toggle("src/main.rs").unwrap();
+        let reloaded = ReviewState::load(Some(path));
+        assert!(!reloaded.is_reviewed("src/main.rs"));
+    }
+
+    #[test]
+    fn test_review_state_without_path() {
+        let mut state = ReviewState::load(None);
+        state.toggle("a.rs").unwrap();
+        assert!(state.is_reviewed("a.rs"));
+        assert_eq!(
+            state.count_reviewed(&["a.rs".to_owned(), "b.rs".to_owned()]),
+            1
+        );
+    }
+
+    #[test]
+    fn test_review_state_missing_file_is_empty() {
+        let state = ReviewState::load(Some(temp_state_file("missing")));
+        assert_eq!(state.count_reviewed(&["a.rs".to_owned()]), 0);
+    }
+
+    #[test]
+    fn test_last_run_record_and_reload() {
+        let path = temp_state_file("last_version");
+        let mut last_run = LastRun::load(Some(path.clone()));
+        assert_eq!(last_run.version(), None);
+
+        last_run.record("0.2.0").unwrap();
+        assert_eq!(LastRun::load(Some(path)).version(), Some("0.2.0"));
+    }
+
+    #[test]
+    fn test_panel_layout_record_and_reload() {
+        let path = temp_state_file("layout");
+        let layout = PanelLayout::load(Some(path.clone()));
+        assert_eq!((layout.left, layout.commit), (None, None));
+
+        let layout = PanelLayout {
+            path: Some(path.clone()),
+            left: Some(30),
+            commit: None,
+        };
+        layout.record().unwrap();
+        let reloaded = PanelLayout::load(Some(path.clone()));
+        assert_eq!((reloaded.left, reloaded.commit), (Some(30), None));
+
+        std::fs::write(&path, "left x\ncommit 40\nother 1\n").unwrap();
+        let reloaded = PanelLayout::load(Some(path));
+        assert_eq!((reloaded.left, reloaded.commit), (None, Some(40)));
+    }
+
+    fn note(path: &str, line: usize, text: &str) -> Note {
+        Note {
+            commit: "0123456789abcdef0123456789abcdef01234567".to_owned(),
+            path: path.to_owned(),
+            line,
+            text: text.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_storage_key_is_stable() {
+        let key = storage_key(Path::new("/tmp/repo/.git"));
+        assert_eq!(key.len(), 16);
+        assert_eq!(key, storage_key(Path::new("/tmp/repo/.git")));
+        assert_ne!(key, storage_key(Path::new("/tmp/other/.git")));
+    }
+
+    #[test]
+    fn test_note_store_add_remove_and_reload() {
+        let path = temp_state_file("notes.tsv");
+        let mut store = NoteStore::load(Some(path.clone()));
+        store.add(note("src/main.rs", 3, "check\tthis")).unwrap();
+        store.add(note("README.md", 1, "typo")).unwrap();
+
+        let reloaded = NoteStore::load(Some(path.clone()));
+        assert_eq!(reloaded.notes().len(), 2);
+        assert_eq!(reloaded.notes()[0].text, "check this");
+        assert_eq!(reloaded.notes()[1].path, "README.md");
+
+        store.remove(0).unwrap();
+        store.remove(10).unwrap();
+        let reloaded = NoteStore::load(Some(path));
+        assert_eq!(reloaded.notes(), &[note("README.md", 1, "typo")]);
+    }
+
+    #[test]
+    fn test_note_from_invalid_record() {
+        assert_eq!(Note::from_record("abc\tpath"), None);
+        assert_eq!(Note::from_record("abc\tpath\tnot-a-number\ttext"), None);
+    }
+
+    #[test]
+    fn test_notes_to_markdown() {
+        let notes = vec![
+            note("src/main.rs", 10, "second"),
+            note("README.md", 1, "typo"),
+            note("src/main.rs", 2, "first"),
+        ];
+        assert_eq!(
+            notes_to_markdown(&notes),
+            "## Review notes\n\n### `README.md`\n\n- L1 (`01234567`): typo\n\n### `src/main.rs`\n\n- L2 (`01234567`): first\n- L10 (`01234567`): second\n"
+        );
+        assert_eq!(notes_to_markdown(&[]), "## Review notes\n\nNo notes.\n");
+    }
+}
@@ -0,0 +1,341 @@
+use crate::{
+    repository::{CommitInfo, FileStat},
+    state::{self, Note},
+    trailers,
+};
+
+// Formats a git timestamp as `YYYY-MM-DD HH:MM:SS +HHMM` in the committer's offset.
+pub fn format_time(time: git2::Time) -> String {
+    let offset = time.offset_minutes() as i64;
+    let seconds = time.seconds() + offset * 60;
+    let (days, rest) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+
+    // civil-from-days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} {}{:02}{:02}",
+        year,
+        month,
+        day,
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60,
+        if offset < 0 { '-' } else { '+' },
+        offset.abs() / 60,
+        offset.abs() % 60
+    )
+}
+
+// Seconds at the start of `date`, given as YYYY-MM-DD in UTC.
+pub fn parse_date(date: &str) -> anyhow::Result<i64> {
+    let invalid = || anyhow::anyhow!("{} is not a date as YYYY-MM-DD", date);
+    let mut fields = date.trim().splitn(3, '-').map(str::parse::<i64>);
+    let (Some(Ok(year)), Some(Ok(month)), Some(Ok(day))) =
+        (fields.next(), fields.next(), fields.next())
+    else {
+        return Err(invalid());
+    };
+
+    // days-from-civil, the inverse of `format_time`
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (month + 9).rem_euclid(12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let seconds = (era * 146097 + doe - 719468) * 86400;
+
+    // a month or day out of range lands on another date
+    let expected = format!("{:04}-{:02}-{:02} ", year, month, day);
+    if !format_time(git2::Time::new(seconds, 0)).starts_with(&expected) {
+        return Err(invalid());
+    }
+    Ok(seconds)
+}
+
+// Builds a Markdown review summary meant to be pasted into a PR comment.
+pub fn review_summary(
+    info: &CommitInfo,
+    base: Option<&str>,
+    stats: &[FileStat],
+    notes: &[Note],
+) -> String {
+    let short_id = &info.id[..std::cmp::min(8, info.id.len())];
+    let mut message = info.message.trim().lines();
+    let subject = message.next().unwrap_or("");
+    let body = message.collect::<Vec<&str>>().join("\n");
+
+    let mut markdown = format!("# Review of `{}`: {}\n\n", short_id, subject);
+    markdown.push_str(&format!("- Commit: `{}`\n", info.id));
+    markdown.push_str(&format!("- Author: {} <{}>\n", info.author, info.email));
+    let (_, trailers) = trailers::split(&info.message);
+    let co_authors: Vec<&str> = trailers
+        .iter()
+        .filter(|trailer| trailer.is_co_author())
+        .map(|trailer| trailer.value.as_str())
+        .collect();
+    if !co_authors.is_empty() {
+        markdown.push_str(&format!("- Co-authors: {}\n", co_authors.join(", ")));
+    }
+    markdown.push_str(&format!("- Date: {}\n", info.date));
+    markdown.push_str(&format!(
+        "- Compared against: {}\n",
+        match base {
+            Some(base) => format!("`{}`", &base[..std::cmp::min(8, base.len())]),
+            None => "parent commit".to_owned(),
+        }
+    ));
+    if !body.trim().is_empty() {
+        markdown.push_str(&format!("\n{}\n", body.trim()));
+    }
+
+    let additions: usize = stats.iter().map(|stat| stat.additions).sum();
+    let deletions: usize = stats.iter().map(|stat| stat.deletions).sum();
+    markdown.push_str(&format!(
+        "\n## Changed files ({} files, +{} -{})\n\n",
+        stats.len(),
+        additions,
+        deletions
+    ));
+    if stats.is_empty() {
+        markdown.push_str("No changes.\n");
+    } else {
+        markdown.push_str("| File | + | - |\n| --- | --: | --: |\n");
+        for stat in stats {
+            let file = match &stat.rename {
+                Some(rename) => format!(
+                    "`{}` → `{}` ({}%)",
+                    rename.from, stat.path, rename.similarity
+                ),
+                None => format!("`{}`", stat.path),
+            };
+            markdown.push_str(&format!(
+                "| {} | {} | {} |\n",
+                file, stat.additions, stat.deletions
+            ));
+        }
+    }
+
+    let notes: Vec<Note> = notes
+        .iter()
+        .filter(|note| note.commit == info.id)
+        .cloned()
+        .collect();
+    markdown.push('\n');
+    markdown.push_str(&state::notes_to_markdown(&notes));
+    markdown
+}
+
+// Aggregated diffstat for everything under `dir` ("" for the whole tree), one line per
+// immediate subdirectory or file so large commits can be triaged top-down.
+pub fn directory_summary(dir: &str, stats: &[FileStat]) -> String {
+    let prefix = if dir.is_empty() {
+        "".to_owned()
+    } else {
+        format!("{}/", dir)
+    };
+    let mut entries: Vec<(String, usize, usize, usize)> = vec![];
+    let (mut additions, mut deletions, mut files) = (0, 0, 0);
+    for stat in stats {
+        let Some(relative) = stat.path.strip_prefix(&prefix) else {
+            continue;
+        };
+        let name = match relative.split_once('/') {
+            Some((child, _)) => format!("{}/", child),
+            None => relative.to_owned(),
+        };
+        match entries.iter_mut().find(|entry| entry.0 == name) {
+            Some(entry) => {
+                entry.1 += stat.additions;
+                entry.2 += stat.deletions;
+                entry.3 += 1;
+            }
+            None => entries.push((name, stat.additions, stat.deletions, 1)),
+        }
+        additions += stat.additions;
+        deletions += stat.deletions;
+        files += 1;
+    }
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut summary = format!(
+        "{}: {} files changed, +{} -{}\n",
+        if dir.is_empty() { "/" } else { dir },
+        files,
+        additions,
+        deletions
+    );
+    if !entries.is_empty() {
+        summary.push('\n');
+    }
+    for (name, additions, deletions, files) in entries {
+        let added = format!("+{}", additions);
+        let deleted = format!("-{}", deletions);
+        summary.push_str(&format!("{:>7} {:>7}  {}", added, deleted, name));
+        if name.ends_with('/') {
+            summary.push_str(&format!(" ({} files)", files));
+        }
+        summary.push('\n');
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit_info(message: &str) -> CommitInfo {
+        CommitInfo {
+            id: "0123456789abcdef0123456789abcdef01234567".to_owned(),
+            author: "Test User".to_owned(),
+            email: "test@example.com".to_owned(),
+            date: "2009-02-13 23:31:30 +0000".to_owned(),
+            message: message.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(
+            format_time(git2::Time::new(1234567890, 0)),
+            "2009-02-13 23:31:30 +0000"
+        );
+        assert_eq!(
+            format_time(git2::Time::new(1234567890, 540)),
+            "2009-02-14 08:31:30 +0900"
+        );
+        assert_eq!(
+            format_time(git2::Time::new(0, -90)),
+            "1969-12-31 22:30:00 -0130"
+        );
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("2009-02-13").unwrap(), 1234483200);
+        assert_eq!(parse_date("1970-01-01").unwrap(), 0);
+        assert_eq!(parse_date("2024-2-29").unwrap(), 1709164800);
+        assert!(parse_date("2023-02-29").is_err());
+        assert!(parse_date("2023-13-01").is_err());
+        assert!(parse_date("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_review_summary() {
+        let stats = vec![
+            FileStat {
+                path: "src/main.rs".to_owned(),
+                additions: 3,
+                deletions: 1,
+                rename: None,
+            },
+            FileStat {
+                path: "docs/README.md".to_owned(),
+                additions: 2,
+                deletions: 0,
+                rename: Some(crate::repository::Rename {
+                    from: "README.md".to_owned(),
+                    similarity: 90,
+                }),
+            },
+        ];
+        let info = commit_info("Add feature\n\nLonger description.\n");
+        let notes = vec![
+            Note {
+                commit: info.id.to_owned(),
+                path: "src/main.rs".to_owned(),
+                line: 4,
+                text: "nice".to_owned(),
+            },
+            Note {
+                commit: "ffffffffffffffffffffffffffffffffffffffff".to_owned(),
+                path: "src/main.rs".to_owned(),
+                line: 1,
+                text: "other commit".to_owned(),
+            },
+        ];
+
+        assert_eq!(
+            review_summary(&info, Some("fedcba9876543210"), &stats, &notes),
+            "# Review of `01234567`: Add feature\n\
+             \n\
+             - Commit: `0123456789abcdef0123456789abcdef01234567`\n\
+             - Author: Test User <test@example.com>\n\
+             - Date: 2009-02-13 23:31:30 +0000\n\
+             - Compared against: `fedcba98`\n\
+             \n\
+             Longer description.\n\
+             \n\
+             ## Changed files (2 files, +5 -1)\n\
+             \n\
+             | File | + | - |\n\
+             | --- | --: | --: |\n\
+             | `src/main.rs` | 3 | 1 |\n\
+             | `README.md` → `docs/README.md` (90%) | 2 | 0 |\n\
+             \n\
+             ## Review notes\n\
+             \n\
+             ### `src/main.rs`\n\
+             \n\
+             - L4 (`01234567`): nice\n"
+        );
+    }
+
+    fn stat(path: &str, additions: usize, deletions: usize) -> FileStat {
+        FileStat {
+            path: path.to_owned(),
+            additions,
+            deletions,
+            rename: None,
+        }
+    }
+
+    #[test]
+    fn test_directory_summary() {
+        let stats = vec![
+            stat("src/main.rs", 3, 1),
+            stat("src/components/a.rs", 10, 2),
+            stat("src/components/b.rs", 2, 0),
+            stat("README.md", 1, 1),
+        ];
+        assert_eq!(
+            directory_summary("src", &stats),
+            "src: 3 files changed, +15 -3\n\
+             \n    \
+             +12      -2  components/ (2 files)\n     \
+             +3      -1  main.rs\n"
+        );
+        assert_eq!(
+            directory_summary("", &stats),
+            "/: 4 files changed, +16 -4\n\
+             \n     \
+             +1      -1  README.md\n    \
+             +15      -3  src/ (3 files)\n"
+        );
+        assert_eq!(
+            directory_summary("docs", &stats),
+            "docs: 0 files changed, +0 -0\n"
+        );
+    }
+
+    #[test]
+    fn test_review_summary_without_changes() {
+        let summary = review_summary(&commit_info("Initial commit"), None, &[], &[]);
+        assert!(summary.contains("- Compared against: parent commit\n"));
+        assert!(summary.contains("## Changed files (0 files, +0 -0)\n\nNo changes.\n"));
+        assert!(!summary.contains("Co-authors"));
+
+        let info = commit_info("Pair on it\n\nCo-authored-by: Bob <bob@example.com>\n");
+        let summary = review_summary(&info, None, &[], &[]);
+        assert!(summary.contains("- Co-authors: Bob <bob@example.com>\n"));
+        assert!(summary.ends_with("No notes.\n"));
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+
+// Definition keywords of common languages, followed by the defined name.
+const DEFINITION_PATTERN: &str = r"\b(?:fn|struct|enum|trait|type|const|static|mod|union|macro_rules!|def|class|function|func|interface)\s+([A-Za-z_][A-Za-z0-9_]*)";
+
+/// A ctags-style index from identifiers to the lines defining them.
+#[derive(Debug, Default)]
+pub struct TagIndex {
+    definitions: HashMap<String, Vec<(String, usize)>>,
+}
+
+impl TagIndex {
+    pub fn build<'a>(files: impl Iterator<Item = (&'a str, &'a str)>) -> Self {
+        let Ok(pattern) = Regex::new(DEFINITION_PATTERN) else {
+            return Self::default();
+        };
+        let mut definitions: HashMap<String, Vec<(String, usize)>> = HashMap::new();
+        for (path, content) in files {
+            for (number, line) in content.lines().enumerate() {
+                for captures in pattern.captures_iter(line) {
+                    definitions
+                        .entry(captures[1].to_owned())
+                        .or_default()
+                        .push((path.to_owned(), number + 1));
+                }
+            }
+        }
+        Self { definitions }
+    }
+
+    pub fn lookup(&self, name: &str) -> &[(String, usize)] {
+        self.definitions
+            .get(name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+// The first identifier in `line` that contains or follows `column`.
+pub fn identifier_at(line: &str, column: usize) -> Option<String> {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut start = None;
+    for (i, c) in line
+        .char_indices()
+        .chain(std::iter::once((line.len(), ' ')))
+    {
+        match (start, is_word(c)) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                let word = &line[s..i];
+                if i > column && !word.starts_with(|c: char| c.is_ascii_digit()) {
+                    return Some(word.to_owned());
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_index_lookup() {
+        let files = [
+            (
+                "src/app.rs",
+                "pub struct App {\n}\n\nimpl App {\n    pub fn new() -> App {\n",
+            ),
+            ("tool.py", "class Tool:\n    def run(self):\n        pass\n"),
+            ("main.go", "func main() {\n}\n"),
+        ];
+        let index = TagIndex::build(files.iter().map(|(path, content)| (*path, *content)));
+
+        assert_eq!(index.lookup("App"), &[("src/app.rs".to_owned(), 1)]);
+        assert_eq!(index.lookup("new"), &[("src/app.rs".to_owned(), 5)]);
+        assert_eq!(index.lookup("run"), &[("tool.py".to_owned(), 2)]);
+        assert_eq!(index.lookup("main"), &[("main.go".to_owned(), 1)]);
+        assert!(index.lookup("missing").is_empty());
+    }
+
+    #[test]
+    fn test_identifier_at() {
+        let line = "    let app = App::new();";
+        assert_eq!(identifier_at(line, 0), Some("let".to_owned()));
+        assert_eq!(identifier_at(line, 8), Some("app".to_owned()));
+        assert_eq!(identifier_at(line, 11), Some("App".to_owned()));
+        assert_eq!(identifier_at(line, 17), Some("new".to_owned()));
+        assert_eq!(identifier_at(line, 22), None);
+    }
+}
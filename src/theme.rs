@@ -0,0 +1,227 @@
+use std::str::FromStr;
+
+use ratatui::{style::Color, widgets::BorderType};
+
+/// Colors of the UI by role. Built-in themes are `dark`, the default, and `light`; single
+/// colors can be overridden from the config.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    // borders and titles of panels without the focus
+    pub unfocused: Color,
+    // background of the selected item in lists
+    pub selection: Color,
+    // section headings, paths and notices
+    pub heading: Color,
+    // commit ids, keys and progress
+    pub emphasis: Color,
+    // plain text of the help modal and its hints
+    pub text: Color,
+    pub hint: Color,
+    // cherry-pick and revert tags, and the pinned line
+    pub tag: Color,
+    // background of every other blame hunk, and of the missing side of a split diff
+    pub shaded: Color,
+    // blame gutter of lines from the viewed commit, and of the user's own lines
+    pub current_lines: Color,
+    pub mine_lines: Color,
+    // background of search matches, and of the one jumped to
+    pub search_match: Color,
+    pub current_match: Color,
+    // text on search matches
+    pub match_text: Color,
+    // border of a prompt rejecting its input
+    pub error: Color,
+    pub added: Color,
+    pub removed: Color,
+    pub hunk_header: Color,
+    // filter panel border in partial, fuzzy and regular expression modes
+    pub partial_match: Color,
+    pub fuzzy_match: Color,
+    pub regular_match: Color,
+    // glyph before the title of the focused panel, so the focus shows without colors
+    pub focus_marker: Option<char>,
+    // border of the focused panel
+    pub focus_border: BorderType,
+    // syntect theme coloring the code of files
+    pub syntax: &'static str,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            unfocused: Color::DarkGray,
+            selection: Color::Blue,
+            heading: Color::Cyan,
+            emphasis: Color::Yellow,
+            text: Color::White,
+            hint: Color::Gray,
+            tag: Color::Magenta,
+            shaded: Color::Indexed(236),
+            current_lines: Color::Yellow,
+            mine_lines: Color::Green,
+            search_match: Color::Yellow,
+            current_match: Color::LightRed,
+            match_text: Color::Black,
+            error: Color::Red,
+            added: Color::Green,
+            removed: Color::Red,
+            hunk_header: Color::Cyan,
+            partial_match: Color::Blue,
+            fuzzy_match: Color::Red,
+            regular_match: Color::Green,
+            focus_marker: Some('▶'),
+            focus_border: BorderType::Plain,
+            syntax: "base16-ocean.dark",
+        }
+    }
+
+    // For terminals with a light background, where yellow, white and dark gray fade out.
+    pub fn light() -> Self {
+        Self {
+            unfocused: Color::Indexed(248),
+            selection: Color::Indexed(153),
+            heading: Color::Blue,
+            emphasis: Color::Indexed(130),
+            text: Color::Black,
+            hint: Color::Indexed(242),
+            tag: Color::Magenta,
+            shaded: Color::Indexed(254),
+            current_lines: Color::Indexed(130),
+            mine_lines: Color::Indexed(28),
+            search_match: Color::Indexed(228),
+            current_match: Color::Indexed(210),
+            match_text: Color::Black,
+            error: Color::Indexed(160),
+            added: Color::Indexed(28),
+            removed: Color::Indexed(160),
+            hunk_header: Color::Blue,
+            partial_match: Color::Blue,
+            fuzzy_match: Color::Indexed(160),
+            regular_match: Color::Indexed(28),
+            focus_marker: Some('▶'),
+            focus_border: BorderType::Plain,
+            syntax: "InspiredGitHub",
+        }
+    }
+
+    pub fn by_name(name: &str) -> anyhow::Result<Self> {
+        match name {
+            "dark" => Ok(Self::dark()),
+            "light" => Ok(Self::light()),
+            _ => Err(anyhow::anyhow!(
+                "unknown theme {:?}, expected \"dark\" or \"light\"",
+                name
+            )),
+        }
+    }
+
+    /// Overrides the color of `role` with `value`: a name like `red` or `darkgray`, a
+    /// 256-color index, or `#rrggbb`.
+    pub fn set(&mut self, role: &str, value: &str) -> anyhow::Result<()> {
+        let color = Color::from_str(value)
+            .map_err(|_| anyhow::anyhow!("invalid color {:?} for {}", value, role))?;
+        let slot = match role {
+            "unfocused" => &mut self.unfocused,
+            "selection" => &mut self.selection,
+            "heading" => &mut self.heading,
+            "emphasis" => &mut self.emphasis,
+            "text" => &mut self.text,
+            "hint" => &mut self.hint,
+            "tag" => &mut self.tag,
+            "shaded" => &mut self.shaded,
+            "current_lines" => &mut self.current_lines,
+            "mine_lines" => &mut self.mine_lines,
+            "search_match" => &mut self.search_match,
+            "current_match" => &mut self.current_match,
+            "match_text" => &mut self.match_text,
+            "error" => &mut self.error,
+            "added" => &mut self.added,
+            "removed" => &mut self.removed,
+            "hunk_header" => &mut self.hunk_header,
+            "partial_match" => &mut self.partial_match,
+            "fuzzy_match" => &mut self.fuzzy_match,
+            "regular_match" => &mut self.regular_match,
+            _ => return Err(anyhow::anyhow!("unknown theme color {:?}", role)),
+        };
+        *slot = color;
+        Ok(())
+    }
+
+    // A single character, or nothing to leave the title unmarked.
+    pub fn set_focus_marker(&mut self, marker: &str) -> anyhow::Result<()> {
+        let mut chars = marker.chars();
+        self.focus_marker = match (chars.next(), chars.next()) {
+            (marker, None) => marker,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "invalid focus marker {:?}, expected a single character",
+                    marker
+                ))
+            }
+        };
+        Ok(())
+    }
+
+    pub fn set_focus_border(&mut self, border: &str) -> anyhow::Result<()> {
+        self.focus_border = match border {
+            "plain" => BorderType::Plain,
+            "rounded" => BorderType::Rounded,
+            "double" => BorderType::Double,
+            "thick" => BorderType::Thick,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "invalid focus border {:?}, expected \"plain\", \"rounded\", \"double\" or \"thick\"",
+                    border
+                ))
+            }
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_by_name_and_set() {
+        assert_eq!(Theme::by_name("dark").unwrap(), Theme::default());
+        assert_eq!(Theme::by_name("light").unwrap(), Theme::light());
+        assert!(Theme::by_name("solarized").is_err());
+
+        let mut theme = Theme::dark();
+        theme.set("selection", "#336699").unwrap();
+        theme.set("shaded", "238").unwrap();
+        theme.set("unfocused", "gray").unwrap();
+        theme.set("error", "magenta").unwrap();
+        assert_eq!(theme.selection, Color::Rgb(0x33, 0x66, 0x99));
+        assert_eq!(theme.shaded, Color::Indexed(238));
+        assert_eq!(theme.unfocused, Color::Gray);
+        assert_eq!(theme.error, Color::Magenta);
+        assert_eq!(
+            theme.set("border", "red").unwrap_err().to_string(),
+            "unknown theme color \"border\""
+        );
+        assert!(theme.set("added", "not a color").is_err());
+    }
+
+    #[test]
+    fn test_theme_focus() {
+        let mut theme = Theme::dark();
+        theme.set_focus_marker("*").unwrap();
+        assert_eq!(theme.focus_marker, Some('*'));
+        theme.set_focus_marker("").unwrap();
+        assert_eq!(theme.focus_marker, None);
+        assert!(theme.set_focus_marker("->").is_err());
+
+        theme.set_focus_border("thick").unwrap();
+        assert_eq!(theme.focus_border, BorderType::Thick);
+        assert!(theme.set_focus_border("dotted").is_err());
+    }
+}
@@ -0,0 +1,98 @@
+use std::{fmt, sync::OnceLock};
+
+use regex::Regex;
+
+/// A `Key: value` line closing a commit message, such as `Signed-off-by: Name <email>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trailer {
+    pub key: String,
+    pub value: String,
+}
+
+impl Trailer {
+    pub fn is_co_author(&self) -> bool {
+        self.key.eq_ignore_ascii_case("Co-authored-by")
+    }
+}
+
+impl fmt::Display for Trailer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.key, self.value)
+    }
+}
+
+// A trailer line, compiled on the first split and shared by the later ones; the pattern
+// always compiles, as the tests check.
+fn line_regex() -> Option<&'static Regex> {
+    static LINE: OnceLock<Option<Regex>> = OnceLock::new();
+    LINE.get_or_init(|| Regex::new(r"^([A-Za-z0-9][A-Za-z0-9-]*):\s*(\S.*)$").ok())
+        .as_ref()
+}
+
+/// Splits `message` into the text before its trailers and the trailers. Like
+/// `git interpret-trailers`, only a last paragraph made entirely of trailers counts, and an
+/// indented line continues the value above it.
+pub fn split(message: &str) -> (&str, Vec<Trailer>) {
+    let message = message.trim_end();
+    let Some(start) = message.rfind("\n\n") else {
+        // the subject alone is never a trailer
+        return (message, Vec::new());
+    };
+    let mut trailers: Vec<Trailer> = Vec::new();
+    for line in message[start + 2..].lines() {
+        if line.starts_with([' ', '\t']) {
+            match trailers.last_mut() {
+                Some(trailer) => {
+                    trailer.value.push(' ');
+                    trailer.value.push_str(line.trim());
+                    continue;
+                }
+                None => return (message, Vec::new()),
+            }
+        }
+        match line_regex().and_then(|regex| regex.captures(line)) {
+            Some(captures) => trailers.push(Trailer {
+                key: captures[1].to_owned(),
+                value: captures[2].trim_end().to_owned(),
+            }),
+            None => return (message, Vec::new()),
+        }
+    }
+    (message[..start].trim_end(), trailers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_trailers() {
+        let message = "Fix typo\n\nThe word was misspelled.\n\nSigned-off-by: Ann <ann@example.com>\nCo-authored-by: Bob\n  <bob@example.com>\nReviewed-by: Carol <carol@example.com>\n";
+        assert!(line_regex().is_some());
+        let (body, trailers) = split(message);
+        assert_eq!(body, "Fix typo\n\nThe word was misspelled.");
+        assert_eq!(
+            trailers.iter().map(Trailer::to_string).collect::<Vec<_>>(),
+            vec![
+                "Signed-off-by: Ann <ann@example.com>",
+                "Co-authored-by: Bob <bob@example.com>",
+                "Reviewed-by: Carol <carol@example.com>",
+            ]
+        );
+        assert!(trailers[1].is_co_author());
+        assert!(!trailers[0].is_co_author());
+    }
+
+    #[test]
+    fn test_split_without_trailers() {
+        // a subject that looks like a trailer
+        assert_eq!(split("fix: typo\n"), ("fix: typo", Vec::new()));
+        // a last paragraph that is only partly trailers
+        let message = "Fix typo\n\nNote: this is prose\nthat goes on.";
+        assert_eq!(split(message), (message, Vec::new()));
+        assert_eq!(
+            split("Fix typo\n\nDetails"),
+            ("Fix typo\n\nDetails", Vec::new())
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use std::{process::Command, thread::JoinHandle};
+
+use serde::Deserialize;
+
+use crate::changelog::VERSION;
+
+// Set to anything but empty, turns the update check off whatever the config says.
+pub const DISABLE_VAR: &str = "GVIEW_NO_UPDATE_CHECK";
+
+const CRATE_URL: &str = "https://crates.io/api/v1/crates/gview";
+
+#[derive(Deserialize)]
+struct CrateResponse {
+    #[serde(rename = "crate")]
+    krate: CrateInfo,
+}
+
+#[derive(Deserialize)]
+struct CrateInfo {
+    max_stable_version: String,
+}
+
+/// Reads the newest stable version from crates.io's answer about the crate.
+pub fn parse_latest(json: &str) -> anyhow::Result<String> {
+    let response: CrateResponse = serde_json::from_str(json)?;
+    Ok(response.krate.max_stable_version)
+}
+
+// Orders versions by their `major.minor.patch` numbers, then a release after its
+// pre-releases.
+fn precedence(version: &str) -> (Vec<u64>, bool) {
+    let release = version.split('+').next().unwrap_or_default();
+    let (numbers, pre_release) = match release.split_once('-') {
+        Some((numbers, _)) => (numbers, true),
+        None => (release, false),
+    };
+    let numbers = numbers
+        .split('.')
+        .map(|number| number.parse().unwrap_or(0))
+        .collect();
+    (numbers, !pre_release)
+}
+
+/// Whether `latest` is a later release than `current`.
+pub fn is_newer(latest: &str, current: &str) -> bool {
+    precedence(latest) > precedence(current)
+}
+
+pub fn disabled_by_env() -> bool {
+    std::env::var_os(DISABLE_VAR).is_some_and(|value| !value.is_empty())
+}
+
+/// Asks crates.io for the newest gview on a separate thread, through curl, and answers it
+/// when it is later than this build.
+pub fn spawn_check() -> JoinHandle<anyhow::Result<Option<String>>> {
+    std::thread::spawn(move || {
+        let output = Command::new("curl")
+            .args([
+                "--silent",
+                "--show-error",
+                "--fail",
+                "--max-time",
+                "10",
+                // crates.io turns away requests without one
+                "--user-agent",
+                &format!("gview/{} (https://github.com/kyoto7250/gview)", VERSION),
+                CRATE_URL,
+            ])
+            .output()
+            .map_err(|e| anyhow::anyhow!("cannot run curl: {}", e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow::anyhow!("{}", stderr.trim()));
+        }
+        let latest = parse_latest(&String::from_utf8_lossy(&output.stdout))?;
+        Ok(is_newer(&latest, VERSION).then_some(latest))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_latest_and_compare() {
+        let json = r#"{"crate": {"name": "gview", "max_version": "0.3.0-rc.1", "max_stable_version": "0.2.10"}}"#;
+        assert_eq!(parse_latest(json).unwrap(), "0.2.10");
+        assert!(parse_latest("{}").is_err());
+
+        assert!(is_newer("0.2.10", "0.2.9"));
+        assert!(is_newer("1.0.0", "0.9.9"));
+        assert!(!is_newer("0.2.0", "0.2.0"));
+        assert!(!is_newer("0.1.9", "0.2.0"));
+        assert!(is_newer("0.2.0", "0.2.0-rc.1"));
+        assert!(!is_newer("0.2.0-rc.1", "0.2.0"));
+    }
+}